@@ -18,6 +18,24 @@ diesel::table! {
         updated_at -> Timestamptz,
         description -> Nullable<Text>,
         category_id -> Int4,
+        deleted_at -> Nullable<Timestamptz>,
+        #[max_length = 20]
+        recurrence -> Varchar,
+        #[max_length = 20]
+        next_occurrence_id -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    api_keys (id) {
+        id -> Int4,
+        #[max_length = 64]
+        key_hash -> Varchar,
+        user_id -> Int4,
+        #[max_length = 100]
+        label -> Varchar,
+        created_at -> Timestamptz,
+        last_used_at -> Nullable<Timestamptz>,
     }
 }
 
@@ -29,6 +47,81 @@ diesel::table! {
         name -> Varchar,
         description -> Nullable<Text>,
         created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    idempotency_keys (user_id, key) {
+        user_id -> Int4,
+        #[max_length = 255]
+        key -> Varchar,
+        #[max_length = 20]
+        action_item_id -> Nullable<Varchar>,
+        created_at -> Timestamptz,
+        #[max_length = 64]
+        request_hash -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    item_assignees (action_item_id, user_id) {
+        #[max_length = 20]
+        action_item_id -> Varchar,
+        user_id -> Int4,
+        #[max_length = 50]
+        role -> Varchar,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    item_audit (id) {
+        id -> Int4,
+        #[max_length = 20]
+        action_item_id -> Varchar,
+        #[max_length = 50]
+        field -> Varchar,
+        old_value -> Nullable<Text>,
+        new_value -> Nullable<Text>,
+        changed_by_id -> Int4,
+        changed_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    item_links (from_item_id, to_item_id, link_type) {
+        #[max_length = 20]
+        from_item_id -> Varchar,
+        #[max_length = 20]
+        to_item_id -> Varchar,
+        #[max_length = 20]
+        link_type -> Varchar,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    item_tags (action_item_id, tag_id) {
+        #[max_length = 20]
+        action_item_id -> Varchar,
+        tag_id -> Int4,
+    }
+}
+
+diesel::table! {
+    item_watchers (action_item_id, user_id) {
+        #[max_length = 20]
+        action_item_id -> Varchar,
+        user_id -> Int4,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    note_mentions (note_id, user_id) {
+        note_id -> Int4,
+        user_id -> Int4,
     }
 }
 
@@ -44,6 +137,17 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    saved_views (id) {
+        id -> Int4,
+        user_id -> Int4,
+        #[max_length = 100]
+        name -> Varchar,
+        filter_json -> Text,
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     status_history (id) {
         id -> Int4,
@@ -54,6 +158,17 @@ diesel::table! {
         changed_by_id -> Int4,
         changed_at -> Timestamptz,
         comment -> Nullable<Text>,
+        #[max_length = 50]
+        from_status -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    tags (id) {
+        id -> Int4,
+        #[max_length = 50]
+        name -> Varchar,
+        created_at -> Timestamptz,
     }
 }
 
@@ -67,6 +182,9 @@ diesel::table! {
         #[max_length = 10]
         initials -> Nullable<Varchar>,
         created_at -> Timestamptz,
+        active -> Bool,
+        #[max_length = 20]
+        role -> Varchar,
     }
 }
 
@@ -81,22 +199,62 @@ diesel::table! {
         next_number -> Int4,
         created_at -> Timestamptz,
         archived -> Bool,
+        updated_at -> Timestamptz,
+        number_padding -> Int4,
+    }
+}
+
+diesel::table! {
+    webhooks (id) {
+        id -> Int4,
+        url -> Text,
+        #[max_length = 50]
+        event_type -> Varchar,
+        vendor_id -> Nullable<Int4>,
+        secret -> Text,
+        created_at -> Timestamptz,
     }
 }
 
 diesel::joinable!(action_items -> categories (category_id));
 diesel::joinable!(action_items -> vendors (vendor_id));
+diesel::joinable!(api_keys -> users (user_id));
 diesel::joinable!(categories -> vendors (vendor_id));
+diesel::joinable!(idempotency_keys -> action_items (action_item_id));
+diesel::joinable!(idempotency_keys -> users (user_id));
+diesel::joinable!(item_assignees -> action_items (action_item_id));
+diesel::joinable!(item_assignees -> users (user_id));
+diesel::joinable!(item_audit -> action_items (action_item_id));
+diesel::joinable!(item_audit -> users (changed_by_id));
+diesel::joinable!(item_tags -> action_items (action_item_id));
+diesel::joinable!(item_tags -> tags (tag_id));
+diesel::joinable!(item_watchers -> action_items (action_item_id));
+diesel::joinable!(item_watchers -> users (user_id));
+diesel::joinable!(note_mentions -> notes (note_id));
+diesel::joinable!(note_mentions -> users (user_id));
 diesel::joinable!(notes -> action_items (action_item_id));
 diesel::joinable!(notes -> users (author_id));
+diesel::joinable!(saved_views -> users (user_id));
 diesel::joinable!(status_history -> action_items (action_item_id));
 diesel::joinable!(status_history -> users (changed_by_id));
+diesel::joinable!(webhooks -> vendors (vendor_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
     action_items,
+    api_keys,
     categories,
+    idempotency_keys,
+    item_assignees,
+    item_audit,
+    item_links,
+    item_tags,
+    item_watchers,
+    note_mentions,
     notes,
+    saved_views,
     status_history,
+    tags,
     users,
     vendors,
+    webhooks,
 );
@@ -1,10 +1,21 @@
 mod db;
+mod metrics;
+mod middleware;
 mod models;
+mod notifications;
+mod rate_limit;
 mod routes;
 mod static_files;
+mod webhooks;
+
+#[cfg(test)]
+mod test_support;
 
 use axum::{
-    routing::{get, post},
+    body::Body,
+    extract::DefaultBodyLimit,
+    http::Request,
+    routing::{delete, get, patch, post},
     Router,
 };
 use diesel::ConnectionError;
@@ -14,29 +25,123 @@ use diesel_async::AsyncPgConnection;
 use futures_util::FutureExt;
 use rustls_platform_verifier::ConfigVerifierExt;
 use std::sync::Arc;
-use tower_http::cors::CorsLayer;
+use tower_http::cors::{AllowCredentials, CorsLayer};
 use tower_http::trace::TraceLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use routes::{activity, auth, categories, health, items, notes, status, users, vendors};
+use routes::{
+    activity, assignees, audit, auth, categories, config, dashboard, export, health, items, links,
+    notes, recurring, status, tags, users, vendors, views, watchers,
+};
 
 pub type DbPool = Pool<AsyncPgConnection>;
 
+/// Body limit for item and note writes: a 10000-character note is at most ~40 KB of UTF-8,
+/// plus JSON escaping. Larger bodies are cut off while reading rather than buffered whole.
+const MAX_JSON_BODY_BYTES: usize = 64 * 1024;
+
+/// How long in-flight requests get to finish after SIGTERM/SIGINT before we exit anyway.
+const SHUTDOWN_GRACE_PERIOD: std::time::Duration = std::time::Duration::from_secs(25);
+
 #[derive(Clone)]
 pub struct AppState {
     pub pool: DbPool,
     pub config: AppConfig,
+    pub metrics: metrics_exporter_prometheus::PrometheusHandle,
+    /// Set when notifications are enabled and SMTP is configured
+    pub mailer: Option<notifications::Mailer>,
+    /// In dev mode, the user every request is authenticated as (seeded at startup)
+    pub dev_user: Option<routes::AuthUser>,
+    /// Per-IP limit on `/auth/*`; `None` when `AUTH_RATE_LIMIT_PER_MINUTE` is 0
+    pub auth_rate_limiter: Option<Arc<rate_limit::RateLimiter>>,
 }
 
 #[derive(Clone)]
 pub struct AppConfig {
     pub jwt_secret: String,
     pub dev_mode: bool,
+    /// `DEV_USER_ID`: act as this existing user in dev mode instead of `dev@localhost`
     pub dev_user_id: Option<i32>,
+    /// Role of the dev-mode user (`DEV_USER_ROLE`, admin unless set to `member`)
+    pub dev_user_role: shared::Role,
     pub public_url: String,
+    /// Path component of `PUBLIC_URL` without a trailing slash (e.g. `/tracker`), or empty
+    /// when served from the domain root. A reverse proxy is expected to strip it.
+    pub base_path: String,
     pub google_client_id: Option<String>,
     pub google_client_secret: Option<String>,
     pub allowed_email_domains: Vec<String>,
+    /// Origins allowed to make credentialed cross-origin requests (`ALLOWED_ORIGINS`).
+    /// Defaults to the origin of `PUBLIC_URL`, i.e. same-origin only.
+    pub allowed_origins: Vec<String>,
+    /// Requests per minute each client IP may make to `/auth/*` (0 disables the limit)
+    pub auth_rate_limit_per_minute: u32,
+    /// Reverse proxies whose `X-Forwarded-For` is believed when telling clients apart
+    /// (`TRUSTED_PROXIES`). Empty means clients connect directly.
+    pub trusted_proxies: Vec<std::net::IpAddr>,
+    pub read_only: bool,
+    /// Maximum item title length in characters, capped at the column width.
+    pub title_max_length: usize,
+    /// PostgreSQL `statement_timeout` applied to every pooled connection; 0 disables it.
+    pub statement_timeout_ms: u64,
+    /// Lifetime of an issued session token.
+    pub token_ttl_hours: i64,
+    /// How long after logging in a session can keep being refreshed.
+    pub token_max_age_hours: i64,
+    /// Email owners and watchers about notes and status changes (needs `smtp`).
+    pub notifications_enabled: bool,
+    pub smtp: Option<SmtpConfig>,
+}
+
+#[derive(Clone, Copy)]
+pub enum SmtpTls {
+    /// Plain connection upgraded with STARTTLS (port 587 by default)
+    StartTls,
+    /// TLS from the start (port 465 by default)
+    Tls,
+    /// Unencrypted, for a local relay or mail catcher (port 25 by default)
+    None,
+}
+
+#[derive(Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub tls: SmtpTls,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// `From` mailbox, e.g. `Action Tracker <tracker@example.org>`
+    pub from: String,
+}
+
+impl SmtpConfig {
+    /// `None` unless `SMTP_HOST` is set.
+    fn from_env() -> Option<Self> {
+        let host = std::env::var("SMTP_HOST").ok()?;
+        let tls = match std::env::var("SMTP_TLS").as_deref() {
+            Ok("tls") => SmtpTls::Tls,
+            Ok("none") => SmtpTls::None,
+            _ => SmtpTls::StartTls,
+        };
+        let default_port = match tls {
+            SmtpTls::StartTls => 587,
+            SmtpTls::Tls => 465,
+            SmtpTls::None => 25,
+        };
+
+        Some(Self {
+            port: std::env::var("SMTP_PORT")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default_port),
+            tls,
+            username: std::env::var("SMTP_USERNAME").ok(),
+            password: std::env::var("SMTP_PASSWORD").ok(),
+            from: std::env::var("SMTP_FROM")
+                .unwrap_or_else(|_| format!("Action Tracker <noreply@{}>", host)),
+            host,
+        })
+    }
 }
 
 impl AppConfig {
@@ -45,6 +150,16 @@ impl AppConfig {
             .map(|v| v == "true" || v == "1")
             .unwrap_or(false);
 
+        let public_url =
+            std::env::var("PUBLIC_URL").unwrap_or_else(|_| "http://localhost:8080".to_string());
+
+        let base_path = base_path_from(&public_url);
+        let allowed_origins = allowed_origins_from(
+            &std::env::var("ALLOWED_ORIGINS").unwrap_or_default(),
+            &public_url,
+            &base_path,
+        );
+
         Self {
             jwt_secret: std::env::var("JWT_SECRET").unwrap_or_else(|_| {
                 if dev_mode {
@@ -57,8 +172,12 @@ impl AppConfig {
             dev_user_id: std::env::var("DEV_USER_ID")
                 .ok()
                 .and_then(|v| v.parse().ok()),
-            public_url: std::env::var("PUBLIC_URL")
-                .unwrap_or_else(|_| "http://localhost:8080".to_string()),
+            dev_user_role: match std::env::var("DEV_USER_ROLE").as_deref() {
+                Ok("member") => shared::Role::Member,
+                _ => shared::Role::Admin,
+            },
+            base_path,
+            public_url,
             google_client_id: std::env::var("GOOGLE_CLIENT_ID").ok(),
             google_client_secret: std::env::var("GOOGLE_CLIENT_SECRET").ok(),
             allowed_email_domains: std::env::var("ALLOWED_EMAIL_DOMAINS")
@@ -67,12 +186,197 @@ impl AppConfig {
                 .map(|s| s.trim().to_string())
                 .filter(|s| !s.is_empty())
                 .collect(),
+            allowed_origins,
+            auth_rate_limit_per_minute: std::env::var("AUTH_RATE_LIMIT_PER_MINUTE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(30),
+            trusted_proxies: trusted_proxies_from(
+                &std::env::var("TRUSTED_PROXIES").unwrap_or_default(),
+            ),
+            read_only: std::env::var("READ_ONLY")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            title_max_length: std::env::var("TITLE_MAX_LENGTH")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(shared::DEFAULT_TITLE_MAX_LENGTH)
+                .min(shared::DEFAULT_TITLE_MAX_LENGTH),
+            statement_timeout_ms: std::env::var("DB_STATEMENT_TIMEOUT_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(30_000),
+            token_ttl_hours: std::env::var("TOKEN_TTL_HOURS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(24)
+                .max(1),
+            token_max_age_hours: std::env::var("TOKEN_MAX_AGE_HOURS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(24 * 7)
+                .max(1),
+            notifications_enabled: std::env::var("NOTIFICATIONS_ENABLED")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            smtp: SmtpConfig::from_env(),
+        }
+    }
+}
+
+/// Root span for each request. `user_id` is filled in by the `AuthUser` extractor so
+/// every log line for an authenticated request carries who made it.
+fn request_span(req: &Request<Body>) -> tracing::Span {
+    let request_id = req
+        .headers()
+        .get("x-request-id")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{:016x}", rand::random::<u64>()));
+
+    tracing::info_span!(
+        "request",
+        %request_id,
+        method = %req.method(),
+        uri = %req.uri(),
+        user_id = tracing::field::Empty,
+    )
+}
+
+/// Resolves on SIGTERM (what container orchestrators send) or Ctrl-C.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to listen for Ctrl-C");
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+/// Serve `app` until `shutdown` resolves, then stop accepting connections and let open ones
+/// finish, but only for `grace`: a stuck request shouldn't hold up the deploy.
+async fn serve(
+    listener: tokio::net::TcpListener,
+    app: Router,
+    shutdown: impl std::future::Future<Output = ()> + Send + 'static,
+    grace: std::time::Duration,
+) -> std::io::Result<()> {
+    let draining = Arc::new(tokio::sync::Notify::new());
+    let server = axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .with_graceful_shutdown({
+        let draining = draining.clone();
+        async move {
+            shutdown.await;
+            tracing::info!("Shutting down: no new connections, draining in-flight requests");
+            draining.notify_one();
+        }
+    });
+    tokio::select! {
+        result = server => result?,
+        _ = async {
+            draining.notified().await;
+            tokio::time::sleep(grace).await;
+        } => {
+            tracing::warn!(
+                "Requests still running after {}s; exiting anyway",
+                grace.as_secs()
+            );
         }
     }
+    Ok(())
+}
+
+fn base_path_from(public_url: &str) -> String {
+    let without_scheme = public_url
+        .split_once("://")
+        .map(|(_, rest)| rest)
+        .unwrap_or(public_url);
+    without_scheme
+        .find('/')
+        .map(|i| without_scheme[i..].trim_end_matches('/').to_string())
+        .unwrap_or_default()
+}
+
+/// The comma-separated `ALLOWED_ORIGINS` list, or PUBLIC_URL's origin when it is empty.
+fn allowed_origins_from(list: &str, public_url: &str, base_path: &str) -> Vec<String> {
+    let origins: Vec<String> = list
+        .split(',')
+        .map(|s| s.trim().trim_end_matches('/').to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if origins.is_empty() {
+        // Origins never carry a path, so drop the base path from PUBLIC_URL
+        vec![public_url
+            .trim_end_matches('/')
+            .trim_end_matches(base_path)
+            .to_string()]
+    } else {
+        origins
+    }
+}
+
+/// The comma-separated `TRUSTED_PROXIES` list of IP addresses.
+fn trusted_proxies_from(list: &str) -> Vec<std::net::IpAddr> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            s.parse()
+                .unwrap_or_else(|_| panic!("TRUSTED_PROXIES: '{}' is not an IP address", s))
+        })
+        .collect()
+}
+
+/// Only listed origins get their origin echoed back and credentials allowed; any other
+/// origin gets neither, so the browser blocks it from reading responses.
+fn cors_layer(allowed_origins: &[String]) -> CorsLayer {
+    let origins: Vec<axum::http::HeaderValue> = allowed_origins
+        .iter()
+        .map(|o| {
+            o.parse()
+                .expect("ALLOWED_ORIGINS and PUBLIC_URL must be valid header values")
+        })
+        .collect();
+    let credentialed = origins.clone();
+    CorsLayer::new()
+        .allow_origin(origins)
+        .allow_credentials(AllowCredentials::predicate(move |origin, _| {
+            credentialed.contains(origin)
+        }))
+        .allow_methods([
+            axum::http::Method::GET,
+            axum::http::Method::POST,
+            axum::http::Method::PATCH,
+            axum::http::Method::DELETE,
+        ])
+        .allow_headers([
+            axum::http::header::CONTENT_TYPE,
+            axum::http::header::IF_MATCH,
+            axum::http::HeaderName::from_static("idempotency-key"),
+            axum::http::HeaderName::from_static("x-api-key"),
+        ])
+        .expose_headers([axum::http::header::ETAG])
 }
 
 fn establish_connection(
     config: &str,
+    statement_timeout_ms: u64,
 ) -> futures_util::future::BoxFuture<'_, diesel::ConnectionResult<AsyncPgConnection>> {
     // Strip channel_binding=require — PgBouncer (NeonDB pooler) doesn't support it
     let config = config.replace("&channel_binding=require", "");
@@ -83,11 +387,149 @@ fn establish_connection(
         let (client, conn) = tokio_postgres::connect(&config, tls)
             .await
             .map_err(|e| ConnectionError::BadConnection(e.to_string()))?;
-        AsyncPgConnection::try_from_client_and_connection(client, conn).await
+        let mut conn = AsyncPgConnection::try_from_client_and_connection(client, conn).await?;
+
+        // Session-level, so it only sticks when the pooler hands us the same server
+        // connection (session mode or a direct connection)
+        if statement_timeout_ms > 0 {
+            use diesel_async::RunQueryDsl;
+            diesel::sql_query(format!("SET statement_timeout = {}", statement_timeout_ms))
+                .execute(&mut conn)
+                .await
+                .map_err(|e| ConnectionError::BadConnection(e.to_string()))?;
+        }
+
+        Ok(conn)
     };
     fut.boxed()
 }
 
+/// Every route the server answers, with its middleware.
+fn router(state: Arc<AppState>) -> Router {
+    Router::new()
+        // Health check
+        .route("/health", get(health::health_check))
+        // Prometheus scrape target; unauthenticated like /health
+        .route("/metrics", get(metrics::render))
+        // Auth routes
+        .route("/auth/login", get(auth::login))
+        .route("/auth/callback", get(auth::callback))
+        .route("/auth/logout", post(auth::logout))
+        .route("/auth/refresh", post(auth::refresh))
+        .route("/auth/me", get(auth::me))
+        // Client config
+        .route("/api/config", get(config::get))
+        // Vendor routes
+        .route("/api/vendors", get(vendors::list).post(vendors::create))
+        .route("/api/vendors/:id", get(vendors::get).patch(vendors::update))
+        .route("/api/vendors/:id/stats", get(vendors::stats))
+        // Item routes
+        .route("/api/items", get(items::list_all))
+        .route("/api/items/search", get(items::search))
+        .route("/api/items/watched", get(watchers::watched))
+        .route("/api/items/overdue", get(items::overdue))
+        .route("/api/items/bulk/status", post(status::bulk_change))
+        .route("/api/items/generate-recurring", post(recurring::generate))
+        .route("/api/items/due-soon", get(items::due_soon))
+        .route(
+            "/api/vendors/:id/items",
+            get(items::list)
+                .post(items::create)
+                .layer(DefaultBodyLimit::max(MAX_JSON_BODY_BYTES)),
+        )
+        .route(
+            "/api/vendors/:id/items/export.csv",
+            get(export::vendor_items_csv),
+        )
+        .route(
+            "/api/items/:item_id",
+            get(items::get)
+                .patch(items::update)
+                .delete(items::delete)
+                .layer(DefaultBodyLimit::max(MAX_JSON_BODY_BYTES)),
+        )
+        .route("/api/items/:item_id/duplicate", post(items::duplicate))
+        .route("/api/items/:item_id/move", post(items::move_item))
+        .route("/api/items/:item_id/restore", post(items::restore))
+        // Note routes
+        .route(
+            "/api/items/:item_id/notes",
+            get(notes::list)
+                .post(notes::create)
+                .layer(DefaultBodyLimit::max(MAX_JSON_BODY_BYTES)),
+        )
+        .route(
+            "/api/items/:item_id/notes/:note_id",
+            patch(notes::update)
+                .delete(notes::delete)
+                .layer(DefaultBodyLimit::max(MAX_JSON_BODY_BYTES)),
+        )
+        // Status routes
+        .route("/api/items/:item_id/history", get(status::history))
+        .route("/api/items/:item_id/status", post(status::change))
+        .route("/api/items/:item_id/reopen", post(status::reopen))
+        .route("/api/items/:item_id/audit", get(audit::list))
+        // Assignee routes
+        .route(
+            "/api/items/:item_id/assignees",
+            get(assignees::list).post(assignees::add),
+        )
+        .route(
+            "/api/items/:item_id/assignees/:user_id",
+            delete(assignees::remove),
+        )
+        // Link routes
+        .route(
+            "/api/items/:item_id/links",
+            post(links::add).delete(links::remove),
+        )
+        // Tag routes
+        .route("/api/tags", get(tags::list).post(tags::create))
+        .route("/api/items/:item_id/tags", post(tags::attach))
+        .route("/api/items/:item_id/tags/:name", delete(tags::detach))
+        // Watcher routes
+        .route(
+            "/api/items/:item_id/watch",
+            post(watchers::watch).delete(watchers::unwatch),
+        )
+        // User routes
+        .route("/api/users", get(users::list).post(users::create))
+        .route("/api/users/:id", patch(users::update))
+        // Category routes
+        .route("/api/categories", get(categories::list_all))
+        .route(
+            "/api/vendors/:id/categories",
+            get(categories::list_by_vendor).post(categories::create),
+        )
+        .route(
+            "/api/vendors/:id/categories/:category_id",
+            patch(categories::update).delete(categories::delete),
+        )
+        // Activity feed
+        .route("/api/activity", get(activity::activity))
+        .route("/api/me/dashboard", get(dashboard::dashboard))
+        // Saved view routes
+        .route("/api/me/views", get(views::list).post(views::create))
+        .route("/api/me/views/:view_id", delete(views::delete))
+        // Deep link redirect
+        .route("/go/:item_id", get(items::go_redirect))
+        // Static files (frontend) - fallback for everything else
+        .fallback(static_files::static_handler)
+        .layer(axum::middleware::from_fn(middleware::json_rejections))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::auth_rate_limit,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::read_only_guard,
+        ))
+        .layer(cors_layer(&state.config.allowed_origins))
+        .layer(axum::middleware::from_fn(metrics::track))
+        .layer(TraceLayer::new_for_http().make_span_with(request_span))
+        .with_state(state)
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     if std::env::args().any(|a| a == "--check-assets") {
@@ -116,11 +558,17 @@ async fn main() -> anyhow::Result<()> {
         tracing::warn!("Running in DEV MODE - authentication is bypassed!");
     }
 
+    if config.read_only {
+        tracing::warn!("Running in READ-ONLY mode - mutating API requests are rejected");
+    }
+
     // Database connection with TLS (required for NeonDB)
     let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
 
     let mut manager_config = ManagerConfig::default();
-    manager_config.custom_setup = Box::new(establish_connection);
+    let statement_timeout_ms = config.statement_timeout_ms;
+    manager_config.custom_setup =
+        Box::new(move |url| establish_connection(url, statement_timeout_ms));
 
     let manager = AsyncDieselConnectionManager::<AsyncPgConnection>::new_with_config(
         database_url,
@@ -145,75 +593,301 @@ async fn main() -> anyhow::Result<()> {
         tracing::info!("Database connection verified");
     }
 
-    let state = AppState {
+    let dev_user = if config.dev_mode {
+        let dev_user = routes::auth::ensure_dev_user(&pool, &config)
+            .await
+            .expect("Failed to set up the dev user");
+        tracing::info!(
+            "Dev mode user: id={} <{}>",
+            dev_user.user_id,
+            dev_user.email
+        );
+        Some(dev_user)
+    } else {
+        None
+    };
+
+    let mailer = match (&config.smtp, config.notifications_enabled) {
+        (_, false) => None,
+        (None, true) => {
+            tracing::warn!(
+                "NOTIFICATIONS_ENABLED is set but SMTP_HOST is not; no emails will be sent"
+            );
+            None
+        }
+        (Some(smtp), true) => {
+            let mailer = notifications::Mailer::new(smtp).expect("Invalid SMTP configuration");
+            tracing::info!(
+                "Email notifications enabled via {}:{}",
+                smtp.host,
+                smtp.port
+            );
+            Some(mailer)
+        }
+    };
+
+    let db_pool = pool.clone();
+    let state = Arc::new(AppState {
         pool,
         config: config.clone(),
-    };
+        metrics: metrics::install_recorder(),
+        mailer,
+        dev_user,
+        auth_rate_limiter: (config.auth_rate_limit_per_minute > 0).then(|| {
+            Arc::new(rate_limit::RateLimiter::per_minute(
+                config.auth_rate_limit_per_minute,
+            ))
+        }),
+    });
 
-    // Build router
-    let app = Router::new()
-        // Health check
-        .route("/health", get(health::health_check))
-        // Auth routes
-        .route("/auth/login", get(auth::login))
-        .route("/auth/callback", get(auth::callback))
-        .route("/auth/logout", post(auth::logout))
-        .route("/auth/me", get(auth::me))
-        // Vendor routes
-        .route("/api/vendors", get(vendors::list).post(vendors::create))
-        .route("/api/vendors/:id", get(vendors::get).patch(vendors::update))
-        // Item routes
-        .route("/api/items", get(items::list_all))
-        .route(
-            "/api/vendors/:id/items",
-            get(items::list).post(items::create),
-        )
-        .route("/api/items/:item_id", get(items::get).patch(items::update))
-        // Note routes
-        .route(
-            "/api/items/:item_id/notes",
-            get(notes::list).post(notes::create),
-        )
-        // Status routes
-        .route("/api/items/:item_id/history", get(status::history))
-        .route("/api/items/:item_id/status", post(status::change))
-        // User routes
-        .route("/api/users", get(users::list))
-        // Category routes
-        .route("/api/categories", get(categories::list_all))
-        .route(
-            "/api/vendors/:id/categories",
-            get(categories::list_by_vendor).post(categories::create),
-        )
-        // Activity feed
-        .route("/api/activity", get(activity::activity))
-        // Deep link redirect
-        .route("/go/:item_id", get(items::go_redirect))
-        // Static files (frontend) - fallback for everything else
-        .fallback(static_files::static_handler)
-        .layer({
-            let origin = config
-                .public_url
-                .parse::<axum::http::HeaderValue>()
-                .expect("PUBLIC_URL must be a valid header value");
-            CorsLayer::new()
-                .allow_origin(origin)
-                .allow_methods([
-                    axum::http::Method::GET,
-                    axum::http::Method::POST,
-                    axum::http::Method::PATCH,
-                ])
-                .allow_headers([axum::http::header::CONTENT_TYPE])
-        })
-        .layer(TraceLayer::new_for_http())
-        .with_state(Arc::new(state));
+    let app = router(state);
 
     let port = std::env::var("PORT").unwrap_or_else(|_| "8080".to_string());
     let addr = format!("0.0.0.0:{}", port);
     tracing::info!("Starting server on {}", addr);
 
     let listener = tokio::net::TcpListener::bind(&addr).await?;
-    axum::serve(listener, app).await?;
+
+    serve(listener, app, shutdown_signal(), SHUTDOWN_GRACE_PERIOD).await?;
+
+    db_pool.close();
+    tracing::info!("Shutdown complete");
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{header, Method, StatusCode};
+    use tower::Service;
+
+    /// Serve a `/sleep` route that takes `request_time`, start a request to it and signal
+    /// shutdown once the handler is running. Returns the route's URL, the request and the
+    /// server.
+    async fn shut_down_mid_request(
+        request_time: std::time::Duration,
+        grace: std::time::Duration,
+    ) -> (
+        String,
+        tokio::task::JoinHandle<reqwest::Result<reqwest::Response>>,
+        tokio::task::JoinHandle<std::io::Result<()>>,
+    ) {
+        let started = Arc::new(tokio::sync::Notify::new());
+        let app = Router::new().route(
+            "/sleep",
+            get({
+                let started = started.clone();
+                move || async move {
+                    started.notify_one();
+                    tokio::time::sleep(request_time).await;
+                    "done"
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/sleep", listener.local_addr().unwrap());
+        let (stop, stopped) = tokio::sync::oneshot::channel();
+        let server = tokio::spawn(serve(
+            listener,
+            app,
+            async {
+                let _ = stopped.await;
+            },
+            grace,
+        ));
+
+        let request = tokio::spawn(reqwest::get(url.clone()));
+        started.notified().await;
+        stop.send(()).unwrap();
+        (url, request, server)
+    }
+
+    #[tokio::test]
+    async fn shutdown_lets_in_flight_requests_finish() {
+        let (url, request, server) = shut_down_mid_request(
+            std::time::Duration::from_millis(300),
+            std::time::Duration::from_secs(10),
+        )
+        .await;
+
+        let response = request.await.unwrap().unwrap();
+        assert_eq!(response.text().await.unwrap(), "done");
+        server.await.unwrap().unwrap();
+        assert!(reqwest::get(url).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn shutdown_stops_waiting_after_the_grace_period() {
+        let (_, _request, server) = shut_down_mid_request(
+            std::time::Duration::from_secs(60),
+            std::time::Duration::from_millis(200),
+        )
+        .await;
+
+        tokio::time::timeout(std::time::Duration::from_secs(5), server)
+            .await
+            .expect("Server waited past the grace period")
+            .unwrap()
+            .unwrap();
+    }
+
+    #[test]
+    fn allowed_origins_fall_back_to_the_public_origin() {
+        assert_eq!(
+            allowed_origins_from(
+                " https://a.example/ ,,https://b.example",
+                "https://tracker.example/tracker",
+                "/tracker"
+            ),
+            ["https://a.example", "https://b.example"]
+        );
+        assert_eq!(
+            allowed_origins_from("", "https://tracker.example/tracker/", "/tracker"),
+            ["https://tracker.example"]
+        );
+    }
+
+    async fn cors_headers(method: Method, origin: &str) -> (StatusCode, Option<String>, bool) {
+        let mut app = Router::new()
+            .route("/api/items", get(|| async { "[]" }))
+            .layer(cors_layer(&["http://localhost:8080".to_string()]));
+        let mut request = Request::builder()
+            .method(method.clone())
+            .uri("/api/items")
+            .header(header::ORIGIN, origin);
+        if method == Method::OPTIONS {
+            request = request.header(header::ACCESS_CONTROL_REQUEST_METHOD, "PATCH");
+        }
+        let response = app
+            .call(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let headers = response.headers();
+        (
+            response.status(),
+            headers
+                .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+                .map(|v| v.to_str().unwrap().to_string()),
+            headers.contains_key(header::ACCESS_CONTROL_ALLOW_CREDENTIALS),
+        )
+    }
+
+    #[tokio::test]
+    async fn cors_allows_only_listed_origins_with_credentials() {
+        for method in [Method::OPTIONS, Method::GET] {
+            let (status, origin, credentials) =
+                cors_headers(method.clone(), "http://localhost:8080").await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(origin.as_deref(), Some("http://localhost:8080"));
+            assert!(credentials, "{} should allow credentials", method);
+
+            let (_, origin, credentials) =
+                cors_headers(method.clone(), "https://evil.example").await;
+            assert_eq!(origin, None);
+            assert!(!credentials, "{} from evil.example got credentials", method);
+        }
+    }
+
+    #[tokio::test]
+    async fn cors_preflight_allows_the_api_key_header() {
+        let mut app = Router::new()
+            .route("/api/items", get(|| async { "[]" }))
+            .layer(cors_layer(&["http://localhost:8080".to_string()]));
+        let request = Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/api/items")
+            .header(header::ORIGIN, "http://localhost:8080")
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+            .header(header::ACCESS_CONTROL_REQUEST_HEADERS, "x-api-key")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.call(request).await.unwrap();
+        let allowed = response.headers()[header::ACCESS_CONTROL_ALLOW_HEADERS]
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert!(
+            allowed.split(',').any(|h| h.trim() == "x-api-key"),
+            "allowed headers: {}",
+            allowed
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn statement_timeout_aborts_slow_queries() {
+        use diesel_async::RunQueryDsl;
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
+
+        let mut conn = establish_connection(&url, 200).await.unwrap();
+        let started = std::time::Instant::now();
+        let error = diesel::sql_query("SELECT pg_sleep(5)")
+            .execute(&mut conn)
+            .await
+            .unwrap_err();
+        assert!(started.elapsed() < std::time::Duration::from_secs(3));
+        assert!(error.to_string().contains("statement timeout"), "{}", error);
+        // The connection is still usable afterwards
+        diesel::sql_query("SELECT 1")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        // 0 leaves the server default (no limit) alone
+        let mut unlimited = establish_connection(&url, 0).await.unwrap();
+        diesel::sql_query("SELECT pg_sleep(0.3)")
+            .execute(&mut unlimited)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn activity_feed_is_served_by_the_router() {
+        use crate::db::schema::status_history;
+        use crate::models::NewStatusHistory;
+        use diesel_async::RunQueryDsl;
+
+        let db = test_support::TestDb::new().await;
+        let viewer = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let other = db.user("ada@example.org", "Ada Lovelace", Some("AL")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let item = db.item(&category, &viewer, &viewer).await;
+        diesel::insert_into(status_history::table)
+            .values(&NewStatusHistory {
+                action_item_id: item.id.clone(),
+                status: "In Progress".to_string(),
+                changed_by_id: other.id,
+                comment: None,
+                from_status: None,
+            })
+            .execute(&mut db.conn().await)
+            .await
+            .unwrap();
+
+        // Signed in as the viewer without going through a login
+        let mut state = (*db.state).clone();
+        state.dev_user = Some(test_support::auth(&viewer));
+        let mut app = router(Arc::new(state));
+        let mut request = Request::get("/api/activity").body(Body::empty()).unwrap();
+        request
+            .extensions_mut()
+            .insert(axum::extract::ConnectInfo(std::net::SocketAddr::from((
+                [127, 0, 0, 1],
+                5000,
+            ))));
+        let response = app.call(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+        let feed: shared::ActivityResponse = test_support::json(response).await;
+        let entries: Vec<(&str, &str, &str)> = feed
+            .entries
+            .iter()
+            .map(|e| (e.item_id.as_str(), e.actor_name.as_str(), e.detail.as_str()))
+            .collect();
+        assert_eq!(entries, [("AAA-001", "Ada Lovelace", "→ In Progress")]);
+    }
+}
@@ -0,0 +1,88 @@
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::AppState;
+
+const REQUESTS_TOTAL: &str = "http_requests_total";
+const REQUEST_DURATION: &str = "http_request_duration_seconds";
+
+/// Latency buckets in seconds, from a fast cached read up to a slow export.
+const DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// How often buffered histogram samples are folded into buckets between scrapes.
+const UPKEEP_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Install the global Prometheus recorder. Call once at startup, inside the runtime.
+pub fn install_recorder() -> PrometheusHandle {
+    let handle = PrometheusBuilder::new()
+        .set_buckets_for_metric(
+            Matcher::Full(REQUEST_DURATION.to_string()),
+            DURATION_BUCKETS,
+        )
+        .expect("Invalid histogram buckets")
+        .install_recorder()
+        .expect("Failed to install metrics recorder");
+
+    // Samples otherwise pile up in memory until something scrapes /metrics
+    let upkeep = handle.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(UPKEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            upkeep.run_upkeep();
+        }
+    });
+
+    handle
+}
+
+/// Count requests and record their latency, labelled by route template rather than the
+/// raw path so item ids don't explode the label cardinality.
+pub async fn track(req: Request, next: Next) -> Response {
+    let start = Instant::now();
+    let method = req.method().to_string();
+    // Requests that match no route are served by the static file fallback
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| "fallback".to_string());
+
+    let response = next.run(req).await;
+
+    let status = response.status().as_u16().to_string();
+    metrics::counter!(
+        REQUESTS_TOTAL,
+        "method" => method.clone(),
+        "route" => route.clone(),
+        "status" => status,
+    )
+    .increment(1);
+    metrics::histogram!(REQUEST_DURATION, "method" => method, "route" => route)
+        .record(start.elapsed().as_secs_f64());
+
+    response
+}
+
+/// `GET /metrics` in the Prometheus text format. Pool gauges are sampled on each scrape.
+pub async fn render(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let status = state.pool.status();
+    metrics::gauge!("db_pool_max_size").set(status.max_size as f64);
+    metrics::gauge!("db_pool_size").set(status.size as f64);
+    metrics::gauge!("db_pool_available").set(status.available as f64);
+    metrics::gauge!("db_pool_waiting").set(status.waiting as f64);
+
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics.render(),
+    )
+}
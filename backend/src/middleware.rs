@@ -0,0 +1,240 @@
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use shared::ApiError;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use crate::{rate_limit, AppState};
+
+/// Reject mutating API requests with a 503 while `READ_ONLY` is set; reads pass through.
+pub async fn read_only_guard(
+    State(state): State<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let mutating = !matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+
+    if state.config.read_only && mutating && req.uri().path().starts_with("/api/") {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ApiError::service_unavailable(
+                "The tracker is in read-only maintenance mode; changes are temporarily disabled",
+            )),
+        )
+            .into_response();
+    }
+
+    next.run(req).await
+}
+
+/// Throttle `/auth/*` per client IP; over the limit gets a 429 with `Retry-After`.
+/// `/auth/me` is exempt: it only reads the current session, and the SPA calls it often.
+pub async fn auth_rate_limit(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let path = req.uri().path();
+    let Some(limiter) = &state.auth_rate_limiter else {
+        return next.run(req).await;
+    };
+    if !path.starts_with("/auth/") || path == "/auth/me" {
+        return next.run(req).await;
+    }
+
+    let ip = rate_limit::client_ip(req.headers(), peer, &state.config.trusted_proxies);
+    if let Err(wait) = limiter.check(ip) {
+        tracing::warn!("Rate limited {} on {}", ip, path);
+        let retry_after = (wait.as_secs_f64().ceil() as u64).max(1);
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::RETRY_AFTER, retry_after.to_string())],
+            Json(ApiError::rate_limited(
+                "Too many requests; try again shortly",
+            )),
+        )
+            .into_response();
+    }
+
+    next.run(req).await
+}
+
+/// Replace axum's plain-text body rejections (over the body limit, not JSON) with the
+/// `ApiError` shape clients expect. Handlers never answer 413/415 themselves.
+pub async fn json_rejections(req: Request, next: Next) -> Response {
+    let response = next.run(req).await;
+
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    if is_json {
+        return response;
+    }
+
+    match response.status() {
+        StatusCode::PAYLOAD_TOO_LARGE => (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            Json(ApiError::payload_too_large("Request body is too large")),
+        )
+            .into_response(),
+        StatusCode::UNSUPPORTED_MEDIA_TYPE => (
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Json(ApiError::unsupported_media_type(
+                "Expected a JSON body with Content-Type: application/json",
+            )),
+        )
+            .into_response(),
+        _ => response,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::extract::DefaultBodyLimit;
+    use axum::routing::post;
+    use axum::Router;
+    use tower::Service;
+
+    use crate::test_support::json;
+
+    /// A JSON route limited like the item and note routes, behind `json_rejections`.
+    async fn post_note(content_type: &str, body: String) -> Response {
+        let mut app = Router::new()
+            .route(
+                "/notes",
+                post(|Json(_): Json<serde_json::Value>| async { StatusCode::CREATED })
+                    .layer(DefaultBodyLimit::max(crate::MAX_JSON_BODY_BYTES)),
+            )
+            .layer(axum::middleware::from_fn(json_rejections));
+        let request = Request::post("/notes")
+            .header(header::CONTENT_TYPE, content_type)
+            .body(Body::from(body))
+            .unwrap();
+        app.call(request).await.unwrap()
+    }
+
+    fn note_body(content: &str) -> String {
+        serde_json::json!({ "content": content }).to_string()
+    }
+
+    #[tokio::test]
+    async fn oversized_body_gets_json_413() {
+        let response = post_note("application/json", note_body(&"x".repeat(70 * 1024))).await;
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let error: ApiError = json(response).await;
+        assert_eq!(error.error.code, "PAYLOAD_TOO_LARGE");
+    }
+
+    #[tokio::test]
+    async fn longest_multibyte_note_fits_under_the_limit() {
+        let response = post_note("application/json", note_body(&"é".repeat(10000))).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
+
+    #[tokio::test]
+    async fn non_json_content_type_gets_json_415() {
+        let response = post_note("text/plain", note_body("hello")).await;
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        let error: ApiError = json(response).await;
+        assert_eq!(error.error.code, "UNSUPPORTED_MEDIA_TYPE");
+    }
+
+    /// `POST /auth/logout` through `auth_rate_limit`, from `peer` with an optional
+    /// `X-Forwarded-For`.
+    async fn logout_from(app: &mut Router, peer: &str, forwarded_for: Option<&str>) -> StatusCode {
+        let mut request = Request::post("/auth/logout");
+        if let Some(forwarded_for) = forwarded_for {
+            request = request.header("x-forwarded-for", forwarded_for);
+        }
+        let mut request = request.body(Body::empty()).unwrap();
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(peer.parse::<SocketAddr>().unwrap()));
+        app.call(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn auth_requests_past_the_limit_get_429_whatever_they_claim_to_forward() {
+        let mut config = crate::test_support::test_config();
+        config.auth_rate_limit_per_minute = 3;
+        config.trusted_proxies = vec!["10.0.0.1".parse().unwrap()];
+        let state = crate::test_support::offline_state(config);
+        let mut app = Router::new()
+            .route("/auth/logout", post(|| async { StatusCode::OK }))
+            .layer(axum::middleware::from_fn_with_state(
+                state.clone(),
+                auth_rate_limit,
+            ))
+            .with_state(state);
+
+        // A direct client gets N requests, however it fills in X-Forwarded-For
+        for i in 0..3 {
+            let spoofed = format!("198.51.100.{}", i);
+            let status = logout_from(&mut app, "192.0.2.50:5000", Some(&spoofed)).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        let status = logout_from(&mut app, "192.0.2.50:5000", Some("198.51.100.99")).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+
+        // Behind the trusted proxy each forwarded client has its own budget
+        for _ in 0..3 {
+            let status = logout_from(&mut app, "10.0.0.1:4000", Some("203.0.113.9")).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        let status = logout_from(&mut app, "10.0.0.1:4000", Some("203.0.113.9")).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        let status = logout_from(&mut app, "10.0.0.1:4000", Some("203.0.113.10")).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn read_only_mode_refuses_api_writes_but_serves_reads() {
+        let mut config = crate::test_support::test_config();
+        config.read_only = true;
+        let state = crate::test_support::offline_state(config);
+        let mut app = Router::new()
+            .route(
+                "/api/items",
+                axum::routing::get(|| async { StatusCode::OK })
+                    .post(|| async { StatusCode::CREATED })
+                    .patch(|| async { StatusCode::OK }),
+            )
+            .route("/auth/logout", post(|| async { StatusCode::OK }))
+            .layer(axum::middleware::from_fn_with_state(
+                state.clone(),
+                read_only_guard,
+            ))
+            .with_state(state);
+        let mut send = |method: Method, path: &str| {
+            app.call(
+                Request::builder()
+                    .method(method)
+                    .uri(path)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+
+        for method in [Method::POST, Method::PATCH] {
+            let response = send(method, "/api/items").await.unwrap();
+            assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+            let error: ApiError = json(response).await;
+            assert_eq!(error.error.code, "SERVICE_UNAVAILABLE");
+        }
+        let response = send(Method::GET, "/api/items").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        // Logging out is not an API write
+        let response = send(Method::POST, "/auth/logout").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -15,6 +15,7 @@ pub struct Category {
     pub name: String,
     pub description: Option<String>,
     pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Insertable)]
@@ -25,6 +26,14 @@ pub struct NewCategory {
     pub description: Option<String>,
 }
 
+#[derive(Debug, AsChangeset)]
+#[diesel(table_name = categories)]
+pub struct UpdateCategory {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
 // ============================================================================
 // Vendor
 // ============================================================================
@@ -39,6 +48,8 @@ pub struct Vendor {
     pub next_number: i32,
     pub created_at: DateTime<Utc>,
     pub archived: bool,
+    pub updated_at: DateTime<Utc>,
+    pub number_padding: i32,
 }
 
 #[derive(Debug, Insertable)]
@@ -47,6 +58,7 @@ pub struct NewVendor {
     pub prefix: String,
     pub name: String,
     pub description: Option<String>,
+    pub number_padding: i32,
 }
 
 #[derive(Debug, AsChangeset)]
@@ -55,13 +67,14 @@ pub struct UpdateVendor {
     pub name: Option<String>,
     pub description: Option<String>,
     pub archived: Option<bool>,
+    pub updated_at: Option<DateTime<Utc>>,
 }
 
 // ============================================================================
 // User
 // ============================================================================
 
-#[derive(Debug, Queryable, Selectable)]
+#[derive(Debug, Clone, Queryable, Selectable)]
 #[diesel(table_name = users)]
 pub struct User {
     pub id: i32,
@@ -69,6 +82,8 @@ pub struct User {
     pub name: String,
     pub initials: Option<String>,
     pub created_at: DateTime<Utc>,
+    pub active: bool,
+    pub role: String,
 }
 
 #[derive(Debug, Insertable)]
@@ -79,6 +94,14 @@ pub struct NewUser {
     pub initials: Option<String>,
 }
 
+/// Name and initials only; the email is deliberately not updatable.
+#[derive(Debug, AsChangeset)]
+#[diesel(table_name = users)]
+pub struct UpdateUser {
+    pub name: Option<String>,
+    pub initials: Option<String>,
+}
+
 // ============================================================================
 // ActionItem
 // ============================================================================
@@ -99,9 +122,12 @@ pub struct ActionItem {
     pub updated_at: DateTime<Utc>,
     pub description: Option<String>,
     pub category_id: i32,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub recurrence: String,
+    pub next_occurrence_id: Option<String>,
 }
 
-#[derive(Debug, Insertable)]
+#[derive(Debug, Clone, Insertable)]
 #[diesel(table_name = action_items)]
 pub struct NewActionItem {
     pub id: String,
@@ -115,6 +141,7 @@ pub struct NewActionItem {
     pub priority: String,
     pub description: Option<String>,
     pub category_id: i32,
+    pub recurrence: String,
 }
 
 #[derive(Debug, AsChangeset)]
@@ -126,6 +153,7 @@ pub struct UpdateActionItem {
     pub owner_id: Option<i32>,
     pub priority: Option<String>,
     pub description: Option<Option<String>>,
+    pub recurrence: Option<String>,
     pub updated_at: Option<DateTime<Utc>>,
 }
 
@@ -133,7 +161,7 @@ pub struct UpdateActionItem {
 // StatusHistory
 // ============================================================================
 
-#[derive(Debug, Queryable, Selectable)]
+#[derive(Debug, Clone, Queryable, Selectable)]
 #[diesel(table_name = status_history)]
 pub struct StatusHistory {
     pub id: i32,
@@ -142,6 +170,7 @@ pub struct StatusHistory {
     pub changed_by_id: i32,
     pub changed_at: DateTime<Utc>,
     pub comment: Option<String>,
+    pub from_status: Option<String>,
 }
 
 #[derive(Debug, Insertable)]
@@ -151,6 +180,7 @@ pub struct NewStatusHistory {
     pub status: String,
     pub changed_by_id: i32,
     pub comment: Option<String>,
+    pub from_status: Option<String>,
 }
 
 // ============================================================================
@@ -176,3 +206,160 @@ pub struct NewNote {
     pub author_id: i32,
     pub content: String,
 }
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = note_mentions)]
+pub struct NewNoteMention {
+    pub note_id: i32,
+    pub user_id: i32,
+}
+
+// ============================================================================
+// ItemAudit
+// ============================================================================
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = item_audit)]
+pub struct ItemAudit {
+    pub id: i32,
+    pub action_item_id: String,
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub changed_by_id: i32,
+    pub changed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = item_audit)]
+pub struct NewItemAudit {
+    pub action_item_id: String,
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub changed_by_id: i32,
+}
+
+// ============================================================================
+// ItemAssignee
+// ============================================================================
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = item_assignees)]
+pub struct ItemAssignee {
+    pub action_item_id: String,
+    pub user_id: i32,
+    pub role: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = item_assignees)]
+pub struct NewItemAssignee {
+    pub action_item_id: String,
+    pub user_id: i32,
+    pub role: String,
+}
+
+// ============================================================================
+// ItemWatcher
+// ============================================================================
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = item_watchers)]
+pub struct NewItemWatcher {
+    pub action_item_id: String,
+    pub user_id: i32,
+}
+
+// ============================================================================
+// ItemLink
+// ============================================================================
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = item_links)]
+pub struct ItemLink {
+    pub from_item_id: String,
+    pub to_item_id: String,
+    pub link_type: String,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = item_links)]
+pub struct NewItemLink {
+    pub from_item_id: String,
+    pub to_item_id: String,
+    pub link_type: String,
+}
+
+// ============================================================================
+// Tag
+// ============================================================================
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = tags)]
+pub struct Tag {
+    pub id: i32,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = tags)]
+pub struct NewTag {
+    pub name: String,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = item_tags)]
+pub struct NewItemTag {
+    pub action_item_id: String,
+    pub tag_id: i32,
+}
+
+// ============================================================================
+// SavedView
+// ============================================================================
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = saved_views)]
+pub struct SavedView {
+    pub id: i32,
+    pub name: String,
+    pub filter_json: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = saved_views)]
+pub struct NewSavedView {
+    pub user_id: i32,
+    pub name: String,
+    pub filter_json: String,
+}
+
+// ============================================================================
+// Webhook
+// ============================================================================
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = webhooks)]
+pub struct Webhook {
+    pub id: i32,
+    pub url: String,
+    pub event_type: String,
+    pub vendor_id: Option<i32>,
+    pub secret: String,
+}
+
+// ============================================================================
+// IdempotencyKey
+// ============================================================================
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = idempotency_keys)]
+pub struct NewIdempotencyKey {
+    pub user_id: i32,
+    pub key: String,
+    pub request_hash: String,
+}
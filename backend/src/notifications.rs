@@ -0,0 +1,252 @@
+//! Email notifications to an item's owner and watchers. Like webhooks, sending runs on its
+//! own task after the change is committed, and failures are logged rather than surfaced.
+
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use std::sync::Arc;
+
+use crate::db::schema::{action_items, item_watchers, users};
+use crate::models::StatusHistory;
+use crate::{AppState, SmtpConfig, SmtpTls};
+
+#[derive(Clone)]
+pub struct Mailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl Mailer {
+    pub fn new(smtp: &SmtpConfig) -> anyhow::Result<Self> {
+        let mut builder = match smtp.tls {
+            SmtpTls::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&smtp.host)?,
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&smtp.host)?,
+            SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&smtp.host),
+        }
+        .port(smtp.port);
+        if let (Some(username), Some(password)) = (&smtp.username, &smtp.password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from: smtp.from.parse()?,
+        })
+    }
+
+    async fn send(&self, to: &Recipient, subject: &str, body: String) -> anyhow::Result<()> {
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(Mailbox::new(Some(to.name.clone()), to.email.parse()?))
+            .subject(subject)
+            .body(body)?;
+        self.transport.send(message).await?;
+        Ok(())
+    }
+}
+
+struct Recipient {
+    name: String,
+    email: String,
+}
+
+/// Who hears about a change: the owner and the watchers, once each, minus whoever made it.
+fn recipient_ids(owner_id: i32, watcher_ids: &[i32], actor_id: i32) -> Vec<i32> {
+    let mut ids: Vec<i32> = std::iter::once(owner_id)
+        .chain(watcher_ids.iter().copied())
+        .filter(|&id| id != actor_id)
+        .collect();
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
+
+/// The item's title and the active users to notify about it.
+async fn recipients_for(
+    state: &AppState,
+    item_id: &str,
+    actor_id: i32,
+) -> anyhow::Result<Option<(String, Vec<Recipient>)>> {
+    let mut conn = state.pool.get().await?;
+
+    let Some((title, owner_id)) = action_items::table
+        .filter(action_items::id.eq(item_id))
+        .filter(action_items::deleted_at.is_null())
+        .select((action_items::title, action_items::owner_id))
+        .first::<(String, i32)>(&mut conn)
+        .await
+        .optional()?
+    else {
+        return Ok(None);
+    };
+    let watcher_ids: Vec<i32> = item_watchers::table
+        .filter(item_watchers::action_item_id.eq(item_id))
+        .select(item_watchers::user_id)
+        .load(&mut conn)
+        .await?;
+
+    let ids = recipient_ids(owner_id, &watcher_ids, actor_id);
+    let recipients = users::table
+        .filter(users::id.eq_any(&ids))
+        .filter(users::active.eq(true))
+        .select((users::name, users::email))
+        .load::<(String, String)>(&mut conn)
+        .await?
+        .into_iter()
+        .map(|(name, email)| Recipient { name, email })
+        .collect();
+    Ok(Some((title, recipients)))
+}
+
+fn item_link(state: &AppState, item_id: &str) -> String {
+    format!(
+        "{}/go/{}",
+        state.config.public_url.trim_end_matches('/'),
+        item_id
+    )
+}
+
+async fn notify(state: &AppState, item_id: &str, actor_id: i32, subject: &str, text: &str) {
+    let Some(mailer) = &state.mailer else {
+        return;
+    };
+    let (title, recipients) = match recipients_for(state, item_id, actor_id).await {
+        Ok(Some(found)) => found,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::error!("Notification recipients for {item_id} failed: {e}");
+            return;
+        }
+    };
+
+    let subject = format!("[{}] {}", item_id, subject);
+    let body = format!(
+        "{}: {}\n\n{}\n\n{}\n",
+        item_id,
+        title,
+        text,
+        item_link(state, item_id)
+    );
+    for recipient in &recipients {
+        if let Err(e) = mailer.send(recipient, &subject, body.clone()).await {
+            tracing::warn!(
+                "Notification about {item_id} to {} failed: {e}",
+                recipient.email
+            );
+        }
+    }
+}
+
+/// Email the item's owner and watchers about a new note.
+pub fn note_added(
+    state: &Arc<AppState>,
+    item_id: &str,
+    actor_id: i32,
+    actor_name: &str,
+    content: &str,
+) {
+    if state.mailer.is_none() {
+        return;
+    }
+    let state = state.clone();
+    let item_id = item_id.to_string();
+    let subject = format!("New note from {}", actor_name);
+    let text = content.to_string();
+    tokio::spawn(async move {
+        notify(&state, &item_id, actor_id, &subject, &text).await;
+    });
+}
+
+/// Email each item's owner and watchers about these freshly written status changes.
+pub fn status_changed(state: &Arc<AppState>, entries: &[StatusHistory], actor_name: &str) {
+    if state.mailer.is_none() || entries.is_empty() {
+        return;
+    }
+    let state = state.clone();
+    let changes: Vec<(String, i32, String, String)> = entries
+        .iter()
+        .map(|e| {
+            let subject = format!("Status changed to {}", e.status);
+            let mut text = format!(
+                "{} changed the status from {} to {}.",
+                actor_name,
+                e.from_status.as_deref().unwrap_or("New"),
+                e.status
+            );
+            if let Some(comment) = &e.comment {
+                text.push_str(&format!("\n\n{}", comment));
+            }
+            (e.action_item_id.clone(), e.changed_by_id, subject, text)
+        })
+        .collect();
+    tokio::spawn(async move {
+        for (item_id, actor_id, subject, text) in changes {
+            notify(&state, &item_id, actor_id, &subject, &text).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::NewItemWatcher;
+    use crate::test_support::TestDb;
+
+    #[test]
+    fn recipients_are_owner_and_watchers_once_without_the_actor() {
+        assert_eq!(recipient_ids(1, &[2, 1, 3], 3), [1, 2]);
+        assert_eq!(recipient_ids(1, &[], 1), Vec::<i32>::new());
+        assert_eq!(recipient_ids(4, &[2, 2], 1), [2, 4]);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn recipients_skip_inactive_users_and_deleted_items() {
+        let db = TestDb::new().await;
+        let owner = db.user("owner@example.org", "Olive Owner", None).await;
+        let watcher = db.user("watcher@example.org", "Walt Watcher", None).await;
+        let departed = db.user("gone@example.org", "Gus Gone", None).await;
+        let actor = db.user("actor@example.org", "Ann Actor", None).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let item = db.item(&category, &actor, &owner).await;
+
+        let mut conn = db.conn().await;
+        for user in [&watcher, &departed, &actor] {
+            diesel::insert_into(item_watchers::table)
+                .values(&NewItemWatcher {
+                    action_item_id: item.id.clone(),
+                    user_id: user.id,
+                })
+                .execute(&mut conn)
+                .await
+                .unwrap();
+        }
+        diesel::update(users::table.filter(users::id.eq(departed.id)))
+            .set(users::active.eq(false))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let (title, recipients) = recipients_for(&db.state, &item.id, actor.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(title, item.title);
+        let mut emails: Vec<&str> = recipients.iter().map(|r| r.email.as_str()).collect();
+        emails.sort_unstable();
+        assert_eq!(emails, ["owner@example.org", "watcher@example.org"]);
+
+        diesel::update(action_items::table.filter(action_items::id.eq(&item.id)))
+            .set(action_items::deleted_at.eq(Some(chrono::Utc::now())))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        assert!(recipients_for(&db.state, &item.id, actor.id)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
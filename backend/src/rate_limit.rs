@@ -0,0 +1,140 @@
+//! In-memory per-IP token buckets. State lives in this process only, which is fine for a
+//! single backend instance; limits reset on restart.
+
+use axum::http::HeaderMap;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Past this many tracked IPs, full (idle) buckets are dropped so the map can't grow
+/// without bound.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+pub struct RateLimiter {
+    capacity: f64,
+    refill_per_sec: f64,
+    buckets: Mutex<HashMap<IpAddr, Bucket>>,
+}
+
+impl RateLimiter {
+    /// Allow bursts of up to `per_minute` requests, refilling at `per_minute` a minute.
+    pub fn per_minute(per_minute: u32) -> Self {
+        Self {
+            capacity: per_minute as f64,
+            refill_per_sec: per_minute as f64 / 60.0,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take a token for `ip`, or return how long until one is available.
+    pub fn check(&self, ip: IpAddr) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+
+        if buckets.len() >= PRUNE_THRESHOLD {
+            let (capacity, refill) = (self.capacity, self.refill_per_sec);
+            buckets.retain(|_, b| {
+                b.tokens + now.duration_since(b.updated).as_secs_f64() * refill < capacity
+            });
+        }
+
+        let bucket = buckets.entry(ip).or_insert(Bucket {
+            tokens: self.capacity,
+            updated: now,
+        });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            let wait = (1.0 - bucket.tokens) / self.refill_per_sec;
+            Err(Duration::from_secs_f64(wait))
+        }
+    }
+}
+
+/// The client's address. `X-Forwarded-For` is only believed when the peer is one of
+/// `trusted_proxies`; anyone else could send any value in it. Behind trusted proxies the
+/// client is the nearest hop they didn't add themselves.
+pub fn client_ip(headers: &HeaderMap, peer: SocketAddr, trusted_proxies: &[IpAddr]) -> IpAddr {
+    let mut client = peer.ip();
+    if !trusted_proxies.contains(&client) {
+        return client;
+    }
+    let Some(forwarded) = headers.get("x-forwarded-for").and_then(|v| v.to_str().ok()) else {
+        return client;
+    };
+    for hop in forwarded.rsplit(',') {
+        let Ok(ip) = hop.trim().parse() else {
+            break;
+        };
+        client = ip;
+        if !trusted_proxies.contains(&ip) {
+            break;
+        }
+    }
+    client
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allows_a_burst_then_says_how_long_to_wait() {
+        let limiter = RateLimiter::per_minute(5);
+        let ip: IpAddr = "192.0.2.1".parse().unwrap();
+        for _ in 0..5 {
+            assert!(limiter.check(ip).is_ok());
+        }
+        let wait = limiter.check(ip).unwrap_err();
+        assert!(
+            wait > Duration::from_millis(11_900) && wait <= Duration::from_secs(12),
+            "waited {:?}",
+            wait
+        );
+
+        // Other clients have their own bucket
+        assert!(limiter.check("192.0.2.2".parse().unwrap()).is_ok());
+    }
+
+    #[test]
+    fn forwarded_for_is_ignored_unless_the_peer_is_a_trusted_proxy() {
+        let proxy: SocketAddr = "10.0.0.1:4000".parse().unwrap();
+        let trusted: Vec<IpAddr> = vec!["10.0.0.1".parse().unwrap(), "10.0.0.2".parse().unwrap()];
+        let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(client_ip(&headers, proxy, &trusted), proxy.ip());
+
+        headers.insert(
+            "x-forwarded-for",
+            "203.0.113.9, 198.51.100.7".parse().unwrap(),
+        );
+        assert_eq!(client_ip(&headers, proxy, &trusted), ip("198.51.100.7"));
+
+        // A client talking to us directly can't pick its address
+        let direct: SocketAddr = "192.0.2.50:5000".parse().unwrap();
+        assert_eq!(client_ip(&headers, direct, &trusted), direct.ip());
+        assert_eq!(client_ip(&headers, proxy, &[]), proxy.ip());
+
+        // Hops added by our own proxy chain are skipped
+        headers.insert(
+            "x-forwarded-for",
+            "203.0.113.9, 198.51.100.7, 10.0.0.2".parse().unwrap(),
+        );
+        assert_eq!(client_ip(&headers, proxy, &trusted), ip("198.51.100.7"));
+
+        headers.insert("x-forwarded-for", "not-an-ip".parse().unwrap());
+        assert_eq!(client_ip(&headers, proxy, &trusted), proxy.ip());
+    }
+}
@@ -4,17 +4,23 @@ use axum::{
     response::IntoResponse,
     Json,
 };
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use diesel::prelude::*;
 use diesel::sql_types::{Timestamptz, Varchar};
-use diesel_async::RunQueryDsl;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use serde::Deserialize;
-use shared::{ActivityEntry, ActivityEventType, ApiError};
+use shared::{ActivityEntry, ActivityEventType, ActivityResponse, ApiError};
 use std::sync::Arc;
 
 use super::AuthUser;
 use crate::AppState;
 
+/// Window used when the client has no `since` of its own (e.g. a first-time user).
+const DEFAULT_WINDOW_DAYS: i64 = 7;
+
+/// Note previews are cut to this many characters, with "..." when something was cut.
+const NOTE_PREVIEW_CHARS: usize = 120;
+
 #[derive(Debug, Deserialize)]
 pub struct ActivityQuery {
     pub since: Option<String>,
@@ -47,12 +53,12 @@ pub async fn activity(
         Err(resp) => return resp,
     };
 
-    let since: DateTime<Utc> = query
+    let explicit_since: Option<DateTime<Utc>> = query
         .since
         .as_deref()
         .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
-        .map(|dt| dt.with_timezone(&Utc))
-        .unwrap_or_else(|| DateTime::from_timestamp(0, 0).unwrap());
+        .map(|dt| dt.with_timezone(&Utc));
+    let since = explicit_since.unwrap_or_else(|| Utc::now() - Duration::days(DEFAULT_WINDOW_DAYS));
 
     let limit = query.limit.unwrap_or(50).min(200);
 
@@ -64,12 +70,15 @@ pub async fn activity(
                 ai.title AS item_title,
                 u.name AS actor_name,
                 'note_added' AS event_type,
-                LEFT(n.content, 120) AS detail
+                -- One character past the limit, so truncation is detectable below
+                LEFT(n.content, 121) AS detail,
+                n.id AS source_id
             FROM notes n
             INNER JOIN users u ON u.id = n.author_id
             INNER JOIN action_items ai ON ai.id = n.action_item_id
             WHERE n.author_id != $1
               AND n.created_at > $2
+              AND ai.deleted_at IS NULL
         )
         UNION ALL
         (
@@ -79,21 +88,25 @@ pub async fn activity(
                 ai.title AS item_title,
                 u.name AS actor_name,
                 'status_changed' AS event_type,
-                sh.status AS detail
+                sh.status AS detail,
+                sh.id AS source_id
             FROM status_history sh
             INNER JOIN users u ON u.id = sh.changed_by_id
             INNER JOIN action_items ai ON ai.id = sh.action_item_id
             WHERE sh.changed_by_id != $1
               AND sh.changed_at > $2
+              AND ai.deleted_at IS NULL
         )
-        ORDER BY timestamp DESC
+        -- Tiebreakers keep entries with equal timestamps in a stable order
+        ORDER BY timestamp DESC, event_type, item_id, source_id DESC
         LIMIT $3
     "#;
 
-    let rows: Vec<RawActivityRow> = match diesel::sql_query(sql)
+    // Fetch one extra row to learn whether the limit truncated the feed.
+    let mut rows: Vec<RawActivityRow> = match diesel::sql_query(sql)
         .bind::<diesel::sql_types::Int4, _>(auth.user_id)
         .bind::<Timestamptz, _>(since)
-        .bind::<diesel::sql_types::BigInt, _>(limit)
+        .bind::<diesel::sql_types::BigInt, _>(limit + 1)
         .load(&mut conn)
         .await
     {
@@ -108,33 +121,292 @@ pub async fn activity(
         }
     };
 
-    let entries: Vec<ActivityEntry> = rows
-        .into_iter()
-        .map(|row| {
-            let event_type = match row.event_type.as_str() {
-                "status_changed" => ActivityEventType::StatusChanged,
-                _ => ActivityEventType::NoteAdded,
-            };
-            let detail = match &event_type {
-                ActivityEventType::StatusChanged => format!("→ {}", row.detail),
-                ActivityEventType::NoteAdded => {
-                    if row.detail.len() >= 120 {
-                        format!("{}...", row.detail)
-                    } else {
-                        row.detail
-                    }
-                }
-            };
-            ActivityEntry {
-                timestamp: row.timestamp,
-                item_id: row.item_id,
-                item_title: row.item_title,
-                actor_name: row.actor_name,
-                event_type,
-                detail,
+    let mut has_more = rows.len() as i64 > limit;
+    rows.truncate(limit as usize);
+
+    // The default window hides anything older, so report whether such activity exists.
+    if !has_more && explicit_since.is_none() {
+        has_more = match older_activity_exists(&mut conn, auth.user_id, since).await {
+            Ok(exists) => exists,
+            Err(e) => {
+                tracing::error!("Older activity check failed: {e}");
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiError::internal_error("Failed to load activity")),
+                )
+                    .into_response();
+            }
+        };
+    }
+
+    let entries: Vec<ActivityEntry> = rows.into_iter().map(to_activity_entry).collect();
+
+    Json(ActivityResponse { entries, has_more }).into_response()
+}
+
+fn to_activity_entry(row: RawActivityRow) -> ActivityEntry {
+    let event_type = match row.event_type.as_str() {
+        "status_changed" => ActivityEventType::StatusChanged,
+        _ => ActivityEventType::NoteAdded,
+    };
+    let detail = match &event_type {
+        ActivityEventType::StatusChanged => format!("→ {}", row.detail),
+        ActivityEventType::NoteAdded => {
+            // Characters, not bytes: LEFT() counts characters
+            if row.detail.chars().count() > NOTE_PREVIEW_CHARS {
+                let preview: String = row.detail.chars().take(NOTE_PREVIEW_CHARS).collect();
+                format!("{}...", preview)
+            } else {
+                row.detail
             }
-        })
-        .collect();
+        }
+    };
+    ActivityEntry {
+        timestamp: row.timestamp,
+        item_id: row.item_id,
+        item_title: row.item_title,
+        actor_name: row.actor_name,
+        event_type,
+        detail,
+    }
+}
+
+/// The `limit` most recent notes and status changes made by `user_id` themselves,
+/// newest first. The mirror image of the feed above, which leaves them out.
+pub(super) async fn own_activity(
+    conn: &mut AsyncPgConnection,
+    user_id: i32,
+    limit: i64,
+) -> QueryResult<Vec<ActivityEntry>> {
+    let sql = r#"
+        (
+            SELECT
+                n.created_at AS timestamp,
+                n.action_item_id AS item_id,
+                ai.title AS item_title,
+                u.name AS actor_name,
+                'note_added' AS event_type,
+                LEFT(n.content, 121) AS detail,
+                n.id AS source_id
+            FROM notes n
+            INNER JOIN users u ON u.id = n.author_id
+            INNER JOIN action_items ai ON ai.id = n.action_item_id
+            WHERE n.author_id = $1
+              AND ai.deleted_at IS NULL
+        )
+        UNION ALL
+        (
+            SELECT
+                sh.changed_at AS timestamp,
+                sh.action_item_id AS item_id,
+                ai.title AS item_title,
+                u.name AS actor_name,
+                'status_changed' AS event_type,
+                sh.status AS detail,
+                sh.id AS source_id
+            FROM status_history sh
+            INNER JOIN users u ON u.id = sh.changed_by_id
+            INNER JOIN action_items ai ON ai.id = sh.action_item_id
+            WHERE sh.changed_by_id = $1
+              AND ai.deleted_at IS NULL
+        )
+        ORDER BY timestamp DESC, event_type, item_id, source_id DESC
+        LIMIT $2
+    "#;
+
+    let rows: Vec<RawActivityRow> = diesel::sql_query(sql)
+        .bind::<diesel::sql_types::Int4, _>(user_id)
+        .bind::<diesel::sql_types::BigInt, _>(limit)
+        .load(conn)
+        .await?;
+
+    Ok(rows.into_iter().map(to_activity_entry).collect())
+}
+
+#[derive(Debug, QueryableByName)]
+struct ExistsRow {
+    #[diesel(sql_type = diesel::sql_types::Bool)]
+    exists: bool,
+}
+
+/// Whether any activity by other users happened at or before `before`.
+async fn older_activity_exists(
+    conn: &mut AsyncPgConnection,
+    user_id: i32,
+    before: DateTime<Utc>,
+) -> QueryResult<bool> {
+    let sql = r#"
+        SELECT (
+            EXISTS (SELECT 1 FROM notes WHERE author_id != $1 AND created_at <= $2)
+            OR EXISTS (SELECT 1 FROM status_history WHERE changed_by_id != $1 AND changed_at <= $2)
+        ) AS exists
+    "#;
+
+    let row: ExistsRow = diesel::sql_query(sql)
+        .bind::<diesel::sql_types::Int4, _>(user_id)
+        .bind::<Timestamptz, _>(before)
+        .get_result(conn)
+        .await?;
+
+    Ok(row.exists)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::{notes, status_history};
+    use crate::models::{ActionItem, User};
+    use crate::test_support::{auth, json, query, TestDb};
+
+    async fn status_change(
+        db: &TestDb,
+        item: &ActionItem,
+        by: &User,
+        status: &str,
+        at: DateTime<Utc>,
+    ) {
+        diesel::insert_into(status_history::table)
+            .values((
+                status_history::action_item_id.eq(&item.id),
+                status_history::status.eq(status),
+                status_history::changed_by_id.eq(by.id),
+                status_history::changed_at.eq(at),
+            ))
+            .execute(&mut db.conn().await)
+            .await
+            .unwrap();
+    }
 
-    Json(entries).into_response()
+    /// `user`'s activity feed for the query string `params`.
+    async fn feed(db: &TestDb, user: &User, params: &str) -> ActivityResponse {
+        json(
+            activity(State(db.state.clone()), query(params), auth(user))
+                .await
+                .into_response(),
+        )
+        .await
+    }
+
+    fn since(at: DateTime<Utc>) -> String {
+        format!("since={}", at.format("%Y-%m-%dT%H:%M:%SZ"))
+    }
+
+    fn raw_row(event_type: &str, detail: &str) -> RawActivityRow {
+        RawActivityRow {
+            timestamp: Utc::now(),
+            item_id: "AAA-001".to_string(),
+            item_title: "Item 1".to_string(),
+            actor_name: "Ada Lovelace".to_string(),
+            event_type: event_type.to_string(),
+            detail: detail.to_string(),
+        }
+    }
+
+    #[test]
+    fn status_changes_are_shown_after_a_real_arrow() {
+        let entry = to_activity_entry(raw_row("status_changed", "Blocked"));
+        assert!(entry.detail.starts_with('\u{2192}'), "{:?}", entry.detail);
+        assert_eq!(entry.detail, "\u{2192} Blocked");
+    }
+
+    #[test]
+    fn note_previews_get_a_single_ellipsis_only_when_cut() {
+        // The query fetches one character past the preview so a cut can be detected
+        let fetched = "é".repeat(NOTE_PREVIEW_CHARS + 1);
+        let entry = to_activity_entry(raw_row("note_added", &fetched));
+        assert_eq!(
+            entry.detail,
+            format!("{}...", "é".repeat(NOTE_PREVIEW_CHARS))
+        );
+
+        let whole = "é".repeat(NOTE_PREVIEW_CHARS);
+        let entry = to_activity_entry(raw_row("note_added", &whole));
+        assert_eq!(entry.detail, whole);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn default_window_is_the_last_week_and_has_more_reports_older_activity() {
+        let db = TestDb::new().await;
+        let viewer = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let other = db.user("ada@example.org", "Ada Lovelace", Some("AL")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let item = db.item(&category, &viewer, &viewer).await;
+        let now = Utc::now();
+        status_change(&db, &item, &other, "In Progress", now - Duration::days(3)).await;
+        status_change(&db, &item, &other, "Blocked", now - Duration::days(10)).await;
+        status_change(&db, &item, &viewer, "Complete", now - Duration::days(1)).await;
+
+        // Only the other user's change from this week; the older one is past the window
+        let recent = feed(&db, &viewer, "").await;
+        let details: Vec<&str> = recent.entries.iter().map(|e| e.detail.as_str()).collect();
+        assert_eq!(details, ["→ In Progress"]);
+        assert!(recent.has_more);
+
+        let everything = feed(&db, &viewer, &since(now - Duration::days(30))).await;
+        assert_eq!(everything.entries.len(), 2);
+        assert!(!everything.has_more);
+
+        let first_page = feed(
+            &db,
+            &viewer,
+            &format!("{}&limit=1", since(now - Duration::days(30))),
+        )
+        .await;
+        assert_eq!(first_page.entries.len(), 1);
+        assert!(first_page.has_more);
+
+        // Nothing by anyone else is older than the window for Ada
+        let theirs = feed(&db, &other, "").await;
+        assert_eq!(theirs.entries.len(), 1);
+        assert!(!theirs.has_more);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn entries_at_the_same_moment_come_back_in_a_stable_order() {
+        let db = TestDb::new().await;
+        let viewer = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let other = db.user("ada@example.org", "Ada Lovelace", Some("AL")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let first = db.item(&category, &viewer, &viewer).await;
+        let second = db.item(&category, &viewer, &viewer).await;
+        let at = Utc::now() - Duration::hours(1);
+        for item in [&second, &first] {
+            status_change(&db, item, &other, "In Progress", at).await;
+            status_change(&db, item, &other, "Blocked", at).await;
+            diesel::insert_into(notes::table)
+                .values((
+                    notes::action_item_id.eq(&item.id),
+                    notes::note_date.eq(at.date_naive()),
+                    notes::author_id.eq(other.id),
+                    notes::content.eq("Checked"),
+                    notes::created_at.eq(at),
+                ))
+                .execute(&mut db.conn().await)
+                .await
+                .unwrap();
+        }
+
+        // Notes before status changes, then by item, then the later-recorded change first
+        let expected = [
+            ("AAA-001", "Checked"),
+            ("AAA-002", "Checked"),
+            ("AAA-001", "→ Blocked"),
+            ("AAA-001", "→ In Progress"),
+            ("AAA-002", "→ Blocked"),
+            ("AAA-002", "→ In Progress"),
+        ];
+        for _ in 0..3 {
+            let response = feed(&db, &viewer, "").await;
+            let entries: Vec<(&str, &str)> = response
+                .entries
+                .iter()
+                .map(|e| (e.item_id.as_str(), e.detail.as_str()))
+                .collect();
+            assert_eq!(entries, expected);
+        }
+    }
 }
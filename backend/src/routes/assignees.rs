@@ -0,0 +1,343 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use shared::{AddAssignee, ApiError, AssigneeResponse};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::db::schema::{action_items, item_assignees, users};
+use crate::models::{ItemAssignee, NewItemAssignee, User};
+use crate::AppState;
+
+use super::AuthUser;
+
+const DEFAULT_ROLE: &str = "secondary";
+/// Matches the `role` column width.
+const MAX_ROLE_LENGTH: usize = 50;
+
+fn to_assignee_response(assignee: ItemAssignee, user: User) -> AssigneeResponse {
+    AssigneeResponse {
+        user_id: assignee.user_id,
+        user_name: user.name,
+        user_initials: user.initials,
+        role: assignee.role,
+        created_at: assignee.created_at,
+    }
+}
+
+/// Secondary assignees for each of `item_ids`, in the order they were added. Items
+/// without any are absent from the map.
+pub(super) async fn assignees_by_item(
+    conn: &mut AsyncPgConnection,
+    item_ids: &[&str],
+) -> QueryResult<HashMap<String, Vec<AssigneeResponse>>> {
+    let rows: Vec<(ItemAssignee, User)> = item_assignees::table
+        .inner_join(users::table)
+        .filter(item_assignees::action_item_id.eq_any(item_ids))
+        .order(item_assignees::created_at)
+        .select((ItemAssignee::as_select(), User::as_select()))
+        .load(conn)
+        .await?;
+
+    let mut map: HashMap<String, Vec<AssigneeResponse>> = HashMap::new();
+    for (assignee, user) in rows {
+        map.entry(assignee.action_item_id.clone())
+            .or_default()
+            .push(to_assignee_response(assignee, user));
+    }
+    Ok(map)
+}
+
+/// Secondary assignees of a single item; empty if the lookup fails.
+pub(super) async fn item_assignees(
+    conn: &mut AsyncPgConnection,
+    item_id: &str,
+) -> Vec<AssigneeResponse> {
+    assignees_by_item(conn, &[item_id])
+        .await
+        .ok()
+        .and_then(|mut map| map.remove(item_id))
+        .unwrap_or_default()
+}
+
+pub async fn list(
+    State(state): State<Arc<AppState>>,
+    Path(item_id): Path<String>,
+    _auth: AuthUser,
+) -> impl IntoResponse {
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    if let Err(resp) = super::ensure_item_exists(&mut conn, &item_id).await {
+        return resp;
+    }
+
+    match assignees_by_item(&mut conn, &[item_id.as_str()]).await {
+        Ok(mut map) => Json(map.remove(&item_id).unwrap_or_default()).into_response(),
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::internal_error("Failed to fetch assignees")),
+        )
+            .into_response(),
+    }
+}
+
+pub async fn add(
+    State(state): State<Arc<AppState>>,
+    Path(item_id): Path<String>,
+    _auth: AuthUser,
+    Json(payload): Json<AddAssignee>,
+) -> impl IntoResponse {
+    let role = payload
+        .role
+        .as_deref()
+        .map(str::trim)
+        .unwrap_or(DEFAULT_ROLE)
+        .to_string();
+    if role.is_empty() || role.chars().count() > MAX_ROLE_LENGTH {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::validation_error(format!(
+                "role must be 1-{} characters",
+                MAX_ROLE_LENGTH
+            ))),
+        )
+            .into_response();
+    }
+    // "primary" is what owner_id means; keep the two from drifting apart
+    if role.eq_ignore_ascii_case("primary") {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::validation_error(
+                "The primary owner is set with owner_id",
+            )),
+        )
+            .into_response();
+    }
+
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    let owner_id: i32 = match action_items::table
+        .filter(action_items::id.eq(&item_id))
+        .select(action_items::owner_id)
+        .first(&mut conn)
+        .await
+    {
+        Ok(o) => o,
+        Err(diesel::NotFound) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiError::not_found(format!(
+                    "Action item {} not found",
+                    item_id
+                ))),
+            )
+                .into_response()
+        }
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to fetch item")),
+            )
+                .into_response()
+        }
+    };
+
+    if payload.user_id == owner_id {
+        return (
+            StatusCode::CONFLICT,
+            Json(ApiError::conflict(
+                "User is already the item's primary owner",
+            )),
+        )
+            .into_response();
+    }
+
+    let user: User = match users::table
+        .filter(users::id.eq(payload.user_id))
+        .select(User::as_select())
+        .first(&mut conn)
+        .await
+    {
+        Ok(u) => u,
+        Err(diesel::NotFound) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::validation_error(
+                    "user_id does not refer to an existing record",
+                )),
+            )
+                .into_response()
+        }
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to fetch user")),
+            )
+                .into_response()
+        }
+    };
+
+    let new_assignee = NewItemAssignee {
+        action_item_id: item_id,
+        user_id: payload.user_id,
+        role,
+    };
+
+    let assignee: ItemAssignee = match diesel::insert_into(item_assignees::table)
+        .values(&new_assignee)
+        .returning(ItemAssignee::as_returning())
+        .get_result(&mut conn)
+        .await
+    {
+        Ok(a) => a,
+        Err(diesel::result::Error::DatabaseError(
+            diesel::result::DatabaseErrorKind::UniqueViolation,
+            _,
+        )) => {
+            return (
+                StatusCode::CONFLICT,
+                Json(ApiError::conflict("User is already assigned to this item")),
+            )
+                .into_response()
+        }
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to add assignee")),
+            )
+                .into_response()
+        }
+    };
+
+    (
+        StatusCode::CREATED,
+        Json(to_assignee_response(assignee, user)),
+    )
+        .into_response()
+}
+
+pub async fn remove(
+    State(state): State<Arc<AppState>>,
+    Path((item_id, user_id)): Path<(String, i32)>,
+    _auth: AuthUser,
+) -> impl IntoResponse {
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    let deleted = diesel::delete(
+        item_assignees::table
+            .filter(item_assignees::action_item_id.eq(&item_id))
+            .filter(item_assignees::user_id.eq(user_id)),
+    )
+    .execute(&mut conn)
+    .await;
+
+    match deleted {
+        Ok(0) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiError::not_found(format!(
+                "User {} is not assigned to {}",
+                user_id, item_id
+            ))),
+        )
+            .into_response(),
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::internal_error("Failed to remove assignee")),
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{auth, json, query, TestDb};
+    use shared::ItemListResponse;
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn secondary_assignee_is_listed_and_matched_by_owner_filter() {
+        let db = TestDb::new().await;
+        let mike = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let ada = db.user("ada@example.org", "Ada Lovelace", Some("AL")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let item = db.item(&category, &mike, &mike).await;
+
+        let add_as = |user_id| {
+            add(
+                State(db.state.clone()),
+                Path(item.id.clone()),
+                auth(&mike),
+                Json(AddAssignee {
+                    user_id,
+                    role: None,
+                }),
+            )
+        };
+        let response = add_as(ada.id).await.into_response();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let added: AssigneeResponse = json(response).await;
+        assert_eq!(added.user_name, "Ada Lovelace");
+        assert_eq!(added.role, DEFAULT_ROLE);
+
+        assert_eq!(
+            add_as(ada.id).await.into_response().status(),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            add_as(mike.id).await.into_response().status(),
+            StatusCode::CONFLICT
+        );
+
+        let owned_by_ada = |params: &'static str| {
+            let db = &db;
+            let ada = &ada;
+            async move {
+                let params = format!("owner_id={}{}", ada.id, params);
+                let response = super::super::items::list_all(
+                    State(db.state.clone()),
+                    query(&params),
+                    query(&params),
+                    auth(ada),
+                )
+                .await
+                .into_response();
+                assert_eq!(response.status(), StatusCode::OK);
+                json::<ItemListResponse>(response).await.items
+            }
+        };
+        assert!(owned_by_ada("").await.is_empty());
+        let items = owned_by_ada("&include_assignees=true").await;
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].id, item.id);
+        assert_eq!(items[0].owner_id, mike.id);
+        let assignees: Vec<i32> = items[0].assignees.iter().map(|a| a.user_id).collect();
+        assert_eq!(assignees, [ada.id]);
+
+        let response = remove(
+            State(db.state.clone()),
+            Path((item.id.clone(), ada.id)),
+            auth(&mike),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(owned_by_ada("&include_assignees=true").await.is_empty());
+    }
+}
@@ -0,0 +1,177 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use shared::{ApiError, ItemAuditEntry};
+use std::sync::Arc;
+
+use crate::db::schema::{item_audit, users};
+use crate::models::{ActionItem, ItemAudit, NewItemAudit, User};
+use crate::AppState;
+
+use super::AuthUser;
+
+/// One audit row per editable field whose value differs between `old` and `new`.
+pub(super) fn item_changes(
+    old: &ActionItem,
+    new: &ActionItem,
+    changed_by_id: i32,
+) -> Vec<NewItemAudit> {
+    let fields: [(&str, Option<String>, Option<String>); 7] = [
+        ("title", Some(old.title.clone()), Some(new.title.clone())),
+        (
+            "description",
+            old.description.clone(),
+            new.description.clone(),
+        ),
+        (
+            "due_date",
+            old.due_date.map(|d| d.to_string()),
+            new.due_date.map(|d| d.to_string()),
+        ),
+        (
+            "owner_id",
+            Some(old.owner_id.to_string()),
+            Some(new.owner_id.to_string()),
+        ),
+        (
+            "priority",
+            Some(old.priority.clone()),
+            Some(new.priority.clone()),
+        ),
+        (
+            "category_id",
+            Some(old.category_id.to_string()),
+            Some(new.category_id.to_string()),
+        ),
+        (
+            "recurrence",
+            Some(old.recurrence.clone()),
+            Some(new.recurrence.clone()),
+        ),
+    ];
+
+    fields
+        .into_iter()
+        .filter(|(_, old_value, new_value)| old_value != new_value)
+        .map(|(field, old_value, new_value)| NewItemAudit {
+            action_item_id: new.id.clone(),
+            field: field.to_string(),
+            old_value,
+            new_value,
+            changed_by_id,
+        })
+        .collect()
+}
+
+pub async fn list(
+    State(state): State<Arc<AppState>>,
+    Path(item_id): Path<String>,
+    _auth: AuthUser,
+) -> impl IntoResponse {
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    let entries: Vec<(ItemAudit, User)> = match item_audit::table
+        .inner_join(users::table.on(users::id.eq(item_audit::changed_by_id)))
+        .filter(item_audit::action_item_id.eq(&item_id))
+        .order((item_audit::changed_at.desc(), item_audit::id.desc()))
+        .select((ItemAudit::as_select(), User::as_select()))
+        .load(&mut conn)
+        .await
+    {
+        Ok(e) => e,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to fetch audit trail")),
+            )
+                .into_response()
+        }
+    };
+
+    // Only an empty result needs a second query to tell "no entries" from "no such item"
+    if entries.is_empty() {
+        if let Err(resp) = super::ensure_item_exists(&mut conn, &item_id).await {
+            return resp;
+        }
+    }
+
+    let result: Vec<_> = entries
+        .into_iter()
+        .map(|(a, u)| ItemAuditEntry {
+            id: a.id,
+            action_item_id: a.action_item_id,
+            field: a.field,
+            old_value: a.old_value,
+            new_value: a.new_value,
+            changed_by_id: a.changed_by_id,
+            changed_by_name: u.name,
+            changed_at: a.changed_at,
+        })
+        .collect();
+
+    Json(result).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes::items;
+    use crate::test_support::{auth, json, TestDb};
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn priority_update_writes_exactly_one_audit_row() {
+        let db = TestDb::new().await;
+        let mike = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let item = db.item(&category, &mike, &mike).await;
+
+        // The title and owner are sent unchanged and so are not audited
+        let response = items::update(
+            State(db.state.clone()),
+            Path(item.id.clone()),
+            auth(&mike),
+            Json(
+                serde_json::from_value(serde_json::json!({
+                    "title": item.title,
+                    "owner_id": mike.id,
+                    "priority": "High",
+                }))
+                .unwrap(),
+            ),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = list(State(db.state.clone()), Path(item.id.clone()), auth(&mike))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let entries: Vec<ItemAuditEntry> = json(response).await;
+        let changes: Vec<_> = entries
+            .iter()
+            .map(|e| {
+                (
+                    e.field.as_str(),
+                    e.old_value.as_deref(),
+                    e.new_value.as_deref(),
+                    e.changed_by_name.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            [("priority", Some("Medium"), Some("High"), "Mike Fox")]
+        );
+    }
+}
@@ -1,20 +1,21 @@
 use axum::{
     extract::{Query, State},
-    http::{header, StatusCode},
+    http::{header, HeaderMap, StatusCode},
     response::{IntoResponse, Redirect, Response},
     Json,
 };
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use diesel::prelude::*;
-use diesel_async::RunQueryDsl;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use jsonwebtoken::{encode, EncodingKey, Header};
 use rand::Rng;
 use serde::Deserialize;
+use shared::ApiError;
 use std::sync::Arc;
 
 use crate::db::schema::users;
 use crate::models::{NewUser, User};
-use crate::AppState;
+use crate::{AppConfig, AppState, DbPool};
 
 use super::{AuthUser, Claims};
 
@@ -38,7 +39,11 @@ struct GoogleUserInfo {
 pub async fn login(State(state): State<Arc<AppState>>) -> Response {
     if state.config.dev_mode {
         // In dev mode, just redirect to callback with a fake code
-        return Redirect::to("/auth/callback?code=dev").into_response();
+        return Redirect::to(&format!(
+            "{}/auth/callback?code=dev",
+            state.config.base_path
+        ))
+        .into_response();
     }
 
     let client_id = match &state.config.google_client_id {
@@ -119,32 +124,22 @@ pub async fn callback(
             Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
         };
 
-        // Get or create dev user
+        // The same user the AuthUser extractor uses, seeded at startup
+        let Some(dev_user_id) = state.dev_user.as_ref().map(|u| u.user_id) else {
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Dev user not set up").into_response();
+        };
         let dev_user: User = match users::table
-            .filter(users::email.eq("dev@localhost"))
+            .find(dev_user_id)
+            .select(User::as_select())
             .first(&mut conn)
             .await
         {
             Ok(user) => user,
-            Err(diesel::NotFound) => {
-                // Create dev user
-                let new_user = NewUser {
-                    email: "dev@localhost".to_string(),
-                    name: "Dev User".to_string(),
-                    initials: Some("DV".to_string()),
-                };
-                diesel::insert_into(users::table)
-                    .values(&new_user)
-                    .returning(User::as_returning())
-                    .get_result(&mut conn)
-                    .await
-                    .unwrap()
-            }
             Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response(),
         };
 
-        let token = create_jwt(&state.config.jwt_secret, &dev_user);
-        return set_token_cookie_and_redirect(token, &state.config.public_url);
+        let (token, expires_at) = create_jwt(&state.config, &dev_user, Utc::now());
+        return set_token_cookie_and_redirect(&token, expires_at, &state.config);
     }
 
     // Exchange code for token
@@ -208,6 +203,7 @@ pub async fn callback(
             .allowed_email_domains
             .contains(&domain.to_string())
         {
+            tracing::warn!(domain, "rejected login from disallowed email domain");
             return (StatusCode::FORBIDDEN, "Email domain not allowed").into_response();
         }
     }
@@ -225,11 +221,22 @@ pub async fn callback(
         }
     };
 
-    tracing::info!("Looking up user by email: {}", user_info.email);
+    let user = match oauth_user(&mut conn, &user_info).await {
+        Ok(user) => user,
+        Err(resp) => return resp,
+    };
+
+    let (token, expires_at) = create_jwt(&state.config, &user, Utc::now());
+    set_token_cookie_and_redirect(&token, expires_at, &state.config)
+}
+
+/// The user signing in as `info`, created on first login. Deactivated users are refused.
+async fn oauth_user(conn: &mut AsyncPgConnection, info: &GoogleUserInfo) -> Result<User, Response> {
+    tracing::info!("Looking up user by email: {}", info.email);
 
     let user: User = match users::table
-        .filter(users::email.eq(&user_info.email))
-        .first::<User>(&mut conn)
+        .filter(users::email.eq(&info.email))
+        .first::<User>(conn)
         .await
     {
         Ok(user) => {
@@ -237,26 +244,20 @@ pub async fn callback(
             user
         }
         Err(diesel::NotFound) => {
-            tracing::info!("User not found, creating new user for {}", user_info.email);
+            tracing::info!("User not found, creating new user for {}", info.email);
             // Create new user
-            let initials = user_info
-                .name
-                .split_whitespace()
-                .filter_map(|w| w.chars().next())
-                .take(2)
-                .collect::<String>()
-                .to_uppercase();
+            let initials = super::users::initials_from_name(&info.name);
 
             let new_user = NewUser {
-                email: user_info.email.clone(),
-                name: user_info.name.clone(),
+                email: info.email.clone(),
+                name: info.name.clone(),
                 initials: Some(initials),
             };
 
             match diesel::insert_into(users::table)
                 .values(&new_user)
                 .returning(User::as_returning())
-                .get_result(&mut conn)
+                .get_result(conn)
                 .await
             {
                 Ok(user) => {
@@ -265,26 +266,29 @@ pub async fn callback(
                 }
                 Err(e) => {
                     tracing::error!("Failed to create user: {e}");
-                    return (
+                    return Err((
                         StatusCode::INTERNAL_SERVER_ERROR,
                         format!("Failed to create user: {e}"),
                     )
-                        .into_response();
+                        .into_response());
                 }
             }
         }
         Err(e) => {
             tracing::error!("Database query error looking up user: {e}");
-            return (
+            return Err((
                 StatusCode::INTERNAL_SERVER_ERROR,
                 format!("Database error: {e}"),
             )
-                .into_response();
+                .into_response());
         }
     };
 
-    let token = create_jwt(&state.config.jwt_secret, &user);
-    set_token_cookie_and_redirect(token, &state.config.public_url)
+    if !user.active {
+        tracing::warn!(user_id = user.id, "rejected login from deactivated user");
+        return Err((StatusCode::FORBIDDEN, "User is deactivated").into_response());
+    }
+    Ok(user)
 }
 
 pub async fn logout() -> Response {
@@ -298,50 +302,327 @@ pub async fn logout() -> Response {
         .into_response()
 }
 
+const DEV_USER_EMAIL: &str = "dev@localhost";
+
+/// Find the user dev mode acts as: the `DEV_USER_ID` user, which must exist, or else
+/// `dev@localhost`, created on first run so a fresh database works without seeding.
+pub async fn ensure_dev_user(pool: &DbPool, config: &AppConfig) -> anyhow::Result<AuthUser> {
+    let mut conn = pool.get().await?;
+
+    let user: User = match config.dev_user_id {
+        Some(id) => users::table
+            .find(id)
+            .select(User::as_select())
+            .first(&mut conn)
+            .await
+            .optional()?
+            .ok_or_else(|| anyhow::anyhow!("DEV_USER_ID={id} does not match any user"))?,
+        None => {
+            diesel::insert_into(users::table)
+                .values(&NewUser {
+                    email: DEV_USER_EMAIL.to_string(),
+                    name: "Dev User".to_string(),
+                    initials: Some("DV".to_string()),
+                })
+                .on_conflict(users::email)
+                .do_nothing()
+                .execute(&mut conn)
+                .await?;
+            users::table
+                .filter(users::email.eq(DEV_USER_EMAIL))
+                .select(User::as_select())
+                .first(&mut conn)
+                .await?
+        }
+    };
+
+    Ok(AuthUser {
+        user_id: user.id,
+        email: user.email,
+        name: user.name,
+        role: config.dev_user_role,
+    })
+}
+
 pub async fn me(auth_user: AuthUser) -> Json<shared::CurrentUserResponse> {
     Json(shared::CurrentUserResponse {
         user_id: auth_user.user_id,
         email: auth_user.email,
         name: auth_user.name,
+        role: auth_user.role,
     })
 }
 
-fn create_jwt(secret: &str, user: &User) -> String {
+/// Issue a session token for `user` who logged in at `auth_time`, returning it with its
+/// expiry. Tokens never outlive `token_max_age_hours` past the login.
+fn create_jwt(
+    config: &AppConfig,
+    user: &User,
+    auth_time: DateTime<Utc>,
+) -> (String, DateTime<Utc>) {
     let now = Utc::now();
-    let exp = now + Duration::hours(24);
+    let exp = (now + Duration::hours(config.token_ttl_hours))
+        .min(auth_time + Duration::hours(config.token_max_age_hours));
 
     let claims = Claims {
         sub: user.email.clone(),
         name: user.name.clone(),
         user_id: user.id,
+        role: shared::Role::from_db(&user.role),
         iat: now.timestamp() as usize,
         exp: exp.timestamp() as usize,
+        auth_time: Some(auth_time.timestamp() as usize),
     };
 
-    encode(
+    let token = encode(
         &Header::default(),
         &claims,
-        &EncodingKey::from_secret(secret.as_bytes()),
+        &EncodingKey::from_secret(config.jwt_secret.as_bytes()),
     )
-    .expect("Failed to create JWT")
+    .expect("Failed to create JWT");
+    (token, exp)
 }
 
-fn set_token_cookie_and_redirect(token: String, public_url: &str) -> Response {
-    let secure = if public_url.starts_with("https://") {
+fn token_cookie(token: &str, expires_at: DateTime<Utc>, config: &AppConfig) -> String {
+    let secure = if config.public_url.starts_with("https://") {
         "; Secure"
     } else {
         ""
     };
-    let cookie = format!(
-        "token={}; Path=/; HttpOnly; SameSite=Lax; Max-Age=86400{}",
-        token, secure
-    );
+    let max_age = (expires_at - Utc::now()).num_seconds().max(0);
+    format!(
+        "token={}; Path=/; HttpOnly; SameSite=Lax; Max-Age={}{}",
+        token, max_age, secure
+    )
+}
+
+fn set_token_cookie_and_redirect(
+    token: &str,
+    expires_at: DateTime<Utc>,
+    config: &AppConfig,
+) -> Response {
     (
         StatusCode::FOUND,
         [
-            (header::SET_COOKIE, cookie),
-            (header::LOCATION, "/".to_string()),
+            (header::SET_COOKIE, token_cookie(token, expires_at, config)),
+            (header::LOCATION, format!("{}/", config.base_path)),
         ],
     )
         .into_response()
 }
+
+/// Swap a still-valid session token for a fresh one, until `token_max_age_hours` after the
+/// original login; after that the user has to log in again.
+pub async fn refresh(State(state): State<Arc<AppState>>, headers: HeaderMap) -> Response {
+    let unauthorized = |message: &str| {
+        (
+            StatusCode::UNAUTHORIZED,
+            [(header::SET_COOKIE, super::CLEAR_TOKEN_COOKIE)],
+            Json(ApiError::unauthorized(message)),
+        )
+            .into_response()
+    };
+
+    let Some(token) = super::request_token(&headers) else {
+        return unauthorized("Missing authentication token");
+    };
+    let claims = match super::decode_token(token, &state.config.jwt_secret) {
+        Ok(c) => c,
+        Err(_) => return unauthorized("Invalid or expired token"),
+    };
+
+    let auth_time = claims.auth_time.unwrap_or(claims.iat) as i64;
+    let Some(auth_time) = DateTime::from_timestamp(auth_time, 0) else {
+        return unauthorized("Invalid or expired token");
+    };
+    if Utc::now() >= auth_time + Duration::hours(state.config.token_max_age_hours) {
+        return unauthorized("Session expired; please log in again");
+    }
+
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    // Re-read the user so the new token picks up name changes
+    let user: User = match users::table
+        .filter(users::id.eq(claims.user_id))
+        .select(User::as_select())
+        .first(&mut conn)
+        .await
+    {
+        Ok(u) if !u.active => return unauthorized("User is deactivated"),
+        Ok(u) => u,
+        Err(diesel::NotFound) => return unauthorized("User no longer exists"),
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to look up user")),
+            )
+                .into_response()
+        }
+    };
+
+    let (token, expires_at) = create_jwt(&state.config, &user, auth_time);
+    (
+        [(
+            header::SET_COOKIE,
+            token_cookie(&token, expires_at, &state.config),
+        )],
+        Json(shared::TokenRefreshResponse { expires_at }),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{json, TestDb};
+    use shared::Role;
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn dev_user_is_created_once_or_taken_from_dev_user_id() {
+        let db = TestDb::new().await;
+        let bob = db.user("bob@example.org", "Bob Jones", Some("BJ")).await;
+        let mut config = db.state.config.clone();
+
+        let first = ensure_dev_user(&db.state.pool, &config).await.unwrap();
+        assert_eq!(first.email, DEV_USER_EMAIL);
+        assert_eq!(first.role, Role::Admin);
+        let again = ensure_dev_user(&db.state.pool, &config).await.unwrap();
+        assert_eq!(again.user_id, first.user_id);
+
+        config.dev_user_id = Some(bob.id);
+        config.dev_user_role = Role::Member;
+        let as_bob = ensure_dev_user(&db.state.pool, &config).await.unwrap();
+        assert_eq!(as_bob.user_id, bob.id);
+        assert_eq!(as_bob.name, "Bob Jones");
+        assert_eq!(as_bob.role, Role::Member);
+
+        config.dev_user_id = Some(bob.id + 100);
+        let Err(error) = ensure_dev_user(&db.state.pool, &config).await else {
+            panic!("an unknown DEV_USER_ID should stop startup");
+        };
+        assert_eq!(
+            error.to_string(),
+            format!("DEV_USER_ID={} does not match any user", bob.id + 100)
+        );
+    }
+
+    /// Request headers carrying `user`'s session token from a login at `auth_time`.
+    fn session(config: &AppConfig, user: &User, auth_time: DateTime<Utc>) -> HeaderMap {
+        let (token, _) = create_jwt(config, user, auth_time);
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            format!("Bearer {}", token).parse().unwrap(),
+        );
+        headers
+    }
+
+    /// Request headers carrying a token for `user` that expires at `exp`, from a login an
+    /// hour ago.
+    fn expiring_session(config: &AppConfig, user: &User, exp: DateTime<Utc>) -> HeaderMap {
+        let issued = Utc::now() - Duration::hours(1);
+        let claims = Claims {
+            sub: user.email.clone(),
+            name: user.name.clone(),
+            user_id: user.id,
+            role: Role::from_db(&user.role),
+            iat: issued.timestamp() as usize,
+            exp: exp.timestamp() as usize,
+            auth_time: Some(issued.timestamp() as usize),
+        };
+        let token = encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(config.jwt_secret.as_bytes()),
+        )
+        .unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            format!("Bearer {}", token).parse().unwrap(),
+        );
+        headers
+    }
+
+    async fn deactivate(db: &TestDb, user: &User) {
+        diesel::update(users::table.filter(users::id.eq(user.id)))
+            .set(users::active.eq(false))
+            .execute(&mut db.conn().await)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn refresh_refuses_deactivated_users() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let headers = session(&db.state.config, &user, Utc::now());
+
+        let response = refresh(State(db.state.clone()), headers.clone()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        deactivate(&db, &user).await;
+        let response = refresh(State(db.state.clone()), headers).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn oauth_login_refuses_deactivated_users() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let info = GoogleUserInfo {
+            email: user.email.clone(),
+            name: user.name.clone(),
+        };
+        let mut conn = db.conn().await;
+
+        let found = oauth_user(&mut conn, &info).await.ok().unwrap();
+        assert_eq!(found.id, user.id);
+
+        deactivate(&db, &user).await;
+        let Err(response) = oauth_user(&mut conn, &info).await else {
+            panic!("a deactivated user should not get a session");
+        };
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // Someone new is still signed up on first login
+        let newcomer = GoogleUserInfo {
+            email: "ada@example.org".to_string(),
+            name: "Ada Lovelace".to_string(),
+        };
+        let created = oauth_user(&mut conn, &newcomer).await.ok().unwrap();
+        assert_eq!(created.initials.as_deref(), Some("AL"));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn refresh_renews_a_token_about_to_expire_but_not_an_expired_one() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let config = &db.state.config;
+
+        let headers = expiring_session(config, &user, Utc::now() + Duration::seconds(30));
+        let response = refresh(State(db.state.clone()), headers).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers()[header::SET_COOKIE]
+            .to_str()
+            .unwrap()
+            .starts_with("token="));
+        let renewed: shared::TokenRefreshResponse = json(response).await;
+        let expected = Utc::now() + Duration::hours(config.token_ttl_hours);
+        assert!((expected - renewed.expires_at).num_seconds().abs() < 60);
+
+        // Past jsonwebtoken's 60 second leeway
+        let headers = expiring_session(config, &user, Utc::now() - Duration::minutes(5));
+        let response = refresh(State(db.state.clone()), headers).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let error: ApiError = json(response).await;
+        assert_eq!(error.error.message, "Invalid or expired token");
+    }
+}
@@ -1,20 +1,22 @@
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
     response::IntoResponse,
     Json,
 };
+use chrono::Utc;
 use diesel::prelude::*;
-use diesel_async::RunQueryDsl;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, RunQueryDsl};
 use serde::Deserialize;
 use shared::{ApiError, CategoryResponse};
 use std::sync::Arc;
 
-use crate::db::schema::{categories, vendors};
-use crate::models::{Category, NewCategory, Vendor};
+use crate::db::schema::{action_items, categories, vendors};
+use crate::models::{Category, NewCategory, UpdateCategory, Vendor};
 use crate::AppState;
 
-use super::AuthUser;
+use super::{AuthUser, RequireAdmin};
 
 #[derive(Debug, Deserialize)]
 pub struct CreateCategoryReq {
@@ -22,6 +24,18 @@ pub struct CreateCategoryReq {
     pub description: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct UpdateCategoryReq {
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteCategoryQuery {
+    /// Move the category's items here before deleting; must belong to the same vendor
+    pub reassign_to: Option<i32>,
+}
+
 pub async fn list_all(State(state): State<Arc<AppState>>, _auth: AuthUser) -> impl IntoResponse {
     let mut conn = match super::get_conn(&state).await {
         Ok(c) => c,
@@ -162,6 +176,258 @@ pub async fn create(
     (StatusCode::CREATED, Json(to_category_response(category))).into_response()
 }
 
+/// Rename or describe a category. With `If-Match`, a stale write gets 409.
+pub async fn update(
+    State(state): State<Arc<AppState>>,
+    Path((vendor_id, category_id)): Path<(i32, i32)>,
+    _auth: AuthUser,
+    headers: HeaderMap,
+    Json(payload): Json<UpdateCategoryReq>,
+) -> impl IntoResponse {
+    // Validate name if provided
+    if let Some(ref name) = payload.name {
+        if name.is_empty() || name.len() > 100 {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::validation_error("Name must be 1-100 characters")),
+            )
+                .into_response();
+        }
+    }
+    if payload.name.is_none() && payload.description.is_none() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::validation_error("Nothing to update")),
+        )
+            .into_response();
+    }
+
+    let expected_version = match super::if_match_version(&headers) {
+        Ok(v) => v,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(e)).into_response(),
+    };
+
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    let changeset = UpdateCategory {
+        name: payload.name,
+        description: payload.description,
+        updated_at: Some(Utc::now()),
+    };
+
+    let in_vendor = categories::table
+        .filter(categories::id.eq(category_id))
+        .filter(categories::vendor_id.eq(vendor_id));
+    let mut update = diesel::update(in_vendor).set(&changeset).into_boxed();
+    if let Some(version) = expected_version {
+        update = update.filter(categories::updated_at.eq(version));
+    }
+
+    let result: Result<Category, _> = update
+        .returning(Category::as_returning())
+        .get_result(&mut conn)
+        .await;
+
+    let not_found = || {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ApiError::not_found(format!(
+                "Category {} not found for vendor {}",
+                category_id, vendor_id
+            ))),
+        )
+            .into_response()
+    };
+
+    match result {
+        Ok(c) => (
+            [(header::ETAG, super::etag_for(c.updated_at))],
+            Json(to_category_response(c)),
+        )
+            .into_response(),
+        // With If-Match, no row means either the category is gone or it changed underneath us
+        Err(diesel::NotFound) if expected_version.is_some() => {
+            let exists = diesel::select(diesel::dsl::exists(in_vendor))
+                .get_result::<bool>(&mut conn)
+                .await
+                .unwrap_or(false);
+
+            if exists {
+                (
+                    StatusCode::CONFLICT,
+                    Json(ApiError::conflict(format!(
+                        "Category {} was modified by someone else; reload and try again",
+                        category_id
+                    ))),
+                )
+                    .into_response()
+            } else {
+                not_found()
+            }
+        }
+        Err(diesel::NotFound) => not_found(),
+        Err(diesel::result::Error::DatabaseError(
+            diesel::result::DatabaseErrorKind::UniqueViolation,
+            _,
+        )) => (
+            StatusCode::CONFLICT,
+            Json(ApiError::conflict(
+                "Category with this name already exists for this vendor",
+            )),
+        )
+            .into_response(),
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::internal_error("Failed to update category")),
+        )
+            .into_response(),
+    }
+}
+
+/// Delete a category. Refused with 409 while items (soft-deleted ones included) still use
+/// it, unless `reassign_to` names another category of the vendor to move them to first.
+/// Admins only.
+pub async fn delete(
+    State(state): State<Arc<AppState>>,
+    Path((vendor_id, category_id)): Path<(i32, i32)>,
+    Query(query): Query<DeleteCategoryQuery>,
+    _admin: RequireAdmin,
+) -> impl IntoResponse {
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    match categories::table
+        .filter(categories::id.eq(category_id))
+        .filter(categories::vendor_id.eq(vendor_id))
+        .select(categories::id)
+        .first::<i32>(&mut conn)
+        .await
+    {
+        Ok(_) => {}
+        Err(diesel::NotFound) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiError::not_found(format!(
+                    "Category {} not found for vendor {}",
+                    category_id, vendor_id
+                ))),
+            )
+                .into_response()
+        }
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to fetch category")),
+            )
+                .into_response()
+        }
+    }
+
+    if let Some(target_id) = query.reassign_to {
+        let target_ok = target_id != category_id
+            && match categories::table
+                .filter(categories::id.eq(target_id))
+                .filter(categories::vendor_id.eq(vendor_id))
+                .count()
+                .get_result::<i64>(&mut conn)
+                .await
+            {
+                Ok(n) => n > 0,
+                Err(_) => {
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(ApiError::internal_error("Failed to verify category")),
+                    )
+                        .into_response()
+                }
+            };
+        if !target_ok {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::validation_error(
+                    "reassign_to must be another category of the same vendor",
+                )),
+            )
+                .into_response();
+        }
+    } else {
+        let in_use: i64 = match action_items::table
+            .filter(action_items::category_id.eq(category_id))
+            .count()
+            .get_result(&mut conn)
+            .await
+        {
+            Ok(n) => n,
+            Err(_) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiError::internal_error("Failed to count items")),
+                )
+                    .into_response()
+            }
+        };
+        if in_use > 0 {
+            return (
+                StatusCode::CONFLICT,
+                Json(ApiError::conflict(format!(
+                    "Category is used by {} item(s); pass reassign_to to move them first",
+                    in_use
+                ))),
+            )
+                .into_response();
+        }
+    }
+
+    let reassign_to = query.reassign_to;
+    let deleted = conn
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            async move {
+                if let Some(target_id) = reassign_to {
+                    diesel::update(
+                        action_items::table.filter(action_items::category_id.eq(category_id)),
+                    )
+                    .set((
+                        action_items::category_id.eq(target_id),
+                        action_items::updated_at.eq(Utc::now()),
+                    ))
+                    .execute(conn)
+                    .await?;
+                }
+                diesel::delete(categories::table.filter(categories::id.eq(category_id)))
+                    .execute(conn)
+                    .await?;
+                Ok(())
+            }
+            .scope_boxed()
+        })
+        .await;
+
+    match deleted {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        // An item was filed under the category after the check above
+        Err(diesel::result::Error::DatabaseError(
+            diesel::result::DatabaseErrorKind::ForeignKeyViolation,
+            _,
+        )) => (
+            StatusCode::CONFLICT,
+            Json(ApiError::conflict(
+                "Category is still used by items; pass reassign_to to move them first",
+            )),
+        )
+            .into_response(),
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::internal_error("Failed to delete category")),
+        )
+            .into_response(),
+    }
+}
+
 fn to_category_response(c: Category) -> CategoryResponse {
     CategoryResponse {
         id: c.id,
@@ -169,5 +435,169 @@ fn to_category_response(c: Category) -> CategoryResponse {
         name: c.name,
         description: c.description,
         created_at: c.created_at,
+        updated_at: c.updated_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{auth, json, query, TestDb};
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn delete_refuses_a_used_category_unless_its_items_are_reassigned() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let other_vendor = db.vendor("BBB").await;
+        let design = db.category(&vendor, "Design").await;
+        let testing = db.category(&vendor, "Testing").await;
+        let elsewhere = db.category(&other_vendor, "Design").await;
+        let item = db.item(&design, &user, &user).await;
+
+        let delete_design = |params: String| {
+            delete(
+                State(db.state.clone()),
+                Path((vendor.id, design.id)),
+                query(&params),
+                RequireAdmin,
+            )
+        };
+        assert_eq!(
+            delete_design(String::new()).await.into_response().status(),
+            StatusCode::CONFLICT
+        );
+        for target in [design.id, elsewhere.id] {
+            assert_eq!(
+                delete_design(format!("reassign_to={}", target))
+                    .await
+                    .into_response()
+                    .status(),
+                StatusCode::BAD_REQUEST
+            );
+        }
+        assert_eq!(
+            delete_design(format!("reassign_to={}", testing.id))
+                .await
+                .into_response()
+                .status(),
+            StatusCode::NO_CONTENT
+        );
+
+        let mut conn = db.conn().await;
+        let category_id: i32 = action_items::table
+            .filter(action_items::id.eq(&item.id))
+            .select(action_items::category_id)
+            .first(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(category_id, testing.id);
+        let remaining: Vec<i32> = categories::table
+            .filter(categories::vendor_id.eq(vendor.id))
+            .select(categories::id)
+            .load(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(remaining, [testing.id]);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn update_renames_within_the_vendor_only() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let other_vendor = db.vendor("BBB").await;
+        let design = db.category(&vendor, "Design").await;
+        db.category(&vendor, "Testing").await;
+
+        let rename = |vendor_id, name: &str| {
+            update(
+                State(db.state.clone()),
+                Path((vendor_id, design.id)),
+                auth(&user),
+                HeaderMap::new(),
+                Json(UpdateCategoryReq {
+                    name: Some(name.to_string()),
+                    description: None,
+                }),
+            )
+        };
+
+        let response = rename(vendor.id, "Layout").await.into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let renamed: CategoryResponse = json(response).await;
+        assert_eq!(renamed.name, "Layout");
+        assert_eq!(renamed.id, design.id);
+
+        assert_eq!(
+            rename(vendor.id, "Testing").await.into_response().status(),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            rename(vendor.id, "").await.into_response().status(),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            rename(other_vendor.id, "Layout")
+                .await
+                .into_response()
+                .status(),
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn update_with_a_stale_if_match_gets_409() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let design = db.category(&vendor, "Design").await;
+
+        let rename = |name: &str, if_match: Option<String>| {
+            let mut headers = HeaderMap::new();
+            if let Some(tag) = if_match {
+                headers.insert(header::IF_MATCH, tag.parse().unwrap());
+            }
+            update(
+                State(db.state.clone()),
+                Path((vendor.id, design.id)),
+                auth(&user),
+                headers,
+                Json(UpdateCategoryReq {
+                    name: Some(name.to_string()),
+                    description: None,
+                }),
+            )
+        };
+
+        let original = crate::routes::etag_for(design.updated_at);
+        let response = rename("Layout", Some(original.clone()))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let current = response.headers()[header::ETAG]
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert_ne!(current, original);
+
+        // Someone still holding the original version loses
+        let response = rename("Drawings", Some(original)).await.into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(
+            rename("Drawings", Some(current))
+                .await
+                .into_response()
+                .status(),
+            StatusCode::OK
+        );
+        // No header is an unconditional write
+        assert_eq!(
+            rename("Sketches", None).await.into_response().status(),
+            StatusCode::OK
+        );
     }
 }
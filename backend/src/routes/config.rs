@@ -0,0 +1,15 @@
+use axum::{extract::State, Json};
+use shared::ClientConfig;
+use std::sync::Arc;
+
+use crate::AppState;
+
+use super::AuthUser;
+
+/// Runtime settings the frontend needs to adapt its UI.
+pub async fn get(State(state): State<Arc<AppState>>, _auth: AuthUser) -> Json<ClientConfig> {
+    Json(ClientConfig {
+        read_only: state.config.read_only,
+        title_max_length: state.config.title_max_length,
+    })
+}
@@ -0,0 +1,150 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Varchar};
+use diesel_async::RunQueryDsl;
+use serde::Deserialize;
+use shared::{ApiError, DashboardResponse, Status, StatusCount};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::db::schema::action_items;
+use crate::models::ActionItem;
+use crate::AppState;
+
+use super::activity::own_activity;
+use super::items::LATEST_STATUS_SQL;
+use super::AuthUser;
+
+const DEFAULT_RECENT: i64 = 10;
+const MAX_RECENT: i64 = 50;
+
+const OWNED_STATUS_COUNTS_SQL: &str = r#"
+    SELECT COALESCE(latest.status, 'New') AS status, COUNT(*) AS count
+    FROM action_items ai
+    LEFT JOIN LATERAL (
+        SELECT sh.status
+        FROM status_history sh
+        WHERE sh.action_item_id = ai.id
+        ORDER BY sh.changed_at DESC, sh.id DESC
+        LIMIT 1
+    ) latest ON true
+    WHERE ai.owner_id = $1
+      AND ai.deleted_at IS NULL
+    GROUP BY 1
+"#;
+
+#[derive(Debug, Deserialize)]
+pub struct DashboardQuery {
+    /// How many of the user's recent notes/status changes to include
+    pub recent: Option<i64>,
+}
+
+#[derive(Debug, QueryableByName)]
+struct StatusCountRow {
+    #[diesel(sql_type = Varchar)]
+    status: String,
+    #[diesel(sql_type = BigInt)]
+    count: i64,
+}
+
+/// Every workflow status in order, zero if absent from `counts`, then any statuses outside
+/// the workflow (e.g. from old imports) alphabetically, so they still count.
+pub(super) fn status_counts_in_order(mut counts: HashMap<String, i64>) -> Vec<StatusCount> {
+    let mut status_counts: Vec<StatusCount> = Status::all()
+        .iter()
+        .map(|s| StatusCount {
+            status: s.as_str().to_string(),
+            count: counts.remove(s.as_str()).unwrap_or(0),
+        })
+        .collect();
+    let mut others: Vec<StatusCount> = counts
+        .into_iter()
+        .map(|(status, count)| StatusCount { status, count })
+        .collect();
+    others.sort_by(|a, b| a.status.cmp(&b.status));
+    status_counts.extend(others);
+    status_counts
+}
+
+/// Everything the home page shows about the current user, in one response.
+pub async fn dashboard(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DashboardQuery>,
+    auth: AuthUser,
+) -> impl IntoResponse {
+    let recent = query.recent.unwrap_or(DEFAULT_RECENT).clamp(0, MAX_RECENT);
+
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    let counts: HashMap<String, i64> = match diesel::sql_query(OWNED_STATUS_COUNTS_SQL)
+        .bind::<diesel::sql_types::Int4, _>(auth.user_id)
+        .load::<StatusCountRow>(&mut conn)
+        .await
+    {
+        Ok(rows) => rows.into_iter().map(|r| (r.status, r.count)).collect(),
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to count items")),
+            )
+                .into_response()
+        }
+    };
+    let status_counts = status_counts_in_order(counts);
+
+    let yesterday = Utc::now().date_naive() - chrono::Days::new(1);
+    let overdue: Vec<ActionItem> = match action_items::table
+        .filter(action_items::owner_id.eq(auth.user_id))
+        .filter(action_items::due_date.le(yesterday))
+        .filter(action_items::deleted_at.is_null())
+        .filter(
+            diesel::dsl::sql::<diesel::sql_types::Bool>(&format!("{} <> ", LATEST_STATUS_SQL))
+                .bind::<diesel::sql_types::Text, _>("Complete"),
+        )
+        .order((action_items::due_date.asc(), action_items::id.asc()))
+        .select(ActionItem::as_select())
+        .load(&mut conn)
+        .await
+    {
+        Ok(i) => i,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to fetch items")),
+            )
+                .into_response()
+        }
+    };
+    let overdue = match super::items::build_item_responses(&mut conn, overdue).await {
+        Ok(items) => items,
+        Err(resp) => return resp,
+    };
+
+    let recent_activity = match own_activity(&mut conn, auth.user_id, recent).await {
+        Ok(entries) => entries,
+        Err(e) => {
+            tracing::error!("Own activity query failed: {e}");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to load activity")),
+            )
+                .into_response();
+        }
+    };
+
+    Json(DashboardResponse {
+        status_counts,
+        overdue,
+        recent_activity,
+    })
+    .into_response()
+}
@@ -0,0 +1,317 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{NaiveDate, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use shared::{csv_date, note_line, ActionItemResponse, ApiError, CSV_HEADERS};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::db::schema::{action_items, notes, users, vendors};
+use crate::models::{ActionItem, Note, User, Vendor};
+use crate::AppState;
+
+use super::AuthUser;
+
+fn write_csv(
+    vendor_name: &str,
+    today: NaiveDate,
+    items: &[ActionItemResponse],
+    notes_by_item: &HashMap<String, Vec<String>>,
+) -> csv::Result<Vec<u8>> {
+    let mut writer = csv::WriterBuilder::new()
+        .flexible(true)
+        .from_writer(Vec::new());
+
+    // The source spreadsheets carry two info rows above the header; the importer skips them
+    writer.write_record([format!("{} Action Items", vendor_name)])?;
+    writer.write_record([format!("Exported {}", csv_date(today))])?;
+    writer.write_record(CSV_HEADERS)?;
+
+    for item in items {
+        let notes = notes_by_item
+            .get(&item.id)
+            .map(|lines| lines.join("\n"))
+            .unwrap_or_default();
+        writer.write_record([
+            item.id.clone(),
+            item.title.clone(),
+            csv_date(item.create_date),
+            item.created_by_name.clone(),
+            item.due_date.map(csv_date).unwrap_or_default(),
+            item.category.clone(),
+            item.owner_name.clone(),
+            item.priority.clone(),
+            item.status.clone(),
+            csv_date(item.status_changed_at.date_naive()),
+            notes,
+        ])?;
+    }
+
+    writer
+        .into_inner()
+        .map_err(|e| csv::Error::from(e.into_error()))
+}
+
+/// Export a vendor's items in the spreadsheet format `cli import` reads.
+pub async fn vendor_items_csv(
+    State(state): State<Arc<AppState>>,
+    Path(vendor_id): Path<i32>,
+    _auth: AuthUser,
+) -> impl IntoResponse {
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    let vendor: Vendor = match vendors::table
+        .filter(vendors::id.eq(vendor_id))
+        .first(&mut conn)
+        .await
+    {
+        Ok(v) => v,
+        Err(diesel::NotFound) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiError::not_found(format!(
+                    "Vendor {} not found",
+                    vendor_id
+                ))),
+            )
+                .into_response()
+        }
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to fetch vendor")),
+            )
+                .into_response()
+        }
+    };
+
+    let items: Vec<ActionItem> = match action_items::table
+        .filter(action_items::vendor_id.eq(vendor_id))
+        .filter(action_items::deleted_at.is_null())
+        .order(action_items::number.asc())
+        .select(ActionItem::as_select())
+        .load(&mut conn)
+        .await
+    {
+        Ok(i) => i,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to fetch items")),
+            )
+                .into_response()
+        }
+    };
+
+    let item_ids: Vec<String> = items.iter().map(|i| i.id.clone()).collect();
+    let items = match super::items::build_item_responses(&mut conn, items).await {
+        Ok(items) => items,
+        Err(resp) => return resp,
+    };
+
+    // Notes oldest first, which is the order the importer replays them in
+    let item_notes: Vec<(Note, User)> = match notes::table
+        .inner_join(users::table.on(users::id.eq(notes::author_id)))
+        .filter(notes::action_item_id.eq_any(&item_ids))
+        .order((notes::note_date.asc(), notes::created_at.asc()))
+        .select((Note::as_select(), User::as_select()))
+        .load(&mut conn)
+        .await
+    {
+        Ok(n) => n,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to fetch notes")),
+            )
+                .into_response()
+        }
+    };
+
+    let mut notes_by_item: HashMap<String, Vec<String>> = HashMap::new();
+    for (note, author) in &item_notes {
+        notes_by_item
+            .entry(note.action_item_id.clone())
+            .or_default()
+            .push(note_line(
+                note.note_date,
+                &note.content,
+                Some(author.initials.as_deref().unwrap_or(&author.name)),
+            ));
+    }
+
+    let today = Utc::now().date_naive();
+    let body = match write_csv(&vendor.name, today, &items, &notes_by_item) {
+        Ok(b) => b,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to write CSV")),
+            )
+                .into_response()
+        }
+    };
+
+    let filename = format!("{}-action-items-{}.csv", vendor.prefix, today);
+    (
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::NewNote;
+    use crate::test_support::{auth, TestDb};
+    use axum::body::to_bytes;
+
+    fn rows(body: &[u8]) -> Vec<Vec<String>> {
+        csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(body)
+            .records()
+            .map(|record| record.unwrap().iter().map(str::to_string).collect())
+            .collect()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn csv_export_is_in_number_order_with_quoted_fields_and_signed_notes() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let design = db.category(&vendor, "Design").await;
+        let mut conn = db.conn().await;
+        // By id, "AAA-1000" would sort ahead of "AAA-999"
+        diesel::update(vendors::table.filter(vendors::id.eq(vendor.id)))
+            .set(vendors::next_number.eq(999))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        let first = db.item(&design, &user, &user).await;
+        let second = db.item(&design, &user, &user).await;
+        assert_eq!(
+            (first.id.as_str(), second.id.as_str()),
+            ("AAA-999", "AAA-1000")
+        );
+
+        let title = "Check \"gain\", then retest";
+        diesel::update(action_items::table.filter(action_items::id.eq(&first.id)))
+            .set(action_items::title.eq(title))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        let note = |day: u32, content: &str| NewNote {
+            action_item_id: first.id.clone(),
+            note_date: NaiveDate::from_ymd_opt(2024, 3, day).unwrap(),
+            author_id: user.id,
+            content: content.to_string(),
+        };
+        diesel::insert_into(notes::table)
+            .values(&vec![
+                note(6, "MF: Called vendor"),
+                note(5, "Sent drawings"),
+            ])
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let response = vendor_items_csv(State(db.state.clone()), Path(vendor.id), auth(&user))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let text = std::str::from_utf8(&body).unwrap();
+        assert!(text.contains("\"Check \"\"gain\"\", then retest\""));
+
+        let rows = rows(&body);
+        assert_eq!(rows[0], ["Vendor AAA Action Items"]);
+        assert_eq!(rows[2], CSV_HEADERS);
+        let ids: Vec<&str> = rows[3..].iter().map(|row| row[0].as_str()).collect();
+        assert_eq!(ids, ["AAA-999", "AAA-1000"]);
+        assert_eq!(rows[3][1], title);
+        assert_eq!(
+            rows[3][10],
+            "03/05/2024 MF: Sent drawings\n03/06/2024 MF: Called vendor"
+        );
+        assert_eq!(rows[4][10], "");
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn csv_export_reads_back_as_the_items_with_comma_names_quoted() {
+        let db = TestDb::new().await;
+        let fox = db.user("mf@example.org", "Fox, Mike", Some("MF")).await;
+        let lee = db.user("al@example.org", "Lee, Ann", None).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design, Mechanical").await;
+        let first = db.item(&category, &fox, &lee).await;
+        db.item(&category, &lee, &fox).await;
+        let mut conn = db.conn().await;
+        diesel::update(action_items::table.filter(action_items::id.eq(&first.id)))
+            .set(action_items::due_date.eq(NaiveDate::from_ymd_opt(2024, 6, 30)))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let response = vendor_items_csv(State(db.state.clone()), Path(vendor.id), auth(&fox))
+            .await
+            .into_response();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let text = std::str::from_utf8(&body).unwrap();
+        assert!(text.contains(",\"Fox, Mike\","));
+        assert!(text.contains(",\"Lee, Ann\","));
+        assert!(text.contains(",\"Design, Mechanical\","));
+
+        let items: Vec<ActionItem> = action_items::table
+            .order(action_items::number)
+            .select(ActionItem::as_select())
+            .load(&mut conn)
+            .await
+            .unwrap();
+        let items = crate::routes::items::build_item_responses(&mut conn, items)
+            .await
+            .ok()
+            .unwrap();
+        let expected: Vec<Vec<String>> = items
+            .iter()
+            .map(|item| {
+                vec![
+                    item.id.clone(),
+                    item.title.clone(),
+                    csv_date(item.create_date),
+                    item.created_by_name.clone(),
+                    item.due_date.map(csv_date).unwrap_or_default(),
+                    item.category.clone(),
+                    item.owner_name.clone(),
+                    item.priority.clone(),
+                    item.status.clone(),
+                    csv_date(item.status_changed_at.date_naive()),
+                    String::new(),
+                ]
+            })
+            .collect();
+        assert_eq!(rows(&body)[3..], expected);
+        assert_eq!(
+            expected[0][3..7],
+            ["Fox, Mike", "06/30/2024", "Design, Mechanical", "Lee, Ann"]
+        );
+    }
+}
@@ -1,8 +1,42 @@
-use axum::Json;
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use diesel_async::RunQueryDsl;
 use shared::HealthResponse;
+use std::sync::Arc;
+use std::time::Duration;
 
-pub async fn health_check() -> Json<HealthResponse> {
-    Json(HealthResponse {
-        status: "ok".to_string(),
-    })
+use crate::AppState;
+
+/// Probes must answer quickly; a pool waiting on a dead database would otherwise hang.
+const DB_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+async fn database_reachable(state: &AppState) -> bool {
+    let check = async {
+        let mut conn = state.pool.get().await.ok()?;
+        diesel::sql_query("SELECT 1").execute(&mut conn).await.ok()
+    };
+    matches!(
+        tokio::time::timeout(DB_CHECK_TIMEOUT, check).await,
+        Ok(Some(_))
+    )
+}
+
+/// Readiness check: 200 when a pooled connection can run `SELECT 1`, 503 otherwise.
+pub async fn health_check(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    if database_reachable(&state).await {
+        (
+            StatusCode::OK,
+            Json(HealthResponse {
+                status: "ok".to_string(),
+                db: Some("ok".to_string()),
+            }),
+        )
+    } else {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(HealthResponse {
+                status: "degraded".to_string(),
+                db: Some("unreachable".to_string()),
+            }),
+        )
+    }
 }
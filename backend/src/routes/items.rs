@@ -1,20 +1,29 @@
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{header, HeaderMap, StatusCode},
     response::{IntoResponse, Redirect, Response},
     Json,
 };
 use chrono::{DateTime, Utc};
 use diesel::prelude::*;
-use diesel_async::{AsyncPgConnection, RunQueryDsl};
-use serde::Deserialize;
-use shared::{ActionItemResponse, ApiError};
+use diesel::query_source::{Alias, AliasedField};
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use shared::{
+    ActionItemResponse, ApiError, AssigneeResponse, ItemLinks, ItemListResponse, ItemSearchResult,
+    Recurrence, SearchField,
+};
 use std::sync::Arc;
 
-use crate::db::schema::{action_items, categories, status_history, users, vendors};
+use crate::db::schema::{
+    action_items, categories, idempotency_keys, item_assignees, item_audit, item_tags, notes,
+    status_history, tags, users, vendors,
+};
 use crate::models::{
-    ActionItem, Category, NewActionItem, NewStatusHistory, StatusHistory, UpdateActionItem, User,
-    Vendor,
+    ActionItem, Category, NewActionItem, NewIdempotencyKey, NewItemAudit, NewNote,
+    NewStatusHistory, StatusHistory, UpdateActionItem, User, Vendor,
 };
 use crate::AppState;
 
@@ -25,11 +34,120 @@ pub struct ItemsQuery {
     pub vendor_id: Option<i32>,
     pub status: Option<String>,
     pub owner_id: Option<i32>,
+    /// Make `owner_id` also match items where the user is a secondary assignee
+    pub include_assignees: Option<bool>,
     pub category_id: Option<i32>,
     pub priority: Option<String>,
+    pub tag: Option<String>,
+    pub created_after: Option<chrono::NaiveDate>,
+    pub created_before: Option<chrono::NaiveDate>,
+    /// Also list soft-deleted items
+    pub include_deleted: Option<bool>,
+    #[serde(default)]
+    pub sort_by: ItemSortField,
+    #[serde(default)]
+    pub sort_dir: SortDir,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// Every `ItemsQuery` field; keep in step with the struct. Other keys are rejected so a
+/// typo'd filter doesn't silently match everything.
+pub(super) const ITEMS_QUERY_PARAMS: &[&str] = &[
+    "vendor_id",
+    "status",
+    "owner_id",
+    "include_assignees",
+    "category_id",
+    "priority",
+    "tag",
+    "created_after",
+    "created_before",
+    "include_deleted",
+    "sort_by",
+    "sort_dir",
+    "limit",
+    "offset",
+];
+
+/// Columns `GET /api/items` can sort by. Parsing into an enum is the whitelist: anything
+/// else is rejected before it gets near SQL.
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemSortField {
+    #[default]
+    Id,
+    Title,
+    Priority,
+    DueDate,
+    Status,
+    UpdatedAt,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortDir {
+    #[default]
+    Asc,
+    Desc,
 }
 
 #[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    pub q: Option<String>,
+}
+
+const SEARCH_LIMIT: i64 = 50;
+
+#[derive(Debug, QueryableByName)]
+struct SearchHitRow {
+    #[diesel(sql_type = diesel::sql_types::Varchar)]
+    id: String,
+    #[diesel(sql_type = diesel::sql_types::Bool)]
+    title_match: bool,
+    #[diesel(sql_type = diesel::sql_types::Bool)]
+    description_match: bool,
+    #[diesel(sql_type = diesel::sql_types::Bool)]
+    notes_match: bool,
+}
+
+/// Substring match over titles, descriptions and note contents, ranked title > description
+/// > notes. `ILIKE` rather than full-text search so partial words ("thrust") still match.
+const SEARCH_SQL: &str = r#"
+    SELECT id, title_match, description_match, notes_match
+    FROM (
+        SELECT
+            ai.id,
+            ai.title ILIKE $1 AS title_match,
+            COALESCE(ai.description ILIKE $1, false) AS description_match,
+            EXISTS (
+                SELECT 1 FROM notes n
+                WHERE n.action_item_id = ai.id AND n.content ILIKE $1
+            ) AS notes_match
+        FROM action_items ai
+        WHERE ai.deleted_at IS NULL
+    ) hits
+    WHERE title_match OR description_match OR notes_match
+    ORDER BY title_match::int * 4 + description_match::int * 2 + notes_match::int DESC, id
+    LIMIT $2
+"#;
+
+/// Priority as a sortable rank, High first, matching the home table's client-side order.
+const PRIORITY_RANK_SQL: &str = "CASE action_items.priority \
+     WHEN 'High' THEN 0 WHEN 'Medium' THEN 1 WHEN 'Low' THEN 2 ELSE 3 END";
+
+/// An item's current status as a SQL expression over `action_items`; "New" without history.
+pub(super) const LATEST_STATUS_SQL: &str = "COALESCE((SELECT sh.status FROM status_history sh \
+     WHERE sh.action_item_id = action_items.id \
+     ORDER BY sh.changed_at DESC, sh.id DESC LIMIT 1), 'New')";
+
+/// The vendor prefix part of an item id.
+const ID_PREFIX_SQL: &str = "split_part(action_items.id, '-', 1)";
+
+const DEFAULT_PAGE_SIZE: i64 = 100;
+const MAX_PAGE_SIZE: i64 = 500;
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct CreateActionItemReq {
     pub title: String,
     pub due_date: Option<chrono::NaiveDate>,
@@ -37,6 +155,40 @@ pub struct CreateActionItemReq {
     pub owner_id: i32,
     pub priority: String,
     pub description: Option<String>,
+    /// Defaults to `none`
+    pub recurrence: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DueSoonQuery {
+    pub days: Option<i64>,
+}
+
+const DEFAULT_DUE_SOON_DAYS: i64 = 7;
+const MAX_DUE_SOON_DAYS: i64 = 365;
+
+#[derive(Debug, Deserialize)]
+pub struct DuplicateQuery {
+    /// Append " (copy)" to the title; defaults to true.
+    pub copy_suffix: Option<bool>,
+}
+
+const COPY_SUFFIX: &str = " (copy)";
+
+/// Request header that makes item creation safe to repeat; see `create`.
+const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+const IDEMPOTENCY_KEY_MAX_LENGTH: usize = 255;
+/// How long a used `Idempotency-Key` keeps returning its item.
+const IDEMPOTENCY_KEY_TTL_HOURS: i64 = 24;
+/// How long a claimed key whose request never finished blocks repeats. Well past the
+/// statement timeout, so the first request has given up by then.
+const IDEMPOTENCY_CLAIM_TIMEOUT_SECS: i64 = 60;
+
+#[derive(Debug, Deserialize)]
+pub struct MoveItemReq {
+    pub vendor_id: i32,
+    /// Category in the target vendor; defaults to the one with the same name as the current one.
+    pub category_id: Option<i32>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -47,6 +199,10 @@ pub struct UpdateActionItemReq {
     pub owner_id: Option<i32>,
     pub priority: Option<String>,
     pub description: Option<Option<String>>,
+    pub recurrence: Option<String>,
+    /// The `updated_at` the client last saw; if set, the update is rejected with 409 when
+    /// the item has changed since
+    pub expected_updated_at: Option<DateTime<Utc>>,
 }
 
 /// Return an item's current status and when it was set, falling back to "New" / creation time.
@@ -56,7 +212,7 @@ async fn current_status(
 ) -> (String, DateTime<Utc>) {
     let status_entry: Option<StatusHistory> = status_history::table
         .filter(status_history::action_item_id.eq(&item.id))
-        .order(status_history::changed_at.desc())
+        .order((status_history::changed_at.desc(), status_history::id.desc()))
         .first(conn)
         .await
         .ok();
@@ -67,14 +223,61 @@ async fn current_status(
     }
 }
 
+/// Users referenced by items can't be deleted (foreign keys), so a missing user means the
+/// lookup itself failed. Show the id rather than a generic placeholder.
+fn user_display_name(user: Option<&User>, user_id: i32) -> String {
+    user.map(|u| u.name.clone())
+        .unwrap_or_else(|| format!("User #{}", user_id))
+}
+
+/// Map an `action_items` foreign key violation to a validation error naming the bad field.
+fn foreign_key_error(info: &dyn diesel::result::DatabaseErrorInformation) -> ApiError {
+    let field = match info.constraint_name() {
+        Some("action_items_owner_id_fkey") => "owner_id",
+        Some("action_items_created_by_id_fkey") => "created_by_id",
+        Some("action_items_category_id_fkey") => "category_id",
+        Some("action_items_vendor_id_fkey") => "vendor_id",
+        _ => return ApiError::validation_error("Item references a record that does not exist"),
+    };
+    ApiError::validation_error(format!("{} does not refer to an existing record", field))
+}
+
+/// The parts of an item response that live in their own tables.
+#[derive(Default)]
+struct ItemRelations {
+    assignees: Vec<AssigneeResponse>,
+    watcher_count: i64,
+    tags: Vec<String>,
+    links: ItemLinks,
+}
+
+/// Relations of a single item. Best effort, like the creator/owner lookups: a failed
+/// query leaves that part empty.
+async fn item_relations(conn: &mut AsyncPgConnection, item_id: &str) -> ItemRelations {
+    ItemRelations {
+        assignees: super::assignees::item_assignees(conn, item_id).await,
+        watcher_count: super::watchers::watcher_count(conn, item_id).await,
+        tags: super::tags::tags_by_item(conn, &[item_id])
+            .await
+            .ok()
+            .and_then(|mut map| map.remove(item_id))
+            .unwrap_or_default(),
+        links: super::links::links_by_item(conn, &[item_id])
+            .await
+            .ok()
+            .and_then(|mut map| map.remove(item_id))
+            .unwrap_or_default(),
+    }
+}
+
 /// Assemble an `ActionItemResponse` from an item plus its resolved category, users, and status.
 fn build_item_response(
     item: ActionItem,
     category_name: String,
     creator: Option<&User>,
     owner: Option<&User>,
-    status: String,
-    status_changed_at: DateTime<Utc>,
+    (status, status_changed_at): (String, DateTime<Utc>),
+    relations: ItemRelations,
 ) -> ActionItemResponse {
     ActionItemResponse {
         id: item.id,
@@ -84,56 +287,156 @@ fn build_item_response(
         description: item.description,
         create_date: item.create_date,
         created_by_id: item.created_by_id,
-        created_by_name: creator
-            .map(|u| u.name.clone())
-            .unwrap_or_else(|| "Unknown".to_string()),
+        created_by_name: user_display_name(creator, item.created_by_id),
         created_by_initials: creator.and_then(|u| u.initials.clone()),
         due_date: item.due_date,
         category_id: item.category_id,
         category: category_name,
         owner_id: item.owner_id,
-        owner_name: owner
-            .map(|u| u.name.clone())
-            .unwrap_or_else(|| "Unknown".to_string()),
+        owner_name: user_display_name(owner, item.owner_id),
         owner_initials: owner.and_then(|u| u.initials.clone()),
         priority: item.priority,
         created_at: item.created_at,
         updated_at: item.updated_at,
         status,
         status_changed_at,
+        assignees: relations.assignees,
+        watcher_count: relations.watcher_count,
+        watching: None,
+        tags: relations.tags,
+        links: relations.links,
+        deleted_at: item.deleted_at,
+        recurrence: item.recurrence,
+        next_occurrence_id: item.next_occurrence_id,
     }
 }
 
 pub async fn list_all(
     State(state): State<Arc<AppState>>,
     Query(query): Query<ItemsQuery>,
+    Query(raw): Query<std::collections::HashMap<String, String>>,
     _auth: AuthUser,
 ) -> impl IntoResponse {
-    list_items_internal(&state, None, query).await
+    list_items_internal(&state, None, query, &raw).await
 }
 
 pub async fn list(
     State(state): State<Arc<AppState>>,
     Path(vendor_id): Path<i32>,
     Query(query): Query<ItemsQuery>,
+    Query(raw): Query<std::collections::HashMap<String, String>>,
     _auth: AuthUser,
 ) -> impl IntoResponse {
-    list_items_internal(&state, Some(vendor_id), query).await
+    list_items_internal(&state, Some(vendor_id), query, &raw).await
 }
 
-async fn list_items_internal(
-    state: &Arc<AppState>,
-    vendor_id: Option<i32>,
-    query: ItemsQuery,
-) -> Response {
-    let mut conn = match super::get_conn(state).await {
-        Ok(c) => c,
-        Err(resp) => return resp,
+/// Reject filters naming a vendor, category or owner that doesn't exist, so a mistyped id
+/// gets an error instead of an empty list that looks like "no items". An unknown vendor in
+/// the path is a 404; in the query string it's a 400 like the others.
+async fn check_filter_ids(
+    conn: &mut AsyncPgConnection,
+    path_vendor_id: Option<i32>,
+    query: &ItemsQuery,
+) -> Result<(), Response> {
+    let db_error = || {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::internal_error("Failed to check filters")),
+        )
+            .into_response()
     };
 
-    let mut items_query = action_items::table
-        .inner_join(categories::table.on(categories::id.eq(action_items::category_id)))
-        .into_boxed();
+    if let Some(id) = path_vendor_id {
+        let exists = diesel::select(diesel::dsl::exists(vendors::table.find(id)))
+            .get_result::<bool>(conn)
+            .await
+            .map_err(|_| db_error())?;
+        if !exists {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ApiError::not_found(format!("Vendor {} not found", id))),
+            )
+                .into_response());
+        }
+    }
+
+    let mut unknown = Vec::new();
+    if let Some(id) = query.vendor_id {
+        let exists = diesel::select(diesel::dsl::exists(vendors::table.find(id)))
+            .get_result::<bool>(conn)
+            .await
+            .map_err(|_| db_error())?;
+        if !exists {
+            unknown.push(format!("vendor_id {}", id));
+        }
+    }
+    if let Some(id) = query.category_id {
+        let exists = diesel::select(diesel::dsl::exists(categories::table.find(id)))
+            .get_result::<bool>(conn)
+            .await
+            .map_err(|_| db_error())?;
+        if !exists {
+            unknown.push(format!("category_id {}", id));
+        }
+    }
+    if let Some(id) = query.owner_id {
+        let exists = diesel::select(diesel::dsl::exists(users::table.find(id)))
+            .get_result::<bool>(conn)
+            .await
+            .map_err(|_| db_error())?;
+        if !exists {
+            unknown.push(format!("owner_id {}", id));
+        }
+    }
+
+    if unknown.is_empty() {
+        Ok(())
+    } else {
+        Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::validation_error(format!(
+                "No such {}",
+                unknown.join(", ")
+            ))),
+        )
+            .into_response())
+    }
+}
+
+diesel::alias!(users as item_creators: ItemCreators, users as item_owners: ItemOwners);
+
+/// `action_items` joined to its creator and owner, so a page of items comes back with
+/// both people in the same query.
+type ItemsWithPeople = diesel::dsl::InnerJoinOn<
+    diesel::dsl::InnerJoinOn<
+        action_items::table,
+        Alias<ItemCreators>,
+        diesel::dsl::Eq<AliasedField<ItemCreators, users::id>, action_items::created_by_id>,
+    >,
+    Alias<ItemOwners>,
+    diesel::dsl::Eq<AliasedField<ItemOwners, users::id>, action_items::owner_id>,
+>;
+
+type BoxedItemsWithPeople<'a> = diesel::dsl::IntoBoxed<'a, ItemsWithPeople, diesel::pg::Pg>;
+
+fn items_with_people() -> ItemsWithPeople {
+    action_items::table
+        .inner_join(
+            item_creators.on(item_creators
+                .field(users::id)
+                .eq(action_items::created_by_id)),
+        )
+        .inner_join(item_owners.on(item_owners.field(users::id).eq(action_items::owner_id)))
+}
+
+/// `items_with_people` with every `ItemsQuery` filter applied. Built fresh for the page and the
+/// total count since boxed queries can't be cloned.
+fn filtered_items<'a>(vendor_id: Option<i32>, query: &'a ItemsQuery) -> BoxedItemsWithPeople<'a> {
+    let mut items_query = items_with_people().into_boxed();
+
+    if !query.include_deleted.unwrap_or(false) {
+        items_query = items_query.filter(action_items::deleted_at.is_null());
+    }
 
     if let Some(vid) = vendor_id.or(query.vendor_id) {
         items_query = items_query.filter(action_items::vendor_id.eq(vid));
@@ -144,18 +447,189 @@ async fn list_items_internal(
     }
 
     if let Some(owner_id) = query.owner_id {
-        items_query = items_query.filter(action_items::owner_id.eq(owner_id));
+        if query.include_assignees.unwrap_or(false) {
+            let assigned = item_assignees::table
+                .filter(item_assignees::user_id.eq(owner_id))
+                .select(item_assignees::action_item_id);
+            items_query = items_query.filter(
+                action_items::owner_id
+                    .eq(owner_id)
+                    .or(action_items::id.eq_any(assigned)),
+            );
+        } else {
+            items_query = items_query.filter(action_items::owner_id.eq(owner_id));
+        }
     }
 
     if let Some(ref priority) = query.priority {
         items_query = items_query.filter(action_items::priority.eq(priority));
     }
 
-    let items: Vec<(ActionItem, Category)> = match items_query
-        .order(action_items::id.asc())
-        .select((ActionItem::as_select(), Category::as_select()))
-        .load(&mut conn)
+    // Tags are stored lowercased; an unusable name simply matches nothing
+    if let Some(ref tag) = query.tag {
+        let tag = tag.trim().to_lowercase();
+        let tagged = item_tags::table
+            .inner_join(tags::table)
+            .filter(tags::name.eq(tag))
+            .select(item_tags::action_item_id);
+        items_query = items_query.filter(action_items::id.eq_any(tagged));
+    }
+
+    // Filter on the current (latest) status in the database, treating items with no
+    // history as "New" just like the response does
+    if let Some(ref status) = query.status {
+        items_query = items_query.filter(
+            diesel::dsl::sql::<diesel::sql_types::Bool>(&format!("{} = ", LATEST_STATUS_SQL))
+                .bind::<diesel::sql_types::Text, _>(status.clone()),
+        );
+    }
+
+    // Both bounds are inclusive
+    if let Some(after) = query.created_after {
+        items_query = items_query.filter(action_items::create_date.ge(after));
+    }
+
+    if let Some(before) = query.created_before {
+        items_query = items_query.filter(action_items::create_date.le(before));
+    }
+
+    items_query
+}
+
+/// Workflow position of the current status, matching the home table's client-side order.
+fn status_rank_sql() -> String {
+    format!(
+        "CASE {} WHEN 'New' THEN 0 WHEN 'Not Started' THEN 1 WHEN 'In Progress' THEN 2 \
+         WHEN 'TBC' THEN 3 WHEN 'Blocked' THEN 4 WHEN 'Complete' THEN 5 ELSE 6 END",
+        LATEST_STATUS_SQL
+    )
+}
+
+/// Order by the requested column, then by id so pages stay deterministic. Missing due
+/// dates sort last in either direction.
+fn sort_items<'a>(
+    items_query: BoxedItemsWithPeople<'a>,
+    sort_by: ItemSortField,
+    dir: SortDir,
+) -> BoxedItemsWithPeople<'a> {
+    use diesel::dsl::sql;
+    use diesel::sql_types::{Integer, Text};
+
+    let desc = dir == SortDir::Desc;
+    let sorted = match sort_by {
+        // By prefix, then number, so AD-1000 sorts after AD-999 whatever the padding
+        ItemSortField::Id if desc => {
+            return items_query
+                .order(sql::<Text>(ID_PREFIX_SQL).desc())
+                .then_order_by(action_items::number.desc())
+        }
+        ItemSortField::Id => {
+            return items_query
+                .order(sql::<Text>(ID_PREFIX_SQL).asc())
+                .then_order_by(action_items::number.asc())
+        }
+        ItemSortField::Title if desc => items_query.order(action_items::title.desc()),
+        ItemSortField::Title => items_query.order(action_items::title.asc()),
+        ItemSortField::Priority if desc => {
+            items_query.order(sql::<Integer>(PRIORITY_RANK_SQL).desc())
+        }
+        ItemSortField::Priority => items_query.order(sql::<Integer>(PRIORITY_RANK_SQL).asc()),
+        ItemSortField::DueDate if desc => {
+            items_query.order(action_items::due_date.desc().nulls_last())
+        }
+        ItemSortField::DueDate => items_query.order(action_items::due_date.asc().nulls_last()),
+        ItemSortField::Status if desc => {
+            items_query.order(sql::<Integer>(&status_rank_sql()).desc())
+        }
+        ItemSortField::Status => items_query.order(sql::<Integer>(&status_rank_sql()).asc()),
+        ItemSortField::UpdatedAt if desc => items_query.order(action_items::updated_at.desc()),
+        ItemSortField::UpdatedAt => items_query.order(action_items::updated_at.asc()),
+    };
+    sorted.then_order_by(action_items::id.asc())
+}
+
+async fn list_items_internal(
+    state: &Arc<AppState>,
+    vendor_id: Option<i32>,
+    query: ItemsQuery,
+    raw: &std::collections::HashMap<String, String>,
+) -> Response {
+    let mut unknown: Vec<&str> = raw
+        .keys()
+        .map(String::as_str)
+        .filter(|key| !ITEMS_QUERY_PARAMS.contains(key))
+        .collect();
+    if !unknown.is_empty() {
+        unknown.sort_unstable();
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::validation_error(format!(
+                "Unknown query parameter(s): {}. Accepted: {}",
+                unknown.join(", "),
+                ITEMS_QUERY_PARAMS.join(", ")
+            ))),
+        )
+            .into_response();
+    }
+
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::validation_error(format!(
+                "limit must be between 1 and {}",
+                MAX_PAGE_SIZE
+            ))),
+        )
+            .into_response();
+    }
+    let offset = query.offset.unwrap_or(0);
+    if offset < 0 {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::validation_error("offset must not be negative")),
+        )
+            .into_response();
+    }
+
+    let mut conn = match super::get_conn(state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    if let Err(resp) = check_filter_ids(&mut conn, vendor_id, &query).await {
+        return resp;
+    }
+
+    let total_count: i64 = match filtered_items(vendor_id, &query)
+        .count()
+        .get_result(&mut conn)
         .await
+    {
+        Ok(n) => n,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to count items")),
+            )
+                .into_response()
+        }
+    };
+
+    let page: Vec<(ActionItem, User, User)> = match sort_items(
+        filtered_items(vendor_id, &query),
+        query.sort_by,
+        query.sort_dir,
+    )
+    .limit(limit)
+    .offset(offset)
+    .select((
+        ActionItem::as_select(),
+        item_creators.fields(users::all_columns),
+        item_owners.fields(users::all_columns),
+    ))
+    .load(&mut conn)
+    .await
     {
         Ok(items) => items,
         Err(_) => {
@@ -167,310 +641,670 @@ async fn list_items_internal(
         }
     };
 
-    // Build a map of user IDs to users for efficient lookup
-    let user_ids: Vec<i32> = items
+    let page = page
+        .into_iter()
+        .map(|(item, creator, owner)| (item, Some(creator), Some(owner)))
+        .collect();
+    let items = match item_responses_with_people(&mut conn, page).await {
+        Ok(items) => items,
+        Err(resp) => return resp,
+    };
+
+    Json(ItemListResponse { items, total_count }).into_response()
+}
+
+/// Responses for items loaded without their people; `list_items_internal` joins them
+/// in instead (`items_with_people`).
+pub(super) async fn build_item_responses(
+    conn: &mut AsyncPgConnection,
+    page: Vec<ActionItem>,
+) -> Result<Vec<ActionItemResponse>, Response> {
+    let mut user_ids: Vec<i32> = page
         .iter()
-        .flat_map(|(item, _)| [item.created_by_id, item.owner_id])
+        .flat_map(|item| [item.created_by_id, item.owner_id])
         .collect();
+    user_ids.sort_unstable();
+    user_ids.dedup();
 
-    let users_list: Vec<User> = users::table
+    let users_map: std::collections::HashMap<i32, User> = users::table
         .filter(users::id.eq_any(&user_ids))
-        .load(&mut conn)
+        .load::<User>(conn)
         .await
-        .unwrap_or_default();
+        .unwrap_or_default()
+        .into_iter()
+        .map(|u| (u.id, u))
+        .collect();
 
-    let users_map: std::collections::HashMap<i32, &User> =
-        users_list.iter().map(|u| (u.id, u)).collect();
+    let page = page
+        .into_iter()
+        .map(|item| {
+            let creator = users_map.get(&item.created_by_id).cloned();
+            let owner = users_map.get(&item.owner_id).cloned();
+            (item, creator, owner)
+        })
+        .collect();
+    item_responses_with_people(conn, page).await
+}
 
-    // Get current status for each item
-    let mut result = Vec::new();
-    for (item, category) in items {
-        let (status, status_changed_at) = current_status(&mut conn, &item).await;
+async fn item_responses_with_people(
+    conn: &mut AsyncPgConnection,
+    page: Vec<(ActionItem, Option<User>, Option<User>)>,
+) -> Result<Vec<ActionItemResponse>, Response> {
+    let category_ids: Vec<i32> = page.iter().map(|(item, ..)| item.category_id).collect();
+    let category_names: std::collections::HashMap<i32, String> = match categories::table
+        .filter(categories::id.eq_any(&category_ids))
+        .select(Category::as_select())
+        .load(conn)
+        .await
+    {
+        Ok(cats) => cats.into_iter().map(|c| (c.id, c.name)).collect(),
+        Err(_) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to fetch categories")),
+            )
+                .into_response())
+        }
+    };
 
-        // Filter by status if requested
-        if let Some(ref query_status) = query.status {
-            if &status != query_status {
-                continue;
-            }
+    // Latest status per item in one query rather than one round trip per item
+    let item_ids: Vec<&str> = page.iter().map(|(item, ..)| item.id.as_str()).collect();
+    let latest_statuses: Vec<StatusHistory> = match status_history::table
+        .filter(status_history::action_item_id.eq_any(&item_ids))
+        .distinct_on(status_history::action_item_id)
+        .order((
+            status_history::action_item_id,
+            status_history::changed_at.desc(),
+            status_history::id.desc(),
+        ))
+        .load(conn)
+        .await
+    {
+        Ok(s) => s,
+        Err(_) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to fetch item statuses")),
+            )
+                .into_response())
+        }
+    };
+
+    let mut status_map: std::collections::HashMap<String, (String, DateTime<Utc>)> =
+        latest_statuses
+            .into_iter()
+            .map(|sh| (sh.action_item_id, (sh.status, sh.changed_at)))
+            .collect();
+
+    let mut assignee_map = match super::assignees::assignees_by_item(conn, &item_ids).await {
+        Ok(m) => m,
+        Err(_) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to fetch item assignees")),
+            )
+                .into_response())
+        }
+    };
+
+    let watcher_counts = match super::watchers::watcher_counts(conn, &item_ids).await {
+        Ok(m) => m,
+        Err(_) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to fetch watcher counts")),
+            )
+                .into_response())
         }
+    };
+
+    let mut tag_map = match super::tags::tags_by_item(conn, &item_ids).await {
+        Ok(m) => m,
+        Err(_) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to fetch item tags")),
+            )
+                .into_response())
+        }
+    };
+
+    let mut link_map = match super::links::links_by_item(conn, &item_ids).await {
+        Ok(m) => m,
+        Err(_) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to fetch item links")),
+            )
+                .into_response())
+        }
+    };
+
+    let mut result = Vec::new();
+    for (item, creator, owner) in page {
+        let category = category_names
+            .get(&item.category_id)
+            .cloned()
+            .unwrap_or_default();
+        let status = status_map
+            .remove(&item.id)
+            .unwrap_or_else(|| ("New".to_string(), item.created_at));
 
-        let creator = users_map.get(&item.created_by_id).copied();
-        let owner = users_map.get(&item.owner_id).copied();
+        let relations = ItemRelations {
+            assignees: assignee_map.remove(&item.id).unwrap_or_default(),
+            watcher_count: watcher_counts.get(&item.id).copied().unwrap_or(0),
+            tags: tag_map.remove(&item.id).unwrap_or_default(),
+            links: link_map.remove(&item.id).unwrap_or_default(),
+        };
 
         result.push(build_item_response(
             item,
-            category.name,
-            creator,
-            owner,
+            category,
+            creator.as_ref(),
+            owner.as_ref(),
             status,
-            status_changed_at,
+            relations,
         ));
     }
 
-    Json(result).into_response()
+    Ok(result)
 }
 
-pub async fn get(
-    State(state): State<Arc<AppState>>,
-    Path(item_id): Path<String>,
-    _auth: AuthUser,
-) -> impl IntoResponse {
-    let mut conn = match super::get_conn(&state).await {
+/// Open (not Complete) items with a due date in `from..=to`, soonest first.
+async fn open_items_due_between(
+    state: &Arc<AppState>,
+    from: Option<chrono::NaiveDate>,
+    to: chrono::NaiveDate,
+) -> Response {
+    let mut conn = match super::get_conn(state).await {
         Ok(c) => c,
         Err(resp) => return resp,
     };
 
-    let result: (ActionItem, Category) = match action_items::table
-        .inner_join(categories::table.on(categories::id.eq(action_items::category_id)))
-        .filter(action_items::id.eq(&item_id))
-        .select((ActionItem::as_select(), Category::as_select()))
-        .first(&mut conn)
+    let mut items_query = action_items::table
+        .filter(action_items::due_date.le(to))
+        .filter(action_items::deleted_at.is_null())
+        .filter(
+            diesel::dsl::sql::<diesel::sql_types::Bool>(&format!("{} <> ", LATEST_STATUS_SQL))
+                .bind::<diesel::sql_types::Text, _>("Complete"),
+        )
+        .into_boxed();
+    if let Some(from) = from {
+        items_query = items_query.filter(action_items::due_date.ge(from));
+    }
+
+    let items: Vec<ActionItem> = match items_query
+        .order((action_items::due_date.asc(), action_items::id.asc()))
+        .select(ActionItem::as_select())
+        .load(&mut conn)
         .await
     {
-        Ok(r) => r,
-        Err(diesel::NotFound) => {
-            return (
-                StatusCode::NOT_FOUND,
-                Json(ApiError::not_found(format!(
-                    "Action item {} not found",
-                    item_id
-                ))),
-            )
-                .into_response()
-        }
+        Ok(i) => i,
         Err(_) => {
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiError::internal_error("Failed to fetch item")),
+                Json(ApiError::internal_error("Failed to fetch items")),
             )
                 .into_response()
         }
     };
 
-    let (item, category) = result;
+    match build_item_responses(&mut conn, items).await {
+        Ok(items) => Json(items).into_response(),
+        Err(resp) => resp,
+    }
+}
 
-    // Fetch creator and owner
-    let creator: Option<User> = users::table
-        .filter(users::id.eq(item.created_by_id))
-        .first(&mut conn)
-        .await
-        .ok();
+/// Open items whose due date has passed. "Today" is the UTC date, like `create_date`.
+pub async fn overdue(State(state): State<Arc<AppState>>, _auth: AuthUser) -> impl IntoResponse {
+    let yesterday = Utc::now().date_naive() - chrono::Days::new(1);
+    open_items_due_between(&state, None, yesterday).await
+}
 
-    let owner: Option<User> = users::table
-        .filter(users::id.eq(item.owner_id))
-        .first(&mut conn)
-        .await
-        .ok();
-
-    let (status, status_changed_at) = current_status(&mut conn, &item).await;
+/// Open items due between today and `days` from now (inclusive). Overdue items are left
+/// to `overdue`.
+pub async fn due_soon(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DueSoonQuery>,
+    _auth: AuthUser,
+) -> impl IntoResponse {
+    let days = query.days.unwrap_or(DEFAULT_DUE_SOON_DAYS);
+    if !(0..=MAX_DUE_SOON_DAYS).contains(&days) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::validation_error(format!(
+                "days must be between 0 and {}",
+                MAX_DUE_SOON_DAYS
+            ))),
+        )
+            .into_response();
+    }
 
-    Json(build_item_response(
-        item,
-        category.name,
-        creator.as_ref(),
-        owner.as_ref(),
-        status,
-        status_changed_at,
-    ))
-    .into_response()
+    let today = Utc::now().date_naive();
+    let until = today + chrono::Days::new(days as u64);
+    open_items_due_between(&state, Some(today), until).await
 }
 
-pub async fn create(
+pub async fn search(
     State(state): State<Arc<AppState>>,
-    Path(vendor_id): Path<i32>,
-    auth: AuthUser,
-    Json(payload): Json<CreateActionItemReq>,
+    Query(query): Query<SearchQuery>,
+    _auth: AuthUser,
 ) -> impl IntoResponse {
-    // Validate title
-    if payload.title.is_empty() || payload.title.len() > 500 {
+    let q = query.q.as_deref().unwrap_or("").trim();
+    if q.is_empty() {
         return (
             StatusCode::BAD_REQUEST,
-            Json(ApiError::validation_error("Title must be 1-500 characters")),
+            Json(ApiError::validation_error("Search text must not be empty")),
         )
             .into_response();
     }
 
+    // Treat the search text literally, including any LIKE wildcards it contains
+    let escaped = q
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    let pattern = format!("%{}%", escaped);
+
     let mut conn = match super::get_conn(&state).await {
         Ok(c) => c,
         Err(resp) => return resp,
     };
 
-    // Get vendor and increment next_number
-    let vendor: Vendor = match vendors::table
-        .filter(vendors::id.eq(vendor_id))
-        .first(&mut conn)
+    let hits: Vec<SearchHitRow> = match diesel::sql_query(SEARCH_SQL)
+        .bind::<diesel::sql_types::Text, _>(&pattern)
+        .bind::<diesel::sql_types::BigInt, _>(SEARCH_LIMIT)
+        .load(&mut conn)
         .await
     {
-        Ok(v) => v,
-        Err(diesel::NotFound) => {
-            return (
-                StatusCode::NOT_FOUND,
-                Json(ApiError::not_found(format!(
-                    "Vendor {} not found",
-                    vendor_id
-                ))),
-            )
-                .into_response()
-        }
+        Ok(rows) => rows,
         Err(_) => {
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiError::internal_error("Failed to fetch vendor")),
+                Json(ApiError::internal_error("Failed to search items")),
             )
                 .into_response()
         }
     };
 
-    // Verify category exists
-    let category: Category = match categories::table
-        .filter(categories::id.eq(payload.category_id))
-        .first(&mut conn)
+    let hit_ids: Vec<&str> = hits.iter().map(|h| h.id.as_str()).collect();
+    let items: Vec<ActionItem> = match action_items::table
+        .filter(action_items::id.eq_any(&hit_ids))
+        .select(ActionItem::as_select())
+        .load(&mut conn)
         .await
     {
-        Ok(c) => c,
-        Err(diesel::NotFound) => {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(ApiError::validation_error("Invalid category")),
-            )
-                .into_response()
-        }
+        Ok(items) => items,
         Err(_) => {
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiError::internal_error("Failed to verify category")),
+                Json(ApiError::internal_error("Failed to fetch items")),
             )
                 .into_response()
         }
     };
 
-    let number = vendor.next_number;
-    let item_id = format!("{}-{:03}", vendor.prefix, number);
+    let mut responses: std::collections::HashMap<String, ActionItemResponse> =
+        match build_item_responses(&mut conn, items).await {
+            Ok(items) => items.into_iter().map(|i| (i.id.clone(), i)).collect(),
+            Err(resp) => return resp,
+        };
 
-    // Update vendor's next_number
-    if diesel::update(vendors::table.filter(vendors::id.eq(vendor_id)))
-        .set(vendors::next_number.eq(number + 1))
-        .execute(&mut conn)
-        .await
-        .is_err()
-    {
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiError::internal_error("Failed to update vendor")),
-        )
-            .into_response();
-    }
+    // Keep the relevance order from the search query
+    let results: Vec<ItemSearchResult> = hits
+        .into_iter()
+        .filter_map(|hit| {
+            let item = responses.remove(&hit.id)?;
+            let matched_fields = [
+                (hit.title_match, SearchField::Title),
+                (hit.description_match, SearchField::Description),
+                (hit.notes_match, SearchField::Notes),
+            ]
+            .into_iter()
+            .filter_map(|(matched, field)| matched.then_some(field))
+            .collect();
+            Some(ItemSearchResult {
+                item,
+                matched_fields,
+            })
+        })
+        .collect();
 
-    let now = Utc::now();
-    let new_item = NewActionItem {
-        id: item_id.clone(),
-        vendor_id,
-        number,
-        title: payload.title,
-        create_date: now.date_naive(),
-        created_by_id: auth.user_id,
-        due_date: payload.due_date,
-        owner_id: payload.owner_id,
-        priority: payload.priority,
-        description: payload.description,
-        category_id: payload.category_id,
-    };
+    Json(results).into_response()
+}
 
-    let item: ActionItem = match diesel::insert_into(action_items::table)
-        .values(&new_item)
-        .returning(ActionItem::as_returning())
-        .get_result(&mut conn)
+/// An item with its category, creator, owner, current status and relations, or the
+/// error response when it doesn't exist (or is deleted).
+async fn load_item_response(
+    conn: &mut AsyncPgConnection,
+    item_id: &str,
+) -> Result<ActionItemResponse, Response> {
+    let (item, category): (ActionItem, Category) = match action_items::table
+        .inner_join(categories::table.on(categories::id.eq(action_items::category_id)))
+        .filter(action_items::id.eq(item_id))
+        .filter(action_items::deleted_at.is_null())
+        .select((ActionItem::as_select(), Category::as_select()))
+        .first(conn)
         .await
     {
-        Ok(i) => i,
+        Ok(r) => r,
+        Err(diesel::NotFound) => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ApiError::not_found(format!(
+                    "Action item {} not found",
+                    item_id
+                ))),
+            )
+                .into_response())
+        }
         Err(_) => {
-            return (
+            return Err((
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiError::internal_error("Failed to create item")),
+                Json(ApiError::internal_error("Failed to fetch item")),
             )
-                .into_response()
+                .into_response())
         }
     };
 
-    // Create initial status entry
-    let initial_status = NewStatusHistory {
-        action_item_id: item.id.clone(),
-        status: "New".to_string(),
-        changed_by_id: auth.user_id,
-        comment: Some("Item created".to_string()),
-    };
-
-    let _ = diesel::insert_into(status_history::table)
-        .values(&initial_status)
-        .execute(&mut conn)
-        .await;
-
-    // Fetch creator name for response
+    // Fetch creator and owner
     let creator: Option<User> = users::table
         .filter(users::id.eq(item.created_by_id))
-        .first(&mut conn)
+        .first(conn)
         .await
         .ok();
 
-    // Fetch owner name for response
     let owner: Option<User> = users::table
         .filter(users::id.eq(item.owner_id))
-        .first(&mut conn)
+        .first(conn)
         .await
         .ok();
 
-    let created_at = item.created_at;
+    let status = current_status(conn, &item).await;
+    let relations = item_relations(conn, &item.id).await;
+
+    Ok(build_item_response(
+        item,
+        category.name,
+        creator.as_ref(),
+        owner.as_ref(),
+        status,
+        relations,
+    ))
+}
+
+/// Get one item, including whether the requesting user watches it. The `ETag` is a hash
+/// of the response, so it changes with `updated_at`, the latest status change and the
+/// item's tags, assignees, links and watchers (which don't touch `updated_at`). A matching
+/// `If-None-Match` gets an empty `304`.
+pub async fn get(
+    State(state): State<Arc<AppState>>,
+    Path(item_id): Path<String>,
+    auth: AuthUser,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    let mut response = match load_item_response(&mut conn, &item_id).await {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    response.watching = Some(super::watchers::is_watching(&mut conn, &item_id, auth.user_id).await);
+    let body = match serde_json::to_vec(&response) {
+        Ok(b) => b,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to encode item")),
+            )
+                .into_response()
+        }
+    };
+    let etag = super::etag_for_body(&body);
+    if super::if_none_match(&headers, &etag) {
+        return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response();
+    }
+
     (
-        StatusCode::CREATED,
-        Json(build_item_response(
-            item,
-            category.name,
-            creator.as_ref(),
-            owner.as_ref(),
-            "New".to_string(),
-            created_at,
-        )),
+        [
+            (header::ETAG, etag),
+            (header::CONTENT_TYPE, "application/json".to_string()),
+        ],
+        body,
     )
         .into_response()
 }
 
-pub async fn update(
+/// Titles are limited in characters (not bytes) to match the `VARCHAR` column.
+fn validate_title(title: &str, max_length: usize) -> Result<(), ApiError> {
+    if title.is_empty() || title.chars().count() > max_length {
+        return Err(ApiError::validation_error(format!(
+            "Title must be 1-{} characters",
+            max_length
+        )));
+    }
+    Ok(())
+}
+
+fn validate_recurrence(recurrence: &str) -> Result<(), ApiError> {
+    if Recurrence::all().iter().any(|r| r.as_str() == recurrence) {
+        return Ok(());
+    }
+    let valid: Vec<&str> = Recurrence::all().iter().map(Recurrence::as_str).collect();
+    Err(ApiError::validation_error(format!(
+        "Invalid recurrence. Must be one of: {}",
+        valid.join(", ")
+    )))
+}
+
+/// Create an item. With an `Idempotency-Key` header, a repeat of a key the same user sent
+/// in the last `IDEMPOTENCY_KEY_TTL_HOURS` returns the item the first request made (`200`
+/// rather than `201`) instead of creating another, so double submits and client retries
+/// are safe. A repeat that arrives while the first is still running gets a `409`, until
+/// `IDEMPOTENCY_CLAIM_TIMEOUT_SECS` have passed and it takes the key over. Reusing a key
+/// for a different vendor or body gets a `422`.
+pub async fn create(
     State(state): State<Arc<AppState>>,
-    Path(item_id): Path<String>,
-    _auth: AuthUser,
-    Json(payload): Json<UpdateActionItemReq>,
+    Path(vendor_id): Path<i32>,
+    auth: AuthUser,
+    headers: HeaderMap,
+    Json(payload): Json<CreateActionItemReq>,
 ) -> impl IntoResponse {
-    // Validate title if provided
-    if let Some(ref title) = payload.title {
-        if title.is_empty() || title.len() > 500 {
+    let Some(key) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return match create_item_internal(&state, vendor_id, auth.user_id, payload).await {
+            Ok(item) => (StatusCode::CREATED, Json(item)).into_response(),
+            Err(resp) => resp,
+        };
+    };
+    let key = match key.to_str() {
+        Ok(k) if !k.is_empty() && k.len() <= IDEMPOTENCY_KEY_MAX_LENGTH => k.to_string(),
+        _ => {
             return (
                 StatusCode::BAD_REQUEST,
-                Json(ApiError::validation_error("Title must be 1-500 characters")),
+                Json(ApiError::validation_error(format!(
+                    "Idempotency-Key must be 1-{} visible ASCII characters",
+                    IDEMPOTENCY_KEY_MAX_LENGTH
+                ))),
             )
-                .into_response();
+                .into_response()
+        }
+    };
+
+    // What the key stands for: a repeat must be for the same vendor and body
+    let request_hash = format!(
+        "{:x}",
+        Sha256::digest(serde_json::to_vec(&(vendor_id, &payload)).expect("Request serializes"))
+    );
+
+    {
+        let mut conn = match super::get_conn(&state).await {
+            Ok(c) => c,
+            Err(resp) => return resp,
+        };
+
+        // Expired keys are forgotten so they can be used again
+        let cutoff = Utc::now() - chrono::Duration::hours(IDEMPOTENCY_KEY_TTL_HOURS);
+        let _ =
+            diesel::delete(idempotency_keys::table.filter(idempotency_keys::created_at.lt(cutoff)))
+                .execute(&mut conn)
+                .await;
+
+        // Claim the key; the primary key makes a concurrent repeat lose this race
+        let claimed = match diesel::insert_into(idempotency_keys::table)
+            .values(&NewIdempotencyKey {
+                user_id: auth.user_id,
+                key: key.clone(),
+                request_hash: request_hash.clone(),
+            })
+            .on_conflict_do_nothing()
+            .execute(&mut conn)
+            .await
+        {
+            Ok(n) => n == 1,
+            Err(_) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiError::internal_error("Failed to record idempotency key")),
+                )
+                    .into_response()
+            }
+        };
+
+        if !claimed {
+            let key_row = idempotency_keys::table
+                .filter(idempotency_keys::user_id.eq(auth.user_id))
+                .filter(idempotency_keys::key.eq(&key));
+            let existing: Option<(Option<String>, Option<String>, DateTime<Utc>)> = match key_row
+                .select((
+                    idempotency_keys::action_item_id,
+                    idempotency_keys::request_hash,
+                    idempotency_keys::created_at,
+                ))
+                .first(&mut conn)
+                .await
+                .optional()
+            {
+                Ok(row) => row,
+                Err(_) => {
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(ApiError::internal_error(
+                            "Failed to look up idempotency key",
+                        )),
+                    )
+                        .into_response()
+                }
+            };
+            let in_progress = || {
+                (
+                    StatusCode::CONFLICT,
+                    Json(ApiError::conflict(
+                        "A request with this Idempotency-Key is still in progress; retry shortly",
+                    )),
+                )
+                    .into_response()
+            };
+            match existing {
+                // The first request failed and released the key in the meantime
+                None => return in_progress(),
+                Some((_, Some(stored), _)) if stored != request_hash => {
+                    return (
+                        StatusCode::UNPROCESSABLE_ENTITY,
+                        Json(ApiError::unprocessable(
+                            "This Idempotency-Key was already used for a different request",
+                        )),
+                    )
+                        .into_response()
+                }
+                Some((Some(item_id), _, _)) => {
+                    return match load_item_response(&mut conn, &item_id).await {
+                        Ok(item) => (StatusCode::OK, Json(item)).into_response(),
+                        Err(resp) => resp,
+                    }
+                }
+                Some((None, _, claimed_at)) => {
+                    // A claim that never finished (the server died mid-request) is taken
+                    // over once it is stale; if another repeat got there first, this one waits
+                    let stale =
+                        Utc::now() - chrono::Duration::seconds(IDEMPOTENCY_CLAIM_TIMEOUT_SECS);
+                    if claimed_at >= stale {
+                        return in_progress();
+                    }
+                    match diesel::update(
+                        key_row
+                            .filter(idempotency_keys::action_item_id.is_null())
+                            .filter(idempotency_keys::created_at.eq(claimed_at)),
+                    )
+                    .set((
+                        idempotency_keys::created_at.eq(Utc::now()),
+                        idempotency_keys::request_hash.eq(&request_hash),
+                    ))
+                    .execute(&mut conn)
+                    .await
+                    {
+                        Ok(1) => {}
+                        Ok(_) => return in_progress(),
+                        Err(_) => {
+                            return (
+                                StatusCode::INTERNAL_SERVER_ERROR,
+                                Json(ApiError::internal_error("Failed to record idempotency key")),
+                            )
+                                .into_response()
+                        }
+                    }
+                }
+            }
         }
     }
 
+    let result = create_item_internal(&state, vendor_id, auth.user_id, payload).await;
+
     let mut conn = match super::get_conn(&state).await {
         Ok(c) => c,
         Err(resp) => return resp,
     };
+    let key_row = idempotency_keys::table
+        .filter(idempotency_keys::user_id.eq(auth.user_id))
+        .filter(idempotency_keys::key.eq(&key));
+    match result {
+        Ok(item) => {
+            if let Err(e) = diesel::update(key_row)
+                .set(idempotency_keys::action_item_id.eq(&item.id))
+                .execute(&mut conn)
+                .await
+            {
+                tracing::warn!("Failed to record item for idempotency key: {e}");
+            }
+            (StatusCode::CREATED, Json(item)).into_response()
+        }
+        Err(resp) => {
+            // Nothing was created, so let a corrected retry reuse the key
+            let _ = diesel::delete(key_row).execute(&mut conn).await;
+            resp
+        }
+    }
+}
 
-    let changeset = UpdateActionItem {
-        title: payload.title,
-        due_date: payload.due_date,
-        category_id: payload.category_id,
-        owner_id: payload.owner_id,
-        priority: payload.priority,
-        description: payload.description,
-        updated_at: Some(Utc::now()),
-    };
-
-    let item: ActionItem =
-        match diesel::update(action_items::table.filter(action_items::id.eq(&item_id)))
-            .set(&changeset)
-            .returning(ActionItem::as_returning())
-            .get_result(&mut conn)
+/// Copy an item's title, description, category, priority and owner into a new "New" item
+/// in the same vendor.
+pub async fn duplicate(
+    State(state): State<Arc<AppState>>,
+    Path(item_id): Path<String>,
+    Query(query): Query<DuplicateQuery>,
+    auth: AuthUser,
+) -> impl IntoResponse {
+    let source: ActionItem = {
+        let mut conn = match super::get_conn(&state).await {
+            Ok(c) => c,
+            Err(resp) => return resp,
+        };
+        match action_items::table
+            .filter(action_items::id.eq(&item_id))
+            .filter(action_items::deleted_at.is_null())
+            .first(&mut conn)
             .await
         {
             Ok(i) => i,
@@ -487,38 +1321,387 @@ pub async fn update(
             Err(_) => {
                 return (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ApiError::internal_error("Failed to update item")),
+                    Json(ApiError::internal_error("Failed to fetch item")),
                 )
                     .into_response()
             }
-        };
+        }
+    };
 
-    // Get category name
-    let category: Category = match categories::table
-        .filter(categories::id.eq(item.category_id))
+    let title = if query.copy_suffix.unwrap_or(true) {
+        // Trim the original so the suffixed title still fits the limit
+        let max = state
+            .config
+            .title_max_length
+            .saturating_sub(COPY_SUFFIX.chars().count());
+        let base: String = source.title.chars().take(max).collect();
+        format!("{}{}", base, COPY_SUFFIX)
+    } else {
+        source.title
+    };
+
+    let payload = CreateActionItemReq {
+        title,
+        due_date: None,
+        category_id: source.category_id,
+        owner_id: source.owner_id,
+        priority: source.priority,
+        description: source.description,
+        recurrence: None,
+    };
+
+    match create_item_internal(&state, source.vendor_id, auth.user_id, payload).await {
+        Ok(item) => (StatusCode::CREATED, Json(item)).into_response(),
+        Err(resp) => resp,
+    }
+}
+
+async fn create_item_internal(
+    state: &Arc<AppState>,
+    vendor_id: i32,
+    created_by_id: i32,
+    payload: CreateActionItemReq,
+) -> Result<ActionItemResponse, Response> {
+    // Validate title
+    if let Err(e) = validate_title(&payload.title, state.config.title_max_length) {
+        return Err((StatusCode::BAD_REQUEST, Json(e)).into_response());
+    }
+    let recurrence = payload
+        .recurrence
+        .unwrap_or_else(|| Recurrence::None.as_str().to_string());
+    if let Err(e) = validate_recurrence(&recurrence) {
+        return Err((StatusCode::BAD_REQUEST, Json(e)).into_response());
+    }
+
+    let mut conn = match super::get_conn(state).await {
+        Ok(c) => c,
+        Err(resp) => return Err(resp),
+    };
+
+    // Get vendor for its prefix
+    let vendor: Vendor = match vendors::table
+        .filter(vendors::id.eq(vendor_id))
         .first(&mut conn)
         .await
     {
-        Ok(c) => c,
+        Ok(v) => v,
+        Err(diesel::NotFound) => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ApiError::not_found(format!(
+                    "Vendor {} not found",
+                    vendor_id
+                ))),
+            )
+                .into_response())
+        }
         Err(_) => {
-            return (
+            return Err((
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiError::internal_error("Failed to fetch category")),
+                Json(ApiError::internal_error("Failed to fetch vendor")),
             )
-                .into_response()
+                .into_response())
         }
     };
 
-    let (status, status_changed_at) = current_status(&mut conn, &item).await;
-
-    // Fetch creator and owner names
-    let creator: Option<User> = users::table
-        .filter(users::id.eq(item.created_by_id))
+    // Verify category exists
+    let category: Category = match categories::table
+        .filter(categories::id.eq(payload.category_id))
         .first(&mut conn)
         .await
-        .ok();
-
-    let owner: Option<User> = users::table
+    {
+        Ok(c) => c,
+        Err(diesel::NotFound) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::validation_error("Invalid category")),
+            )
+                .into_response())
+        }
+        Err(_) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to verify category")),
+            )
+                .into_response())
+        }
+    };
+
+    let now = Utc::now();
+    // id and number are filled in once a number is claimed
+    let template = NewActionItem {
+        id: String::new(),
+        vendor_id,
+        number: 0,
+        title: payload.title,
+        create_date: now.date_naive(),
+        created_by_id,
+        due_date: payload.due_date,
+        owner_id: payload.owner_id,
+        priority: payload.priority,
+        description: payload.description,
+        category_id: payload.category_id,
+        recurrence,
+    };
+
+    // Claim the number and insert in one transaction, so concurrent creates serialize on
+    // the vendor row. A unique violation means next_number had fallen behind the vendor's
+    // items (say after an import); the retry first moves it past the highest one.
+    let mut resync = false;
+    let item: ActionItem = loop {
+        let new_item = template.clone();
+        let prefix = vendor.prefix.clone();
+        let padding = vendor.number_padding;
+        let result = conn
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                async move {
+                    if resync {
+                        let highest: Option<i32> = action_items::table
+                            .filter(action_items::vendor_id.eq(vendor_id))
+                            .select(diesel::dsl::max(action_items::number))
+                            .first(conn)
+                            .await?;
+                        if let Some(highest) = highest {
+                            diesel::update(
+                                vendors::table
+                                    .filter(vendors::id.eq(vendor_id))
+                                    .filter(vendors::next_number.le(highest)),
+                            )
+                            .set(vendors::next_number.eq(highest + 1))
+                            .execute(conn)
+                            .await?;
+                        }
+                    }
+
+                    let next_number: i32 =
+                        diesel::update(vendors::table.filter(vendors::id.eq(vendor_id)))
+                            .set(vendors::next_number.eq(vendors::next_number + 1))
+                            .returning(vendors::next_number)
+                            .get_result(conn)
+                            .await?;
+                    let number = next_number - 1;
+                    let new_item = NewActionItem {
+                        id: super::format_item_id(&prefix, number, padding),
+                        number,
+                        ..new_item
+                    };
+
+                    let item: ActionItem = diesel::insert_into(action_items::table)
+                        .values(&new_item)
+                        .returning(ActionItem::as_returning())
+                        .get_result(conn)
+                        .await?;
+
+                    diesel::insert_into(status_history::table)
+                        .values(&NewStatusHistory {
+                            action_item_id: item.id.clone(),
+                            status: "New".to_string(),
+                            changed_by_id: created_by_id,
+                            comment: Some("Item created".to_string()),
+                            from_status: None,
+                        })
+                        .execute(conn)
+                        .await?;
+
+                    Ok(item)
+                }
+                .scope_boxed()
+            })
+            .await;
+
+        match result {
+            Ok(item) => break item,
+            Err(diesel::result::Error::DatabaseError(
+                diesel::result::DatabaseErrorKind::UniqueViolation,
+                _,
+            )) if !resync => resync = true,
+            Err(diesel::result::Error::DatabaseError(
+                diesel::result::DatabaseErrorKind::ForeignKeyViolation,
+                info,
+            )) => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(foreign_key_error(info.as_ref())),
+                )
+                    .into_response())
+            }
+            Err(_) => {
+                return Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiError::internal_error("Failed to create item")),
+                )
+                    .into_response())
+            }
+        }
+    };
+
+    // Fetch creator name for response
+    let creator: Option<User> = users::table
+        .filter(users::id.eq(item.created_by_id))
+        .first(&mut conn)
+        .await
+        .ok();
+
+    // Fetch owner name for response
+    let owner: Option<User> = users::table
+        .filter(users::id.eq(item.owner_id))
+        .first(&mut conn)
+        .await
+        .ok();
+
+    let created_at = item.created_at;
+    Ok(build_item_response(
+        item,
+        category.name,
+        creator.as_ref(),
+        owner.as_ref(),
+        ("New".to_string(), created_at),
+        ItemRelations::default(),
+    ))
+}
+
+pub async fn update(
+    State(state): State<Arc<AppState>>,
+    Path(item_id): Path<String>,
+    auth: AuthUser,
+    Json(payload): Json<UpdateActionItemReq>,
+) -> impl IntoResponse {
+    // Validate title if provided
+    if let Some(ref title) = payload.title {
+        if let Err(e) = validate_title(title, state.config.title_max_length) {
+            return (StatusCode::BAD_REQUEST, Json(e)).into_response();
+        }
+    }
+    if let Some(ref recurrence) = payload.recurrence {
+        if let Err(e) = validate_recurrence(recurrence) {
+            return (StatusCode::BAD_REQUEST, Json(e)).into_response();
+        }
+    }
+
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    let changeset = UpdateActionItem {
+        title: payload.title,
+        due_date: payload.due_date,
+        category_id: payload.category_id,
+        owner_id: payload.owner_id,
+        priority: payload.priority,
+        description: payload.description,
+        recurrence: payload.recurrence,
+        updated_at: Some(Utc::now()),
+    };
+
+    let id = item_id.as_str();
+    let changed_by_id = auth.user_id;
+    let expected_updated_at = payload.expected_updated_at;
+    let updated = conn
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            async move {
+                // Lock the row so the audit diff matches what we overwrite
+                let old: ActionItem = action_items::table
+                    .filter(action_items::id.eq(id))
+                    .filter(action_items::deleted_at.is_null())
+                    .select(ActionItem::as_select())
+                    .for_update()
+                    .first(conn)
+                    .await?;
+
+                // Someone else saved since the client loaded the item
+                if expected_updated_at.is_some_and(|expected| expected != old.updated_at) {
+                    return Ok(None);
+                }
+
+                let item: ActionItem =
+                    diesel::update(action_items::table.filter(action_items::id.eq(id)))
+                        .set(&changeset)
+                        .returning(ActionItem::as_returning())
+                        .get_result(conn)
+                        .await?;
+
+                let changes = super::audit::item_changes(&old, &item, changed_by_id);
+                if !changes.is_empty() {
+                    diesel::insert_into(item_audit::table)
+                        .values(&changes)
+                        .execute(conn)
+                        .await?;
+                }
+                Ok(Some(item))
+            }
+            .scope_boxed()
+        })
+        .await;
+
+    let item = match updated {
+        Ok(Some(i)) => i,
+        Ok(None) => {
+            return (
+                StatusCode::CONFLICT,
+                Json(ApiError::conflict(format!(
+                    "Action item {} was modified by someone else; reload and try again",
+                    item_id
+                ))),
+            )
+                .into_response()
+        }
+        Err(diesel::NotFound) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiError::not_found(format!(
+                    "Action item {} not found",
+                    item_id
+                ))),
+            )
+                .into_response()
+        }
+        Err(diesel::result::Error::DatabaseError(
+            diesel::result::DatabaseErrorKind::ForeignKeyViolation,
+            info,
+        )) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(foreign_key_error(info.as_ref())),
+            )
+                .into_response()
+        }
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to update item")),
+            )
+                .into_response()
+        }
+    };
+
+    // Get category name
+    let category: Category = match categories::table
+        .filter(categories::id.eq(item.category_id))
+        .first(&mut conn)
+        .await
+    {
+        Ok(c) => c,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to fetch category")),
+            )
+                .into_response()
+        }
+    };
+
+    let status = current_status(&mut conn, &item).await;
+    let relations = item_relations(&mut conn, &item.id).await;
+
+    // Fetch creator and owner names
+    let creator: Option<User> = users::table
+        .filter(users::id.eq(item.created_by_id))
+        .first(&mut conn)
+        .await
+        .ok();
+
+    let owner: Option<User> = users::table
         .filter(users::id.eq(item.owner_id))
         .first(&mut conn)
         .await
@@ -530,7 +1713,295 @@ pub async fn update(
         creator.as_ref(),
         owner.as_ref(),
         status,
-        status_changed_at,
+        relations,
+    ))
+    .into_response()
+}
+
+/// Soft-delete an item: it drops out of lists and lookups but keeps its notes and
+/// history, and can be brought back with `restore`.
+pub async fn delete(
+    State(state): State<Arc<AppState>>,
+    Path(item_id): Path<String>,
+    _auth: AuthUser,
+) -> impl IntoResponse {
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    match diesel::update(
+        action_items::table
+            .filter(action_items::id.eq(&item_id))
+            .filter(action_items::deleted_at.is_null()),
+    )
+    .set(action_items::deleted_at.eq(Utc::now()))
+    .execute(&mut conn)
+    .await
+    {
+        Ok(0) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiError::not_found(format!(
+                "Action item {} not found",
+                item_id
+            ))),
+        )
+            .into_response(),
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::internal_error("Failed to delete item")),
+        )
+            .into_response(),
+    }
+}
+
+/// Undo a soft delete. `409` if the item isn't deleted.
+pub async fn restore(
+    State(state): State<Arc<AppState>>,
+    Path(item_id): Path<String>,
+    auth: AuthUser,
+) -> impl IntoResponse {
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    let deleted_at: Option<DateTime<Utc>> = match action_items::table
+        .filter(action_items::id.eq(&item_id))
+        .select(action_items::deleted_at)
+        .first(&mut conn)
+        .await
+    {
+        Ok(d) => d,
+        Err(diesel::NotFound) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiError::not_found(format!(
+                    "Action item {} not found",
+                    item_id
+                ))),
+            )
+                .into_response()
+        }
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to fetch item")),
+            )
+                .into_response()
+        }
+    };
+
+    if deleted_at.is_none() {
+        return (
+            StatusCode::CONFLICT,
+            Json(ApiError::conflict(format!(
+                "Action item {} is not deleted",
+                item_id
+            ))),
+        )
+            .into_response();
+    }
+
+    if diesel::update(action_items::table.filter(action_items::id.eq(&item_id)))
+        .set(action_items::deleted_at.eq(None::<DateTime<Utc>>))
+        .execute(&mut conn)
+        .await
+        .is_err()
+    {
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::internal_error("Failed to restore item")),
+        )
+            .into_response();
+    }
+
+    // Respond with the item as it is now listed again
+    drop(conn);
+    get(State(state), Path(item_id), auth, HeaderMap::new())
+        .await
+        .into_response()
+}
+
+/// Refile an item under another vendor. The item gets a new id from the target vendor's
+/// sequence; notes, status history and the rest follow it via `ON UPDATE CASCADE`, and the
+/// move is recorded in the audit log and as a note.
+pub async fn move_item(
+    State(state): State<Arc<AppState>>,
+    Path(item_id): Path<String>,
+    auth: AuthUser,
+    Json(payload): Json<MoveItemReq>,
+) -> impl IntoResponse {
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    let id = item_id.as_str();
+    let changed_by_id = auth.user_id;
+    let moved = conn
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            async move {
+                // Lock the item and the target vendor, so neither changes between the
+                // checks below and the move itself
+                let Some(old) = action_items::table
+                    .filter(action_items::id.eq(id))
+                    .filter(action_items::deleted_at.is_null())
+                    .select(ActionItem::as_select())
+                    .for_update()
+                    .first(conn)
+                    .await
+                    .optional()?
+                else {
+                    return Ok(Err((
+                        StatusCode::NOT_FOUND,
+                        ApiError::not_found(format!("Action item {} not found", id)),
+                    )));
+                };
+
+                if old.vendor_id == payload.vendor_id {
+                    return Ok(Err((
+                        StatusCode::BAD_REQUEST,
+                        ApiError::validation_error(format!(
+                            "{} already belongs to this vendor",
+                            id
+                        )),
+                    )));
+                }
+
+                let Some(target) = vendors::table
+                    .filter(vendors::id.eq(payload.vendor_id))
+                    .select(Vendor::as_select())
+                    .for_update()
+                    .first(conn)
+                    .await
+                    .optional()?
+                else {
+                    return Ok(Err((
+                        StatusCode::BAD_REQUEST,
+                        ApiError::validation_error("Invalid vendor"),
+                    )));
+                };
+
+                // Categories are per vendor, so resolve one that belongs to the target
+                let category_query = categories::table
+                    .filter(categories::vendor_id.eq(target.id))
+                    .into_boxed();
+                let category_query = match payload.category_id {
+                    Some(category_id) => category_query.filter(categories::id.eq(category_id)),
+                    None => {
+                        let current_name: String = categories::table
+                            .filter(categories::id.eq(old.category_id))
+                            .select(categories::name)
+                            .first(conn)
+                            .await?;
+                        category_query.filter(categories::name.eq(current_name))
+                    }
+                };
+                let Some(category) = category_query
+                    .select(Category::as_select())
+                    .first(conn)
+                    .await
+                    .optional()?
+                else {
+                    return Ok(Err((
+                        StatusCode::BAD_REQUEST,
+                        ApiError::validation_error(format!(
+                            "No matching category in vendor {}; pass a category_id from that vendor",
+                            target.name
+                        )),
+                    )));
+                };
+
+                let next_number: i32 =
+                    diesel::update(vendors::table.filter(vendors::id.eq(target.id)))
+                        .set(vendors::next_number.eq(vendors::next_number + 1))
+                        .returning(vendors::next_number)
+                        .get_result(conn)
+                        .await?;
+                let number = next_number - 1;
+                let new_id = super::format_item_id(&target.prefix, number, target.number_padding);
+
+                let item: ActionItem =
+                    diesel::update(action_items::table.filter(action_items::id.eq(id)))
+                        .set((
+                            action_items::id.eq(&new_id),
+                            action_items::vendor_id.eq(target.id),
+                            action_items::number.eq(number),
+                            action_items::category_id.eq(category.id),
+                            action_items::updated_at.eq(Utc::now()),
+                        ))
+                        .returning(ActionItem::as_returning())
+                        .get_result(conn)
+                        .await?;
+
+                // Record the id change in the audit log, plus the category if that changed
+                // too, and leave a note on the timeline; links to the old id stop working
+                let mut changes = vec![NewItemAudit {
+                    action_item_id: new_id.clone(),
+                    field: "id".to_string(),
+                    old_value: Some(old.id.clone()),
+                    new_value: Some(new_id),
+                    changed_by_id,
+                }];
+                changes.extend(super::audit::item_changes(&old, &item, changed_by_id));
+                diesel::insert_into(item_audit::table)
+                    .values(&changes)
+                    .execute(conn)
+                    .await?;
+
+                let note = NewNote {
+                    action_item_id: item.id.clone(),
+                    note_date: item.updated_at.date_naive(),
+                    author_id: changed_by_id,
+                    content: format!("Moved from {} to {}", old.id, item.id),
+                };
+                diesel::insert_into(notes::table)
+                    .values(&note)
+                    .execute(conn)
+                    .await?;
+
+                Ok(Ok((item, category)))
+            }
+            .scope_boxed()
+        })
+        .await;
+
+    let (item, category) = match moved {
+        Ok(Ok(moved)) => moved,
+        Ok(Err((status, error))) => return (status, Json(error)).into_response(),
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to move item")),
+            )
+                .into_response()
+        }
+    };
+
+    let status = current_status(&mut conn, &item).await;
+    let relations = item_relations(&mut conn, &item.id).await;
+
+    let creator: Option<User> = users::table
+        .filter(users::id.eq(item.created_by_id))
+        .first(&mut conn)
+        .await
+        .ok();
+
+    let owner: Option<User> = users::table
+        .filter(users::id.eq(item.owner_id))
+        .first(&mut conn)
+        .await
+        .ok();
+
+    Json(build_item_response(
+        item,
+        category.name,
+        creator.as_ref(),
+        owner.as_ref(),
+        status,
+        relations,
     ))
     .into_response()
 }
@@ -547,6 +2018,7 @@ pub async fn go_redirect(
 
     let exists: bool = action_items::table
         .filter(action_items::id.eq(&item_id))
+        .filter(action_items::deleted_at.is_null())
         .select(action_items::id)
         .first::<String>(&mut conn)
         .await
@@ -560,5 +2032,1239 @@ pub async fn go_redirect(
             .into_response();
     }
 
-    Redirect::to(&format!("/items/{}", item_id)).into_response()
+    Redirect::to(&format!("{}/items/{}", state.config.base_path, item_id)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::item_watchers;
+    use crate::models::{ItemAudit, NewItemWatcher, Note};
+    use crate::test_support::{auth, json, query, TestDb};
+
+    /// `GET /api/items?{params}` as `user`.
+    async fn list_all_as(db: &TestDb, user: &User, params: &str) -> Response {
+        list_all(
+            State(db.state.clone()),
+            query(params),
+            query(params),
+            auth(user),
+        )
+        .await
+        .into_response()
+    }
+
+    fn ids(list: &ItemListResponse) -> Vec<&str> {
+        list.items.iter().map(|i| i.id.as_str()).collect()
+    }
+
+    /// `POST /api/vendors/:id/items` as `user`, owned by them, with an optional
+    /// Idempotency-Key.
+    async fn create_as(
+        db: &TestDb,
+        user: &User,
+        category: &Category,
+        title: &str,
+        key: Option<&str>,
+    ) -> Response {
+        let mut headers = HeaderMap::new();
+        if let Some(key) = key {
+            headers.insert(IDEMPOTENCY_KEY_HEADER, key.parse().unwrap());
+        }
+        create(
+            State(db.state.clone()),
+            Path(category.vendor_id),
+            auth(user),
+            headers,
+            Json(CreateActionItemReq {
+                title: title.to_string(),
+                due_date: None,
+                category_id: category.id,
+                owner_id: user.id,
+                priority: "Medium".to_string(),
+                description: None,
+                recurrence: None,
+            }),
+        )
+        .await
+        .into_response()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn list_names_creator_and_owner_when_they_are_the_same_user() {
+        let db = TestDb::new().await;
+        let mike = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let ada = db.user("ada@example.org", "Ada Lovelace", None).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let own = db.item(&category, &mike, &mike).await;
+        let handed_over = db.item(&category, &mike, &ada).await;
+
+        let response = list(
+            State(db.state.clone()),
+            Path(vendor.id),
+            Query(serde_json::from_str("{}").unwrap()),
+            Query(std::collections::HashMap::new()),
+            auth(&ada),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let list: ItemListResponse = json(response).await;
+        assert_eq!(list.total_count, 2);
+
+        let people: Vec<_> = list
+            .items
+            .iter()
+            .map(|i| {
+                (
+                    i.id.as_str(),
+                    i.created_by_name.as_str(),
+                    i.created_by_initials.as_deref(),
+                    i.owner_name.as_str(),
+                    i.owner_initials.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            people,
+            [
+                (
+                    own.id.as_str(),
+                    "Mike Fox",
+                    Some("MF"),
+                    "Mike Fox",
+                    Some("MF")
+                ),
+                (
+                    handed_over.id.as_str(),
+                    "Mike Fox",
+                    Some("MF"),
+                    "Ada Lovelace",
+                    None
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn move_item_carries_notes_and_history_to_the_new_id() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let from = db.vendor("AAA").await;
+        let to = db.vendor("BBB").await;
+        let from_category = db.category(&from, "Design").await;
+        let to_category = db.category(&to, "Design").await;
+        let item = db.item(&from_category, &user, &user).await;
+
+        let mut conn = db.conn().await;
+        diesel::insert_into(notes::table)
+            .values(&NewNote {
+                action_item_id: item.id.clone(),
+                note_date: item.create_date,
+                author_id: user.id,
+                content: "Filed under the wrong vendor".to_string(),
+            })
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        diesel::insert_into(status_history::table)
+            .values(&NewStatusHistory {
+                action_item_id: item.id.clone(),
+                status: "In Progress".to_string(),
+                changed_by_id: user.id,
+                comment: None,
+                from_status: None,
+            })
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let response = move_item(
+            State(db.state.clone()),
+            Path(item.id.clone()),
+            auth(&user),
+            Json(MoveItemReq {
+                vendor_id: to.id,
+                category_id: None,
+            }),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let moved: ActionItemResponse = json(response).await;
+        assert_eq!(moved.id, "BBB-001");
+        assert_eq!(moved.vendor_id, to.id);
+        assert_eq!(moved.category_id, to_category.id);
+        assert_eq!(moved.status, "In Progress");
+
+        let notes: Vec<Note> = notes::table
+            .filter(notes::action_item_id.eq(&moved.id))
+            .order(notes::id)
+            .select(Note::as_select())
+            .load(&mut conn)
+            .await
+            .unwrap();
+        let contents: Vec<&str> = notes.iter().map(|n| n.content.as_str()).collect();
+        assert_eq!(
+            contents,
+            [
+                "Filed under the wrong vendor",
+                "Moved from AAA-001 to BBB-001"
+            ]
+        );
+
+        let history: i64 = status_history::table
+            .filter(status_history::action_item_id.eq(&moved.id))
+            .count()
+            .get_result(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(history, 1);
+
+        let audit: Vec<ItemAudit> = item_audit::table
+            .filter(item_audit::action_item_id.eq(&moved.id))
+            .order(item_audit::id)
+            .select(ItemAudit::as_select())
+            .load(&mut conn)
+            .await
+            .unwrap();
+        let audit: Vec<_> = audit
+            .iter()
+            .map(|a| (a.field.as_str(), a.old_value.clone(), a.new_value.clone()))
+            .collect();
+        assert_eq!(
+            audit,
+            [
+                ("id", Some(item.id.clone()), Some(moved.id.clone())),
+                (
+                    "category_id",
+                    Some(from_category.id.to_string()),
+                    Some(to_category.id.to_string())
+                ),
+            ]
+        );
+
+        let old_id_rows: i64 = notes::table
+            .filter(notes::action_item_id.eq(&item.id))
+            .count()
+            .get_result(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(old_id_rows, 0);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn move_item_rejects_a_vendor_without_a_matching_category() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let from = db.vendor("AAA").await;
+        let to = db.vendor("BBB").await;
+        let category = db.category(&from, "Design").await;
+        db.category(&to, "Testing").await;
+        let item = db.item(&category, &user, &user).await;
+
+        let response = move_item(
+            State(db.state.clone()),
+            Path(item.id.clone()),
+            auth(&user),
+            Json(MoveItemReq {
+                vendor_id: to.id,
+                category_id: None,
+            }),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // Nothing was claimed or moved
+        let mut conn = db.conn().await;
+        let next_number: i32 = vendors::table
+            .filter(vendors::id.eq(to.id))
+            .select(vendors::next_number)
+            .first(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(next_number, 1);
+        assert!(super::super::ensure_item_exists(&mut conn, &item.id)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn list_sorts_by_priority_and_due_date_with_undated_items_last() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let mut conn = db.conn().await;
+        for (due, priority) in [
+            (chrono::NaiveDate::from_ymd_opt(2026, 3, 1), "Low"),
+            (None, "High"),
+            (chrono::NaiveDate::from_ymd_opt(2026, 1, 1), "Medium"),
+        ] {
+            let item = db.item(&category, &user, &user).await;
+            diesel::update(action_items::table.filter(action_items::id.eq(&item.id)))
+                .set((
+                    action_items::due_date.eq(due),
+                    action_items::priority.eq(priority),
+                ))
+                .execute(&mut conn)
+                .await
+                .unwrap();
+        }
+
+        for (params, expected) in [
+            ("", ["AAA-001", "AAA-002", "AAA-003"]),
+            ("sort_dir=desc", ["AAA-003", "AAA-002", "AAA-001"]),
+            ("sort_by=due_date", ["AAA-003", "AAA-001", "AAA-002"]),
+            (
+                "sort_by=due_date&sort_dir=desc",
+                ["AAA-001", "AAA-003", "AAA-002"],
+            ),
+            ("sort_by=priority", ["AAA-002", "AAA-003", "AAA-001"]),
+            (
+                "sort_by=priority&sort_dir=desc",
+                ["AAA-001", "AAA-003", "AAA-002"],
+            ),
+        ] {
+            let response = list_all_as(&db, &user, params).await;
+            assert_eq!(response.status(), StatusCode::OK, "{}", params);
+            let list: ItemListResponse = json(response).await;
+            assert_eq!(ids(&list), expected, "{}", params);
+        }
+
+        let uri: axum::http::Uri = "/?sort_by=owner".parse().unwrap();
+        assert!(Query::<ItemsQuery>::try_from_uri(&uri).is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn list_sorts_ids_by_prefix_then_number() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        for (prefix, padding, next_number) in [("BBB", 1, 9), ("AAA", 3, 999)] {
+            let vendor = db.vendor(prefix).await;
+            diesel::update(vendors::table.find(vendor.id))
+                .set((
+                    vendors::number_padding.eq(padding),
+                    vendors::next_number.eq(next_number),
+                ))
+                .execute(&mut db.conn().await)
+                .await
+                .unwrap();
+            let category = db.category(&vendor, "Design").await;
+            db.item(&category, &user, &user).await;
+            db.item(&category, &user, &user).await;
+        }
+
+        let response = list_all_as(&db, &user, "sort_by=id").await;
+        let list: ItemListResponse = json(response).await;
+        assert_eq!(ids(&list), ["AAA-999", "AAA-1000", "BBB-9", "BBB-10"]);
+
+        let response = list_all_as(&db, &user, "sort_by=id&sort_dir=desc").await;
+        let list: ItemListResponse = json(response).await;
+        assert_eq!(ids(&list), ["BBB-10", "BBB-9", "AAA-1000", "AAA-999"]);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn list_rejects_unknown_query_parameters() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        db.item(&category, &user, &user).await;
+
+        let response = list_all_as(&db, &user, "statuss=Complete&limit=10").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let error: ApiError = json(response).await;
+        assert!(
+            error
+                .error
+                .message
+                .starts_with("Unknown query parameter(s): statuss. Accepted: vendor_id,"),
+            "{}",
+            error.error.message
+        );
+
+        let response = list_all_as(&db, &user, "status=New&limit=10").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let list: ItemListResponse = json(response).await;
+        assert_eq!(list.total_count, 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn list_rejects_filters_naming_rows_that_do_not_exist() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let other = db.category(&vendor, "Ops").await;
+        db.item(&category, &user, &user).await;
+
+        let response = list_all_as(&db, &user, "vendor_id=77&category_id=999").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let error: ApiError = json(response).await;
+        assert_eq!(error.error.message, "No such vendor_id 77, category_id 999");
+
+        let response = list_all_as(&db, &user, &format!("owner_id={}", user.id + 100)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = list(
+            State(db.state.clone()),
+            Path(77),
+            query(""),
+            query(""),
+            auth(&user),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        // Filters that exist but match nothing are still an empty list
+        let response = list_all_as(&db, &user, &format!("category_id={}", other.id)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let list: ItemListResponse = json(response).await;
+        assert_eq!(list.total_count, 0);
+
+        let response = list_all_as(
+            &db,
+            &user,
+            &format!("owner_id={}&category_id={}", user.id, category.id),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let list: ItemListResponse = json(response).await;
+        assert_eq!(list.total_count, 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn create_replays_a_repeated_idempotency_key() {
+        let db = TestDb::new().await;
+        let mike = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let ada = db.user("ada@example.org", "Ada Lovelace", Some("AL")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+
+        let response = create_as(&db, &mike, &category, "Order parts", Some("abc")).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let first: ActionItemResponse = json(response).await;
+
+        let response = create_as(&db, &mike, &category, "Order parts", Some("abc")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let repeat: ActionItemResponse = json(response).await;
+        assert_eq!(repeat.id, first.id);
+
+        // Keys are per user
+        let response = create_as(&db, &ada, &category, "Order parts", Some("abc")).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let other: ActionItemResponse = json(response).await;
+        assert_ne!(other.id, first.id);
+
+        // A failed create releases its key for the corrected retry
+        let response = create_as(&db, &mike, &category, "", Some("retry")).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = create_as(&db, &mike, &category, "Book travel", Some("retry")).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let response = create_as(&db, &mike, &category, "Order parts", Some("")).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // An expired key creates a new item
+        diesel::update(idempotency_keys::table.filter(idempotency_keys::key.eq("abc")))
+            .set(
+                idempotency_keys::created_at
+                    .eq(Utc::now() - chrono::Duration::hours(IDEMPOTENCY_KEY_TTL_HOURS + 1)),
+            )
+            .execute(&mut db.conn().await)
+            .await
+            .unwrap();
+        let response = create_as(&db, &mike, &category, "Order parts", Some("abc")).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let renewed: ActionItemResponse = json(response).await;
+        assert_ne!(renewed.id, first.id);
+
+        let items: i64 = action_items::table
+            .count()
+            .get_result(&mut db.conn().await)
+            .await
+            .unwrap();
+        assert_eq!(items, 4);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn create_refuses_a_key_reused_for_another_request() {
+        let db = TestDb::new().await;
+        let mike = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let other_vendor = db.vendor("BBB").await;
+        let category = db.category(&vendor, "Design").await;
+        let elsewhere = db.category(&other_vendor, "Design").await;
+
+        let response = create_as(&db, &mike, &category, "Order parts", Some("abc")).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        for (category, title) in [(&category, "Book travel"), (&elsewhere, "Order parts")] {
+            let response = create_as(&db, &mike, category, title, Some("abc")).await;
+            assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+            let error: ApiError = json(response).await;
+            assert_eq!(error.error.code, "UNPROCESSABLE");
+        }
+        let response = create_as(&db, &mike, &category, "Order parts", Some("abc")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn create_takes_over_a_claim_that_never_finished() {
+        let db = TestDb::new().await;
+        let mike = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let mut conn = db.conn().await;
+
+        // A request that claimed the key and then died without creating anything
+        diesel::insert_into(idempotency_keys::table)
+            .values((
+                idempotency_keys::user_id.eq(mike.id),
+                idempotency_keys::key.eq("abc"),
+            ))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        let response = create_as(&db, &mike, &category, "Order parts", Some("abc")).await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        diesel::update(idempotency_keys::table)
+            .set(
+                idempotency_keys::created_at
+                    .eq(Utc::now() - chrono::Duration::seconds(IDEMPOTENCY_CLAIM_TIMEOUT_SECS + 1)),
+            )
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        let response = create_as(&db, &mike, &category, "Order parts", Some("abc")).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let created: ActionItemResponse = json(response).await;
+
+        let response = create_as(&db, &mike, &category, "Order parts", Some("abc")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let repeat: ActionItemResponse = json(response).await;
+        assert_eq!(repeat.id, created.id);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn created_after_and_before_are_inclusive_bounds() {
+        let db = TestDb::new().await;
+        let mike = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        for day in [1, 5, 10] {
+            let item = db.item(&category, &mike, &mike).await;
+            diesel::update(action_items::table.filter(action_items::id.eq(&item.id)))
+                .set(
+                    action_items::create_date
+                        .eq(chrono::NaiveDate::from_ymd_opt(2024, 3, day).unwrap()),
+                )
+                .execute(&mut db.conn().await)
+                .await
+                .unwrap();
+        }
+
+        for (params, expected) in [
+            ("created_after=2024-03-05", vec!["AAA-002", "AAA-003"]),
+            ("created_before=2024-03-05", vec!["AAA-001", "AAA-002"]),
+            (
+                "created_after=2024-03-05&created_before=2024-03-05",
+                vec!["AAA-002"],
+            ),
+            (
+                "created_after=2024-03-02&created_before=2024-03-09",
+                vec!["AAA-002"],
+            ),
+            ("created_after=2024-03-11", vec![]),
+        ] {
+            let response =
+                list_all_as(&db, &mike, &format!("{}&sort_by=id&sort_dir=asc", params)).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            let list: ItemListResponse = json(response).await;
+            assert_eq!(ids(&list), expected, "{}", params);
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn items_keep_showing_deactivated_users_and_users_cannot_be_deleted_from_under_them() {
+        let db = TestDb::new().await;
+        let mike = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let ada = db.user("ada@example.org", "Ada Lovelace", Some("AL")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let item = db.item(&category, &ada, &ada).await;
+        let mut conn = db.conn().await;
+
+        diesel::update(users::table.filter(users::id.eq(ada.id)))
+            .set(users::active.eq(false))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        let response = get(
+            State(db.state.clone()),
+            Path(item.id.clone()),
+            auth(&mike),
+            HeaderMap::new(),
+        )
+        .await
+        .into_response();
+        let shown: ActionItemResponse = json(response).await;
+        assert_eq!(shown.created_by_name, "Ada Lovelace");
+        assert_eq!(shown.owner_name, "Ada Lovelace");
+        assert_eq!(shown.owner_initials.as_deref(), Some("AL"));
+
+        let deleted = diesel::delete(users::table.filter(users::id.eq(ada.id)))
+            .execute(&mut conn)
+            .await;
+        assert!(
+            matches!(
+                deleted,
+                Err(diesel::result::Error::DatabaseError(
+                    diesel::result::DatabaseErrorKind::ForeignKeyViolation,
+                    _
+                ))
+            ),
+            "{:?}",
+            deleted
+        );
+        // So a missing user only shows up if the lookup itself failed
+        assert_eq!(user_display_name(None, ada.id), format!("User #{}", ada.id));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn unknown_owner_gets_400_naming_the_field() {
+        let db = TestDb::new().await;
+        let mike = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let item = db.item(&category, &mike, &mike).await;
+        let nobody = mike.id + 100;
+        let expected = "owner_id does not refer to an existing record";
+
+        let response = create(
+            State(db.state.clone()),
+            Path(vendor.id),
+            auth(&mike),
+            HeaderMap::new(),
+            Json(CreateActionItemReq {
+                title: "Order parts".to_string(),
+                due_date: None,
+                category_id: category.id,
+                owner_id: nobody,
+                priority: "Medium".to_string(),
+                description: None,
+                recurrence: None,
+            }),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let error: ApiError = json(response).await;
+        assert_eq!(error.error.message, expected);
+
+        let response = update(
+            State(db.state.clone()),
+            Path(item.id.clone()),
+            auth(&mike),
+            Json(serde_json::from_value(serde_json::json!({ "owner_id": nobody })).unwrap()),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let error: ApiError = json(response).await;
+        assert_eq!(error.error.message, expected);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn duplicate_makes_a_new_item_with_new_status() {
+        let db = TestDb::new().await;
+        let mike = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let ada = db.user("ada@example.org", "Ada Lovelace", Some("AL")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let source = db.item(&category, &mike, &ada).await;
+        let mut conn = db.conn().await;
+        diesel::update(action_items::table.filter(action_items::id.eq(&source.id)))
+            .set((
+                action_items::priority.eq("High"),
+                action_items::description.eq("Check the mounts"),
+            ))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        diesel::insert_into(status_history::table)
+            .values(&NewStatusHistory {
+                action_item_id: source.id.clone(),
+                status: "In Progress".to_string(),
+                changed_by_id: mike.id,
+                comment: None,
+                from_status: Some("New".to_string()),
+            })
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let copy_of = |item_id: &str, params: &str| {
+            duplicate(
+                State(db.state.clone()),
+                Path(item_id.to_string()),
+                query(params),
+                auth(&ada),
+            )
+        };
+        let response = copy_of(&source.id, "").await.into_response();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let copy: ActionItemResponse = json(response).await;
+        assert_eq!(copy.id, "AAA-002");
+        assert_eq!(copy.status, "New");
+        assert_eq!(copy.title, format!("{} (copy)", source.title));
+        assert_eq!(copy.category_id, category.id);
+        assert_eq!(copy.owner_id, ada.id);
+        assert_eq!(copy.created_by_id, ada.id);
+        assert_eq!(copy.priority, "High");
+        assert_eq!(copy.description.as_deref(), Some("Check the mounts"));
+
+        let response = copy_of(&source.id, "copy_suffix=false")
+            .await
+            .into_response();
+        let plain: ActionItemResponse = json(response).await;
+        assert_eq!(plain.id, "AAA-003");
+        assert_eq!(plain.title, source.title);
+
+        let response = copy_of("AAA-999", "").await.into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn listing_takes_the_same_number_of_queries_for_200_items_as_for_2() {
+        use diesel::connection::InstrumentationEvent;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let db = TestDb::new().await;
+        let user = db.user("ada@example.org", "Ada Lovelace", Some("AL")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+
+        // Count every statement on every pooled connection, whichever the handler gets
+        let queries = Arc::new(AtomicUsize::new(0));
+        let mut pooled = Vec::new();
+        for _ in 0..4 {
+            pooled.push(db.conn().await);
+        }
+        for conn in &mut pooled {
+            let queries = queries.clone();
+            conn.set_instrumentation(move |event: InstrumentationEvent<'_>| {
+                if matches!(event, InstrumentationEvent::StartQuery { .. }) {
+                    queries.fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        }
+        drop(pooled);
+
+        let (mut created, mut counts) = (0, Vec::new());
+        for total in [2, 200] {
+            while created < total {
+                created += 1;
+                let item = db.item(&category, &user, &user).await;
+                diesel::insert_into(status_history::table)
+                    .values(&NewStatusHistory {
+                        action_item_id: item.id,
+                        status: "In Progress".to_string(),
+                        changed_by_id: user.id,
+                        comment: None,
+                        from_status: None,
+                    })
+                    .execute(&mut db.conn().await)
+                    .await
+                    .unwrap();
+            }
+
+            queries.store(0, Ordering::SeqCst);
+            let response = list_all_as(&db, &user, "limit=500").await;
+            assert_eq!(response.status(), StatusCode::OK);
+            let list: ItemListResponse = json(response).await;
+            assert_eq!(list.items.len(), total);
+            assert!(list.items.iter().all(|i| i.status == "In Progress"));
+            counts.push(AtomicUsize::load(&queries, Ordering::SeqCst));
+        }
+        assert!(counts[0] > 0);
+        assert_eq!(counts[0], counts[1]);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn status_filter_matches_the_latest_status_and_pages_in_the_database() {
+        /// Move `item` through `statuses`, one transaction (so one `now()`) each.
+        async fn move_through(db: &TestDb, item: &ActionItem, by: &User, statuses: &[&str]) {
+            for status in statuses {
+                diesel::insert_into(status_history::table)
+                    .values(&NewStatusHistory {
+                        action_item_id: item.id.clone(),
+                        status: status.to_string(),
+                        changed_by_id: by.id,
+                        comment: None,
+                        from_status: None,
+                    })
+                    .execute(&mut db.conn().await)
+                    .await
+                    .unwrap();
+            }
+        }
+
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let blocked = db.item(&category, &user, &user).await;
+        let unblocked = db.item(&category, &user, &user).await;
+        let finished = db.item(&category, &user, &user).await;
+        let blocked_again = db.item(&category, &user, &user).await;
+        let untouched = db.item(&category, &user, &user).await;
+
+        move_through(&db, &blocked, &user, &["In Progress", "Blocked"]).await;
+        move_through(&db, &unblocked, &user, &["Blocked", "In Progress"]).await;
+        move_through(&db, &finished, &user, &["Blocked", "Complete"]).await;
+        move_through(
+            &db,
+            &blocked_again,
+            &user,
+            &["Blocked", "In Progress", "Blocked"],
+        )
+        .await;
+
+        let page = |offset: i64| {
+            format!(
+                "status=Blocked&sort_by=id&sort_dir=asc&limit=1&offset={}",
+                offset
+            )
+        };
+        let first: ItemListResponse = json(list_all_as(&db, &user, &page(0)).await).await;
+        assert_eq!(first.total_count, 2);
+        assert_eq!(ids(&first), [blocked.id.as_str()]);
+        let second: ItemListResponse = json(list_all_as(&db, &user, &page(1)).await).await;
+        assert_eq!(ids(&second), [blocked_again.id.as_str()]);
+
+        let in_progress: ItemListResponse =
+            json(list_all_as(&db, &user, "status=In%20Progress").await).await;
+        assert_eq!(ids(&in_progress), [unblocked.id.as_str()]);
+        // Items that never changed status are New
+        let new: ItemListResponse = json(list_all_as(&db, &user, "status=New").await).await;
+        assert_eq!(ids(&new), [untouched.id.as_str()]);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn overdue_and_due_soon_split_items_around_today() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+
+        let today = Utc::now().date_naive();
+        let mut conn = db.conn().await;
+        let mut due = Vec::new();
+        for due_date in [
+            Some(today + chrono::Days::new(7)),
+            Some(today),
+            Some(today - chrono::Days::new(1)),
+            Some(today - chrono::Days::new(1)),
+            None,
+        ] {
+            let item = db.item(&category, &user, &user).await;
+            diesel::update(action_items::table.find(&item.id))
+                .set(action_items::due_date.eq(due_date))
+                .execute(&mut conn)
+                .await
+                .unwrap();
+            due.push(item.id);
+        }
+        let [next_week, due_today, yesterday, finished_yesterday, _undated] = &due[..] else {
+            unreachable!()
+        };
+        diesel::insert_into(status_history::table)
+            .values(&NewStatusHistory {
+                action_item_id: finished_yesterday.clone(),
+                status: "Complete".to_string(),
+                changed_by_id: user.id,
+                comment: None,
+                from_status: None,
+            })
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        drop(conn);
+
+        fn item_ids(items: &[ActionItemResponse]) -> Vec<&str> {
+            items.iter().map(|i| i.id.as_str()).collect()
+        }
+        let response = overdue(State(db.state.clone()), auth(&user))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let items: Vec<ActionItemResponse> = json(response).await;
+        assert_eq!(item_ids(&items), [yesterday]);
+
+        let due_within = |days: &str| {
+            due_soon(
+                State(db.state.clone()),
+                query(&format!("days={}", days)),
+                auth(&user),
+            )
+        };
+        let items: Vec<ActionItemResponse> = json(due_within("7").await.into_response()).await;
+        assert_eq!(item_ids(&items), [due_today, next_week]);
+        let items: Vec<ActionItemResponse> = json(due_within("6").await.into_response()).await;
+        assert_eq!(item_ids(&items), [due_today]);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn update_from_a_stale_copy_gets_409() {
+        let db = TestDb::new().await;
+        let ada = db.user("ada@example.org", "Ada Lovelace", Some("AL")).await;
+        let mike = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let item = db.item(&category, &ada, &ada).await;
+
+        // Both loaded the item before either saved
+        let save = |user: &User, body: serde_json::Value| {
+            update(
+                State(db.state.clone()),
+                Path(item.id.clone()),
+                auth(user),
+                Json(serde_json::from_value(body).unwrap()),
+            )
+        };
+        let response = save(
+            &ada,
+            serde_json::json!({
+                "title": "Ada's title",
+                "expected_updated_at": item.updated_at,
+            }),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let saved: ActionItemResponse = json(response).await;
+        assert!(saved.updated_at > item.updated_at);
+
+        let response = save(
+            &mike,
+            serde_json::json!({
+                "title": "Mike's title",
+                "expected_updated_at": item.updated_at,
+            }),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let error: ApiError = json(response).await;
+        assert_eq!(error.error.code, "CONFLICT");
+        let title: String = action_items::table
+            .find(&item.id)
+            .select(action_items::title)
+            .first(&mut db.conn().await)
+            .await
+            .unwrap();
+        assert_eq!(title, "Ada's title");
+
+        // Without the field the update is unconditional, as before
+        let response = save(&mike, serde_json::json!({ "title": "Mike's title" }))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let saved: ActionItemResponse = json(response).await;
+        assert_eq!(saved.title, "Mike's title");
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn deleted_items_leave_lists_until_restored() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let kept = db.item(&category, &user, &user).await;
+        let removed = db.item(&category, &user, &user).await;
+
+        let listed = |params: &'static str| {
+            let (db, user) = (&db, &user);
+            async move {
+                let list: ItemListResponse = json(list_all_as(db, user, params).await).await;
+                ids(&list)
+                    .into_iter()
+                    .map(str::to_string)
+                    .collect::<Vec<_>>()
+            }
+        };
+        let fetch = || {
+            get(
+                State(db.state.clone()),
+                Path(removed.id.clone()),
+                auth(&user),
+                HeaderMap::new(),
+            )
+        };
+        let delete_it = || {
+            delete(
+                State(db.state.clone()),
+                Path(removed.id.clone()),
+                auth(&user),
+            )
+        };
+        let restore_it = || {
+            restore(
+                State(db.state.clone()),
+                Path(removed.id.clone()),
+                auth(&user),
+            )
+        };
+
+        let response = delete_it().await.into_response();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(listed("sort_by=id&sort_dir=asc").await, [kept.id.as_str()]);
+        assert_eq!(
+            fetch().await.into_response().status(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            listed("include_deleted=true&sort_by=id&sort_dir=asc").await,
+            [kept.id.as_str(), removed.id.as_str()]
+        );
+        assert_eq!(
+            delete_it().await.into_response().status(),
+            StatusCode::NOT_FOUND
+        );
+
+        let response = restore_it().await.into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let restored: ActionItemResponse = json(response).await;
+        assert_eq!(restored.id, removed.id);
+        assert_eq!(
+            restore_it().await.into_response().status(),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            listed("sort_by=id&sort_dir=asc").await,
+            [kept.id.as_str(), removed.id.as_str()]
+        );
+        assert_eq!(fetch().await.into_response().status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn title_limit_counts_characters() {
+        assert!(validate_title(&"é".repeat(10), 10).is_ok());
+        assert!(validate_title(&"é".repeat(11), 10).is_err());
+        assert!(validate_title("", 10).is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn titles_over_the_limit_are_rejected_on_create_and_update() {
+        let db = TestDb::new().await;
+        let mike = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let limit = db.state.config.title_max_length;
+
+        let longest = "x".repeat(limit);
+        let response = create_as(&db, &mike, &category, &longest, None).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let item: ActionItemResponse = json(response).await;
+
+        let too_long = "x".repeat(limit + 1);
+        let response = create_as(&db, &mike, &category, &too_long, None).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let error: ApiError = json(response).await;
+        assert_eq!(
+            error.error.message,
+            format!("Title must be 1-{} characters", limit)
+        );
+
+        let response = update(
+            State(db.state.clone()),
+            Path(item.id.clone()),
+            auth(&mike),
+            Json(serde_json::from_value(serde_json::json!({ "title": too_long })).unwrap()),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let items: i64 = action_items::table
+            .count()
+            .get_result(&mut db.conn().await)
+            .await
+            .unwrap();
+        assert_eq!(items, 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn concurrent_creates_get_distinct_numbers() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+
+        let responses = futures_util::future::join_all(
+            (0..10).map(|_| create_as(&db, &user, &category, "Order parts", None)),
+        )
+        .await;
+        let mut ids = Vec::new();
+        for response in responses {
+            assert_eq!(response.status(), StatusCode::CREATED);
+            let item: ActionItemResponse = json(response).await;
+            ids.push(item.id);
+        }
+        ids.sort();
+        let expected: Vec<_> = (1..=10)
+            .map(|n| crate::routes::format_item_id("AAA", n, 3))
+            .collect();
+        assert_eq!(ids, expected);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn create_recovers_when_next_number_falls_behind() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        for _ in 0..3 {
+            db.item(&category, &user, &user).await;
+        }
+        diesel::update(vendors::table.find(vendor.id))
+            .set(vendors::next_number.eq(2))
+            .execute(&mut db.conn().await)
+            .await
+            .unwrap();
+
+        let response = create_as(&db, &user, &category, "Order parts", None).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let item: ActionItemResponse = json(response).await;
+        assert_eq!(item.id, "AAA-004");
+
+        let response = create_as(&db, &user, &category, "Book travel", None).await;
+        let item: ActionItemResponse = json(response).await;
+        assert_eq!(item.id, "AAA-005");
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn get_answers_304_until_the_item_changes() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let watcher = db.user("ada@example.org", "Ada Lovelace", Some("AL")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let item = db.item(&category, &user, &user).await;
+
+        let get_with = |if_none_match: Option<String>| {
+            let mut headers = HeaderMap::new();
+            if let Some(tag) = if_none_match {
+                headers.insert(header::IF_NONE_MATCH, tag.parse().unwrap());
+            }
+            get(
+                State(db.state.clone()),
+                Path(item.id.clone()),
+                auth(&user),
+                headers,
+            )
+        };
+
+        let response = get_with(None).await.into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response.headers()[header::ETAG]
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        for tag in [etag.clone(), format!("W/{}", etag), "*".to_string()] {
+            let response = get_with(Some(tag.clone())).await.into_response();
+            assert_eq!(response.status(), StatusCode::NOT_MODIFIED, "{}", tag);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            assert!(body.is_empty());
+        }
+
+        // Watchers don't bump updated_at but are part of the response
+        diesel::insert_into(item_watchers::table)
+            .values(&NewItemWatcher {
+                action_item_id: item.id.clone(),
+                user_id: watcher.id,
+            })
+            .execute(&mut db.conn().await)
+            .await
+            .unwrap();
+        let response = get_with(Some(etag.clone())).await.into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_ne!(response.headers()[header::ETAG], etag.as_str());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn status_changes_at_the_same_moment_resolve_to_the_later_one() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let item = db.item(&category, &user, &user).await;
+
+        // One INSERT, so both rows get the same now()
+        let change = |status: &str, from: &str| NewStatusHistory {
+            action_item_id: item.id.clone(),
+            status: status.to_string(),
+            changed_by_id: user.id,
+            comment: None,
+            from_status: Some(from.to_string()),
+        };
+        diesel::insert_into(status_history::table)
+            .values(&[
+                change("In Progress", "New"),
+                change("Complete", "In Progress"),
+            ])
+            .execute(&mut db.conn().await)
+            .await
+            .unwrap();
+
+        let listed: ItemListResponse = json(list_all_as(&db, &user, "").await).await;
+        assert_eq!(listed.items[0].status, "Complete");
+        let filtered: ItemListResponse =
+            json(list_all_as(&db, &user, "status=Complete").await).await;
+        assert_eq!(ids(&filtered), [item.id.as_str()]);
+
+        let response = get(
+            State(db.state.clone()),
+            Path(item.id.clone()),
+            auth(&user),
+            HeaderMap::new(),
+        )
+        .await
+        .into_response();
+        let fetched: ActionItemResponse = json(response).await;
+        assert_eq!(fetched.status, "Complete");
+    }
 }
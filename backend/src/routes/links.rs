@@ -0,0 +1,416 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use diesel::prelude::*;
+use diesel::sql_types::{Bool, Varchar};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use shared::{ApiError, ItemLinkReq, ItemLinks};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::db::schema::{action_items, item_links};
+use crate::models::{ItemLink, NewItemLink};
+use crate::AppState;
+
+use super::AuthUser;
+
+const BLOCKS: &str = "blocks";
+const RELATES_TO: &str = "relates_to";
+
+/// Links of each of `item_ids`, seen from that item's side. Links to soft-deleted items
+/// are left out, and items without any links are absent from the map.
+pub(super) async fn links_by_item(
+    conn: &mut AsyncPgConnection,
+    item_ids: &[&str],
+) -> QueryResult<HashMap<String, ItemLinks>> {
+    let rows: Vec<ItemLink> = item_links::table
+        .filter(
+            item_links::from_item_id
+                .eq_any(item_ids)
+                .or(item_links::to_item_id.eq_any(item_ids)),
+        )
+        .order(item_links::created_at)
+        .select(ItemLink::as_select())
+        .load(conn)
+        .await?;
+
+    let linked_ids: Vec<&str> = rows
+        .iter()
+        .flat_map(|l| [l.from_item_id.as_str(), l.to_item_id.as_str()])
+        .collect();
+    let deleted: HashSet<String> = action_items::table
+        .filter(action_items::id.eq_any(&linked_ids))
+        .filter(action_items::deleted_at.is_not_null())
+        .select(action_items::id)
+        .load::<String>(conn)
+        .await?
+        .into_iter()
+        .collect();
+
+    let wanted: HashSet<&str> = item_ids.iter().copied().collect();
+    let mut map: HashMap<String, ItemLinks> = HashMap::new();
+    for link in &rows {
+        if deleted.contains(&link.from_item_id) || deleted.contains(&link.to_item_id) {
+            continue;
+        }
+        let blocks = link.link_type == BLOCKS;
+        if wanted.contains(link.from_item_id.as_str()) {
+            let links = map.entry(link.from_item_id.clone()).or_default();
+            let group = if blocks {
+                &mut links.blocks
+            } else {
+                &mut links.relates_to
+            };
+            group.push(link.to_item_id.clone());
+        }
+        if wanted.contains(link.to_item_id.as_str()) {
+            let links = map.entry(link.to_item_id.clone()).or_default();
+            let group = if blocks {
+                &mut links.blocked_by
+            } else {
+                &mut links.relates_to
+            };
+            group.push(link.from_item_id.clone());
+        }
+    }
+    Ok(map)
+}
+
+async fn item_links_of(conn: &mut AsyncPgConnection, item_id: &str) -> QueryResult<ItemLinks> {
+    Ok(links_by_item(conn, &[item_id])
+        .await?
+        .remove(item_id)
+        .unwrap_or_default())
+}
+
+#[derive(Debug, QueryableByName)]
+struct ReachableRow {
+    #[diesel(sql_type = Bool)]
+    reachable: bool,
+}
+
+/// Whether `from` already blocks `to`, directly or through a chain of `blocks` links.
+async fn blocks_transitively(
+    conn: &mut AsyncPgConnection,
+    from: &str,
+    to: &str,
+) -> QueryResult<bool> {
+    let sql = r#"
+        WITH RECURSIVE downstream(item_id) AS (
+            SELECT to_item_id FROM item_links WHERE from_item_id = $1 AND link_type = 'blocks'
+            UNION
+            SELECT l.to_item_id
+            FROM item_links l
+            INNER JOIN downstream d ON l.from_item_id = d.item_id
+            WHERE l.link_type = 'blocks'
+        )
+        SELECT EXISTS (SELECT 1 FROM downstream WHERE item_id = $2) AS reachable
+    "#;
+
+    let row: ReachableRow = diesel::sql_query(sql)
+        .bind::<Varchar, _>(from)
+        .bind::<Varchar, _>(to)
+        .get_result(conn)
+        .await?;
+    Ok(row.reachable)
+}
+
+fn validate_link_type(link_type: &str) -> Result<(), ApiError> {
+    if link_type == BLOCKS || link_type == RELATES_TO {
+        Ok(())
+    } else {
+        Err(ApiError::validation_error(format!(
+            "link_type must be \"{}\" or \"{}\"",
+            BLOCKS, RELATES_TO
+        )))
+    }
+}
+
+/// Link an item to another. Adding a link that already exists is a no-op; a `blocks`
+/// link that would close a cycle is refused. Responds with the item's links.
+pub async fn add(
+    State(state): State<Arc<AppState>>,
+    Path(item_id): Path<String>,
+    _auth: AuthUser,
+    Json(payload): Json<ItemLinkReq>,
+) -> impl IntoResponse {
+    if let Err(e) = validate_link_type(&payload.link_type) {
+        return (StatusCode::BAD_REQUEST, Json(e)).into_response();
+    }
+    if payload.to_item_id == item_id {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::validation_error(
+                "An item cannot be linked to itself",
+            )),
+        )
+            .into_response();
+    }
+
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    if let Err(resp) = super::ensure_item_exists(&mut conn, &item_id).await {
+        return resp;
+    }
+    if let Err(resp) = super::ensure_item_exists(&mut conn, &payload.to_item_id).await {
+        return resp;
+    }
+
+    if payload.link_type == BLOCKS {
+        match blocks_transitively(&mut conn, &payload.to_item_id, &item_id).await {
+            Ok(false) => {}
+            Ok(true) => {
+                return (
+                    StatusCode::CONFLICT,
+                    Json(ApiError::conflict(format!(
+                        "{} already blocks {}; this link would create a cycle",
+                        payload.to_item_id, item_id
+                    ))),
+                )
+                    .into_response()
+            }
+            Err(_) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiError::internal_error("Failed to check item links")),
+                )
+                    .into_response()
+            }
+        }
+    } else {
+        // relates_to has no direction, so the reverse link counts as this one
+        let reverse_exists = diesel::select(diesel::dsl::exists(
+            item_links::table
+                .filter(item_links::from_item_id.eq(&payload.to_item_id))
+                .filter(item_links::to_item_id.eq(&item_id))
+                .filter(item_links::link_type.eq(RELATES_TO)),
+        ))
+        .get_result::<bool>(&mut conn)
+        .await;
+        match reverse_exists {
+            Ok(true) => {
+                return match item_links_of(&mut conn, &item_id).await {
+                    Ok(links) => Json(links).into_response(),
+                    Err(_) => (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(ApiError::internal_error("Failed to fetch item links")),
+                    )
+                        .into_response(),
+                }
+            }
+            Ok(false) => {}
+            Err(_) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiError::internal_error("Failed to check item links")),
+                )
+                    .into_response()
+            }
+        }
+    }
+
+    let added = diesel::insert_into(item_links::table)
+        .values(&NewItemLink {
+            from_item_id: item_id.clone(),
+            to_item_id: payload.to_item_id,
+            link_type: payload.link_type,
+        })
+        .on_conflict_do_nothing()
+        .execute(&mut conn)
+        .await;
+
+    match added {
+        Ok(_) => match item_links_of(&mut conn, &item_id).await {
+            Ok(links) => Json(links).into_response(),
+            Err(_) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to fetch item links")),
+            )
+                .into_response(),
+        },
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::internal_error("Failed to add link")),
+        )
+            .into_response(),
+    }
+}
+
+/// Remove a link. A `relates_to` link is removed whichever item it was added from.
+pub async fn remove(
+    State(state): State<Arc<AppState>>,
+    Path(item_id): Path<String>,
+    _auth: AuthUser,
+    Json(payload): Json<ItemLinkReq>,
+) -> impl IntoResponse {
+    if let Err(e) = validate_link_type(&payload.link_type) {
+        return (StatusCode::BAD_REQUEST, Json(e)).into_response();
+    }
+
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    let forward = item_links::from_item_id
+        .eq(&item_id)
+        .and(item_links::to_item_id.eq(&payload.to_item_id));
+    let deleted = if payload.link_type == BLOCKS {
+        diesel::delete(
+            item_links::table
+                .filter(forward)
+                .filter(item_links::link_type.eq(BLOCKS)),
+        )
+        .execute(&mut conn)
+        .await
+    } else {
+        let reverse = item_links::from_item_id
+            .eq(&payload.to_item_id)
+            .and(item_links::to_item_id.eq(&item_id));
+        diesel::delete(
+            item_links::table
+                .filter(forward.or(reverse))
+                .filter(item_links::link_type.eq(RELATES_TO)),
+        )
+        .execute(&mut conn)
+        .await
+    };
+
+    match deleted {
+        Ok(0) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiError::not_found(format!(
+                "{} has no {} link to {}",
+                item_id, payload.link_type, payload.to_item_id
+            ))),
+        )
+            .into_response(),
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::internal_error("Failed to remove link")),
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ActionItem, User};
+    use crate::test_support::{auth, json, TestDb};
+
+    async fn link(
+        db: &TestDb,
+        user: &User,
+        from: &ActionItem,
+        to: &ActionItem,
+        link_type: &str,
+    ) -> axum::response::Response {
+        add(
+            State(db.state.clone()),
+            Path(from.id.clone()),
+            auth(user),
+            Json(ItemLinkReq {
+                to_item_id: to.id.clone(),
+                link_type: link_type.to_string(),
+            }),
+        )
+        .await
+        .into_response()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn blocks_links_refuse_self_links_and_cycles() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let a = db.item(&category, &user, &user).await;
+        let b = db.item(&category, &user, &user).await;
+        let c = db.item(&category, &user, &user).await;
+
+        assert_eq!(
+            link(&db, &user, &a, &a, BLOCKS).await.status(),
+            StatusCode::BAD_REQUEST
+        );
+        let response = link(&db, &user, &a, &b, BLOCKS).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let links: ItemLinks = json(response).await;
+        assert_eq!(links.blocks, [b.id.as_str()]);
+        assert_eq!(
+            link(&db, &user, &b, &c, BLOCKS).await.status(),
+            StatusCode::OK
+        );
+
+        // c -> a would close a -> b -> c -> a
+        assert_eq!(
+            link(&db, &user, &c, &a, BLOCKS).await.status(),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            link(&db, &user, &b, &a, BLOCKS).await.status(),
+            StatusCode::CONFLICT
+        );
+
+        let mut conn = db.conn().await;
+        let c_links = item_links_of(&mut conn, &c.id).await.unwrap();
+        assert_eq!(c_links.blocked_by, [b.id.as_str()]);
+        assert!(c_links.blocks.is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn relates_to_is_stored_once_and_removable_from_either_side() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let a = db.item(&category, &user, &user).await;
+        let b = db.item(&category, &user, &user).await;
+
+        assert_eq!(
+            link(&db, &user, &a, &b, RELATES_TO).await.status(),
+            StatusCode::OK
+        );
+        let response = link(&db, &user, &b, &a, RELATES_TO).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let links: ItemLinks = json(response).await;
+        assert_eq!(links.relates_to, [a.id.as_str()]);
+
+        let mut conn = db.conn().await;
+        let stored: i64 = item_links::table
+            .count()
+            .get_result(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(stored, 1);
+
+        let unlink = || {
+            remove(
+                State(db.state.clone()),
+                Path(b.id.clone()),
+                auth(&user),
+                Json(ItemLinkReq {
+                    to_item_id: a.id.clone(),
+                    link_type: RELATES_TO.to_string(),
+                }),
+            )
+        };
+        assert_eq!(
+            unlink().await.into_response().status(),
+            StatusCode::NO_CONTENT
+        );
+        assert_eq!(
+            unlink().await.into_response().status(),
+            StatusCode::NOT_FOUND
+        );
+    }
+}
@@ -1,28 +1,41 @@
 pub mod activity;
+pub mod assignees;
+pub mod audit;
 pub mod auth;
 pub mod categories;
+pub mod config;
+pub mod dashboard;
+pub mod export;
 pub mod health;
 pub mod items;
+pub mod links;
 pub mod notes;
+pub mod recurring;
 pub mod status;
+pub mod tags;
 pub mod users;
 pub mod vendors;
+pub mod views;
+pub mod watchers;
 
 use axum::{
     extract::FromRequestParts,
-    http::{header, request::Parts, StatusCode},
+    http::{header, request::Parts, HeaderMap, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
+use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 use diesel_async::pooled_connection::deadpool::Object;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use jsonwebtoken::{decode, DecodingKey, Validation};
 use serde::{Deserialize, Serialize};
-use shared::ApiError;
+use sha2::{Digest, Sha256};
+use shared::{ApiError, Role};
 use std::sync::Arc;
 
-use crate::db::schema::action_items;
+use crate::db::schema::{action_items, api_keys};
+use crate::models::User;
 use crate::AppState;
 
 pub(super) const CLEAR_TOKEN_COOKIE: &str = "token=; Path=/; HttpOnly; SameSite=Lax; Max-Age=0";
@@ -38,13 +51,14 @@ pub(super) async fn get_conn(state: &AppState) -> Result<Object<AsyncPgConnectio
     })
 }
 
-/// Ensure an action item exists, returning a 404 response if it does not.
+/// Ensure an action item exists and isn't soft-deleted, returning a 404 response otherwise.
 pub(super) async fn ensure_item_exists(
     conn: &mut AsyncPgConnection,
     item_id: &str,
 ) -> Result<(), Response> {
     let exists: bool = action_items::table
         .filter(action_items::id.eq(item_id))
+        .filter(action_items::deleted_at.is_null())
         .count()
         .get_result::<i64>(conn)
         .await
@@ -65,19 +79,180 @@ pub(super) async fn ensure_item_exists(
     }
 }
 
+/// An item id: the vendor prefix and the number zero-padded to `padding` digits.
+pub(super) fn format_item_id(prefix: &str, number: i32, padding: i32) -> String {
+    format!("{}-{:0width$}", prefix, number, width = padding as usize)
+}
+
+/// Format a row's version timestamp as a strong ETag value.
+pub(super) fn etag_for(version: DateTime<Utc>) -> String {
+    format!("\"{}\"", version.timestamp_micros())
+}
+
+/// Strong ETag for a response body: the hex SHA-256 of its bytes.
+pub(super) fn etag_for_body(body: &[u8]) -> String {
+    format!("\"{:x}\"", Sha256::digest(body))
+}
+
+/// Whether `If-None-Match` names `etag` (or is `*`), so a GET can answer `304`. Weak
+/// comparison, as RFC 9110 specifies for this header.
+pub(super) fn if_none_match(headers: &HeaderMap, etag: &str) -> bool {
+    let Some(value) = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+    else {
+        return false;
+    };
+
+    value
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+/// Parse an `If-Match` header into the row version it names.
+///
+/// Returns `Ok(None)` when the header is absent or `*`, in which case the caller
+/// performs an unconditional update. Malformed values yield a validation error.
+pub(super) fn if_match_version(headers: &HeaderMap) -> Result<Option<DateTime<Utc>>, ApiError> {
+    let Some(value) = headers.get(header::IF_MATCH) else {
+        return Ok(None);
+    };
+
+    let value = value.to_str().unwrap_or_default().trim();
+    if value == "*" {
+        return Ok(None);
+    }
+
+    value
+        .trim_start_matches("W/")
+        .trim_matches('"')
+        .parse::<i64>()
+        .ok()
+        .and_then(DateTime::from_timestamp_micros)
+        .map(Some)
+        .ok_or_else(|| ApiError::validation_error("Malformed If-Match header"))
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String, // email
     pub name: String,
     pub user_id: i32,
+    /// Missing on tokens issued before roles existed, which count as members
+    #[serde(default)]
+    pub role: Role,
     pub exp: usize,
     pub iat: usize,
+    /// When the user completed OAuth login; carried over by refreshes. Missing on tokens
+    /// issued before refresh existed, where `iat` stands in.
+    #[serde(default)]
+    pub auth_time: Option<usize>,
 }
 
+/// The session token from the `token` cookie, or failing that a bearer token.
+pub(super) fn request_token(headers: &HeaderMap) -> Option<&str> {
+    let cookie_header = headers
+        .get(header::COOKIE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+
+    cookie_header
+        .split(';')
+        .find_map(|cookie| cookie.trim().strip_prefix("token="))
+        .or_else(|| {
+            headers
+                .get(header::AUTHORIZATION)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.strip_prefix("Bearer "))
+        })
+}
+
+/// Header carrying an API key for scripted access, as an alternative to a session token.
+const API_KEY_HEADER: &str = "x-api-key";
+
+/// How API keys are stored: lowercase hex SHA-256. The CLI's `create-api-key` must match.
+fn hash_api_key(key: &str) -> String {
+    format!("{:x}", Sha256::digest(key.as_bytes()))
+}
+
+/// The user an API key belongs to, or `None` if no key matches. Records the key's use.
+///
+/// The lookup is a plain SQL equality on the hash, which isn't constant time. At most it
+/// leaks how many leading characters of a stored hash the hash of a guess shares, and an
+/// attacker can't choose what a guess hashes to, so that says nothing about the key
+/// (40 random characters from `create-api-key`). Comparing again in constant time after
+/// the lookup would add nothing.
+async fn api_key_user(state: &AppState, key: &str) -> Result<Option<User>, Response> {
+    let mut conn = get_conn(state).await?;
+    let hash = hash_api_key(key);
+
+    // A deactivated user's keys stop working along with their logins
+    let found: Option<(i32, User)> = api_keys::table
+        .inner_join(crate::db::schema::users::table)
+        .filter(api_keys::key_hash.eq(&hash))
+        .filter(crate::db::schema::users::active.eq(true))
+        .select((api_keys::id, User::as_select()))
+        .first(&mut conn)
+        .await
+        .optional()
+        .map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to check API key")),
+            )
+                .into_response()
+        })?;
+
+    let Some((key_id, user)) = found else {
+        return Ok(None);
+    };
+
+    let _ = diesel::update(api_keys::table.filter(api_keys::id.eq(key_id)))
+        .set(api_keys::last_used_at.eq(Utc::now()))
+        .execute(&mut conn)
+        .await;
+
+    Ok(Some(user))
+}
+
+/// Verify a session token's signature and expiry.
+pub(super) fn decode_token(token: &str, secret: &str) -> jsonwebtoken::errors::Result<Claims> {
+    decode::<Claims>(
+        token,
+        &DecodingKey::from_secret(secret.as_bytes()),
+        &Validation::default(),
+    )
+    .map(|data| data.claims)
+}
+
+#[derive(Clone)]
 pub struct AuthUser {
     pub user_id: i32,
     pub email: String,
     pub name: String,
+    pub role: Role,
+}
+
+/// Record the authenticated user on the request span and audit-log the access.
+fn log_authorized(parts: &Parts, user_id: i32) {
+    tracing::Span::current().record("user_id", user_id);
+    tracing::info!(
+        user_id,
+        method = %parts.method,
+        resource = %parts.uri.path(),
+        "authorized request"
+    );
+}
+
+/// Audit-log a rejected request. Never pass the token itself as `reason`.
+fn log_rejected(parts: &Parts, reason: &str) {
+    tracing::warn!(
+        method = %parts.method,
+        resource = %parts.uri.path(),
+        reason,
+        "rejected unauthenticated request"
+    );
 }
 
 #[axum::async_trait]
@@ -89,65 +264,48 @@ impl FromRequestParts<Arc<AppState>> for AuthUser {
         state: &Arc<AppState>,
     ) -> Result<Self, Self::Rejection> {
         // Dev mode bypass
-        if state.config.dev_mode {
-            if let Some(dev_user_id) = state.config.dev_user_id {
-                return Ok(AuthUser {
-                    user_id: dev_user_id,
-                    email: "dev@localhost".to_string(),
-                    name: "Dev User".to_string(),
-                });
-            }
-            // Default dev user
-            return Ok(AuthUser {
-                user_id: 1,
-                email: "dev@localhost".to_string(),
-                name: "Dev User".to_string(),
-            });
+        if let Some(dev_user) = &state.dev_user {
+            log_authorized(parts, dev_user.user_id);
+            return Ok(dev_user.clone());
         }
 
-        // Try to get token from cookie
-        let cookie_header = parts
-            .headers
-            .get(axum::http::header::COOKIE)
-            .and_then(|v| v.to_str().ok())
-            .unwrap_or("");
-
-        let token = cookie_header
-            .split(';')
-            .find_map(|cookie| {
-                let cookie = cookie.trim();
-                if cookie.starts_with("token=") {
-                    Some(cookie.trim_start_matches("token="))
-                } else {
-                    None
+        if let Some(key) = parts.headers.get(API_KEY_HEADER) {
+            let key = key.to_str().unwrap_or_default();
+            return match api_key_user(state, key).await? {
+                Some(user) => {
+                    log_authorized(parts, user.id);
+                    Ok(AuthUser {
+                        user_id: user.id,
+                        role: Role::from_db(&user.role),
+                        email: user.email,
+                        name: user.name,
+                    })
                 }
-            })
-            .or_else(|| {
-                // Fallback to Authorization header
-                parts
-                    .headers
-                    .get(axum::http::header::AUTHORIZATION)
-                    .and_then(|v| v.to_str().ok())
-                    .and_then(|v| v.strip_prefix("Bearer "))
-            });
-
-        let token = match token {
+                None => {
+                    log_rejected(parts, "invalid API key");
+                    Err((
+                        StatusCode::UNAUTHORIZED,
+                        Json(ApiError::unauthorized("Invalid API key")),
+                    )
+                        .into_response())
+                }
+            };
+        }
+
+        let token = match request_token(&parts.headers) {
             Some(t) => t,
             None => {
+                log_rejected(parts, "missing token");
                 return Err((
                     StatusCode::UNAUTHORIZED,
                     Json(ApiError::unauthorized("Missing authentication token")),
                 )
-                    .into_response())
+                    .into_response());
             }
         };
 
-        let token_data = decode::<Claims>(
-            token,
-            &DecodingKey::from_secret(state.config.jwt_secret.as_bytes()),
-            &Validation::default(),
-        )
-        .map_err(|_| {
+        let claims = decode_token(token, &state.config.jwt_secret).map_err(|e| {
+            log_rejected(parts, &format!("invalid token: {:?}", e.kind()));
             (
                 StatusCode::UNAUTHORIZED,
                 [(header::SET_COOKIE, CLEAR_TOKEN_COOKIE)],
@@ -156,10 +314,274 @@ impl FromRequestParts<Arc<AppState>> for AuthUser {
                 .into_response()
         })?;
 
+        log_authorized(parts, claims.user_id);
         Ok(AuthUser {
-            user_id: token_data.claims.user_id,
-            email: token_data.claims.sub,
-            name: token_data.claims.name,
+            user_id: claims.user_id,
+            email: claims.sub,
+            name: claims.name,
+            role: claims.role,
         })
     }
 }
+
+/// Only lets authenticated admins through; anyone else gets `403`.
+pub struct RequireAdmin;
+
+#[axum::async_trait]
+impl FromRequestParts<Arc<AppState>> for RequireAdmin {
+    type Rejection = Response;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let auth = AuthUser::from_request_parts(parts, state).await?;
+        if auth.role != Role::Admin {
+            tracing::warn!(
+                user_id = auth.user_id,
+                method = %parts.method,
+                resource = %parts.uri.path(),
+                "rejected non-admin request"
+            );
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(ApiError::forbidden("Only admins can do this")),
+            )
+                .into_response());
+        }
+        Ok(RequireAdmin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestDb;
+    use axum::{routing::get, Router};
+    use shared::ItemListResponse;
+
+    #[test]
+    fn item_ids_pad_numbers_to_at_least_the_vendor_padding() {
+        assert_eq!(format_item_id("AD", 7, 3), "AD-007");
+        assert_eq!(format_item_id("AD", 7, 1), "AD-7");
+        assert_eq!(format_item_id("XY", 42, 6), "XY-000042");
+        assert_eq!(format_item_id("AD", 1000, 3), "AD-1000");
+    }
+
+    async fn add_api_key(db: &TestDb, user: &User, key: &str) {
+        diesel::insert_into(api_keys::table)
+            .values((
+                api_keys::key_hash.eq(hash_api_key(key)),
+                api_keys::user_id.eq(user.id),
+                api_keys::label.eq("ci"),
+            ))
+            .execute(&mut db.conn().await)
+            .await
+            .unwrap();
+    }
+
+    /// Serve `GET /api/items` on an ephemeral port, returning its URL.
+    async fn serve_item_list(db: &TestDb) -> String {
+        let app = Router::new()
+            .route("/api/items", get(items::list_all))
+            .with_state(db.state.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/api/items", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        url
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn api_key_authenticates_item_list() {
+        let db = TestDb::new().await;
+        let user = db.user("ci@example.org", "CI Bot", None).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let item = db.item(&category, &user, &user).await;
+
+        let key = "k3yForTheCiPipelineOnlyDoNotShare0123456";
+        add_api_key(&db, &user, key).await;
+        let mut conn = db.conn().await;
+
+        let url = serve_item_list(&db).await;
+        let client = reqwest::Client::new();
+        let response = client
+            .get(&url)
+            .header(API_KEY_HEADER, key)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let list: ItemListResponse = response.json().await.unwrap();
+        assert_eq!(list.total_count, 1);
+        assert_eq!(list.items[0].id, item.id);
+
+        let last_used_at: Option<DateTime<Utc>> = api_keys::table
+            .select(api_keys::last_used_at)
+            .first(&mut conn)
+            .await
+            .unwrap();
+        assert!(last_used_at.is_some());
+
+        let response = client
+            .get(&url)
+            .header(API_KEY_HEADER, "not-the-key")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn tokens_from_before_roles_are_members() {
+        let claims: Claims = serde_json::from_str(
+            r#"{"sub":"mf@example.org","name":"Mike Fox","user_id":1,"exp":2,"iat":1}"#,
+        )
+        .unwrap();
+        assert_eq!(claims.role, Role::Member);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn require_admin_follows_the_key_owners_role() {
+        use crate::db::schema::users;
+
+        let db = TestDb::new().await;
+        let user = db.user("ci@example.org", "CI Bot", None).await;
+        let key = "k3yForTheCiPipelineOnlyDoNotShare0123456";
+        add_api_key(&db, &user, key).await;
+
+        let require_admin = || async {
+            let (mut parts, ()) = axum::http::Request::builder()
+                .uri("/api/vendors")
+                .header(API_KEY_HEADER, key)
+                .body(())
+                .unwrap()
+                .into_parts();
+            RequireAdmin::from_request_parts(&mut parts, &db.state)
+                .await
+                .map(|_| ())
+                .map_err(|response| response.status())
+        };
+        assert_eq!(require_admin().await, Err(StatusCode::FORBIDDEN));
+
+        diesel::update(users::table.filter(users::id.eq(user.id)))
+            .set(users::role.eq(Role::Admin.as_str()))
+            .execute(&mut db.conn().await)
+            .await
+            .unwrap();
+        assert_eq!(require_admin().await, Ok(()));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn deactivated_users_api_keys_are_rejected() {
+        use crate::db::schema::users;
+
+        let db = TestDb::new().await;
+        let user = db.user("ci@example.org", "CI Bot", None).await;
+        let key = "k3yForTheCiPipelineOnlyDoNotShare0123456";
+        add_api_key(&db, &user, key).await;
+        diesel::update(users::table.filter(users::id.eq(user.id)))
+            .set(users::active.eq(false))
+            .execute(&mut db.conn().await)
+            .await
+            .unwrap();
+
+        let response = reqwest::Client::new()
+            .get(serve_item_list(&db).await)
+            .header(API_KEY_HEADER, key)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+    }
+
+    /// A writer that keeps everything a test's tracing subscriber logs.
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl tracing_subscriber::fmt::MakeWriter<'_> for CapturedLogs {
+        type Writer = Self;
+
+        fn make_writer(&self) -> Self {
+            self.clone()
+        }
+    }
+
+    /// Request parts for `DELETE /api/users/7` carrying a session token for `role`.
+    fn admin_request(config: &crate::AppConfig, user_id: i32, role: Role) -> Parts {
+        let now = Utc::now().timestamp() as usize;
+        let claims = Claims {
+            sub: "mf@example.org".to_string(),
+            name: "Mike Fox".to_string(),
+            user_id,
+            role,
+            exp: now + 3600,
+            iat: now,
+            auth_time: Some(now),
+        };
+        let token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &claims,
+            &jsonwebtoken::EncodingKey::from_secret(config.jwt_secret.as_bytes()),
+        )
+        .unwrap();
+        let (parts, ()) = axum::http::Request::delete("/api/users/7")
+            .header(header::AUTHORIZATION, format!("Bearer {}", token))
+            .body(())
+            .unwrap()
+            .into_parts();
+        parts
+    }
+
+    #[tokio::test]
+    async fn denied_admin_actions_are_logged() {
+        let logs = CapturedLogs::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(logs.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+        let state = crate::test_support::offline_state(crate::test_support::test_config());
+
+        let mut parts = admin_request(&state.config, 42, Role::Member);
+        let Err(response) = RequireAdmin::from_request_parts(&mut parts, &state).await else {
+            panic!("a member got through RequireAdmin");
+        };
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let mut parts = admin_request(&state.config, 43, Role::Admin);
+        assert!(RequireAdmin::from_request_parts(&mut parts, &state)
+            .await
+            .is_ok());
+
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        let denied: Vec<&str> = logs
+            .lines()
+            .filter(|line| line.contains("rejected non-admin request"))
+            .collect();
+        assert_eq!(denied.len(), 1, "{}", logs);
+        assert!(denied[0].contains("WARN"), "{}", denied[0]);
+        for field in ["user_id=42", "method=DELETE", "resource=/api/users/7"] {
+            assert!(
+                denied[0].contains(field),
+                "{} missing from {}",
+                field,
+                denied[0]
+            );
+        }
+    }
+}
@@ -1,18 +1,23 @@
 use axum::{
     extract::{Path, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::{IntoResponse, Response},
     Json,
 };
 use chrono::Utc;
 use diesel::prelude::*;
-use diesel_async::RunQueryDsl;
-use shared::{ApiError, CreateNote, NoteCreateResponse, NoteResponse};
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use shared::{
+    mention_matches, mention_tokens, ApiError, CreateNote, NoteCreateResponse, NoteResponse,
+    UpdateNote,
+};
+use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::db::schema::{action_items, notes, users};
-use crate::models::{NewNote, Note, UpdateActionItem, User};
-use crate::AppState;
+use crate::db::schema::{action_items, note_mentions, notes, users};
+use crate::models::{NewNote, NewNoteMention, Note, UpdateActionItem, User};
+use crate::{notifications, AppState};
 
 use super::AuthUser;
 
@@ -26,10 +31,6 @@ pub async fn list(
         Err(resp) => return resp,
     };
 
-    if let Err(resp) = super::ensure_item_exists(&mut conn, &item_id).await {
-        return resp;
-    }
-
     // Join with users to get author name
     let updates_result: Vec<(Note, User)> = match notes::table
         .inner_join(users::table.on(users::id.eq(notes::author_id)))
@@ -49,6 +50,25 @@ pub async fn list(
         }
     };
 
+    // Only an empty result needs a second query to tell "no entries" from "no such item"
+    if updates_result.is_empty() {
+        if let Err(resp) = super::ensure_item_exists(&mut conn, &item_id).await {
+            return resp;
+        }
+    }
+
+    let note_ids: Vec<i32> = updates_result.iter().map(|(n, _)| n.id).collect();
+    let mut mentions = match mentions_by_note(&mut conn, &note_ids).await {
+        Ok(m) => m,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to fetch mentions")),
+            )
+                .into_response()
+        }
+    };
+
     let result: Vec<_> = updates_result
         .into_iter()
         .map(|(n, u)| NoteResponse {
@@ -57,8 +77,10 @@ pub async fn list(
             date: n.note_date,
             author_id: n.author_id,
             author_name: u.name,
+            author_initials: u.initials,
             content: n.content,
             created_at: n.created_at,
+            mentioned_user_ids: mentions.remove(&n.id).unwrap_or_default(),
         })
         .collect();
 
@@ -71,15 +93,8 @@ pub async fn create(
     auth: AuthUser,
     Json(payload): Json<CreateNote>,
 ) -> impl IntoResponse {
-    // Validate content
-    if payload.content.is_empty() || payload.content.len() > 10000 {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(ApiError::validation_error(
-                "Content must be 1-10000 characters",
-            )),
-        )
-            .into_response();
+    if let Err(e) = validate_content(&payload.content) {
+        return (StatusCode::BAD_REQUEST, Json(e)).into_response();
     }
 
     let mut conn = match super::get_conn(&state).await {
@@ -91,6 +106,17 @@ pub async fn create(
         return resp;
     }
 
+    let mentioned = match resolve_mentions(&mut conn, &payload.content).await {
+        Ok(ids) => ids,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to resolve mentions")),
+            )
+                .into_response()
+        }
+    };
+
     let note_date = payload.note_date.unwrap_or_else(|| Utc::now().date_naive());
 
     let new_note = NewNote {
@@ -100,12 +126,23 @@ pub async fn create(
         content: payload.content,
     };
 
-    let note: Note = match diesel::insert_into(notes::table)
-        .values(&new_note)
-        .returning(Note::as_returning())
-        .get_result(&mut conn)
-        .await
-    {
+    let mentioned_ids = mentioned.clone();
+    let created = conn
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            async move {
+                let note: Note = diesel::insert_into(notes::table)
+                    .values(&new_note)
+                    .returning(Note::as_returning())
+                    .get_result(conn)
+                    .await?;
+                save_mentions(conn, note.id, &mentioned_ids).await?;
+                Ok(note)
+            }
+            .scope_boxed()
+        })
+        .await;
+
+    let note = match created {
         Ok(n) => n,
         Err(_) => {
             return (
@@ -116,7 +153,236 @@ pub async fn create(
         }
     };
 
-    // Update the action item's updated_at timestamp
+    touch_item(&mut conn, &note.action_item_id).await;
+    notifications::note_added(
+        &state,
+        &note.action_item_id,
+        auth.user_id,
+        &auth.name,
+        &note.content,
+    );
+
+    (StatusCode::CREATED, Json(to_note_response(note, mentioned))).into_response()
+}
+
+pub async fn update(
+    State(state): State<Arc<AppState>>,
+    Path((item_id, note_id)): Path<(String, i32)>,
+    auth: AuthUser,
+    Json(payload): Json<UpdateNote>,
+) -> impl IntoResponse {
+    if let Err(e) = validate_content(&payload.content) {
+        return (StatusCode::BAD_REQUEST, Json(e)).into_response();
+    }
+
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    if let Err(resp) = authored_note(&mut conn, &item_id, note_id, auth.user_id).await {
+        return resp;
+    }
+
+    let mentioned = match resolve_mentions(&mut conn, &payload.content).await {
+        Ok(ids) => ids,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to resolve mentions")),
+            )
+                .into_response()
+        }
+    };
+
+    let mentioned_ids = mentioned.clone();
+    let updated = conn
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            async move {
+                let note: Note = diesel::update(notes::table.filter(notes::id.eq(note_id)))
+                    .set(notes::content.eq(payload.content))
+                    .returning(Note::as_returning())
+                    .get_result(conn)
+                    .await?;
+                save_mentions(conn, note.id, &mentioned_ids).await?;
+                Ok(note)
+            }
+            .scope_boxed()
+        })
+        .await;
+
+    let note = match updated {
+        Ok(n) => n,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to update note")),
+            )
+                .into_response()
+        }
+    };
+
+    touch_item(&mut conn, &item_id).await;
+
+    Json(to_note_response(note, mentioned)).into_response()
+}
+
+pub async fn delete(
+    State(state): State<Arc<AppState>>,
+    Path((item_id, note_id)): Path<(String, i32)>,
+    auth: AuthUser,
+) -> impl IntoResponse {
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    if let Err(resp) = authored_note(&mut conn, &item_id, note_id, auth.user_id).await {
+        return resp;
+    }
+
+    if diesel::delete(notes::table.filter(notes::id.eq(note_id)))
+        .execute(&mut conn)
+        .await
+        .is_err()
+    {
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::internal_error("Failed to delete note")),
+        )
+            .into_response();
+    }
+
+    touch_item(&mut conn, &item_id).await;
+
+    StatusCode::NO_CONTENT.into_response()
+}
+
+/// Counted in characters of the decoded content, not request bytes.
+fn validate_content(content: &str) -> Result<(), ApiError> {
+    if content.is_empty() || content.chars().count() > 10000 {
+        return Err(ApiError::validation_error(
+            "Content must be 1-10000 characters",
+        ));
+    }
+    Ok(())
+}
+
+/// Ids of the active users `@mentioned` in `content`, ascending. A token that matches no
+/// user, or more than one, is left as plain text.
+async fn resolve_mentions(conn: &mut AsyncPgConnection, content: &str) -> QueryResult<Vec<i32>> {
+    let tokens = mention_tokens(content);
+    if tokens.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let candidates: Vec<(i32, String, Option<String>)> = users::table
+        .filter(users::active.eq(true))
+        .select((users::id, users::name, users::initials))
+        .load(conn)
+        .await?;
+
+    let mut ids = Vec::new();
+    for mention in tokens {
+        let mut matching = candidates.iter().filter(|(_, name, initials)| {
+            mention_matches(mention.token, name, initials.as_deref())
+        });
+        if let (Some((id, _, _)), None) = (matching.next(), matching.next()) {
+            ids.push(*id);
+        }
+    }
+    ids.sort_unstable();
+    ids.dedup();
+    Ok(ids)
+}
+
+/// Replace the mentions recorded for a note.
+async fn save_mentions(
+    conn: &mut AsyncPgConnection,
+    note_id: i32,
+    user_ids: &[i32],
+) -> QueryResult<()> {
+    diesel::delete(note_mentions::table.filter(note_mentions::note_id.eq(note_id)))
+        .execute(conn)
+        .await?;
+    if !user_ids.is_empty() {
+        let rows: Vec<NewNoteMention> = user_ids
+            .iter()
+            .map(|&user_id| NewNoteMention { note_id, user_id })
+            .collect();
+        diesel::insert_into(note_mentions::table)
+            .values(&rows)
+            .execute(conn)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Mentioned user ids of each note, ascending; notes without mentions are absent.
+async fn mentions_by_note(
+    conn: &mut AsyncPgConnection,
+    note_ids: &[i32],
+) -> QueryResult<HashMap<i32, Vec<i32>>> {
+    let rows: Vec<(i32, i32)> = note_mentions::table
+        .filter(note_mentions::note_id.eq_any(note_ids))
+        .order((note_mentions::note_id, note_mentions::user_id))
+        .select((note_mentions::note_id, note_mentions::user_id))
+        .load(conn)
+        .await?;
+
+    let mut map: HashMap<i32, Vec<i32>> = HashMap::new();
+    for (note_id, user_id) in rows {
+        map.entry(note_id).or_default().push(user_id);
+    }
+    Ok(map)
+}
+
+/// Load a note on the given item, rejecting anyone but its author.
+async fn authored_note(
+    conn: &mut AsyncPgConnection,
+    item_id: &str,
+    note_id: i32,
+    user_id: i32,
+) -> Result<Note, Response> {
+    let note: Note = match notes::table
+        .filter(notes::id.eq(note_id))
+        .filter(notes::action_item_id.eq(item_id))
+        .first(conn)
+        .await
+    {
+        Ok(n) => n,
+        Err(diesel::NotFound) => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ApiError::not_found(format!(
+                    "Note {} not found on {}",
+                    note_id, item_id
+                ))),
+            )
+                .into_response())
+        }
+        Err(_) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to fetch note")),
+            )
+                .into_response())
+        }
+    };
+
+    if note.author_id != user_id {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ApiError::forbidden("Only the author can change this note")),
+        )
+            .into_response());
+    }
+
+    Ok(note)
+}
+
+/// Bump the parent item's `updated_at` after its notes change.
+async fn touch_item(conn: &mut AsyncPgConnection, item_id: &str) {
     let update_changeset = UpdateActionItem {
         title: None,
         due_date: None,
@@ -124,23 +390,239 @@ pub async fn create(
         owner_id: None,
         priority: None,
         description: None,
+        recurrence: None,
         updated_at: Some(Utc::now()),
     };
-    let _ = diesel::update(action_items::table.filter(action_items::id.eq(&note.action_item_id)))
+    let _ = diesel::update(action_items::table.filter(action_items::id.eq(item_id)))
         .set(&update_changeset)
-        .execute(&mut conn)
+        .execute(conn)
         .await;
+}
+
+fn to_note_response(note: Note, mentioned_user_ids: Vec<i32>) -> NoteCreateResponse {
+    NoteCreateResponse {
+        id: note.id,
+        action_item_id: note.action_item_id,
+        note_date: note.note_date,
+        author_id: note.author_id,
+        content: note.content,
+        created_at: note.created_at,
+        mentioned_user_ids,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{auth, json, TestDb};
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn create_records_known_mentions_and_ignores_unknown_ones() {
+        let db = TestDb::new().await;
+        let author = db.user("ada@example.org", "Ada Lovelace", Some("AL")).await;
+        let mike = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let item = db.item(&category, &author, &author).await;
+
+        let content = "@MF can you check with @nobody? Copying ada@example.org";
+        let response = create(
+            State(db.state.clone()),
+            Path(item.id.clone()),
+            auth(&author),
+            Json(CreateNote {
+                note_date: None,
+                content: content.to_string(),
+            }),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let note: NoteCreateResponse = json(response).await;
+        assert_eq!(note.mentioned_user_ids, [mike.id]);
+        assert_eq!(note.content, content);
+
+        let stored: Vec<i32> = note_mentions::table
+            .filter(note_mentions::note_id.eq(note.id))
+            .select(note_mentions::user_id)
+            .load(&mut db.conn().await)
+            .await
+            .unwrap();
+        assert_eq!(stored, [mike.id]);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn create_limits_content_by_characters_not_bytes() {
+        let db = TestDb::new().await;
+        let author = db.user("ada@example.org", "Ada Lovelace", Some("AL")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let item = db.item(&category, &author, &author).await;
+
+        let create_with = |content: String| {
+            create(
+                State(db.state.clone()),
+                Path(item.id.clone()),
+                auth(&author),
+                Json(CreateNote {
+                    note_date: None,
+                    content,
+                }),
+            )
+        };
+        assert_eq!(
+            create_with("é".repeat(10000))
+                .await
+                .into_response()
+                .status(),
+            StatusCode::CREATED
+        );
+        assert_eq!(
+            create_with("é".repeat(10001))
+                .await
+                .into_response()
+                .status(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn list_is_empty_for_an_item_without_notes_and_404_for_a_missing_one() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let item = db.item(&category, &user, &user).await;
+
+        let response = list(State(db.state.clone()), Path(item.id.clone()), auth(&user))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let entries: Vec<NoteResponse> = json(response).await;
+        assert!(entries.is_empty());
+
+        let response = list(
+            State(db.state.clone()),
+            Path("AAA-999".to_string()),
+            auth(&user),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
 
-    (
-        StatusCode::CREATED,
-        Json(NoteCreateResponse {
-            id: note.id,
-            action_item_id: note.action_item_id,
-            note_date: note.note_date,
-            author_id: note.author_id,
-            content: note.content,
-            created_at: note.created_at,
-        }),
-    )
-        .into_response()
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn listed_notes_carry_their_authors_initials() {
+        let db = TestDb::new().await;
+        let ada = db.user("ada@example.org", "Ada Lovelace", Some("AL")).await;
+        let bot = db.user("ci@example.org", "CI Bot", None).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let item = db.item(&category, &ada, &ada).await;
+
+        for (author, day) in [(&ada, 4), (&bot, 5)] {
+            let response = create(
+                State(db.state.clone()),
+                Path(item.id.clone()),
+                auth(author),
+                Json(CreateNote {
+                    note_date: chrono::NaiveDate::from_ymd_opt(2024, 3, day),
+                    content: "Checked".to_string(),
+                }),
+            )
+            .await
+            .into_response();
+            assert_eq!(response.status(), StatusCode::CREATED);
+        }
+
+        let response = list(State(db.state.clone()), Path(item.id.clone()), auth(&ada))
+            .await
+            .into_response();
+        let notes: Vec<NoteResponse> = json(response).await;
+        let authors: Vec<(&str, Option<&str>)> = notes
+            .iter()
+            .map(|n| (n.author_name.as_str(), n.author_initials.as_deref()))
+            .collect();
+        assert_eq!(authors, [("CI Bot", None), ("Ada Lovelace", Some("AL"))]);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn only_the_author_can_edit_or_delete_a_note() {
+        let db = TestDb::new().await;
+        let ada = db.user("ada@example.org", "Ada Lovelace", Some("AL")).await;
+        let mike = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let item = db.item(&category, &ada, &ada).await;
+        let other_item = db.item(&category, &ada, &ada).await;
+
+        let response = create(
+            State(db.state.clone()),
+            Path(item.id.clone()),
+            auth(&ada),
+            Json(CreateNote {
+                note_date: None,
+                content: "Recieved the drawings".to_string(),
+            }),
+        )
+        .await
+        .into_response();
+        let note: NoteCreateResponse = json(response).await;
+
+        let edit = |user: &User, item_id: &str, content: &str| {
+            update(
+                State(db.state.clone()),
+                Path((item_id.to_string(), note.id)),
+                auth(user),
+                Json(UpdateNote {
+                    content: content.to_string(),
+                }),
+            )
+        };
+        let response = edit(&mike, &item.id, "Never received anything")
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let error: ApiError = json(response).await;
+        assert_eq!(error.error.code, "FORBIDDEN");
+        let response = delete(
+            State(db.state.clone()),
+            Path((item.id.clone(), note.id)),
+            auth(&mike),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let stored: Note = notes::table
+            .find(note.id)
+            .first(&mut db.conn().await)
+            .await
+            .unwrap();
+        assert_eq!(stored.content, "Recieved the drawings");
+
+        // The note only exists under the item it was posted on
+        let response = edit(&ada, &other_item.id, "Received the drawings")
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = edit(&ada, &item.id, "Received the drawings")
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let edited: NoteCreateResponse = json(response).await;
+        assert_eq!(edited.content, "Received the drawings");
+        let updated_at: chrono::DateTime<chrono::Utc> = action_items::table
+            .find(&item.id)
+            .select(action_items::updated_at)
+            .first(&mut db.conn().await)
+            .await
+            .unwrap();
+        assert!(updated_at > item.updated_at);
+    }
 }
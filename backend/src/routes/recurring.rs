@@ -0,0 +1,267 @@
+//! Recurring items. Once a Complete weekly or monthly item's due date has arrived, it is
+//! cloned into a fresh "New" item due one interval later. The clone keeps the recurrence,
+//! and the original records it in `next_occurrence_id` so it is only ever cloned once.
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use chrono::{DateTime, Days, Months, NaiveDate, Utc};
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use shared::{ApiError, Recurrence};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::db::schema::{action_items, status_history, vendors};
+use crate::models::{ActionItem, NewActionItem, NewStatusHistory, Vendor};
+use crate::AppState;
+
+use super::items::LATEST_STATUS_SQL;
+use super::AuthUser;
+
+/// Due date of the occurrence after one due (or, if undated, completed) on `anchor`.
+fn next_due_date(recurrence: &str, anchor: NaiveDate) -> Option<NaiveDate> {
+    if recurrence == Recurrence::Weekly.as_str() {
+        anchor.checked_add_days(Days::new(7))
+    } else if recurrence == Recurrence::Monthly.as_str() {
+        anchor.checked_add_months(Months::new(1))
+    } else {
+        None
+    }
+}
+
+/// Clone every recurring item that is due for its next occurrence. Safe to run repeatedly,
+/// e.g. from cron with an API key; returns only the items created by this run.
+pub async fn generate(State(state): State<Arc<AppState>>, auth: AuthUser) -> impl IntoResponse {
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    let created_by_id = auth.user_id;
+    let today = Utc::now().date_naive();
+    let generated = conn
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            async move { generate_due(conn, created_by_id, today).await }.scope_boxed()
+        })
+        .await;
+
+    let items = match generated {
+        Ok(i) => i,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error(
+                    "Failed to generate recurring items",
+                )),
+            )
+                .into_response()
+        }
+    };
+    if !items.is_empty() {
+        tracing::info!("Generated {} recurring item(s)", items.len());
+    }
+
+    match super::items::build_item_responses(&mut conn, items).await {
+        Ok(result) => Json(result).into_response(),
+        Err(resp) => resp,
+    }
+}
+
+async fn generate_due(
+    conn: &mut AsyncPgConnection,
+    created_by_id: i32,
+    today: NaiveDate,
+) -> Result<Vec<ActionItem>, diesel::result::Error> {
+    // Lock the candidates so a concurrent run can't clone the same item twice
+    let candidates: Vec<ActionItem> = action_items::table
+        .filter(action_items::recurrence.ne(Recurrence::None.as_str()))
+        .filter(action_items::next_occurrence_id.is_null())
+        .filter(action_items::deleted_at.is_null())
+        .filter(
+            diesel::dsl::sql::<diesel::sql_types::Bool>(&format!("{} = ", LATEST_STATUS_SQL))
+                .bind::<diesel::sql_types::Text, _>("Complete"),
+        )
+        .order(action_items::id.asc())
+        .select(ActionItem::as_select())
+        .for_update()
+        .load(conn)
+        .await?;
+    if candidates.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // When each candidate was completed: the time of its latest status change
+    let candidate_ids: Vec<&str> = candidates.iter().map(|i| i.id.as_str()).collect();
+    let mut completed_at: HashMap<String, DateTime<Utc>> = HashMap::new();
+    let changes: Vec<(String, DateTime<Utc>)> = status_history::table
+        .filter(status_history::action_item_id.eq_any(&candidate_ids))
+        .order(status_history::changed_at.desc())
+        .select((status_history::action_item_id, status_history::changed_at))
+        .load(conn)
+        .await?;
+    for (item_id, changed_at) in changes {
+        completed_at.entry(item_id).or_insert(changed_at);
+    }
+
+    let mut created = Vec::new();
+    for source in candidates {
+        let anchor = match source.due_date {
+            Some(due) => due,
+            None => completed_at
+                .get(&source.id)
+                .copied()
+                .unwrap_or(source.created_at)
+                .date_naive(),
+        };
+        if anchor > today {
+            continue;
+        }
+        let Some(due_date) = next_due_date(&source.recurrence, anchor) else {
+            continue;
+        };
+
+        let vendor: Vendor = vendors::table
+            .filter(vendors::id.eq(source.vendor_id))
+            .for_update()
+            .first(conn)
+            .await?;
+        let number = vendor.next_number;
+        diesel::update(vendors::table.filter(vendors::id.eq(vendor.id)))
+            .set(vendors::next_number.eq(number + 1))
+            .execute(conn)
+            .await?;
+
+        let new_item = NewActionItem {
+            id: super::format_item_id(&vendor.prefix, number, vendor.number_padding),
+            vendor_id: vendor.id,
+            number,
+            title: source.title.clone(),
+            create_date: today,
+            created_by_id,
+            due_date: Some(due_date),
+            owner_id: source.owner_id,
+            priority: source.priority.clone(),
+            description: source.description.clone(),
+            category_id: source.category_id,
+            recurrence: source.recurrence.clone(),
+        };
+        let item: ActionItem = diesel::insert_into(action_items::table)
+            .values(&new_item)
+            .returning(ActionItem::as_returning())
+            .get_result(conn)
+            .await?;
+
+        diesel::insert_into(status_history::table)
+            .values(&NewStatusHistory {
+                action_item_id: item.id.clone(),
+                status: "New".to_string(),
+                changed_by_id: created_by_id,
+                comment: Some(format!("Recurring from {}", source.id)),
+                from_status: None,
+            })
+            .execute(conn)
+            .await?;
+
+        diesel::update(action_items::table.filter(action_items::id.eq(&source.id)))
+            .set(action_items::next_occurrence_id.eq(&item.id))
+            .execute(conn)
+            .await?;
+
+        created.push(item);
+    }
+
+    Ok(created)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{auth, json, TestDb};
+    use shared::ActionItemResponse;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn next_due_date_steps_one_interval() {
+        assert_eq!(
+            next_due_date("weekly", date(2026, 2, 26)),
+            Some(date(2026, 3, 5))
+        );
+        assert_eq!(
+            next_due_date("monthly", date(2026, 1, 31)),
+            Some(date(2026, 2, 28))
+        );
+        assert_eq!(next_due_date("none", date(2026, 1, 31)), None);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn generate_clones_each_due_item_once() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let owner = db.user("ada@example.org", "Ada Lovelace", Some("AL")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let today = Utc::now().date_naive();
+
+        let mut conn = db.conn().await;
+        let mut items = Vec::new();
+        for (due, status) in [
+            (today - Days::new(3), "Complete"),
+            (today + Days::new(30), "Complete"),
+            (today - Days::new(3), "In Progress"),
+        ] {
+            let item = db.item(&category, &user, &owner).await;
+            diesel::update(action_items::table.filter(action_items::id.eq(&item.id)))
+                .set((
+                    action_items::due_date.eq(Some(due)),
+                    action_items::recurrence.eq("weekly"),
+                ))
+                .execute(&mut conn)
+                .await
+                .unwrap();
+            diesel::insert_into(status_history::table)
+                .values(&NewStatusHistory {
+                    action_item_id: item.id.clone(),
+                    status: status.to_string(),
+                    changed_by_id: user.id,
+                    comment: None,
+                    from_status: None,
+                })
+                .execute(&mut conn)
+                .await
+                .unwrap();
+            items.push(item);
+        }
+
+        let response = generate(State(db.state.clone()), auth(&user))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let created: Vec<ActionItemResponse> = json(response).await;
+        assert_eq!(created.len(), 1);
+        let clone = &created[0];
+        assert_eq!(clone.id, "AAA-004");
+        assert_eq!(clone.title, items[0].title);
+        assert_eq!(clone.owner_id, owner.id);
+        assert_eq!(clone.status, "New");
+        assert_eq!(clone.due_date, Some(today + Days::new(4)));
+        assert_eq!(clone.recurrence, "weekly");
+
+        let next_occurrence: Option<String> = action_items::table
+            .filter(action_items::id.eq(&items[0].id))
+            .select(action_items::next_occurrence_id)
+            .first(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(next_occurrence.as_deref(), Some("AAA-004"));
+
+        let response = generate(State(db.state.clone()), auth(&user))
+            .await
+            .into_response();
+        let created: Vec<ActionItemResponse> = json(response).await;
+        assert!(created.is_empty());
+    }
+}
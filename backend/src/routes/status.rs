@@ -6,13 +6,17 @@ use axum::{
 };
 use chrono::Utc;
 use diesel::prelude::*;
-use diesel_async::RunQueryDsl;
-use shared::{ApiError, ChangeStatus, StatusChangeResponse, StatusHistoryResponse};
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, RunQueryDsl};
+use shared::{
+    ApiError, BulkStatusChange, BulkStatusOutcome, BulkStatusResult, ChangeStatus, ReopenItem,
+    StatusChangeResponse, StatusHistoryResponse,
+};
 use std::sync::Arc;
 
 use crate::db::schema::{action_items, status_history, users};
 use crate::models::{NewStatusHistory, StatusHistory, User};
-use crate::AppState;
+use crate::{notifications, webhooks, AppState};
 
 use super::AuthUser;
 
@@ -25,6 +29,43 @@ const VALID_STATUSES: &[&str] = &[
     "Blocked",
 ];
 
+/// Upper bound on `item_ids` in one bulk status change.
+const MAX_BULK_ITEMS: usize = 500;
+
+/// Allowed workflow moves, keyed by the current status. `Blocked` is handled separately in
+/// `transition_allowed`: it can be entered from, and left to, any status.
+const ALLOWED_TRANSITIONS: &[(&str, &[&str])] = &[
+    ("New", &["Not Started", "In Progress", "TBC", "Complete"]),
+    ("Not Started", &["New", "In Progress", "TBC", "Complete"]),
+    ("In Progress", &["Not Started", "TBC", "Complete"]),
+    ("TBC", &["Not Started", "In Progress", "Complete"]),
+    // Reopening completed work goes back through In Progress
+    ("Complete", &["In Progress"]),
+];
+
+/// Statuses that can only be set with a non-blank comment, e.g. saying what an item is
+/// blocked on.
+const COMMENT_REQUIRED: &[&str] = &["Blocked"];
+
+/// Whether moving to `to` with this comment satisfies `COMMENT_REQUIRED`.
+fn comment_ok(to: &str, comment: Option<&str>) -> bool {
+    !COMMENT_REQUIRED.contains(&to) || comment.is_some_and(|c| !c.trim().is_empty())
+}
+
+/// Whether an item may move from `from` (its latest status, if any) to `to`.
+fn transition_allowed(from: Option<&str>, to: &str) -> bool {
+    let Some(from) = from else {
+        return true;
+    };
+    if from == to || from == "Blocked" || to == "Blocked" {
+        return true;
+    }
+    ALLOWED_TRANSITIONS
+        .iter()
+        .find(|(status, _)| *status == from)
+        .is_some_and(|(_, targets)| targets.contains(&to))
+}
+
 pub async fn history(
     State(state): State<Arc<AppState>>,
     Path(item_id): Path<String>,
@@ -35,14 +76,10 @@ pub async fn history(
         Err(resp) => return resp,
     };
 
-    if let Err(resp) = super::ensure_item_exists(&mut conn, &item_id).await {
-        return resp;
-    }
-
     let history: Vec<(StatusHistory, User)> = match status_history::table
         .inner_join(users::table.on(users::id.eq(status_history::changed_by_id)))
         .filter(status_history::action_item_id.eq(&item_id))
-        .order(status_history::changed_at.desc())
+        .order((status_history::changed_at.desc(), status_history::id.desc()))
         .select((StatusHistory::as_select(), User::as_select()))
         .load(&mut conn)
         .await
@@ -57,11 +94,19 @@ pub async fn history(
         }
     };
 
+    // Only an empty result needs a second query to tell "no entries" from "no such item"
+    if history.is_empty() {
+        if let Err(resp) = super::ensure_item_exists(&mut conn, &item_id).await {
+            return resp;
+        }
+    }
+
     let result: Vec<_> = history
         .into_iter()
         .map(|(h, u)| StatusHistoryResponse {
             id: h.id,
             action_item_id: h.action_item_id,
+            from_status: h.from_status,
             status: h.status,
             changed_by_id: h.changed_by_id,
             changed_by_name: u.name,
@@ -93,6 +138,136 @@ pub async fn change(
             .into_response();
     }
 
+    if !comment_ok(status_str, payload.comment.as_deref()) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::validation_error(format!(
+                "A comment is required when changing status to {}",
+                status_str
+            ))),
+        )
+            .into_response();
+    }
+
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    let id = item_id.as_str();
+    let changed_by_id = auth.user_id;
+    let comment = payload.comment;
+    let changed = conn
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            async move {
+                // Lock the item, as in `bulk_change`, so a concurrent change can't slip in
+                // between the workflow check and the insert
+                let locked: Option<String> = action_items::table
+                    .filter(action_items::id.eq(id))
+                    .filter(action_items::deleted_at.is_null())
+                    .select(action_items::id)
+                    .for_update()
+                    .first(conn)
+                    .await
+                    .optional()?;
+                if locked.is_none() {
+                    return Ok(Err((
+                        StatusCode::NOT_FOUND,
+                        ApiError::not_found(format!("Action item {} not found", id)),
+                    )));
+                }
+
+                // Record what the status was changed from; None when the item has no history yet
+                let from_status: Option<String> = status_history::table
+                    .filter(status_history::action_item_id.eq(id))
+                    .order((status_history::changed_at.desc(), status_history::id.desc()))
+                    .select(status_history::status)
+                    .first(conn)
+                    .await
+                    .optional()?;
+
+                if !transition_allowed(from_status.as_deref(), status_str) {
+                    return Ok(Err((
+                        StatusCode::CONFLICT,
+                        ApiError::conflict(format!(
+                            "Cannot change status from {} to {}",
+                            from_status.as_deref().unwrap_or("New"),
+                            status_str
+                        )),
+                    )));
+                }
+
+                let entry: StatusHistory = diesel::insert_into(status_history::table)
+                    .values(&NewStatusHistory {
+                        action_item_id: id.to_string(),
+                        status: status_str.to_string(),
+                        changed_by_id,
+                        comment,
+                        from_status,
+                    })
+                    .returning(StatusHistory::as_returning())
+                    .get_result(conn)
+                    .await?;
+
+                diesel::update(action_items::table.filter(action_items::id.eq(id)))
+                    .set(action_items::updated_at.eq(Utc::now()))
+                    .execute(conn)
+                    .await?;
+
+                Ok(Ok(entry))
+            }
+            .scope_boxed()
+        })
+        .await;
+
+    let entry = match changed {
+        Ok(Ok(entry)) => entry,
+        Ok(Err((status, error))) => return (status, Json(error)).into_response(),
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to update status")),
+            )
+                .into_response()
+        }
+    };
+
+    notifications::status_changed(&state, std::slice::from_ref(&entry), &auth.name);
+    webhooks::status_changed(&state, vec![entry.clone()]);
+
+    (
+        StatusCode::CREATED,
+        Json(StatusChangeResponse {
+            id: entry.id,
+            action_item_id: entry.action_item_id,
+            from_status: entry.from_status,
+            status: entry.status,
+            changed_by_id: entry.changed_by_id,
+            changed_at: entry.changed_at,
+            comment: entry.comment,
+        }),
+    )
+        .into_response()
+}
+
+/// Move a Complete item back to In Progress, recording why as the history comment.
+pub async fn reopen(
+    State(state): State<Arc<AppState>>,
+    Path(item_id): Path<String>,
+    auth: AuthUser,
+    Json(payload): Json<ReopenItem>,
+) -> impl IntoResponse {
+    let reason = payload.reason.trim();
+    if reason.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::validation_error(
+                "A reason is required to reopen an item",
+            )),
+        )
+            .into_response();
+    }
+
     let mut conn = match super::get_conn(&state).await {
         Ok(c) => c,
         Err(resp) => return resp,
@@ -102,11 +277,42 @@ pub async fn change(
         return resp;
     }
 
+    let current: Option<String> = match status_history::table
+        .filter(status_history::action_item_id.eq(&item_id))
+        .order((status_history::changed_at.desc(), status_history::id.desc()))
+        .select(status_history::status)
+        .first(&mut conn)
+        .await
+        .optional()
+    {
+        Ok(s) => s,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to fetch current status")),
+            )
+                .into_response()
+        }
+    };
+
+    if current.as_deref() != Some("Complete") {
+        return (
+            StatusCode::CONFLICT,
+            Json(ApiError::conflict(format!(
+                "Only Complete items can be reopened; {} is {}",
+                item_id,
+                current.as_deref().unwrap_or("New")
+            ))),
+        )
+            .into_response();
+    }
+
     let new_status = NewStatusHistory {
         action_item_id: item_id,
-        status: status_str.to_string(),
+        status: "In Progress".to_string(),
         changed_by_id: auth.user_id,
-        comment: payload.comment,
+        comment: Some(reason.to_string()),
+        from_status: current,
     };
 
     let entry: StatusHistory = match diesel::insert_into(status_history::table)
@@ -119,28 +325,406 @@ pub async fn change(
         Err(_) => {
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiError::internal_error("Failed to update status")),
+                Json(ApiError::internal_error("Failed to reopen item")),
             )
                 .into_response()
         }
     };
 
-    // Update the action item's updated_at timestamp
     let _ = diesel::update(action_items::table.filter(action_items::id.eq(&entry.action_item_id)))
         .set(action_items::updated_at.eq(Utc::now()))
         .execute(&mut conn)
         .await;
 
+    notifications::status_changed(&state, std::slice::from_ref(&entry), &auth.name);
+    webhooks::status_changed(&state, vec![entry.clone()]);
+
     (
         StatusCode::CREATED,
-        Json(StatusChangeResponse {
+        Json(StatusHistoryResponse {
             id: entry.id,
             action_item_id: entry.action_item_id,
+            from_status: entry.from_status,
             status: entry.status,
             changed_by_id: entry.changed_by_id,
+            changed_by_name: auth.name,
             changed_at: entry.changed_at,
             comment: entry.comment,
         }),
     )
         .into_response()
 }
+
+/// Apply one status change to many items in a single transaction. Missing items and
+/// disallowed transitions are reported per item instead of failing the whole request.
+pub async fn bulk_change(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Json(payload): Json<BulkStatusChange>,
+) -> impl IntoResponse {
+    let status_str = payload.status.as_str();
+
+    if !VALID_STATUSES.contains(&status_str) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::validation_error(format!(
+                "Invalid status. Must be one of: {}",
+                VALID_STATUSES.join(", ")
+            ))),
+        )
+            .into_response();
+    }
+
+    if payload.item_ids.is_empty() || payload.item_ids.len() > MAX_BULK_ITEMS {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::validation_error(format!(
+                "item_ids must contain between 1 and {} ids",
+                MAX_BULK_ITEMS
+            ))),
+        )
+            .into_response();
+    }
+
+    if !comment_ok(status_str, payload.comment.as_deref()) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::validation_error(format!(
+                "A comment is required when changing status to {}",
+                status_str
+            ))),
+        )
+            .into_response();
+    }
+
+    // A repeated id would otherwise get a second, same-status history row
+    let mut seen = std::collections::HashSet::new();
+    let item_ids: Vec<String> = payload
+        .item_ids
+        .into_iter()
+        .filter(|id| seen.insert(id.clone()))
+        .collect();
+
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    let changed_by_id = auth.user_id;
+    let comment = payload.comment;
+    let results = conn
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            async move {
+                // Lock the items so their latest status can't move underneath us
+                let existing: std::collections::HashSet<String> = action_items::table
+                    .filter(action_items::id.eq_any(&item_ids))
+                    .filter(action_items::deleted_at.is_null())
+                    .select(action_items::id)
+                    .for_update()
+                    .load::<String>(conn)
+                    .await?
+                    .into_iter()
+                    .collect();
+
+                let mut latest: std::collections::HashMap<String, String> = status_history::table
+                    .filter(status_history::action_item_id.eq_any(&item_ids))
+                    .distinct_on(status_history::action_item_id)
+                    .order((
+                        status_history::action_item_id,
+                        status_history::changed_at.desc(),
+                        status_history::id.desc(),
+                    ))
+                    .select((status_history::action_item_id, status_history::status))
+                    .load::<(String, String)>(conn)
+                    .await?
+                    .into_iter()
+                    .collect();
+
+                let mut results = Vec::with_capacity(item_ids.len());
+                let mut rows = Vec::new();
+                for item_id in item_ids {
+                    if !existing.contains(&item_id) {
+                        results.push(BulkStatusResult {
+                            item_id,
+                            outcome: BulkStatusOutcome::NotFound,
+                            from_status: None,
+                        });
+                        continue;
+                    }
+
+                    let from_status = latest.remove(&item_id);
+                    let outcome = if transition_allowed(from_status.as_deref(), status_str) {
+                        rows.push(NewStatusHistory {
+                            action_item_id: item_id.clone(),
+                            status: status_str.to_string(),
+                            changed_by_id,
+                            comment: comment.clone(),
+                            from_status: from_status.clone(),
+                        });
+                        BulkStatusOutcome::Updated
+                    } else {
+                        BulkStatusOutcome::NotAllowed
+                    };
+                    results.push(BulkStatusResult {
+                        item_id,
+                        outcome,
+                        from_status,
+                    });
+                }
+
+                let mut written = Vec::new();
+                if !rows.is_empty() {
+                    written = diesel::insert_into(status_history::table)
+                        .values(&rows)
+                        .returning(StatusHistory::as_returning())
+                        .get_results(conn)
+                        .await?;
+
+                    let updated_ids: Vec<&str> =
+                        rows.iter().map(|r| r.action_item_id.as_str()).collect();
+                    diesel::update(
+                        action_items::table.filter(action_items::id.eq_any(updated_ids)),
+                    )
+                    .set(action_items::updated_at.eq(Utc::now()))
+                    .execute(conn)
+                    .await?;
+                }
+
+                Ok((results, written))
+            }
+            .scope_boxed()
+        })
+        .await;
+
+    match results {
+        Ok((results, written)) => {
+            notifications::status_changed(&state, &written, &auth.name);
+            webhooks::status_changed(&state, written);
+            Json(results).into_response()
+        }
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::internal_error("Failed to update statuses")),
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{auth, json, TestDb};
+    use shared::Status;
+
+    #[test]
+    fn complete_cannot_go_straight_back_to_new() {
+        assert!(!transition_allowed(Some("Complete"), "New"));
+        assert!(!transition_allowed(Some("Complete"), "Not Started"));
+    }
+
+    #[test]
+    fn complete_reopens_through_in_progress() {
+        assert!(transition_allowed(Some("Complete"), "In Progress"));
+        assert!(transition_allowed(Some("In Progress"), "Complete"));
+    }
+
+    #[test]
+    fn blocked_is_reachable_from_and_back_to_any_status() {
+        for status in VALID_STATUSES {
+            assert!(transition_allowed(Some(status), "Blocked"), "{status}");
+            assert!(transition_allowed(Some("Blocked"), status), "{status}");
+        }
+    }
+
+    #[test]
+    fn items_without_history_can_take_any_status() {
+        assert!(transition_allowed(None, "Complete"));
+        assert!(transition_allowed(Some("Complete"), "Complete"));
+    }
+
+    #[test]
+    fn blocked_needs_a_comment_that_is_not_blank() {
+        assert!(!comment_ok("Blocked", None));
+        assert!(!comment_ok("Blocked", Some(" \n\t")));
+        assert!(comment_ok("Blocked", Some("Waiting on the vendor")));
+        assert!(comment_ok("Complete", None));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn blocking_without_a_comment_is_rejected_on_both_routes() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let item = db.item(&category, &user, &user).await;
+
+        let block = |comment: Option<&str>| {
+            change(
+                State(db.state.clone()),
+                Path(item.id.clone()),
+                auth(&user),
+                Json(ChangeStatus {
+                    status: Status::Blocked,
+                    comment: comment.map(str::to_string),
+                }),
+            )
+        };
+        let bulk_block = |comment: Option<&str>| {
+            bulk_change(
+                State(db.state.clone()),
+                auth(&user),
+                Json(BulkStatusChange {
+                    item_ids: vec![item.id.clone()],
+                    status: Status::Blocked,
+                    comment: comment.map(str::to_string),
+                }),
+            )
+        };
+
+        for comment in [None, Some("   ")] {
+            let response = block(comment).await.into_response();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{:?}", comment);
+            let response = bulk_block(comment).await.into_response();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{:?}", comment);
+        }
+        let history: i64 = status_history::table
+            .count()
+            .get_result(&mut db.conn().await)
+            .await
+            .unwrap();
+        assert_eq!(history, 0);
+
+        let response = block(Some("Waiting on the vendor")).await.into_response();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let entry: StatusChangeResponse = json(response).await;
+        assert_eq!(entry.comment.as_deref(), Some("Waiting on the vendor"));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn change_rejects_a_forbidden_transition_with_409() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let item = db.item(&category, &user, &user).await;
+
+        let change_to = |status: Status| {
+            change(
+                State(db.state.clone()),
+                Path(item.id.clone()),
+                auth(&user),
+                Json(ChangeStatus {
+                    status,
+                    comment: None,
+                }),
+            )
+        };
+
+        let response = change_to(Status::Complete).await.into_response();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let entry: StatusChangeResponse = json(response).await;
+        assert_eq!(entry.from_status, None);
+
+        let response = change_to(Status::New).await.into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let response = change_to(Status::InProgress).await.into_response();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let entry: StatusChangeResponse = json(response).await;
+        assert_eq!(entry.from_status.as_deref(), Some("Complete"));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn history_is_empty_for_an_item_without_changes_and_404_for_a_missing_one() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let item = db.item(&category, &user, &user).await;
+
+        let response = history(State(db.state.clone()), Path(item.id.clone()), auth(&user))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let entries: Vec<StatusHistoryResponse> = json(response).await;
+        assert!(entries.is_empty());
+
+        let response = history(
+            State(db.state.clone()),
+            Path("AAA-999".to_string()),
+            auth(&user),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn reopen_takes_complete_items_back_to_in_progress_and_refuses_others() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let finished = db.item(&category, &user, &user).await;
+        let started = db.item(&category, &user, &user).await;
+
+        for (item, status) in [
+            (&finished, Status::Complete),
+            (&started, Status::InProgress),
+        ] {
+            let response = change(
+                State(db.state.clone()),
+                Path(item.id.clone()),
+                auth(&user),
+                Json(ChangeStatus {
+                    status,
+                    comment: None,
+                }),
+            )
+            .await
+            .into_response();
+            assert_eq!(response.status(), StatusCode::CREATED);
+        }
+        let reopen_item = |item_id: &str, reason: &str| {
+            reopen(
+                State(db.state.clone()),
+                Path(item_id.to_string()),
+                auth(&user),
+                Json(ReopenItem {
+                    reason: reason.to_string(),
+                }),
+            )
+        };
+
+        let response = reopen_item(&finished.id, "  ").await.into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = reopen_item(&finished.id, "Torque values were wrong")
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let entry: StatusHistoryResponse = json(response).await;
+        assert_eq!(entry.from_status.as_deref(), Some("Complete"));
+        assert_eq!(entry.status, "In Progress");
+        assert_eq!(entry.comment.as_deref(), Some("Torque values were wrong"));
+        assert_eq!(entry.changed_by_name, "Mike Fox");
+
+        let response = reopen_item(&started.id, "Needs another look")
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let error: ApiError = json(response).await;
+        assert_eq!(error.error.code, "CONFLICT");
+        let history: i64 = status_history::table
+            .filter(status_history::action_item_id.eq(&started.id))
+            .count()
+            .get_result(&mut db.conn().await)
+            .await
+            .unwrap();
+        assert_eq!(history, 1);
+    }
+}
@@ -0,0 +1,320 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use shared::{ApiError, TagName, TagResponse};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::db::schema::{item_tags, tags};
+use crate::models::{NewItemTag, NewTag, Tag};
+use crate::AppState;
+
+use super::AuthUser;
+
+/// Matches the `name` column width.
+const MAX_TAG_LENGTH: usize = 50;
+
+/// Canonical form of a tag name: trimmed and lowercased, so "Waiver" and "waiver " are
+/// the same tag.
+pub(super) fn normalize_tag(name: &str) -> Result<String, ApiError> {
+    let name = name.trim().to_lowercase();
+    if name.is_empty() || name.chars().count() > MAX_TAG_LENGTH {
+        return Err(ApiError::validation_error(format!(
+            "Tag names must be 1-{} characters",
+            MAX_TAG_LENGTH
+        )));
+    }
+    Ok(name)
+}
+
+fn to_tag_response(tag: Tag) -> TagResponse {
+    TagResponse {
+        id: tag.id,
+        name: tag.name,
+        created_at: tag.created_at,
+    }
+}
+
+/// Tag names for each of `item_ids`, alphabetical. Untagged items are absent from the map.
+pub(super) async fn tags_by_item(
+    conn: &mut AsyncPgConnection,
+    item_ids: &[&str],
+) -> QueryResult<HashMap<String, Vec<String>>> {
+    let rows: Vec<(String, String)> = item_tags::table
+        .inner_join(tags::table)
+        .filter(item_tags::action_item_id.eq_any(item_ids))
+        .order(tags::name)
+        .select((item_tags::action_item_id, tags::name))
+        .load(conn)
+        .await?;
+
+    let mut map: HashMap<String, Vec<String>> = HashMap::new();
+    for (item_id, name) in rows {
+        map.entry(item_id).or_default().push(name);
+    }
+    Ok(map)
+}
+
+async fn item_tag_names(conn: &mut AsyncPgConnection, item_id: &str) -> QueryResult<Vec<String>> {
+    Ok(tags_by_item(conn, &[item_id])
+        .await?
+        .remove(item_id)
+        .unwrap_or_default())
+}
+
+pub async fn list(State(state): State<Arc<AppState>>, _auth: AuthUser) -> impl IntoResponse {
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    match tags::table
+        .order(tags::name)
+        .select(Tag::as_select())
+        .load(&mut conn)
+        .await
+    {
+        Ok(t) => Json(t.into_iter().map(to_tag_response).collect::<Vec<_>>()).into_response(),
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::internal_error("Failed to fetch tags")),
+        )
+            .into_response(),
+    }
+}
+
+pub async fn create(
+    State(state): State<Arc<AppState>>,
+    _auth: AuthUser,
+    Json(payload): Json<TagName>,
+) -> impl IntoResponse {
+    let name = match normalize_tag(&payload.name) {
+        Ok(n) => n,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(e)).into_response(),
+    };
+
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    match diesel::insert_into(tags::table)
+        .values(&NewTag { name })
+        .returning(Tag::as_returning())
+        .get_result(&mut conn)
+        .await
+    {
+        Ok(t) => (StatusCode::CREATED, Json(to_tag_response(t))).into_response(),
+        Err(diesel::result::Error::DatabaseError(
+            diesel::result::DatabaseErrorKind::UniqueViolation,
+            _,
+        )) => (
+            StatusCode::CONFLICT,
+            Json(ApiError::conflict("Tag already exists")),
+        )
+            .into_response(),
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::internal_error("Failed to create tag")),
+        )
+            .into_response(),
+    }
+}
+
+/// Attach an existing tag to an item. Attaching a tag the item already has is a no-op.
+/// Responds with the item's tags.
+pub async fn attach(
+    State(state): State<Arc<AppState>>,
+    Path(item_id): Path<String>,
+    _auth: AuthUser,
+    Json(payload): Json<TagName>,
+) -> impl IntoResponse {
+    let name = match normalize_tag(&payload.name) {
+        Ok(n) => n,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(e)).into_response(),
+    };
+
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    if let Err(resp) = super::ensure_item_exists(&mut conn, &item_id).await {
+        return resp;
+    }
+
+    let tag_id: i32 = match tags::table
+        .filter(tags::name.eq(&name))
+        .select(tags::id)
+        .first(&mut conn)
+        .await
+    {
+        Ok(id) => id,
+        Err(diesel::NotFound) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiError::not_found(format!("Tag {} not found", name))),
+            )
+                .into_response()
+        }
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to fetch tag")),
+            )
+                .into_response()
+        }
+    };
+
+    let attached = diesel::insert_into(item_tags::table)
+        .values(&NewItemTag {
+            action_item_id: item_id.clone(),
+            tag_id,
+        })
+        .on_conflict_do_nothing()
+        .execute(&mut conn)
+        .await;
+
+    match attached {
+        Ok(_) => match item_tag_names(&mut conn, &item_id).await {
+            Ok(names) => Json(names).into_response(),
+            Err(_) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to fetch item tags")),
+            )
+                .into_response(),
+        },
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::internal_error("Failed to attach tag")),
+        )
+            .into_response(),
+    }
+}
+
+pub async fn detach(
+    State(state): State<Arc<AppState>>,
+    Path((item_id, name)): Path<(String, String)>,
+    _auth: AuthUser,
+) -> impl IntoResponse {
+    let name = match normalize_tag(&name) {
+        Ok(n) => n,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(e)).into_response(),
+    };
+
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    let tag_ids = tags::table.filter(tags::name.eq(&name)).select(tags::id);
+    let deleted = diesel::delete(
+        item_tags::table
+            .filter(item_tags::action_item_id.eq(&item_id))
+            .filter(item_tags::tag_id.eq_any(tag_ids)),
+    )
+    .execute(&mut conn)
+    .await;
+
+    match deleted {
+        Ok(0) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiError::not_found(format!(
+                "{} is not tagged {}",
+                item_id, name
+            ))),
+        )
+            .into_response(),
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::internal_error("Failed to detach tag")),
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes::items;
+    use crate::test_support::{auth, json, query, TestDb};
+    use shared::ItemListResponse;
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn tag_filter_returns_only_the_tagged_items() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let first = db.item(&category, &user, &user).await;
+        let _untagged = db.item(&category, &user, &user).await;
+        let third = db.item(&category, &user, &user).await;
+
+        let response = create(
+            State(db.state.clone()),
+            auth(&user),
+            Json(TagName {
+                name: " Flight-Critical".to_string(),
+            }),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let tag: TagResponse = json(response).await;
+        assert_eq!(tag.name, "flight-critical");
+
+        // Names are matched case-insensitively and a repeat attach is a no-op
+        for (item, name) in [
+            (&first, "flight-critical"),
+            (&third, "FLIGHT-CRITICAL"),
+            (&third, "flight-critical"),
+        ] {
+            let response = attach(
+                State(db.state.clone()),
+                Path(item.id.clone()),
+                auth(&user),
+                Json(TagName {
+                    name: name.to_string(),
+                }),
+            )
+            .await
+            .into_response();
+            assert_eq!(response.status(), StatusCode::OK);
+            let names: Vec<String> = json(response).await;
+            assert_eq!(names, ["flight-critical"]);
+        }
+
+        let params = "tag=Flight-Critical&sort_by=id&sort_dir=asc";
+        let response = items::list_all(
+            State(db.state.clone()),
+            query(params),
+            query(params),
+            auth(&user),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let list: ItemListResponse = json(response).await;
+        assert_eq!(list.total_count, 2);
+        let listed: Vec<(&str, &[String])> = list
+            .items
+            .iter()
+            .map(|i| (i.id.as_str(), i.tags.as_slice()))
+            .collect();
+        let tags = ["flight-critical".to_string()];
+        assert_eq!(
+            listed,
+            [
+                (first.id.as_str(), &tags[..]),
+                (third.id.as_str(), &tags[..])
+            ]
+        );
+    }
+}
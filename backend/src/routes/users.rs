@@ -1,22 +1,45 @@
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
-use shared::ApiError;
+use serde::Deserialize;
+use shared::{ApiError, CreateUser, UpdateUser as UpdateUserReq};
 use std::sync::Arc;
 
 use crate::db::schema::users;
-use crate::models::User;
+use crate::models::{NewUser, UpdateUser, User};
 use crate::AppState;
 
-use super::AuthUser;
+use super::{AuthUser, RequireAdmin};
 
-pub async fn list(State(state): State<Arc<AppState>>, _auth: AuthUser) -> impl IntoResponse {
+#[derive(Debug, Deserialize)]
+pub struct ListUsersParams {
+    #[serde(default)]
+    pub include_inactive: bool,
+}
+
+/// Active users by name, for owner and assignee pickers. Deactivated users are left out
+/// unless `include_inactive` is set.
+pub async fn list(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ListUsersParams>,
+    _auth: AuthUser,
+) -> impl IntoResponse {
     let mut conn = match super::get_conn(&state).await {
         Ok(c) => c,
         Err(resp) => return resp,
     };
 
-    let all_users: Vec<User> = match users::table.order(users::name.asc()).load(&mut conn).await {
+    let mut query = users::table.order(users::name.asc()).into_boxed();
+    if !params.include_inactive {
+        query = query.filter(users::active.eq(true));
+    }
+
+    let all_users: Vec<User> = match query.load(&mut conn).await {
         Ok(u) => u,
         Err(_) => {
             return (
@@ -27,16 +50,374 @@ pub async fn list(State(state): State<Arc<AppState>>, _auth: AuthUser) -> impl I
         }
     };
 
-    let result: Vec<_> = all_users
-        .into_iter()
-        .map(|u| shared::User {
-            id: u.id,
-            email: u.email,
-            name: u.name,
-            initials: u.initials,
-            created_at: u.created_at,
-        })
-        .collect();
+    let result: Vec<_> = all_users.into_iter().map(to_shared_user).collect();
 
     Json(result).into_response()
 }
+
+fn to_shared_user(u: User) -> shared::User {
+    shared::User {
+        id: u.id,
+        email: u.email,
+        name: u.name,
+        initials: u.initials,
+        created_at: u.created_at,
+        active: u.active,
+    }
+}
+
+/// First letters of the first two words of `name`, uppercased ("Ada Lovelace" -> "AL").
+pub(super) fn initials_from_name(name: &str) -> String {
+    name.split_whitespace()
+        .filter_map(|w| w.chars().next())
+        .take(2)
+        .collect::<String>()
+        .to_uppercase()
+}
+
+/// Just enough of a check to catch typos: one `@`, a non-empty local part and a dotted
+/// domain, and no whitespace.
+fn is_valid_email(email: &str) -> bool {
+    let Some((local, domain)) = email.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && !domain.contains('@')
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && !email.chars().any(char::is_whitespace)
+}
+
+/// Create a user ahead of their first login. Admins only.
+pub async fn create(
+    State(state): State<Arc<AppState>>,
+    _admin: RequireAdmin,
+    Json(payload): Json<CreateUser>,
+) -> impl IntoResponse {
+    let email = payload.email.trim().to_string();
+    if email.len() > 255 || !is_valid_email(&email) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::validation_error("Email address is not valid")),
+        )
+            .into_response();
+    }
+
+    let name = payload.name.trim().to_string();
+    if name.is_empty() || name.len() > 255 {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::validation_error("Name must be 1-255 characters")),
+        )
+            .into_response();
+    }
+
+    let initials = match payload.initials.as_deref().map(str::trim) {
+        Some(i) if !i.is_empty() => i.to_string(),
+        _ => initials_from_name(&name),
+    };
+    if initials.chars().count() > 10 {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::validation_error(
+                "Initials must be at most 10 characters",
+            )),
+        )
+            .into_response();
+    }
+
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    let new_user = NewUser {
+        email,
+        name,
+        initials: Some(initials),
+    };
+
+    let result: Result<User, _> = diesel::insert_into(users::table)
+        .values(&new_user)
+        .returning(User::as_returning())
+        .get_result(&mut conn)
+        .await;
+
+    match result {
+        Ok(u) => (StatusCode::CREATED, Json(to_shared_user(u))).into_response(),
+        Err(diesel::result::Error::DatabaseError(
+            diesel::result::DatabaseErrorKind::UniqueViolation,
+            _,
+        )) => (
+            StatusCode::CONFLICT,
+            Json(ApiError::conflict(format!(
+                "A user with email '{}' already exists",
+                new_user.email
+            ))),
+        )
+            .into_response(),
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::internal_error("Failed to create user")),
+        )
+            .into_response(),
+    }
+}
+
+/// Fix a user's name or initials. Admins only. The email can't be changed here, since it
+/// is what matches the user to their OAuth login.
+pub async fn update(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i32>,
+    _admin: RequireAdmin,
+    Json(payload): Json<UpdateUserReq>,
+) -> impl IntoResponse {
+    if payload.email.is_some() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::validation_error(
+                "A user's email can't be changed",
+            )),
+        )
+            .into_response();
+    }
+
+    let name = payload.name.as_deref().map(str::trim);
+    if name.is_some_and(|n| n.is_empty() || n.len() > 255) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::validation_error("Name must be 1-255 characters")),
+        )
+            .into_response();
+    }
+    let initials = payload.initials.as_deref().map(str::trim);
+    if initials.is_some_and(|i| i.is_empty() || i.chars().count() > 10) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::validation_error(
+                "Initials must be 1-10 characters",
+            )),
+        )
+            .into_response();
+    }
+
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    let result: Result<User, _> = if name.is_none() && initials.is_none() {
+        // Nothing to change; diesel rejects an empty changeset, so just return the user
+        users::table
+            .filter(users::id.eq(id))
+            .select(User::as_select())
+            .first(&mut conn)
+            .await
+    } else {
+        let changeset = UpdateUser {
+            name: name.map(str::to_string),
+            initials: initials.map(str::to_string),
+        };
+        diesel::update(users::table.filter(users::id.eq(id)))
+            .set(&changeset)
+            .returning(User::as_returning())
+            .get_result(&mut conn)
+            .await
+    };
+
+    match result {
+        Ok(u) => Json(to_shared_user(u)).into_response(),
+        Err(diesel::NotFound) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiError::not_found(format!("User {} not found", id))),
+        )
+            .into_response(),
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::internal_error("Failed to update user")),
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{auth, json, TestDb};
+    use axum::http::HeaderMap;
+    use shared::ActionItemResponse;
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn deactivated_user_is_hidden_but_still_named_on_old_items() {
+        let db = TestDb::new().await;
+        let staying = db.user("ada@example.org", "Ada Lovelace", Some("AL")).await;
+        let departed = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let item = db.item(&category, &departed, &departed).await;
+
+        diesel::update(users::table.filter(users::id.eq(departed.id)))
+            .set(users::active.eq(false))
+            .execute(&mut db.conn().await)
+            .await
+            .unwrap();
+
+        let listed = |include_inactive| {
+            list(
+                State(db.state.clone()),
+                Query(ListUsersParams { include_inactive }),
+                auth(&staying),
+            )
+        };
+        let active: Vec<shared::User> = json(listed(false).await.into_response()).await;
+        let names: Vec<&str> = active.iter().map(|u| u.name.as_str()).collect();
+        assert_eq!(names, ["Ada Lovelace"]);
+        let everyone: Vec<shared::User> = json(listed(true).await.into_response()).await;
+        let names: Vec<&str> = everyone.iter().map(|u| u.name.as_str()).collect();
+        assert_eq!(names, ["Ada Lovelace", "Mike Fox"]);
+
+        let response = super::super::items::get(
+            State(db.state.clone()),
+            Path(item.id.clone()),
+            auth(&staying),
+            HeaderMap::new(),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let item: ActionItemResponse = json(response).await;
+        assert_eq!(item.created_by_name, "Mike Fox");
+        assert_eq!(item.owner_name, "Mike Fox");
+        assert_eq!(item.owner_initials.as_deref(), Some("MF"));
+    }
+
+    #[test]
+    fn initials_come_from_the_first_two_words() {
+        assert_eq!(initials_from_name("Ada Lovelace"), "AL");
+        assert_eq!(initials_from_name("  jean luc   picard "), "JL");
+        assert_eq!(initials_from_name("Cher"), "C");
+    }
+
+    #[test]
+    fn email_check_catches_typos() {
+        for ok in ["ada@example.org", "a.b+tag@mail.example.co.uk"] {
+            assert!(is_valid_email(ok), "{}", ok);
+        }
+        for bad in [
+            "ada.example.org",
+            "@example.org",
+            "ada@localhost",
+            "ada@@example.org",
+            "ada@.example.org",
+            "ada@example.org.",
+            "ada lovelace@example.org",
+        ] {
+            assert!(!is_valid_email(bad), "{}", bad);
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn create_derives_initials_and_rejects_duplicates() {
+        let db = TestDb::new().await;
+
+        let create_user = |email: &str, name: &str, initials: Option<&str>| {
+            create(
+                State(db.state.clone()),
+                RequireAdmin,
+                Json(CreateUser {
+                    email: email.to_string(),
+                    name: name.to_string(),
+                    initials: initials.map(str::to_string),
+                }),
+            )
+        };
+
+        let response = create_user(" ada@example.org ", "Ada Lovelace", Some(" "))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let ada: shared::User = json(response).await;
+        assert_eq!(ada.email, "ada@example.org");
+        assert_eq!(ada.initials.as_deref(), Some("AL"));
+        assert!(ada.active);
+
+        let response = create_user("mf@example.org", "Mike Fox", Some("MFX"))
+            .await
+            .into_response();
+        let mike: shared::User = json(response).await;
+        assert_eq!(mike.initials.as_deref(), Some("MFX"));
+
+        for (email, name, status) in [
+            ("ada@example.org", "Ada Again", StatusCode::CONFLICT),
+            ("nope@localhost", "Nope", StatusCode::BAD_REQUEST),
+            ("blank@example.org", "  ", StatusCode::BAD_REQUEST),
+        ] {
+            let response = create_user(email, name, None).await.into_response();
+            assert_eq!(response.status(), status, "{}", email);
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn update_edits_name_and_initials_but_never_the_email() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+
+        let update_user = |id, body: serde_json::Value| {
+            update(
+                State(db.state.clone()),
+                Path(id),
+                RequireAdmin,
+                Json(serde_json::from_value(body).unwrap()),
+            )
+        };
+
+        let response = update_user(user.id, serde_json::json!({"initials": " MJF "}))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let updated: shared::User = json(response).await;
+        assert_eq!(updated.initials.as_deref(), Some("MJF"));
+        assert_eq!(updated.name, "Mike Fox");
+
+        let response = update_user(user.id, serde_json::json!({}))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let unchanged: shared::User = json(response).await;
+        assert_eq!(unchanged, updated);
+
+        for (id, body, status) in [
+            (
+                user.id,
+                serde_json::json!({"email": "mike@example.org"}),
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                user.id,
+                serde_json::json!({"initials": ""}),
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                user.id + 1,
+                serde_json::json!({"name": "Nobody"}),
+                StatusCode::NOT_FOUND,
+            ),
+        ] {
+            let response = update_user(id, body.clone()).await.into_response();
+            assert_eq!(response.status(), status, "{}", body);
+        }
+
+        let stored: User = users::table
+            .filter(users::id.eq(user.id))
+            .select(User::as_select())
+            .first(&mut db.conn().await)
+            .await
+            .unwrap();
+        assert_eq!(stored.email, "mf@example.org");
+        assert_eq!(stored.initials.as_deref(), Some("MJF"));
+    }
+}
@@ -1,20 +1,25 @@
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{header, HeaderMap, StatusCode},
     response::IntoResponse,
     Json,
 };
+use chrono::Utc;
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
 use serde::Deserialize;
-use shared::{ApiError, CreateVendor, UpdateVendor as UpdateVendorReq, VendorWithCounts};
+use shared::{
+    ApiError, CreateVendor, Priority, PriorityCount, UpdateVendor as UpdateVendorReq, VendorStats,
+    VendorWithCounts,
+};
+use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::db::schema::{action_items, vendors};
+use crate::db::schema::vendors;
 use crate::models::{NewVendor, UpdateVendor, Vendor};
 use crate::AppState;
 
-use super::AuthUser;
+use super::{AuthUser, RequireAdmin};
 
 fn to_shared_vendor(v: &Vendor) -> shared::Vendor {
     shared::Vendor {
@@ -25,9 +30,92 @@ fn to_shared_vendor(v: &Vendor) -> shared::Vendor {
         next_number: v.next_number,
         created_at: v.created_at,
         archived: v.archived,
+        number_padding: v.number_padding,
     }
 }
 
+#[derive(Debug, QueryableByName)]
+struct VendorCountsRow {
+    #[diesel(sql_type = diesel::sql_types::Int4)]
+    vendor_id: i32,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    total_items: i64,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    open_items: i64,
+}
+
+/// Total and open item counts for every vendor in one query. An item is open unless its
+/// latest status is Complete; items with no history are "New" and therefore open.
+const VENDOR_COUNTS_SQL: &str = r#"
+    SELECT
+        ai.vendor_id,
+        COUNT(*) AS total_items,
+        COUNT(*) FILTER (WHERE latest.status IS DISTINCT FROM 'Complete') AS open_items
+    FROM action_items ai
+    LEFT JOIN LATERAL (
+        SELECT sh.status
+        FROM status_history sh
+        WHERE sh.action_item_id = ai.id
+        ORDER BY sh.changed_at DESC, sh.id DESC
+        LIMIT 1
+    ) latest ON true
+    WHERE ai.deleted_at IS NULL
+    GROUP BY ai.vendor_id
+"#;
+
+#[derive(Debug, QueryableByName)]
+struct VendorStatsRow {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    kind: String,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    group_key: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    count: i64,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Double>)]
+    avg_days_to_complete: Option<f64>,
+}
+
+/// One vendor's item counts per current status (`kind = 'status'`), per priority
+/// (`'priority'`) and overall (`'total'`, which also carries the average days from first
+/// status to first Complete; AVG skips items never completed), in one grouped query.
+const VENDOR_STATS_SQL: &str = r#"
+    WITH item_stats AS (
+        SELECT
+            ai.priority,
+            COALESCE(latest.status, 'New') AS status,
+            EXTRACT(EPOCH FROM hist.first_complete - hist.first_status)::float8 / 86400
+                AS days_to_complete
+        FROM action_items ai
+        LEFT JOIN LATERAL (
+            SELECT sh.status
+            FROM status_history sh
+            WHERE sh.action_item_id = ai.id
+            ORDER BY sh.changed_at DESC, sh.id DESC
+            LIMIT 1
+        ) latest ON true
+        LEFT JOIN LATERAL (
+            SELECT
+                MIN(sh.changed_at) AS first_status,
+                MIN(sh.changed_at) FILTER (WHERE sh.status = 'Complete') AS first_complete
+            FROM status_history sh
+            WHERE sh.action_item_id = ai.id
+        ) hist ON true
+        WHERE ai.vendor_id = $1
+          AND ai.deleted_at IS NULL
+    )
+    SELECT
+        CASE
+            WHEN GROUPING(status) = 0 THEN 'status'
+            WHEN GROUPING(priority) = 0 THEN 'priority'
+            ELSE 'total'
+        END AS kind,
+        COALESCE(status, priority) AS group_key,
+        COUNT(*) AS count,
+        AVG(days_to_complete) AS avg_days_to_complete
+    FROM item_stats
+    GROUP BY GROUPING SETS ((status), (priority), ())
+"#;
+
 #[derive(Deserialize)]
 pub struct ListVendorsParams {
     #[serde(default)]
@@ -61,27 +149,35 @@ pub async fn list(
         }
     };
 
-    // Build response with counts
-    let mut result = Vec::new();
-    for vendor in &all_vendors {
-        // Get total items count
-        let total: i64 = action_items::table
-            .filter(action_items::vendor_id.eq(vendor.id))
-            .count()
-            .get_result(&mut conn)
-            .await
-            .unwrap_or(0);
-
-        // Get open items count - simplified for now
-        let open = total; // TODO: Implement proper status filtering
+    let counts: HashMap<i32, VendorCountsRow> = match diesel::sql_query(VENDOR_COUNTS_SQL)
+        .load::<VendorCountsRow>(&mut conn)
+        .await
+    {
+        Ok(rows) => rows.into_iter().map(|r| (r.vendor_id, r)).collect(),
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to count vendor items")),
+            )
+                .into_response()
+        }
+    };
 
-        result.push(VendorWithCounts {
-            vendor: to_shared_vendor(vendor),
-            open_items: open,
-            total_items: total,
-            last_updated: None,
-        });
-    }
+    let result: Vec<_> = all_vendors
+        .iter()
+        .map(|vendor| {
+            let (total, open) = counts
+                .get(&vendor.id)
+                .map(|c| (c.total_items, c.open_items))
+                .unwrap_or((0, 0));
+            VendorWithCounts {
+                vendor: to_shared_vendor(vendor),
+                open_items: open,
+                total_items: total,
+                last_updated: None,
+            }
+        })
+        .collect();
 
     Json(result).into_response()
 }
@@ -102,7 +198,11 @@ pub async fn get(
         .await;
 
     match vendor {
-        Ok(v) => Json(to_shared_vendor(&v)).into_response(),
+        Ok(v) => (
+            [(header::ETAG, super::etag_for(v.updated_at))],
+            Json(to_shared_vendor(&v)),
+        )
+            .into_response(),
         Err(diesel::NotFound) => (
             StatusCode::NOT_FOUND,
             Json(ApiError::not_found(format!("Vendor {} not found", id))),
@@ -116,9 +216,104 @@ pub async fn get(
     }
 }
 
-pub async fn create(
+/// Item counts by status and priority, and how long items take to complete, for a
+/// summary bar.
+pub async fn stats(
     State(state): State<Arc<AppState>>,
+    Path(id): Path<i32>,
     _auth: AuthUser,
+) -> impl IntoResponse {
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    match vendors::table
+        .filter(vendors::id.eq(id))
+        .select(vendors::id)
+        .first::<i32>(&mut conn)
+        .await
+    {
+        Ok(_) => {}
+        Err(diesel::NotFound) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiError::not_found(format!("Vendor {} not found", id))),
+            )
+                .into_response()
+        }
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to fetch vendor")),
+            )
+                .into_response()
+        }
+    }
+
+    let rows: Vec<VendorStatsRow> = match diesel::sql_query(VENDOR_STATS_SQL)
+        .bind::<diesel::sql_types::Int4, _>(id)
+        .load(&mut conn)
+        .await
+    {
+        Ok(r) => r,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to compute vendor stats")),
+            )
+                .into_response()
+        }
+    };
+
+    let mut status_counts = HashMap::new();
+    let mut priority_counts: HashMap<String, i64> = HashMap::new();
+    let mut total_items = 0;
+    let mut avg_days_to_complete = None;
+    for row in rows {
+        match (row.kind.as_str(), row.group_key) {
+            ("status", Some(status)) => {
+                status_counts.insert(status, row.count);
+            }
+            ("priority", Some(priority)) => {
+                priority_counts.insert(priority, row.count);
+            }
+            _ => {
+                total_items = row.count;
+                avg_days_to_complete = row.avg_days_to_complete;
+            }
+        }
+    }
+
+    let mut priorities: Vec<PriorityCount> = Priority::all()
+        .iter()
+        .map(|p| PriorityCount {
+            priority: p.as_str().to_string(),
+            count: priority_counts.remove(p.as_str()).unwrap_or(0),
+        })
+        .collect();
+    // Priorities outside High/Medium/Low (e.g. from old imports) still count
+    let mut others: Vec<PriorityCount> = priority_counts
+        .into_iter()
+        .map(|(priority, count)| PriorityCount { priority, count })
+        .collect();
+    others.sort_by(|a, b| a.priority.cmp(&b.priority));
+    priorities.extend(others);
+
+    Json(VendorStats {
+        vendor_id: id,
+        total_items,
+        status_counts: super::dashboard::status_counts_in_order(status_counts),
+        priority_counts: priorities,
+        avg_days_to_complete,
+    })
+    .into_response()
+}
+
+/// Admins only.
+pub async fn create(
+    State(state): State<Arc<AppState>>,
+    _admin: RequireAdmin,
     Json(payload): Json<CreateVendor>,
 ) -> impl IntoResponse {
     // Validate prefix
@@ -148,6 +343,20 @@ pub async fn create(
             .into_response();
     }
 
+    let number_padding = payload
+        .number_padding
+        .unwrap_or(shared::DEFAULT_NUMBER_PADDING);
+    if !(1..=shared::MAX_NUMBER_PADDING).contains(&number_padding) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::validation_error(format!(
+                "number_padding must be 1-{}",
+                shared::MAX_NUMBER_PADDING
+            ))),
+        )
+            .into_response();
+    }
+
     let mut conn = match super::get_conn(&state).await {
         Ok(c) => c,
         Err(resp) => return resp,
@@ -157,6 +366,7 @@ pub async fn create(
         prefix: payload.prefix.clone(),
         name: payload.name,
         description: payload.description,
+        number_padding,
     };
 
     let result: Result<Vendor, _> = diesel::insert_into(vendors::table)
@@ -186,10 +396,12 @@ pub async fn create(
     }
 }
 
+/// Rename, describe or (un)archive a vendor. Admins only.
 pub async fn update(
     State(state): State<Arc<AppState>>,
     Path(id): Path<i32>,
-    _auth: AuthUser,
+    _admin: RequireAdmin,
+    headers: HeaderMap,
     Json(payload): Json<UpdateVendorReq>,
 ) -> impl IntoResponse {
     // Validate name if provided
@@ -203,6 +415,11 @@ pub async fn update(
         }
     }
 
+    let expected_version = match super::if_match_version(&headers) {
+        Ok(v) => v,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(e)).into_response(),
+    };
+
     let mut conn = match super::get_conn(&state).await {
         Ok(c) => c,
         Err(resp) => return resp,
@@ -212,16 +429,53 @@ pub async fn update(
         name: payload.name,
         description: payload.description,
         archived: payload.archived,
+        updated_at: Some(Utc::now()),
     };
 
-    let result: Result<Vendor, _> = diesel::update(vendors::table.filter(vendors::id.eq(id)))
+    let mut update = diesel::update(vendors::table.filter(vendors::id.eq(id)))
         .set(&changeset)
+        .into_boxed();
+    if let Some(version) = expected_version {
+        update = update.filter(vendors::updated_at.eq(version));
+    }
+
+    let result: Result<Vendor, _> = update
         .returning(Vendor::as_returning())
         .get_result(&mut conn)
         .await;
 
     match result {
-        Ok(v) => Json(to_shared_vendor(&v)).into_response(),
+        Ok(v) => (
+            [(header::ETAG, super::etag_for(v.updated_at))],
+            Json(to_shared_vendor(&v)),
+        )
+            .into_response(),
+        // With If-Match, no row means either the vendor is gone or it changed underneath us.
+        Err(diesel::NotFound) if expected_version.is_some() => {
+            let exists = diesel::select(diesel::dsl::exists(
+                vendors::table.filter(vendors::id.eq(id)),
+            ))
+            .get_result::<bool>(&mut conn)
+            .await
+            .unwrap_or(false);
+
+            if exists {
+                (
+                    StatusCode::CONFLICT,
+                    Json(ApiError::conflict(format!(
+                        "Vendor {} was modified by someone else; reload and try again",
+                        id
+                    ))),
+                )
+                    .into_response()
+            } else {
+                (
+                    StatusCode::NOT_FOUND,
+                    Json(ApiError::not_found(format!("Vendor {} not found", id))),
+                )
+                    .into_response()
+            }
+        }
         Err(diesel::NotFound) => (
             StatusCode::NOT_FOUND,
             Json(ApiError::not_found(format!("Vendor {} not found", id))),
@@ -234,3 +488,193 @@ pub async fn update(
             .into_response(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::{action_items, status_history};
+    use crate::test_support::{auth, json, TestDb};
+    use chrono::{Duration, TimeZone};
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn stats_count_current_statuses_and_average_completion_time() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let other_vendor = db.vendor("BBB").await;
+        let category = db.category(&vendor, "Design").await;
+        let other_category = db.category(&other_vendor, "Design").await;
+        let opened = Utc.with_ymd_and_hms(2026, 2, 1, 9, 0, 0).unwrap();
+
+        let mut conn = db.conn().await;
+        let mut history = |item_id: String, entries: Vec<(&'static str, i64)>| {
+            let rows: Vec<_> = entries
+                .into_iter()
+                .map(|(status, hours)| {
+                    (
+                        status_history::action_item_id.eq(item_id.clone()),
+                        status_history::status.eq(status),
+                        status_history::changed_by_id.eq(user.id),
+                        status_history::changed_at.eq(opened + Duration::hours(hours)),
+                    )
+                })
+                .collect();
+            diesel::insert_into(status_history::table)
+                .values(rows)
+                .execute(&mut conn)
+        };
+
+        let slow = db.item(&category, &user, &user).await;
+        history(slow.id, vec![("New", 0), ("Complete", 15 * 24)])
+            .await
+            .unwrap();
+        // Reopened and completed again; only the first completion counts
+        let quick = db.item(&category, &user, &user).await;
+        history(
+            quick.id,
+            vec![
+                ("New", 0),
+                ("Complete", 3 * 24),
+                ("In Progress", 4 * 24),
+                ("Complete", 40 * 24),
+            ],
+        )
+        .await
+        .unwrap();
+        let open = db.item(&category, &user, &user).await;
+        history(open.id, vec![("New", 0)]).await.unwrap();
+        let deleted = db.item(&category, &user, &user).await;
+        history(deleted.id.clone(), vec![("New", 0), ("Complete", 100 * 24)])
+            .await
+            .unwrap();
+        let elsewhere = db.item(&other_category, &user, &user).await;
+        history(elsewhere.id, vec![("New", 0), ("Complete", 100 * 24)])
+            .await
+            .unwrap();
+        diesel::update(action_items::table.filter(action_items::id.eq(&deleted.id)))
+            .set(action_items::deleted_at.eq(Some(Utc::now())))
+            .execute(&mut db.conn().await)
+            .await
+            .unwrap();
+
+        let response = stats(State(db.state.clone()), Path(vendor.id), auth(&user))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let summary: VendorStats = json(response).await;
+        assert_eq!(summary.total_items, 3);
+        let counts = |status: &str| {
+            summary
+                .status_counts
+                .iter()
+                .find(|c| c.status == status)
+                .map(|c| c.count)
+        };
+        assert_eq!(counts("Complete"), Some(2));
+        assert_eq!(counts("New"), Some(1));
+        assert_eq!(counts("In Progress"), Some(0));
+        let priorities: Vec<(&str, i64)> = summary
+            .priority_counts
+            .iter()
+            .map(|p| (p.priority.as_str(), p.count))
+            .collect();
+        assert_eq!(priorities, [("High", 0), ("Medium", 3), ("Low", 0)]);
+        assert_eq!(summary.avg_days_to_complete, Some(9.0));
+
+        let response = stats(State(db.state.clone()), Path(999), auth(&user))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn update_with_a_stale_if_match_gets_409() {
+        let db = TestDb::new().await;
+        let vendor = db.vendor("AAA").await;
+
+        let rename = |name: &str, if_match: Option<String>| {
+            let mut headers = HeaderMap::new();
+            if let Some(tag) = if_match {
+                headers.insert(header::IF_MATCH, tag.parse().unwrap());
+            }
+            update(
+                State(db.state.clone()),
+                Path(vendor.id),
+                RequireAdmin,
+                headers,
+                Json(UpdateVendorReq {
+                    name: Some(name.to_string()),
+                    description: None,
+                    archived: None,
+                }),
+            )
+        };
+
+        let original = crate::routes::etag_for(vendor.updated_at);
+        let response = rename("Acme", Some(original.clone())).await.into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = rename("Acme Corp", Some(original)).await.into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let error: ApiError = json(response).await;
+        assert_eq!(error.error.code, "CONFLICT");
+        assert_eq!(
+            rename("Acme Corp", None).await.into_response().status(),
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn list_counts_items_not_currently_complete_as_open() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        db.vendor("BBB").await;
+        let category = db.category(&vendor, "Design").await;
+        let at = Utc.with_ymd_and_hms(2026, 2, 1, 9, 0, 0).unwrap();
+        let mut conn = db.conn().await;
+
+        for statuses in [
+            vec!["Complete"],
+            vec!["In Progress"],
+            // Reopened, so open again
+            vec!["Complete", "In Progress"],
+            vec!["In Progress", "Complete"],
+            // No history at all: New
+            vec![],
+        ] {
+            let item = db.item(&category, &user, &user).await;
+            for (hours, status) in statuses.into_iter().enumerate() {
+                diesel::insert_into(status_history::table)
+                    .values((
+                        status_history::action_item_id.eq(&item.id),
+                        status_history::status.eq(status),
+                        status_history::changed_by_id.eq(user.id),
+                        status_history::changed_at.eq(at + Duration::hours(hours as i64)),
+                    ))
+                    .execute(&mut conn)
+                    .await
+                    .unwrap();
+            }
+        }
+
+        let response = list(
+            State(db.state.clone()),
+            Query(ListVendorsParams {
+                include_archived: false,
+            }),
+            auth(&user),
+        )
+        .await
+        .into_response();
+        let vendors: Vec<VendorWithCounts> = json(response).await;
+        let counts: Vec<(&str, i64, i64)> = vendors
+            .iter()
+            .map(|v| (v.vendor.prefix.as_str(), v.open_items, v.total_items))
+            .collect();
+        assert_eq!(counts, [("AAA", 3, 5), ("BBB", 0, 0)]);
+    }
+}
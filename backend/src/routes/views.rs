@@ -0,0 +1,246 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use shared::{ApiError, CreateSavedView};
+use std::sync::Arc;
+
+use crate::db::schema::saved_views;
+use crate::models::{NewSavedView, SavedView};
+use crate::AppState;
+
+use super::items::{ItemsQuery, ITEMS_QUERY_PARAMS};
+use super::AuthUser;
+
+/// A saved filter must be an object of known `ItemsQuery` fields that parses as one, so
+/// applying it later can't fail on a bad value.
+fn validate_filter(filter: &serde_json::Value) -> Result<(), ApiError> {
+    let Some(fields) = filter.as_object() else {
+        return Err(ApiError::validation_error(
+            "filter must be an object of item list query parameters",
+        ));
+    };
+
+    let mut unknown: Vec<&str> = fields
+        .keys()
+        .map(String::as_str)
+        .filter(|key| !ITEMS_QUERY_PARAMS.contains(key))
+        .collect();
+    if !unknown.is_empty() {
+        unknown.sort_unstable();
+        return Err(ApiError::validation_error(format!(
+            "Unknown filter field(s): {}. Accepted: {}",
+            unknown.join(", "),
+            ITEMS_QUERY_PARAMS.join(", ")
+        )));
+    }
+
+    serde_json::from_value::<ItemsQuery>(filter.clone())
+        .map(|_| ())
+        .map_err(|e| ApiError::validation_error(format!("Invalid filter: {}", e)))
+}
+
+fn to_view_response(view: SavedView) -> shared::SavedView {
+    shared::SavedView {
+        id: view.id,
+        name: view.name,
+        // Only validated JSON is ever stored
+        filter: serde_json::from_str(&view.filter_json).unwrap_or_default(),
+        created_at: view.created_at,
+    }
+}
+
+/// The current user's saved views, by name.
+pub async fn list(State(state): State<Arc<AppState>>, auth: AuthUser) -> impl IntoResponse {
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    let views: Vec<SavedView> = match saved_views::table
+        .filter(saved_views::user_id.eq(auth.user_id))
+        .order(saved_views::name.asc())
+        .select(SavedView::as_select())
+        .load(&mut conn)
+        .await
+    {
+        Ok(v) => v,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to fetch saved views")),
+            )
+                .into_response()
+        }
+    };
+
+    let result: Vec<_> = views.into_iter().map(to_view_response).collect();
+
+    Json(result).into_response()
+}
+
+pub async fn create(
+    State(state): State<Arc<AppState>>,
+    auth: AuthUser,
+    Json(payload): Json<CreateSavedView>,
+) -> impl IntoResponse {
+    let name = payload.name.trim();
+    if name.is_empty() || name.chars().count() > 100 {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::validation_error("Name must be 1-100 characters")),
+        )
+            .into_response();
+    }
+    if let Err(e) = validate_filter(&payload.filter) {
+        return (StatusCode::BAD_REQUEST, Json(e)).into_response();
+    }
+
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    let new_view = NewSavedView {
+        user_id: auth.user_id,
+        name: name.to_string(),
+        filter_json: payload.filter.to_string(),
+    };
+
+    match diesel::insert_into(saved_views::table)
+        .values(&new_view)
+        .returning(SavedView::as_returning())
+        .get_result(&mut conn)
+        .await
+    {
+        Ok(view) => (StatusCode::CREATED, Json(to_view_response(view))).into_response(),
+        Err(diesel::result::Error::DatabaseError(
+            diesel::result::DatabaseErrorKind::UniqueViolation,
+            _,
+        )) => (
+            StatusCode::CONFLICT,
+            Json(ApiError::conflict(format!(
+                "You already have a view named \"{}\"",
+                name
+            ))),
+        )
+            .into_response(),
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::internal_error("Failed to save view")),
+        )
+            .into_response(),
+    }
+}
+
+/// Delete one of the current user's views; other users' views are reported as missing.
+pub async fn delete(
+    State(state): State<Arc<AppState>>,
+    Path(view_id): Path<i32>,
+    auth: AuthUser,
+) -> impl IntoResponse {
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    match diesel::delete(
+        saved_views::table
+            .filter(saved_views::id.eq(view_id))
+            .filter(saved_views::user_id.eq(auth.user_id)),
+    )
+    .execute(&mut conn)
+    .await
+    {
+        Ok(0) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiError::not_found(format!(
+                "Saved view {} not found",
+                view_id
+            ))),
+        )
+            .into_response(),
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::internal_error("Failed to delete view")),
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{auth, json, TestDb};
+    use serde_json::json;
+
+    #[test]
+    fn filter_must_be_a_valid_items_query_object() {
+        assert!(validate_filter(&json!({})).is_ok());
+        assert!(validate_filter(&json!({"vendor_id": 1, "status": "In Progress"})).is_ok());
+
+        let error = validate_filter(&json!({"vendor": 1})).unwrap_err();
+        assert!(error
+            .error
+            .message
+            .starts_with("Unknown filter field(s): vendor. Accepted:"));
+        let error = validate_filter(&json!({"vendor_id": "one"})).unwrap_err();
+        assert!(error.error.message.starts_with("Invalid filter:"));
+        assert!(validate_filter(&json!(["vendor_id", 1])).is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn views_are_per_user() {
+        let db = TestDb::new().await;
+        let ada = db.user("ada@example.org", "Ada Lovelace", Some("AL")).await;
+        let mike = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+
+        let save = |user, name: &str| {
+            create(
+                State(db.state.clone()),
+                auth(user),
+                Json(CreateSavedView {
+                    name: name.to_string(),
+                    filter: json!({"status": "Blocked"}),
+                }),
+            )
+        };
+        let response = save(&ada, " Blocked ").await.into_response();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let view: shared::SavedView = json(response).await;
+        assert_eq!(view.name, "Blocked");
+        assert_eq!(view.filter, json!({"status": "Blocked"}));
+
+        assert_eq!(
+            save(&ada, "Blocked").await.into_response().status(),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            save(&mike, "Blocked").await.into_response().status(),
+            StatusCode::CREATED
+        );
+
+        let response = list(State(db.state.clone()), auth(&ada))
+            .await
+            .into_response();
+        let views: Vec<shared::SavedView> = json(response).await;
+        let ids: Vec<i32> = views.iter().map(|v| v.id).collect();
+        assert_eq!(ids, [view.id]);
+
+        let delete_as = |user| delete(State(db.state.clone()), Path(view.id), auth(user));
+        assert_eq!(
+            delete_as(&mike).await.into_response().status(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            delete_as(&ada).await.into_response().status(),
+            StatusCode::NO_CONTENT
+        );
+    }
+}
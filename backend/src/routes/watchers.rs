@@ -0,0 +1,219 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use shared::ApiError;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::db::schema::{action_items, item_watchers};
+use crate::models::{ActionItem, NewItemWatcher};
+use crate::AppState;
+
+use super::AuthUser;
+
+/// Number of watchers for each of `item_ids`. Items nobody watches are absent from the map.
+pub(super) async fn watcher_counts(
+    conn: &mut AsyncPgConnection,
+    item_ids: &[&str],
+) -> QueryResult<HashMap<String, i64>> {
+    let counts: Vec<(String, i64)> = item_watchers::table
+        .filter(item_watchers::action_item_id.eq_any(item_ids))
+        .group_by(item_watchers::action_item_id)
+        .select((item_watchers::action_item_id, diesel::dsl::count_star()))
+        .load(conn)
+        .await?;
+    Ok(counts.into_iter().collect())
+}
+
+/// Watcher count of a single item; 0 if the lookup fails.
+pub(super) async fn watcher_count(conn: &mut AsyncPgConnection, item_id: &str) -> i64 {
+    item_watchers::table
+        .filter(item_watchers::action_item_id.eq(item_id))
+        .count()
+        .get_result(conn)
+        .await
+        .unwrap_or(0)
+}
+
+/// Whether `user_id` watches the item; `false` if the lookup fails.
+pub(super) async fn is_watching(conn: &mut AsyncPgConnection, item_id: &str, user_id: i32) -> bool {
+    diesel::select(diesel::dsl::exists(
+        item_watchers::table
+            .filter(item_watchers::action_item_id.eq(item_id))
+            .filter(item_watchers::user_id.eq(user_id)),
+    ))
+    .get_result(conn)
+    .await
+    .unwrap_or(false)
+}
+
+pub async fn watch(
+    State(state): State<Arc<AppState>>,
+    Path(item_id): Path<String>,
+    auth: AuthUser,
+) -> impl IntoResponse {
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    if let Err(resp) = super::ensure_item_exists(&mut conn, &item_id).await {
+        return resp;
+    }
+
+    let new_watcher = NewItemWatcher {
+        action_item_id: item_id,
+        user_id: auth.user_id,
+    };
+
+    match diesel::insert_into(item_watchers::table)
+        .values(&new_watcher)
+        .execute(&mut conn)
+        .await
+    {
+        Ok(_) => StatusCode::CREATED.into_response(),
+        Err(diesel::result::Error::DatabaseError(
+            diesel::result::DatabaseErrorKind::UniqueViolation,
+            _,
+        )) => (
+            StatusCode::CONFLICT,
+            Json(ApiError::conflict("You are already watching this item")),
+        )
+            .into_response(),
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::internal_error("Failed to watch item")),
+        )
+            .into_response(),
+    }
+}
+
+pub async fn unwatch(
+    State(state): State<Arc<AppState>>,
+    Path(item_id): Path<String>,
+    auth: AuthUser,
+) -> impl IntoResponse {
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    let deleted = diesel::delete(
+        item_watchers::table
+            .filter(item_watchers::action_item_id.eq(&item_id))
+            .filter(item_watchers::user_id.eq(auth.user_id)),
+    )
+    .execute(&mut conn)
+    .await;
+
+    match deleted {
+        Ok(0) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiError::not_found(format!(
+                "You are not watching {}",
+                item_id
+            ))),
+        )
+            .into_response(),
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::internal_error("Failed to unwatch item")),
+        )
+            .into_response(),
+    }
+}
+
+/// Items the current user watches, most recently watched first.
+pub async fn watched(State(state): State<Arc<AppState>>, auth: AuthUser) -> impl IntoResponse {
+    let mut conn = match super::get_conn(&state).await {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+
+    let items: Vec<ActionItem> = match action_items::table
+        .inner_join(item_watchers::table)
+        .filter(item_watchers::user_id.eq(auth.user_id))
+        .filter(action_items::deleted_at.is_null())
+        .order(item_watchers::created_at.desc())
+        .select(ActionItem::as_select())
+        .load(&mut conn)
+        .await
+    {
+        Ok(i) => i,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::internal_error("Failed to fetch watched items")),
+            )
+                .into_response()
+        }
+    };
+
+    match super::items::build_item_responses(&mut conn, items).await {
+        Ok(items) => Json(items).into_response(),
+        Err(resp) => resp,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{auth, json, TestDb};
+    use axum::http::HeaderMap;
+    use shared::ActionItemResponse;
+
+    async fn get_as(db: &TestDb, item_id: &str, user: &crate::models::User) -> ActionItemResponse {
+        let response = super::super::items::get(
+            State(db.state.clone()),
+            Path(item_id.to_string()),
+            auth(user),
+            HeaderMap::new(),
+        )
+        .await
+        .into_response();
+        json(response).await
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn item_reports_whether_the_requesting_user_watches_it() {
+        let db = TestDb::new().await;
+        let watcher = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let other = db.user("ada@example.org", "Ada Lovelace", None).await;
+        let vendor = db.vendor("AAA").await;
+        let category = db.category(&vendor, "Design").await;
+        let item = db.item(&category, &other, &other).await;
+
+        let watch_as = |user| watch(State(db.state.clone()), Path(item.id.clone()), auth(user));
+        assert_eq!(
+            watch_as(&watcher).await.into_response().status(),
+            StatusCode::CREATED
+        );
+        assert_eq!(
+            watch_as(&watcher).await.into_response().status(),
+            StatusCode::CONFLICT
+        );
+
+        let seen = get_as(&db, &item.id, &watcher).await;
+        assert_eq!((seen.watching, seen.watcher_count), (Some(true), 1));
+        let seen = get_as(&db, &item.id, &other).await;
+        assert_eq!((seen.watching, seen.watcher_count), (Some(false), 1));
+
+        let response = unwatch(
+            State(db.state.clone()),
+            Path(item.id.clone()),
+            auth(&watcher),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let seen = get_as(&db, &item.id, &watcher).await;
+        assert_eq!((seen.watching, seen.watcher_count), (Some(false), 0));
+    }
+}
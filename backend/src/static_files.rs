@@ -1,9 +1,15 @@
 use axum::{
     body::Body,
+    extract::State,
     http::{header, Response, StatusCode, Uri},
     response::IntoResponse,
+    Json,
 };
 use rust_embed::RustEmbed;
+use shared::ApiError;
+use std::sync::Arc;
+
+use crate::AppState;
 
 #[derive(RustEmbed)]
 #[folder = "../frontend/dist/"]
@@ -17,11 +23,35 @@ pub fn verify_assets_embedded() {
     );
 }
 
-pub async fn static_handler(uri: Uri) -> impl IntoResponse {
+/// Point the SPA's `<base href>` at the deployment's base path so the router and
+/// API calls resolve under it.
+fn with_base_href(index_html: &[u8], base_path: &str) -> Vec<u8> {
+    let html = String::from_utf8_lossy(index_html);
+    html.replacen(
+        r#"<base href="/""#,
+        &format!(r#"<base href="{}/""#, base_path),
+        1,
+    )
+    .into_bytes()
+}
+
+pub async fn static_handler(State(state): State<Arc<AppState>>, uri: Uri) -> impl IntoResponse {
     let path = uri.path().trim_start_matches('/');
 
-    // Try to serve the exact file
-    if let Some(content) = Assets::get(path) {
+    // Unknown API routes get a JSON 404 rather than the SPA shell
+    if path == "api" || path.starts_with("api/") {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ApiError::not_found(format!(
+                "No API route for {}",
+                uri.path()
+            ))),
+        )
+            .into_response();
+    }
+
+    // Try to serve the exact file (index.html always goes through the base-href rewrite below)
+    if let Some(content) = Assets::get(path).filter(|_| path != "index.html") {
         let mime = mime_guess::from_path(path).first_or_octet_stream();
         return Response::builder()
             .status(StatusCode::OK)
@@ -35,7 +65,10 @@ pub async fn static_handler(uri: Uri) -> impl IntoResponse {
         return Response::builder()
             .status(StatusCode::OK)
             .header(header::CONTENT_TYPE, "text/html")
-            .body(Body::from(content.data.into_owned()))
+            .body(Body::from(with_base_href(
+                &content.data,
+                &state.config.base_path,
+            )))
             .unwrap();
     }
 
@@ -44,3 +77,29 @@ pub async fn static_handler(uri: Uri) -> impl IntoResponse {
         .body(Body::from("Not Found"))
         .unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{json, offline_state, test_config};
+
+    async fn get(path: &str) -> Response<Body> {
+        static_handler(State(offline_state(test_config())), path.parse().unwrap())
+            .await
+            .into_response()
+    }
+
+    #[tokio::test]
+    async fn unknown_api_routes_get_json_404s_and_other_paths_the_spa() {
+        for path in ["/api/bogus", "/api"] {
+            let response = get(path).await;
+            assert_eq!(response.status(), StatusCode::NOT_FOUND, "{}", path);
+            let error: ApiError = json(response).await;
+            assert_eq!(error.error.code, "NOT_FOUND");
+        }
+
+        let response = get("/some/spa/route").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "text/html");
+    }
+}
@@ -0,0 +1,273 @@
+//! Helpers for tests that need a database.
+//!
+//! Each [`TestDb`] is a fresh database created next to `TEST_DATABASE_URL` and migrated
+//! from `migrations/`, so tests can run in parallel without seeing each other's rows. It is
+//! dropped again when the `TestDb` goes out of scope. Tests using it are `#[ignore]`d, so
+//! run them with `TEST_DATABASE_URL=postgres://... cargo test -p backend -- --ignored`.
+
+use axum::body::to_bytes;
+use axum::extract::Query;
+use axum::http::Uri;
+use axum::response::Response;
+use diesel::prelude::*;
+use diesel_async::pooled_connection::deadpool::{Object, Pool};
+use diesel_async::pooled_connection::AsyncDieselConnectionManager;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+
+use crate::db::schema::{action_items, categories, users, vendors};
+use crate::models::{
+    ActionItem, Category, NewActionItem, NewCategory, NewUser, NewVendor, User, Vendor,
+};
+use crate::routes::AuthUser;
+use crate::{AppConfig, AppState};
+
+pub struct TestDb {
+    pub state: Arc<AppState>,
+    admin_url: String,
+    name: String,
+}
+
+/// `url` pointing at database `name` instead, keeping any query parameters.
+fn with_database(url: &str, name: &str) -> String {
+    let (base, params) = match url.split_once('?') {
+        Some((base, params)) => (base, Some(params)),
+        None => (url, None),
+    };
+    let server = base.rsplit_once('/').map_or(base, |(server, _)| server);
+    match params {
+        Some(params) => format!("{}/{}?{}", server, name, params),
+        None => format!("{}/{}", server, name),
+    }
+}
+
+pub fn test_config() -> AppConfig {
+    AppConfig {
+        jwt_secret: "test-secret".to_string(),
+        dev_mode: false,
+        dev_user_id: None,
+        dev_user_role: shared::Role::Admin,
+        public_url: "http://localhost:8080".to_string(),
+        base_path: String::new(),
+        google_client_id: None,
+        google_client_secret: None,
+        allowed_email_domains: Vec::new(),
+        allowed_origins: vec!["http://localhost:8080".to_string()],
+        auth_rate_limit_per_minute: 0,
+        trusted_proxies: Vec::new(),
+        read_only: false,
+        title_max_length: shared::DEFAULT_TITLE_MAX_LENGTH,
+        statement_timeout_ms: 0,
+        token_ttl_hours: 24,
+        token_max_age_hours: 24 * 7,
+        notifications_enabled: false,
+        smtp: None,
+    }
+}
+
+impl TestDb {
+    pub async fn new() -> Self {
+        let admin_url = std::env::var("TEST_DATABASE_URL")
+            .expect("TEST_DATABASE_URL must be set to run database tests");
+        let name = format!("tracker_test_{:016x}", rand::random::<u64>());
+
+        let mut admin = AsyncPgConnection::establish(&admin_url)
+            .await
+            .expect("Failed to connect to TEST_DATABASE_URL");
+        admin
+            .batch_execute(&format!("CREATE DATABASE {}", name))
+            .await
+            .expect("Failed to create test database");
+
+        let url = with_database(&admin_url, &name);
+        let mut conn = AsyncPgConnection::establish(&url)
+            .await
+            .expect("Failed to connect to test database");
+        let migrations_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../migrations");
+        let mut migrations: Vec<_> = std::fs::read_dir(migrations_dir)
+            .expect("Failed to read migrations")
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        migrations.sort();
+        for migration in migrations {
+            let sql = std::fs::read_to_string(migration.join("up.sql")).unwrap();
+            conn.batch_execute(&sql)
+                .await
+                .unwrap_or_else(|e| panic!("Migration {} failed: {}", migration.display(), e));
+        }
+
+        let pool = Pool::builder(AsyncDieselConnectionManager::<AsyncPgConnection>::new(url))
+            .max_size(4)
+            .build()
+            .expect("Failed to create pool");
+
+        let state = Arc::new(AppState {
+            pool,
+            config: test_config(),
+            metrics: metrics_exporter_prometheus::PrometheusBuilder::new()
+                .build_recorder()
+                .handle(),
+            mailer: None,
+            dev_user: None,
+            auth_rate_limiter: None,
+        });
+
+        Self {
+            state,
+            admin_url,
+            name,
+        }
+    }
+
+    pub async fn conn(&self) -> Object<AsyncPgConnection> {
+        self.state
+            .pool
+            .get()
+            .await
+            .expect("Failed to get connection")
+    }
+
+    pub async fn user(&self, email: &str, name: &str, initials: Option<&str>) -> User {
+        diesel::insert_into(users::table)
+            .values(&NewUser {
+                email: email.to_string(),
+                name: name.to_string(),
+                initials: initials.map(str::to_string),
+            })
+            .returning(User::as_returning())
+            .get_result(&mut self.conn().await)
+            .await
+            .expect("Failed to create user")
+    }
+
+    pub async fn vendor(&self, prefix: &str) -> Vendor {
+        diesel::insert_into(vendors::table)
+            .values(&NewVendor {
+                prefix: prefix.to_string(),
+                name: format!("Vendor {}", prefix),
+                description: None,
+                number_padding: 3,
+            })
+            .returning(Vendor::as_returning())
+            .get_result(&mut self.conn().await)
+            .await
+            .expect("Failed to create vendor")
+    }
+
+    pub async fn category(&self, vendor: &Vendor, name: &str) -> Category {
+        diesel::insert_into(categories::table)
+            .values(&NewCategory {
+                vendor_id: vendor.id,
+                name: name.to_string(),
+                description: None,
+            })
+            .returning(Category::as_returning())
+            .get_result(&mut self.conn().await)
+            .await
+            .expect("Failed to create category")
+    }
+
+    /// An item under `category`'s vendor, numbered from the vendor's sequence.
+    pub async fn item(&self, category: &Category, created_by: &User, owner: &User) -> ActionItem {
+        let mut conn = self.conn().await;
+        let vendor: Vendor =
+            diesel::update(vendors::table.filter(vendors::id.eq(category.vendor_id)))
+                .set(vendors::next_number.eq(vendors::next_number + 1))
+                .returning(Vendor::as_returning())
+                .get_result(&mut conn)
+                .await
+                .expect("Failed to claim item number");
+        let number = vendor.next_number - 1;
+
+        diesel::insert_into(action_items::table)
+            .values(&NewActionItem {
+                id: crate::routes::format_item_id(&vendor.prefix, number, vendor.number_padding),
+                vendor_id: vendor.id,
+                number,
+                title: format!("Item {}", number),
+                create_date: chrono::Utc::now().date_naive(),
+                created_by_id: created_by.id,
+                due_date: None,
+                owner_id: owner.id,
+                priority: "Medium".to_string(),
+                description: None,
+                category_id: category.id,
+                recurrence: "none".to_string(),
+            })
+            .returning(ActionItem::as_returning())
+            .get_result(&mut conn)
+            .await
+            .expect("Failed to create item")
+    }
+}
+
+impl Drop for TestDb {
+    fn drop(&mut self) {
+        let admin_url = self.admin_url.clone();
+        let sql = format!("DROP DATABASE IF EXISTS {} WITH (FORCE)", self.name);
+        self.state.pool.close();
+        // Drop runs inside the test's runtime, which can't be blocked on; use a fresh one
+        let _ = std::thread::spawn(move || {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap()
+                .block_on(async {
+                    if let Ok(mut admin) = AsyncPgConnection::establish(&admin_url).await {
+                        let _ = admin.batch_execute(&sql).await;
+                    }
+                })
+        })
+        .join();
+    }
+}
+
+/// App state for tests that never reach the database: the pool is built but never
+/// connects. The `/auth/*` rate limiter is set up from `config` as at startup.
+pub fn offline_state(config: AppConfig) -> Arc<AppState> {
+    let manager =
+        AsyncDieselConnectionManager::<AsyncPgConnection>::new("postgres://offline.invalid/none");
+    Arc::new(AppState {
+        pool: Pool::builder(manager)
+            .build()
+            .expect("Failed to create pool"),
+        auth_rate_limiter: (config.auth_rate_limit_per_minute > 0).then(|| {
+            Arc::new(crate::rate_limit::RateLimiter::per_minute(
+                config.auth_rate_limit_per_minute,
+            ))
+        }),
+        config,
+        metrics: metrics_exporter_prometheus::PrometheusBuilder::new()
+            .build_recorder()
+            .handle(),
+        mailer: None,
+        dev_user: None,
+    })
+}
+
+/// The authenticated user a handler sees for `user`.
+pub fn auth(user: &User) -> AuthUser {
+    AuthUser {
+        user_id: user.id,
+        email: user.email.clone(),
+        name: user.name.clone(),
+        role: shared::Role::from_db(&user.role),
+    }
+}
+
+/// Deserialize a handler's JSON response body.
+pub async fn json<T: DeserializeOwned>(response: Response) -> T {
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("Failed to read response body");
+    serde_json::from_slice(&body).expect("Response body is not the expected JSON")
+}
+
+/// The `Query` extractor a handler sees for the query string `query`, e.g. `"limit=10"`.
+pub fn query<T: DeserializeOwned>(query: &str) -> Query<T> {
+    let uri: Uri = format!("/?{}", query)
+        .parse()
+        .expect("Invalid query string");
+    Query::try_from_uri(&uri).expect("Query string does not deserialize")
+}
@@ -0,0 +1,241 @@
+//! Outgoing webhooks. Deliveries run on their own task once the change is committed, so
+//! a slow or unreachable receiver never holds up the API response; failures are logged.
+
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use hmac::{Hmac, Mac};
+use reqwest::header::CONTENT_TYPE;
+use sha2::Sha256;
+use shared::{StatusChangeResponse, WebhookEvent};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::db::schema::{action_items, webhooks};
+use crate::models::{StatusHistory, Webhook};
+use crate::AppState;
+
+/// Matches every status change; `status_changed:<Status>` matches changes to that status.
+const STATUS_CHANGED: &str = "status_changed";
+
+const SIGNATURE_HEADER: &str = "X-Tracker-Signature";
+
+/// Per-delivery limit, connecting included.
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Hex HMAC-SHA256 of `body` keyed with the webhook's secret.
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    format!("{:x}", mac.finalize().into_bytes())
+}
+
+/// Notify the webhooks subscribed to these freshly written status changes.
+pub fn status_changed(state: &Arc<AppState>, entries: Vec<StatusHistory>) {
+    if entries.is_empty() {
+        return;
+    }
+    let state = state.clone();
+    tokio::spawn(async move {
+        if let Err(e) = deliver_status_changes(&state, entries).await {
+            tracing::error!("Webhook dispatch failed: {e}");
+        }
+    });
+}
+
+async fn deliver_status_changes(
+    state: &AppState,
+    entries: Vec<StatusHistory>,
+) -> anyhow::Result<()> {
+    let mut conn = state.pool.get().await?;
+
+    let mut event_types: HashSet<String> = entries
+        .iter()
+        .map(|e| format!("{}:{}", STATUS_CHANGED, e.status))
+        .collect();
+    event_types.insert(STATUS_CHANGED.to_string());
+    let hooks: Vec<Webhook> = webhooks::table
+        .filter(webhooks::event_type.eq_any(&event_types))
+        .order(webhooks::id)
+        .select(Webhook::as_select())
+        .load(&mut conn)
+        .await?;
+    if hooks.is_empty() {
+        return Ok(());
+    }
+
+    let item_ids: Vec<&str> = entries.iter().map(|e| e.action_item_id.as_str()).collect();
+    let items: HashMap<String, (i32, String)> = action_items::table
+        .filter(action_items::id.eq_any(&item_ids))
+        .select((
+            action_items::id,
+            action_items::vendor_id,
+            action_items::title,
+        ))
+        .load::<(String, i32, String)>(&mut conn)
+        .await?
+        .into_iter()
+        .map(|(id, vendor_id, title)| (id, (vendor_id, title)))
+        .collect();
+    drop(conn);
+
+    let client = reqwest::Client::builder()
+        .timeout(DELIVERY_TIMEOUT)
+        .build()?;
+
+    let mut deliveries = Vec::new();
+    for entry in entries {
+        let Some((vendor_id, title)) = items.get(&entry.action_item_id) else {
+            continue;
+        };
+        let event_type = format!("{}:{}", STATUS_CHANGED, entry.status);
+        let matching: Vec<&Webhook> = hooks
+            .iter()
+            .filter(|h| h.event_type == STATUS_CHANGED || h.event_type == event_type)
+            .filter(|h| h.vendor_id.is_none_or(|v| v == *vendor_id))
+            .collect();
+        if matching.is_empty() {
+            continue;
+        }
+
+        let body = serde_json::to_vec(&WebhookEvent {
+            event_type,
+            item_id: entry.action_item_id.clone(),
+            item_title: title.clone(),
+            vendor_id: *vendor_id,
+            change: StatusChangeResponse {
+                id: entry.id,
+                action_item_id: entry.action_item_id,
+                from_status: entry.from_status,
+                status: entry.status,
+                changed_by_id: entry.changed_by_id,
+                changed_at: entry.changed_at,
+                comment: entry.comment,
+            },
+        })?;
+        for hook in matching {
+            deliveries.push(deliver(&client, hook, body.clone()));
+        }
+    }
+    futures_util::future::join_all(deliveries).await;
+
+    Ok(())
+}
+
+async fn deliver(client: &reqwest::Client, hook: &Webhook, body: Vec<u8>) {
+    let signature = format!("sha256={}", sign(&hook.secret, &body));
+    let result = client
+        .post(&hook.url)
+        .header(CONTENT_TYPE, "application/json")
+        .header(SIGNATURE_HEADER, signature)
+        .body(body)
+        .send()
+        .await
+        .and_then(|resp| resp.error_for_status());
+
+    if let Err(e) = result {
+        tracing::warn!("Webhook {} delivery to {} failed: {e}", hook.id, hook.url);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::status_history;
+    use crate::models::NewStatusHistory;
+    use crate::test_support::TestDb;
+    use axum::{body::Bytes, extract::Path, http::HeaderMap, routing::post, Router};
+    use std::sync::Mutex;
+
+    type Received = Arc<Mutex<Vec<(String, String, Bytes)>>>;
+
+    /// Accept webhook POSTs on an ephemeral port, recording path, signature and body.
+    async fn serve_receiver() -> (String, Received) {
+        let received = Received::default();
+        let app = Router::new().route(
+            "/:hook",
+            post({
+                let received = received.clone();
+                move |Path(hook): Path<String>, headers: HeaderMap, body: Bytes| async move {
+                    let signature = headers[SIGNATURE_HEADER].to_str().unwrap().to_string();
+                    received.lock().unwrap().push((hook, signature, body));
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (url, received)
+    }
+
+    #[test]
+    fn sign_is_hex_hmac_sha256() {
+        // RFC 4231, test case 2
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn status_change_reaches_only_matching_hooks() {
+        let db = TestDb::new().await;
+        let user = db.user("mf@example.org", "Mike Fox", Some("MF")).await;
+        let vendor = db.vendor("AAA").await;
+        let other_vendor = db.vendor("BBB").await;
+        let category = db.category(&vendor, "Design").await;
+        let item = db.item(&category, &user, &user).await;
+        let (url, received) = serve_receiver().await;
+
+        let mut conn = db.conn().await;
+        for (hook, event_type, vendor_id) in [
+            ("all", "status_changed", None),
+            ("blocked", "status_changed:Blocked", Some(vendor.id)),
+            ("complete", "status_changed:Complete", None),
+            ("other-vendor", "status_changed", Some(other_vendor.id)),
+        ] {
+            diesel::insert_into(webhooks::table)
+                .values((
+                    webhooks::url.eq(format!("{}/{}", url, hook)),
+                    webhooks::event_type.eq(event_type),
+                    webhooks::vendor_id.eq(vendor_id),
+                    webhooks::secret.eq(format!("{}-secret", hook)),
+                ))
+                .execute(&mut conn)
+                .await
+                .unwrap();
+        }
+        let entry: StatusHistory = diesel::insert_into(status_history::table)
+            .values(&NewStatusHistory {
+                action_item_id: item.id.clone(),
+                status: "Blocked".to_string(),
+                changed_by_id: user.id,
+                comment: Some("Waiting on parts".to_string()),
+                from_status: Some("New".to_string()),
+            })
+            .returning(StatusHistory::as_returning())
+            .get_result(&mut conn)
+            .await
+            .unwrap();
+
+        deliver_status_changes(&db.state, vec![entry])
+            .await
+            .unwrap();
+
+        let mut received = received.lock().unwrap().clone();
+        received.sort_by(|a, b| a.0.cmp(&b.0));
+        let hooks: Vec<&str> = received.iter().map(|(hook, ..)| hook.as_str()).collect();
+        assert_eq!(hooks, ["all", "blocked"]);
+        for (hook, signature, body) in &received {
+            let secret = format!("{}-secret", hook);
+            assert_eq!(*signature, format!("sha256={}", sign(&secret, body)));
+            let event: WebhookEvent = serde_json::from_slice(body).unwrap();
+            assert_eq!(event.event_type, "status_changed:Blocked");
+            assert_eq!(event.item_id, item.id);
+            assert_eq!(event.change.from_status.as_deref(), Some("New"));
+            assert_eq!(event.change.comment.as_deref(), Some("Waiting on parts"));
+        }
+    }
+}
@@ -1,11 +1,17 @@
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
 use anyhow::Context;
 use chrono::NaiveDate;
 use clap::{Parser, Subcommand};
 use diesel::prelude::*;
 use diesel::PgConnection;
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use shared::{csv_date, note_author_initials, note_line, CSV_HEADERS};
+
+#[cfg(test)]
+mod test_support;
 
 mod schema {
     diesel::table! {
@@ -15,6 +21,8 @@ mod schema {
             name -> Varchar,
             initials -> Nullable<Varchar>,
             created_at -> Timestamptz,
+            active -> Bool,
+            role -> Varchar,
         }
     }
 
@@ -27,6 +35,8 @@ mod schema {
             next_number -> Int4,
             created_at -> Timestamptz,
             archived -> Bool,
+            updated_at -> Timestamptz,
+            number_padding -> Int4,
         }
     }
 
@@ -38,6 +48,7 @@ mod schema {
             name -> Varchar,
             description -> Nullable<Text>,
             created_at -> Timestamptz,
+            updated_at -> Timestamptz,
         }
     }
 
@@ -59,6 +70,11 @@ mod schema {
             updated_at -> Timestamptz,
             description -> Nullable<Text>,
             category_id -> Int4,
+            deleted_at -> Nullable<Timestamptz>,
+            #[max_length = 20]
+            recurrence -> Varchar,
+            #[max_length = 20]
+            next_occurrence_id -> Nullable<Varchar>,
         }
     }
 
@@ -72,6 +88,8 @@ mod schema {
             changed_by_id -> Int4,
             changed_at -> Timestamptz,
             comment -> Nullable<Text>,
+            #[max_length = 50]
+            from_status -> Nullable<Varchar>,
         }
     }
 
@@ -87,13 +105,129 @@ mod schema {
         }
     }
 
+    diesel::table! {
+        api_keys (id) {
+            id -> Int4,
+            #[max_length = 64]
+            key_hash -> Varchar,
+            user_id -> Int4,
+            #[max_length = 100]
+            label -> Varchar,
+            created_at -> Timestamptz,
+            last_used_at -> Nullable<Timestamptz>,
+        }
+    }
+
+    diesel::table! {
+        item_audit (id) {
+            id -> Int4,
+            #[max_length = 20]
+            action_item_id -> Varchar,
+            #[max_length = 50]
+            field -> Varchar,
+            old_value -> Nullable<Text>,
+            new_value -> Nullable<Text>,
+            changed_by_id -> Int4,
+            changed_at -> Timestamptz,
+        }
+    }
+
+    diesel::table! {
+        item_assignees (action_item_id, user_id) {
+            #[max_length = 20]
+            action_item_id -> Varchar,
+            user_id -> Int4,
+            #[max_length = 50]
+            role -> Varchar,
+            created_at -> Timestamptz,
+        }
+    }
+
+    diesel::table! {
+        item_watchers (action_item_id, user_id) {
+            #[max_length = 20]
+            action_item_id -> Varchar,
+            user_id -> Int4,
+            created_at -> Timestamptz,
+        }
+    }
+
+    diesel::table! {
+        tags (id) {
+            id -> Int4,
+            #[max_length = 50]
+            name -> Varchar,
+            created_at -> Timestamptz,
+        }
+    }
+
+    diesel::table! {
+        item_tags (action_item_id, tag_id) {
+            #[max_length = 20]
+            action_item_id -> Varchar,
+            tag_id -> Int4,
+        }
+    }
+
+    diesel::table! {
+        item_links (from_item_id, to_item_id, link_type) {
+            #[max_length = 20]
+            from_item_id -> Varchar,
+            #[max_length = 20]
+            to_item_id -> Varchar,
+            #[max_length = 20]
+            link_type -> Varchar,
+            created_at -> Timestamptz,
+        }
+    }
+
+    diesel::table! {
+        note_mentions (note_id, user_id) {
+            note_id -> Int4,
+            user_id -> Int4,
+        }
+    }
+
+    diesel::table! {
+        webhooks (id) {
+            id -> Int4,
+            url -> Text,
+            #[max_length = 50]
+            event_type -> Varchar,
+            vendor_id -> Nullable<Int4>,
+            secret -> Text,
+            created_at -> Timestamptz,
+        }
+    }
+
+    diesel::table! {
+        saved_views (id) {
+            id -> Int4,
+            user_id -> Int4,
+            #[max_length = 100]
+            name -> Varchar,
+            filter_json -> Text,
+            created_at -> Timestamptz,
+        }
+    }
+
     diesel::allow_tables_to_appear_in_same_query!(
         action_items,
+        api_keys,
         categories,
+        item_assignees,
+        item_audit,
+        item_links,
+        item_tags,
+        item_watchers,
+        note_mentions,
         notes,
+        saved_views,
         status_history,
+        tags,
         users,
         vendors,
+        webhooks,
     );
 }
 
@@ -124,6 +258,18 @@ enum Commands {
     },
     /// List all users
     ListUsers,
+    /// Stop offering a user as an owner; their existing items and history are kept
+    DeactivateUser {
+        #[arg(long)]
+        email: String,
+    },
+    /// Make a user an admin (who can manage vendors and categories) or a member
+    SetRole {
+        #[arg(long)]
+        email: String,
+        #[arg(long, value_parser = ["admin", "member"])]
+        role: String,
+    },
     /// Create a new vendor
     CreateVendor {
         /// Unique prefix for action item IDs (2-5 uppercase letters)
@@ -135,13 +281,70 @@ enum Commands {
         /// Optional description
         #[arg(long)]
         description: Option<String>,
+        /// Digits item numbers are zero-padded to in ids (3: AD-001)
+        #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(i32).range(1..=6))]
+        number_padding: i32,
     },
     /// List all vendors
     ListVendors,
-    /// Reset a vendor's next_number sequence
+    /// List action items with their current status
+    ListItems {
+        /// Vendor prefix (e.g. "AD")
+        #[arg(long)]
+        vendor: Option<String>,
+        /// Only items whose current status matches (e.g. "In Progress")
+        #[arg(long)]
+        status: Option<String>,
+        /// Owner name, initials, or "F. Last" as in the import CSV
+        #[arg(long)]
+        owner: Option<String>,
+        /// Print JSON instead of a table
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Reset a vendor's next_number sequence to one past its highest item number
     ResetSequence {
         #[arg(long)]
         vendor: String,
+        /// Set next_number to this value instead; must be past the highest item number
+        #[arg(long)]
+        to: Option<i32>,
+    },
+    /// Move every item and category of one vendor into another, renumbering the items
+    MergeVendors {
+        /// Prefix of the duplicate vendor; it is archived afterwards
+        #[arg(long)]
+        from: String,
+        /// Prefix of the vendor that keeps the items
+        #[arg(long)]
+        into: String,
+        /// Email of the user the id changes are recorded against in the audit log
+        #[arg(long)]
+        by: String,
+    },
+    /// Create an API key for a user and print it (it is only shown once)
+    CreateApiKey {
+        /// Email of the user the key acts as
+        #[arg(long)]
+        user: String,
+        /// What the key is for, e.g. "nightly CI"
+        #[arg(long, default_value = "cli")]
+        label: String,
+    },
+    /// Register a URL to receive signed POSTs when item statuses change
+    AddWebhook {
+        /// Where to POST the event JSON
+        #[arg(long)]
+        url: String,
+        /// Only fire on changes to this status (e.g. "blocked"); every change if omitted
+        #[arg(long)]
+        status: Option<String>,
+        /// Only fire for this vendor's items (prefix, e.g. "AD")
+        #[arg(long)]
+        vendor: Option<String>,
+        /// HMAC key for the X-Tracker-Signature header; generated and printed if omitted
+        #[arg(long)]
+        secret: Option<String>,
     },
     /// Import action items from a CSV file
     ImportCsv {
@@ -154,6 +357,43 @@ enum Commands {
         /// Dry run - parse and validate without writing to the database
         #[arg(long, default_value_t = false)]
         dry_run: bool,
+        /// Two-digit years below this are read as 20xx, the rest as 19xx
+        #[arg(long, default_value_t = DEFAULT_CENTURY_PIVOT, value_parser = clap::value_parser!(u32).range(0..=100))]
+        century_pivot: u32,
+        /// TOML file mapping import fields to this file's headers, e.g. `owner = "Assigned To"`
+        #[arg(long)]
+        mapping: Option<PathBuf>,
+    },
+    /// Export a vendor's action items to a CSV file that import-csv can read back
+    ExportCsv {
+        /// Vendor prefix (e.g. "AD")
+        #[arg(long)]
+        vendor: String,
+        /// Path of the CSV file to write
+        #[arg(long)]
+        out: PathBuf,
+        /// Only export items whose current status matches (e.g. "complete")
+        #[arg(long)]
+        status: Option<String>,
+    },
+    /// Dump every table to a JSON file, keeping ids and timestamps
+    ExportJson {
+        /// Path of the JSON file to write
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Load a JSON dump written by export-json
+    #[command(group(clap::ArgGroup::new("mode").required(true).args(["replace", "merge"])))]
+    ImportJson {
+        /// Path to the JSON dump
+        #[arg(long)]
+        file: PathBuf,
+        /// Empty the tables first (API keys are removed too)
+        #[arg(long)]
+        replace: bool,
+        /// Keep existing rows and skip dump rows that conflict with them
+        #[arg(long)]
+        merge: bool,
     },
 }
 
@@ -169,14 +409,28 @@ struct NewUser {
     initials: Option<String>,
 }
 
-#[derive(Queryable)]
-#[allow(dead_code)]
+#[derive(Queryable, Insertable, serde::Serialize, serde::Deserialize)]
+#[diesel(table_name = users)]
 struct User {
     id: i32,
     email: String,
     name: String,
     initials: Option<String>,
     created_at: chrono::DateTime<chrono::Utc>,
+    /// Missing from dumps taken before users could be deactivated
+    #[serde(default = "default_active")]
+    active: bool,
+    /// Missing from dumps taken before roles existed
+    #[serde(default = "default_role")]
+    role: String,
+}
+
+fn default_active() -> bool {
+    true
+}
+
+fn default_role() -> String {
+    "member".to_string()
 }
 
 #[derive(Insertable)]
@@ -185,10 +439,11 @@ struct NewVendor {
     prefix: String,
     name: String,
     description: Option<String>,
+    number_padding: i32,
 }
 
-#[derive(Queryable)]
-#[allow(dead_code)]
+#[derive(Queryable, Insertable, serde::Serialize, serde::Deserialize)]
+#[diesel(table_name = vendors)]
 struct Vendor {
     id: i32,
     prefix: String,
@@ -197,16 +452,27 @@ struct Vendor {
     next_number: i32,
     created_at: chrono::DateTime<chrono::Utc>,
     archived: bool,
+    updated_at: chrono::DateTime<chrono::Utc>,
+    /// Missing from dumps taken before padding was configurable
+    #[serde(default = "default_number_padding")]
+    number_padding: i32,
+}
+
+fn default_number_padding() -> i32 {
+    3
 }
 
-#[derive(Queryable)]
-#[allow(dead_code)]
+#[derive(Queryable, Insertable, serde::Serialize, serde::Deserialize)]
+#[diesel(table_name = categories)]
 struct Category {
     id: i32,
     vendor_id: i32,
     name: String,
     description: Option<String>,
     created_at: chrono::DateTime<chrono::Utc>,
+    /// Missing from dumps taken before categories were versioned
+    #[serde(default = "chrono::Utc::now")]
+    updated_at: chrono::DateTime<chrono::Utc>,
 }
 
 #[derive(Insertable)]
@@ -216,6 +482,34 @@ struct NewCategory {
     name: String,
 }
 
+#[derive(Queryable, Insertable, serde::Serialize, serde::Deserialize)]
+#[diesel(table_name = action_items)]
+struct ActionItem {
+    id: String,
+    vendor_id: i32,
+    number: i32,
+    title: String,
+    create_date: NaiveDate,
+    created_by_id: i32,
+    due_date: Option<NaiveDate>,
+    owner_id: i32,
+    priority: String,
+    created_at: chrono::DateTime<chrono::Utc>,
+    updated_at: chrono::DateTime<chrono::Utc>,
+    description: Option<String>,
+    category_id: i32,
+    deleted_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Missing from dumps taken before items could recur
+    #[serde(default = "default_recurrence")]
+    recurrence: String,
+    #[serde(default)]
+    next_occurrence_id: Option<String>,
+}
+
+fn default_recurrence() -> String {
+    "none".to_string()
+}
+
 #[derive(Insertable)]
 #[diesel(table_name = action_items)]
 struct NewActionItem {
@@ -238,7 +532,32 @@ struct NewStatusHistory {
     action_item_id: String,
     status: String,
     changed_by_id: i32,
+    changed_at: chrono::DateTime<chrono::Utc>,
+    comment: Option<String>,
+    from_status: Option<String>,
+}
+
+#[derive(Queryable, Insertable, serde::Serialize, serde::Deserialize)]
+#[diesel(table_name = status_history)]
+struct StatusHistory {
+    id: i32,
+    action_item_id: String,
+    status: String,
+    changed_by_id: i32,
+    changed_at: chrono::DateTime<chrono::Utc>,
     comment: Option<String>,
+    from_status: Option<String>,
+}
+
+#[derive(Queryable, Insertable, serde::Serialize, serde::Deserialize)]
+#[diesel(table_name = notes)]
+struct Note {
+    id: i32,
+    action_item_id: String,
+    note_date: NaiveDate,
+    author_id: i32,
+    content: String,
+    created_at: chrono::DateTime<chrono::Utc>,
 }
 
 #[derive(Insertable)]
@@ -250,52 +569,333 @@ struct NewNote {
     content: String,
 }
 
+#[derive(Insertable)]
+#[diesel(table_name = api_keys)]
+struct NewApiKey {
+    key_hash: String,
+    user_id: i32,
+    label: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = item_audit)]
+struct NewItemAudit {
+    action_item_id: String,
+    field: String,
+    old_value: Option<String>,
+    new_value: Option<String>,
+    changed_by_id: i32,
+}
+
+#[derive(Queryable, Insertable, serde::Serialize, serde::Deserialize)]
+#[diesel(table_name = item_audit)]
+struct ItemAudit {
+    id: i32,
+    action_item_id: String,
+    field: String,
+    old_value: Option<String>,
+    new_value: Option<String>,
+    changed_by_id: i32,
+    changed_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Queryable, Insertable, serde::Serialize, serde::Deserialize)]
+#[diesel(table_name = item_assignees)]
+struct ItemAssignee {
+    action_item_id: String,
+    user_id: i32,
+    role: String,
+    created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Queryable, Insertable, serde::Serialize, serde::Deserialize)]
+#[diesel(table_name = item_watchers)]
+struct ItemWatcher {
+    action_item_id: String,
+    user_id: i32,
+    created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Queryable, Insertable, serde::Serialize, serde::Deserialize)]
+#[diesel(table_name = tags)]
+struct Tag {
+    id: i32,
+    name: String,
+    created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Queryable, Insertable, serde::Serialize, serde::Deserialize)]
+#[diesel(table_name = item_tags)]
+struct ItemTag {
+    action_item_id: String,
+    tag_id: i32,
+}
+
+#[derive(Queryable, Insertable, serde::Serialize, serde::Deserialize)]
+#[diesel(table_name = item_links)]
+struct ItemLink {
+    from_item_id: String,
+    to_item_id: String,
+    link_type: String,
+    created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = webhooks)]
+struct NewWebhook {
+    url: String,
+    event_type: String,
+    vendor_id: Option<i32>,
+    secret: String,
+}
+
+#[derive(Queryable, Insertable, serde::Serialize, serde::Deserialize)]
+#[diesel(table_name = note_mentions)]
+struct NoteMention {
+    note_id: i32,
+    user_id: i32,
+}
+
+#[derive(Queryable, Insertable, serde::Serialize, serde::Deserialize)]
+#[diesel(table_name = saved_views)]
+struct SavedView {
+    id: i32,
+    user_id: i32,
+    name: String,
+    filter_json: String,
+    created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// One row of `list-items` output.
+#[derive(serde::Serialize)]
+struct ItemListing {
+    id: String,
+    title: String,
+    status: String,
+    priority: String,
+    owner: String,
+    due_date: Option<NaiveDate>,
+}
+
+/// Every table in a JSON backup, parents before children. API keys and webhooks are left
+/// out: they are credentials and endpoints for one environment.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Dump {
+    users: Vec<User>,
+    vendors: Vec<Vendor>,
+    categories: Vec<Category>,
+    action_items: Vec<ActionItem>,
+    status_history: Vec<StatusHistory>,
+    notes: Vec<Note>,
+    item_audit: Vec<ItemAudit>,
+    item_assignees: Vec<ItemAssignee>,
+    item_watchers: Vec<ItemWatcher>,
+    tags: Vec<Tag>,
+    item_tags: Vec<ItemTag>,
+    /// Missing from dumps taken before item links existed
+    #[serde(default)]
+    item_links: Vec<ItemLink>,
+    /// Missing from dumps taken before note mentions existed
+    #[serde(default)]
+    note_mentions: Vec<NoteMention>,
+    /// Missing from dumps taken before saved views existed
+    #[serde(default)]
+    saved_views: Vec<SavedView>,
+}
+
 // ============================================================================
 // CSV row
 // ============================================================================
 
-#[derive(Debug, serde::Deserialize)]
+/// One spreadsheet row. Fields are filled by column position (see `CsvColumns`), so a
+/// `--mapping` can point them at whatever the headers are called.
+#[derive(Debug)]
 struct CsvRow {
-    #[serde(rename = "Action Item #")]
     action_item_id: String,
-    #[serde(rename = "Title")]
     title: String,
-    #[serde(rename = "Create Date")]
     create_date: String,
-    #[serde(rename = "Created by")]
     created_by: String,
-    #[serde(rename = "Due Date")]
     due_date: String,
-    #[serde(rename = "Category")]
     category: String,
-    #[serde(rename = "Owner")]
     owner: String,
-    #[serde(rename = "Priority")]
     priority: String,
-    #[serde(rename = "Status")]
     status: String,
-    #[serde(rename = "Status Date")]
     status_date: String,
-    #[serde(rename = "Notes")]
     notes: String,
 }
 
+/// `CsvRow` fields as named in a `--mapping` file, in the same order as `CSV_HEADERS`.
+const CSV_FIELDS: [&str; 11] = [
+    "action_item_id",
+    "title",
+    "create_date",
+    "created_by",
+    "due_date",
+    "category",
+    "owner",
+    "priority",
+    "status",
+    "status_date",
+    "notes",
+];
+
+/// Fields a mapping must name; the others read as blank when left out.
+const REQUIRED_CSV_FIELDS: [&str; 6] = [
+    "action_item_id",
+    "title",
+    "create_date",
+    "category",
+    "priority",
+    "status",
+];
+
+/// The header each `CSV_FIELDS` entry is read from, or `None` for an unmapped optional
+/// field.
+#[derive(Debug)]
+struct CsvColumns {
+    headers: Vec<Option<String>>,
+}
+
+impl CsvColumns {
+    /// The tracker's own export headers (`CSV_HEADERS`).
+    fn standard() -> Self {
+        Self {
+            headers: CSV_HEADERS.iter().map(|h| Some(h.to_string())).collect(),
+        }
+    }
+
+    /// Read a TOML file of `field = "Header"` lines, e.g. `owner = "Assigned To"`.
+    fn from_mapping_file(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read mapping file: {}", path.display()))?;
+        let mut mapping: HashMap<String, String> = toml::from_str(&contents)
+            .with_context(|| format!("Invalid mapping file: {}", path.display()))?;
+
+        let mut unknown: Vec<&String> = mapping
+            .keys()
+            .filter(|k| !CSV_FIELDS.contains(&k.as_str()))
+            .collect();
+        if !unknown.is_empty() {
+            unknown.sort();
+            anyhow::bail!(
+                "Unknown field(s) in mapping file: {}. Fields are: {}",
+                unknown
+                    .iter()
+                    .map(|k| k.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                CSV_FIELDS.join(", ")
+            );
+        }
+        let missing: Vec<&str> = REQUIRED_CSV_FIELDS
+            .iter()
+            .copied()
+            .filter(|f| !mapping.contains_key(*f))
+            .collect();
+        if !missing.is_empty() {
+            anyhow::bail!(
+                "Mapping file must map required field(s): {}",
+                missing.join(", ")
+            );
+        }
+
+        Ok(Self {
+            headers: CSV_FIELDS.iter().map(|f| mapping.remove(*f)).collect(),
+        })
+    }
+
+    fn id_header(&self) -> &str {
+        self.headers[0]
+            .as_deref()
+            .expect("action_item_id is always mapped")
+    }
+
+    /// Position of each field's column in `header_row`. Errors name every mapped header
+    /// the file lacks.
+    fn positions(&self, header_row: &csv::StringRecord) -> anyhow::Result<Vec<Option<usize>>> {
+        let mut missing = Vec::new();
+        let positions = self
+            .headers
+            .iter()
+            .map(|header| {
+                let header = header.as_deref()?;
+                let pos = header_row.iter().position(|h| h.trim() == header);
+                if pos.is_none() {
+                    missing.push(format!("'{}'", header));
+                }
+                pos
+            })
+            .collect();
+        if !missing.is_empty() {
+            anyhow::bail!(
+                "Column(s) {} not found in the header row. Columns are: {}",
+                missing.join(", "),
+                header_row.iter().collect::<Vec<_>>().join(", ")
+            );
+        }
+        Ok(positions)
+    }
+}
+
+impl CsvRow {
+    /// Build a row from a record, with `positions` from `CsvColumns::positions`. Cells
+    /// missing from short rows read as blank.
+    fn from_record(record: &csv::StringRecord, positions: &[Option<usize>]) -> Self {
+        let cell = |field: usize| {
+            positions[field]
+                .and_then(|pos| record.get(pos))
+                .unwrap_or("")
+                .to_string()
+        };
+        Self {
+            action_item_id: cell(0),
+            title: cell(1),
+            create_date: cell(2),
+            created_by: cell(3),
+            due_date: cell(4),
+            category: cell(5),
+            owner: cell(6),
+            priority: cell(7),
+            status: cell(8),
+            status_date: cell(9),
+            notes: cell(10),
+        }
+    }
+}
+
 // ============================================================================
 // Import logic
 // ============================================================================
 
-/// Parse a date string in M/D/YYYY or MM/DD/YYYY format.
-fn parse_date(s: &str) -> anyhow::Result<NaiveDate> {
+/// Longest category name the `categories.name` column takes.
+const CATEGORY_NAME_MAX_LENGTH: usize = 100;
+
+/// Default `--century-pivot`: two-digit years below it are 20xx, the rest 19xx.
+const DEFAULT_CENTURY_PIVOT: u32 = 70;
+
+/// Parse a date as M/D/YYYY (leading zeros optional), M/D/YY or ISO YYYY-MM-DD. Two-digit
+/// years below `century_pivot` land in the 2000s, the rest in the 1900s.
+fn parse_date(s: &str, century_pivot: u32) -> anyhow::Result<NaiveDate> {
     let s = s.trim();
-    // Try M/D/YYYY (US format)
-    if let Ok(d) = NaiveDate::parse_from_str(s, "%m/%d/%Y") {
-        return Ok(d);
-    }
-    // Try MM/DD/YYYY with leading zeros
-    if let Ok(d) = NaiveDate::parse_from_str(s, "%-m/%-d/%Y") {
+    if let Ok(d) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
         return Ok(d);
     }
-    anyhow::bail!("Cannot parse date: '{}'", s)
+
+    // chrono's %Y would read "24" as the year 24, so pick the format by year length
+    let parsed = match s.rsplit_once('/') {
+        Some((_, year)) if year.len() == 4 => NaiveDate::parse_from_str(s, "%m/%d/%Y").ok(),
+        Some((month_day, year)) if year.len() == 2 => year.parse::<u32>().ok().and_then(|yy| {
+            let century = if yy < century_pivot { 2000 } else { 1900 };
+            let (month, day) = month_day.split_once('/')?;
+            NaiveDate::from_ymd_opt(
+                (century + yy) as i32,
+                month.parse().ok()?,
+                day.parse().ok()?,
+            )
+        }),
+        _ => None,
+    };
+    parsed.with_context(|| format!("Cannot parse date: '{}'", s))
 }
 
 /// Normalize a status string to the canonical form used in the database.
@@ -321,6 +921,12 @@ fn normalize_priority(s: &str) -> anyhow::Result<String> {
     }
 }
 
+/// An item ID: the vendor prefix and the number zero-padded to `padding` digits, the way
+/// the server generates them.
+fn format_item_id(prefix: &str, number: i32, padding: i32) -> String {
+    format!("{}-{:0width$}", prefix, number, width = padding as usize)
+}
+
 /// Parse the item ID into (prefix, number). E.g. "AD-001" -> ("AD", 1)
 fn parse_item_id(s: &str) -> anyhow::Result<(String, i32)> {
     let s = s.trim();
@@ -408,35 +1014,109 @@ fn resolve_user(
     )
 }
 
-/// Parse multi-line notes into individual (date, content) entries.
-/// Format: "MM/DD/YYYY INITIALS: content\nMM/DD/YYYY INITIALS: content"
-fn parse_notes(raw: &str) -> Vec<(Option<NaiveDate>, String)> {
-    let raw = raw.trim();
-    if raw.is_empty() {
-        return Vec::new();
-    }
-
-    let mut entries: Vec<(Option<NaiveDate>, String)> = Vec::new();
-
-    for line in raw.lines() {
+/// Every user and category reference in `rows` that can't be imported: unknown users
+/// grouped by name with the rows and fields that use them, and rows whose category is
+/// blank or too long for the column.
+fn unresolved_references(
+    rows: &[CsvRow],
+    users: &[User],
+    user_cache: &mut HashMap<String, i32>,
+) -> (Vec<(String, Vec<String>)>, Vec<String>) {
+    let mut unknown_users: Vec<(String, Vec<String>)> = Vec::new();
+    let mut bad_categories: Vec<String> = Vec::new();
+    for (i, row) in rows.iter().enumerate() {
+        let line = i + 1;
+        for (field, name) in [("created_by", &row.created_by), ("owner", &row.owner)] {
+            let name = name.trim();
+            if name.is_empty() || resolve_user(name, users, user_cache).is_ok() {
+                continue;
+            }
+            let place = format!("row {} {}", line, field);
+            match unknown_users.iter_mut().find(|(n, _)| n == name) {
+                Some((_, places)) => places.push(place),
+                None => unknown_users.push((name.to_string(), vec![place])),
+            }
+        }
+
+        let category = row.category.trim();
+        if category.is_empty() {
+            bad_categories.push(format!(
+                "Row {}: Item '{}' has no category",
+                line, row.action_item_id
+            ));
+        } else if category.chars().count() > CATEGORY_NAME_MAX_LENGTH {
+            bad_categories.push(format!(
+                "Row {}: Category '{}' is longer than {} characters",
+                line, category, CATEGORY_NAME_MAX_LENGTH
+            ));
+        }
+    }
+    (unknown_users, bad_categories)
+}
+
+/// Midnight UTC on the given date, used to timestamp imported history entries.
+fn start_of_day(date: NaiveDate) -> chrono::DateTime<chrono::Utc> {
+    date.and_hms_opt(0, 0, 0).unwrap().and_utc()
+}
+
+/// Resolve note author initials to a user ID using case-insensitive initials matching.
+fn resolve_initials(initials: &str, users: &[User]) -> Option<i32> {
+    users
+        .iter()
+        .find(|u| {
+            u.initials
+                .as_deref()
+                .is_some_and(|i| i.eq_ignore_ascii_case(initials))
+        })
+        .map(|u| u.id)
+}
+
+/// One note from a CSV notes cell. `content` keeps the "INITIALS:" signature.
+struct ParsedNote {
+    date: Option<NaiveDate>,
+    initials: Option<String>,
+    content: String,
+}
+
+impl ParsedNote {
+    fn new(date: Option<NaiveDate>, content: String) -> Self {
+        ParsedNote {
+            date,
+            initials: note_author_initials(&content).map(str::to_string),
+            content,
+        }
+    }
+}
+
+/// Parse multi-line notes into individual entries with their date and author initials.
+/// Format: "MM/DD/YYYY INITIALS: content\nMM/DD/YYYY INITIALS: content"
+fn parse_notes(raw: &str, century_pivot: u32) -> Vec<ParsedNote> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Vec::new();
+    }
+
+    let mut entries: Vec<ParsedNote> = Vec::new();
+
+    for line in raw.lines() {
         let line = line.trim();
         if line.is_empty() {
             continue;
         }
 
         // Try to detect if this line starts a new note entry (date prefix)
-        let has_date_prefix = try_parse_note_date(line).is_some();
+        let has_date_prefix = try_parse_note_date(line, century_pivot).is_some();
 
         if has_date_prefix {
-            let (date, rest) = try_parse_note_date(line).unwrap();
-            entries.push((Some(date), rest));
+            let (date, rest) = try_parse_note_date(line, century_pivot).unwrap();
+            entries.push(ParsedNote::new(Some(date), rest));
         } else if let Some(last) = entries.last_mut() {
             // Continuation of previous note
-            last.1.push('\n');
-            last.1.push_str(line);
+            last.content.push('\n');
+            last.content.push_str(line);
         } else {
             // First entry with no date
-            entries.push((None, line.to_string()));
+            entries.push(ParsedNote::new(None, line.to_string()));
         }
     }
 
@@ -444,8 +1124,8 @@ fn parse_notes(raw: &str) -> Vec<(Option<NaiveDate>, String)> {
 }
 
 /// Try to extract a date from the beginning of a note line.
-/// Formats: "M/D/YYYY text..." or "MM/DD/YYYY text..."
-fn try_parse_note_date(line: &str) -> Option<(NaiveDate, String)> {
+/// Formats: "M/D/YYYY text...", "MM/DD/YYYY text..." or "M/D/YY text..."
+fn try_parse_note_date(line: &str, century_pivot: u32) -> Option<(NaiveDate, String)> {
     // Look for a date-like pattern at the start: digits/digits/digits
     let bytes = line.as_bytes();
     let mut i = 0;
@@ -474,18 +1154,49 @@ fn try_parse_note_date(line: &str) -> Option<(NaiveDate, String)> {
     while i < bytes.len() && bytes[i].is_ascii_digit() {
         i += 1;
     }
-    if i - year_start != 4 {
+    if i - year_start != 4 && i - year_start != 2 {
         return None;
     }
 
     let date_str = &line[..i];
     let rest = line[i..].trim().to_string();
 
-    let date = parse_date(date_str).ok()?;
+    let date = parse_date(date_str, century_pivot).ok()?;
     Some((date, rest))
 }
 
-fn import_csv(file: PathBuf, vendor_prefix: Option<String>, dry_run: bool) -> anyhow::Result<()> {
+fn print_dry_run(rows: &[CsvRow], century_pivot: u32) {
+    println!("\n[DRY RUN] Would import {} action items.", rows.len());
+    for row in rows {
+        let note_entries = parse_notes(&row.notes, century_pivot);
+        println!(
+            "  {} - {} (status: {}, {} notes)",
+            row.action_item_id,
+            row.title,
+            row.status,
+            note_entries.len()
+        );
+    }
+}
+
+fn import_csv(
+    conn: Option<&mut PgConnection>,
+    file: PathBuf,
+    vendor_prefix: Option<String>,
+    dry_run: bool,
+    century_pivot: u32,
+    mapping: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let columns = match &mapping {
+        Some(path) => CsvColumns::from_mapping_file(path)?,
+        None => CsvColumns::standard(),
+    };
+    for (field, header) in CSV_FIELDS.iter().zip(&columns.headers) {
+        if header.is_none() {
+            println!("Field '{}' is not mapped; reading it as blank", field);
+        }
+    }
+
     // Read and parse CSV, skipping the first two header/info rows
     let contents = std::fs::read_to_string(&file)
         .with_context(|| format!("Failed to read file: {}", file.display()))?;
@@ -496,20 +1207,25 @@ fn import_csv(file: PathBuf, vendor_prefix: Option<String>, dry_run: bool) -> an
         anyhow::bail!("CSV file too short - expected header rows + data");
     }
 
-    // Find the header row (contains "Action Item #")
+    // Find the header row: the one with an "Action Item #" (or mapped ID header) cell
+    let id_header = columns.id_header();
     let header_idx = lines
         .iter()
-        .position(|l| l.contains("Action Item #"))
-        .context("Could not find header row containing 'Action Item #'")?;
+        .position(|l| {
+            l.split(',')
+                .any(|cell| cell.trim().trim_matches('"') == id_header)
+        })
+        .with_context(|| format!("Could not find header row containing '{}'", id_header))?;
 
     let csv_body = lines[header_idx..].join("\n");
     let mut rdr = csv::ReaderBuilder::new()
         .flexible(true)
         .from_reader(csv_body.as_bytes());
+    let positions = columns.positions(rdr.headers()?)?;
 
     let mut rows: Vec<CsvRow> = Vec::new();
-    for result in rdr.deserialize() {
-        let row: CsvRow = result?;
+    for result in rdr.records() {
+        let row = CsvRow::from_record(&result?, &positions);
         // Skip empty rows (just an ID with no title)
         if row.title.trim().is_empty() {
             continue;
@@ -534,7 +1250,7 @@ fn import_csv(file: PathBuf, vendor_prefix: Option<String>, dry_run: bool) -> an
 
     // Collect unique values for validation reporting
     let unique_users: Vec<String> = {
-        let mut set = std::collections::HashSet::new();
+        let mut set = HashSet::new();
         for row in &rows {
             if !row.created_by.trim().is_empty() {
                 set.insert(row.created_by.trim().to_string());
@@ -549,7 +1265,7 @@ fn import_csv(file: PathBuf, vendor_prefix: Option<String>, dry_run: bool) -> an
     };
 
     let unique_categories: Vec<String> = {
-        let mut set = std::collections::HashSet::new();
+        let mut set = HashSet::new();
         for row in &rows {
             if !row.category.trim().is_empty() {
                 set.insert(row.category.trim().to_string());
@@ -589,7 +1305,7 @@ fn import_csv(file: PathBuf, vendor_prefix: Option<String>, dry_run: bool) -> an
             Err(e) => errors.push(format!("Row {}: {}", line, e)),
         }
 
-        if let Err(e) = parse_date(&row.create_date) {
+        if let Err(e) = parse_date(&row.create_date, century_pivot) {
             errors.push(format!("Row {}: create_date: {}", line, e));
         }
 
@@ -597,7 +1313,7 @@ fn import_csv(file: PathBuf, vendor_prefix: Option<String>, dry_run: bool) -> an
             && row.due_date.trim().to_uppercase() != "TBD"
             && row.due_date.trim().to_uppercase() != "PDR"
         {
-            if let Err(e) = parse_date(&row.due_date) {
+            if let Err(e) = parse_date(&row.due_date, century_pivot) {
                 errors.push(format!("Row {}: due_date: {}", line, e));
             }
         }
@@ -611,7 +1327,7 @@ fn import_csv(file: PathBuf, vendor_prefix: Option<String>, dry_run: bool) -> an
         }
 
         if !row.status_date.trim().is_empty() {
-            if let Err(e) = parse_date(&row.status_date) {
+            if let Err(e) = parse_date(&row.status_date, century_pivot) {
                 errors.push(format!("Row {}: status_date: {}", line, e));
             }
         }
@@ -627,28 +1343,21 @@ fn import_csv(file: PathBuf, vendor_prefix: Option<String>, dry_run: bool) -> an
 
     println!("\nAll rows validated successfully.");
 
-    if dry_run {
-        println!("\n[DRY RUN] Would import {} action items.", rows.len());
-        for row in &rows {
-            let note_entries = parse_notes(&row.notes);
-            println!(
-                "  {} - {} (status: {}, {} notes)",
-                row.action_item_id,
-                row.title,
-                row.status,
-                note_entries.len()
-            );
+    // A dry run reads from the database too, to check names resolve, but only when one is
+    // reachable; otherwise the parse checks above are all it does
+    let Some(conn) = conn else {
+        if !dry_run {
+            anyhow::bail!("A database connection is required to import");
         }
+        println!("No database connection; user and category names were not checked.");
+        print_dry_run(&rows, century_pivot);
         return Ok(());
-    }
-
-    // Connect to database
-    let mut conn = establish_connection();
+    };
 
     // Look up vendor
     let vendor: Vendor = vendors::table
         .filter(vendors::prefix.eq(&prefix))
-        .first(&mut conn)
+        .first(conn)
         .with_context(|| {
             format!(
                 "Vendor with prefix '{}' not found. Create it first.",
@@ -657,7 +1366,7 @@ fn import_csv(file: PathBuf, vendor_prefix: Option<String>, dry_run: bool) -> an
         })?;
 
     // Load all users for name resolution
-    let all_users: Vec<User> = users::table.order(users::name.asc()).load(&mut conn)?;
+    let all_users: Vec<User> = users::table.order(users::name.asc()).load(conn)?;
 
     if all_users.is_empty() {
         anyhow::bail!("No users in database. Create users first.");
@@ -665,16 +1374,30 @@ fn import_csv(file: PathBuf, vendor_prefix: Option<String>, dry_run: bool) -> an
 
     let mut user_cache: HashMap<String, i32> = HashMap::new();
 
-    // Verify all user references resolve before inserting anything
-    for row in &rows {
-        if !row.created_by.trim().is_empty() {
-            resolve_user(&row.created_by, &all_users, &mut user_cache)
-                .with_context(|| format!("Item {}: created_by", row.action_item_id))?;
+    // Resolve every user and category reference before inserting anything, so one run
+    // reports all of the failures
+    let (unknown_users, bad_categories) = unresolved_references(&rows, &all_users, &mut user_cache);
+
+    let unresolved = unknown_users.len() + bad_categories.len();
+    if unresolved > 0 {
+        println!("\nUnresolved references:");
+        for (name, places) in &unknown_users {
+            println!("  ERROR: Unknown user '{}' ({})", name, places.join(", "));
+        }
+        for e in &bad_categories {
+            println!("  ERROR: {}", e);
         }
-        if !row.owner.trim().is_empty() {
-            resolve_user(&row.owner, &all_users, &mut user_cache)
-                .with_context(|| format!("Item {}: owner", row.action_item_id))?;
+        if !unknown_users.is_empty() {
+            println!(
+                "\nKnown users: {}",
+                all_users
+                    .iter()
+                    .map(|u| u.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
         }
+        anyhow::bail!("{} unresolved reference(s) found", unresolved);
     }
 
     println!("\nUser resolution:");
@@ -687,12 +1410,22 @@ fn import_csv(file: PathBuf, vendor_prefix: Option<String>, dry_run: bool) -> an
     let mut category_cache: HashMap<String, i32> = HashMap::new();
     let existing_categories: Vec<Category> = categories::table
         .filter(categories::vendor_id.eq(vendor.id))
-        .load(&mut conn)?;
+        .load(conn)?;
 
     for cat in &existing_categories {
         category_cache.insert(cat.name.clone(), cat.id);
     }
 
+    if dry_run {
+        for cat_name in &unique_categories {
+            if !category_cache.contains_key(cat_name) {
+                println!("  Would create category: '{}'", cat_name);
+            }
+        }
+        print_dry_run(&rows, century_pivot);
+        return Ok(());
+    }
+
     for cat_name in &unique_categories {
         if !category_cache.contains_key(cat_name) {
             let new_cat = NewCategory {
@@ -701,7 +1434,7 @@ fn import_csv(file: PathBuf, vendor_prefix: Option<String>, dry_run: bool) -> an
             };
             let created: Category = diesel::insert_into(categories::table)
                 .values(&new_cat)
-                .get_result(&mut conn)?;
+                .get_result(conn)?;
             println!("  Created category: '{}' (id={})", cat_name, created.id);
             category_cache.insert(cat_name.clone(), created.id);
         }
@@ -717,10 +1450,14 @@ fn import_csv(file: PathBuf, vendor_prefix: Option<String>, dry_run: bool) -> an
 
         for row in &rows {
             let (_, number) = parse_item_id(&row.action_item_id)?;
+            // Stored with the vendor's padding whatever the spreadsheet used ("AD-1" -> "AD-001")
+            let item_id = format_item_id(&vendor.prefix, number, vendor.number_padding);
 
             // Check if item already exists
             let exists: bool = diesel::select(diesel::dsl::exists(
-                action_items::table.filter(action_items::id.eq(&row.action_item_id)),
+                action_items::table
+                    .filter(action_items::vendor_id.eq(vendor.id))
+                    .filter(action_items::number.eq(number)),
             ))
             .get_result(conn)?;
 
@@ -746,13 +1483,13 @@ fn import_csv(file: PathBuf, vendor_prefix: Option<String>, dry_run: bool) -> an
                 .get(row.category.trim())
                 .context("Category not found")?;
 
-            let create_date = parse_date(&row.create_date)?;
+            let create_date = parse_date(&row.create_date, century_pivot)?;
             let due_date = {
                 let d = row.due_date.trim();
                 if d.is_empty() || d.eq_ignore_ascii_case("TBD") || d.eq_ignore_ascii_case("PDR") {
                     None
                 } else {
-                    Some(parse_date(d)?)
+                    Some(parse_date(d, century_pivot)?)
                 }
             };
 
@@ -760,7 +1497,7 @@ fn import_csv(file: PathBuf, vendor_prefix: Option<String>, dry_run: bool) -> an
             let status = normalize_status(&row.status)?;
 
             let new_item = NewActionItem {
-                id: row.action_item_id.trim().to_string(),
+                id: item_id.clone(),
                 vendor_id: vendor.id,
                 number,
                 title: row.title.trim().to_string(),
@@ -777,25 +1514,57 @@ fn import_csv(file: PathBuf, vendor_prefix: Option<String>, dry_run: bool) -> an
                 .values(&new_item)
                 .execute(conn)?;
 
-            // Insert initial status history
-            let status_entry = NewStatusHistory {
-                action_item_id: row.action_item_id.trim().to_string(),
-                status,
-                changed_by_id: created_by_id,
-                comment: Some("Imported from CSV".to_string()),
+            // Reconstruct a minimal status timeline: "New" on the create date, then the
+            // current status on its Status Date. Without a later Status Date the current
+            // status is the only entry, so two rows never share a timestamp.
+            let status_date = {
+                let d = row.status_date.trim();
+                if d.is_empty() {
+                    create_date
+                } else {
+                    parse_date(d, century_pivot)?.max(create_date)
+                }
             };
+            let mut status_entries = Vec::with_capacity(2);
+            if status == "New" || status_date > create_date {
+                status_entries.push(NewStatusHistory {
+                    action_item_id: item_id.clone(),
+                    status: "New".to_string(),
+                    changed_by_id: created_by_id,
+                    changed_at: start_of_day(create_date),
+                    comment: Some("Item created".to_string()),
+                    from_status: None,
+                });
+            }
+            if status != "New" {
+                let from_status = status_entries.first().map(|_| "New".to_string());
+                status_entries.push(NewStatusHistory {
+                    action_item_id: item_id.clone(),
+                    status,
+                    changed_by_id: created_by_id,
+                    changed_at: start_of_day(status_date),
+                    comment: Some("Imported from CSV".to_string()),
+                    from_status,
+                });
+            }
             diesel::insert_into(status_history::table)
-                .values(&status_entry)
+                .values(&status_entries)
                 .execute(conn)?;
 
             // Parse and insert notes
-            let note_entries = parse_notes(&row.notes);
-            for (note_date, content) in &note_entries {
+            let note_entries = parse_notes(&row.notes, century_pivot);
+            for note in &note_entries {
+                // Attribute the note to whoever signed it, falling back to the item creator
+                let author_id = note
+                    .initials
+                    .as_deref()
+                    .and_then(|initials| resolve_initials(initials, &all_users))
+                    .unwrap_or(created_by_id);
                 let new_note = NewNote {
-                    action_item_id: row.action_item_id.trim().to_string(),
-                    note_date: note_date.unwrap_or(create_date),
-                    author_id: created_by_id,
-                    content: content.clone(),
+                    action_item_id: item_id.clone(),
+                    note_date: note.date.unwrap_or(create_date),
+                    author_id,
+                    content: note.content.clone(),
                 };
                 diesel::insert_into(notes::table)
                     .values(&new_note)
@@ -804,7 +1573,7 @@ fn import_csv(file: PathBuf, vendor_prefix: Option<String>, dry_run: bool) -> an
 
             println!(
                 "  IMPORTED {} - {} ({} notes)",
-                row.action_item_id,
+                item_id,
                 row.title,
                 note_entries.len()
             );
@@ -834,6 +1603,766 @@ fn import_csv(file: PathBuf, vendor_prefix: Option<String>, dry_run: bool) -> an
     Ok(())
 }
 
+// ============================================================================
+// Export logic
+// ============================================================================
+
+fn export_csv(
+    conn: &mut PgConnection,
+    vendor_prefix: String,
+    out: PathBuf,
+    status: Option<String>,
+) -> anyhow::Result<()> {
+    let status_filter = status.as_deref().map(normalize_status).transpose()?;
+
+    let vendor: Vendor = vendors::table
+        .filter(vendors::prefix.eq(&vendor_prefix))
+        .first(conn)
+        .optional()?
+        .with_context(|| format!("Vendor with prefix '{}' not found", vendor_prefix))?;
+
+    // Soft-deleted items are left out, as in the web export
+    let items: Vec<ActionItem> = action_items::table
+        .filter(action_items::vendor_id.eq(vendor.id))
+        .filter(action_items::deleted_at.is_null())
+        .order(action_items::number.asc())
+        .load(conn)?;
+
+    let category_names: HashMap<i32, String> = categories::table
+        .filter(categories::vendor_id.eq(vendor.id))
+        .load::<Category>(conn)?
+        .into_iter()
+        .map(|c| (c.id, c.name))
+        .collect();
+
+    let all_users: HashMap<i32, User> = users::table
+        .load::<User>(conn)?
+        .into_iter()
+        .map(|u| (u.id, u))
+        .collect();
+
+    let item_ids: Vec<&str> = items.iter().map(|i| i.id.as_str()).collect();
+
+    // Latest status per item; items without history are "New" as of their creation
+    let latest_statuses: HashMap<String, (String, chrono::DateTime<chrono::Utc>)> =
+        status_history::table
+            .filter(status_history::action_item_id.eq_any(&item_ids))
+            .distinct_on(status_history::action_item_id)
+            .order((
+                status_history::action_item_id,
+                status_history::changed_at.desc(),
+                status_history::id.desc(),
+            ))
+            .select((
+                status_history::action_item_id,
+                status_history::status,
+                status_history::changed_at,
+            ))
+            .load::<(String, String, chrono::DateTime<chrono::Utc>)>(conn)?
+            .into_iter()
+            .map(|(id, status, at)| (id, (status, at)))
+            .collect();
+
+    // Notes oldest first, which is the order the importer replays them in
+    let mut notes_by_item: HashMap<String, Vec<String>> = HashMap::new();
+    let item_notes: Vec<(String, NaiveDate, i32, String)> = notes::table
+        .filter(notes::action_item_id.eq_any(&item_ids))
+        .order((notes::note_date.asc(), notes::created_at.asc()))
+        .select((
+            notes::action_item_id,
+            notes::note_date,
+            notes::author_id,
+            notes::content,
+        ))
+        .load(conn)?;
+    for (item_id, note_date, author_id, content) in &item_notes {
+        notes_by_item
+            .entry(item_id.clone())
+            .or_default()
+            .push(note_line(
+                *note_date,
+                content,
+                all_users.get(author_id).and_then(|u| u.initials.as_deref()),
+            ));
+    }
+
+    let user_name = |id: i32| -> anyhow::Result<String> {
+        all_users
+            .get(&id)
+            .map(|u| u.name.clone())
+            .with_context(|| format!("User {} not found", id))
+    };
+
+    let mut writer = csv::WriterBuilder::new()
+        .flexible(true)
+        .from_path(&out)
+        .with_context(|| format!("Failed to create file: {}", out.display()))?;
+
+    // The source spreadsheets carry two info rows above the header; the importer skips them
+    writer.write_record([format!("{} Action Items", vendor.name)])?;
+    writer.write_record([format!(
+        "Exported {}",
+        csv_date(chrono::Utc::now().date_naive())
+    )])?;
+    writer.write_record(CSV_HEADERS)?;
+
+    let mut exported = 0;
+    for item in &items {
+        let (status, status_changed_at) = latest_statuses
+            .get(&item.id)
+            .cloned()
+            .unwrap_or_else(|| ("New".to_string(), item.created_at));
+
+        // Stored values are already the canonical spellings; fail here rather than
+        // write a row the importer would reject
+        let status =
+            normalize_status(&status).with_context(|| format!("Item {}: status", item.id))?;
+        let priority = normalize_priority(&item.priority)
+            .with_context(|| format!("Item {}: priority", item.id))?;
+
+        if status_filter
+            .as_ref()
+            .is_some_and(|wanted| *wanted != status)
+        {
+            continue;
+        }
+
+        let category = category_names
+            .get(&item.category_id)
+            .cloned()
+            .with_context(|| {
+                format!("Item {}: category {} not found", item.id, item.category_id)
+            })?;
+
+        writer.write_record([
+            item.id.clone(),
+            item.title.clone(),
+            csv_date(item.create_date),
+            user_name(item.created_by_id)?,
+            item.due_date.map(csv_date).unwrap_or_default(),
+            category,
+            user_name(item.owner_id)?,
+            priority,
+            status,
+            csv_date(status_changed_at.date_naive()),
+            notes_by_item
+                .get(&item.id)
+                .map(|lines| lines.join("\n"))
+                .unwrap_or_default(),
+        ])?;
+        exported += 1;
+    }
+
+    writer.flush()?;
+
+    println!(
+        "Exported {} action items for vendor '{}' to {}",
+        exported,
+        vendor.prefix,
+        out.display()
+    );
+
+    Ok(())
+}
+
+// ============================================================================
+// Item listing
+// ============================================================================
+
+fn list_items(
+    vendor_prefix: Option<String>,
+    status: Option<String>,
+    owner: Option<String>,
+    json: bool,
+) -> anyhow::Result<()> {
+    let status_filter = status.as_deref().map(normalize_status).transpose()?;
+
+    let mut conn = establish_connection();
+
+    let all_users: Vec<User> = users::table.order(users::name.asc()).load(&mut conn)?;
+
+    let mut items_query = action_items::table
+        .filter(action_items::deleted_at.is_null())
+        .into_boxed();
+
+    if let Some(prefix) = &vendor_prefix {
+        let vendor: Vendor = vendors::table
+            .filter(vendors::prefix.eq(prefix))
+            .first(&mut conn)
+            .optional()?
+            .with_context(|| format!("Vendor with prefix '{}' not found", prefix))?;
+        items_query = items_query.filter(action_items::vendor_id.eq(vendor.id));
+    }
+
+    if let Some(owner) = &owner {
+        let owner_id = resolve_user(owner, &all_users, &mut HashMap::new())?;
+        items_query = items_query.filter(action_items::owner_id.eq(owner_id));
+    }
+
+    let items: Vec<ActionItem> = items_query.order(action_items::id.asc()).load(&mut conn)?;
+
+    // Latest status per item; items without history are "New"
+    let item_ids: Vec<&str> = items.iter().map(|i| i.id.as_str()).collect();
+    let latest_statuses: HashMap<String, String> = status_history::table
+        .filter(status_history::action_item_id.eq_any(&item_ids))
+        .distinct_on(status_history::action_item_id)
+        .order((
+            status_history::action_item_id,
+            status_history::changed_at.desc(),
+            status_history::id.desc(),
+        ))
+        .select((status_history::action_item_id, status_history::status))
+        .load::<(String, String)>(&mut conn)?
+        .into_iter()
+        .collect();
+
+    let user_names: HashMap<i32, &str> =
+        all_users.iter().map(|u| (u.id, u.name.as_str())).collect();
+
+    let listings: Vec<ItemListing> = items
+        .into_iter()
+        .map(|item| ItemListing {
+            status: latest_statuses
+                .get(&item.id)
+                .cloned()
+                .unwrap_or_else(|| "New".to_string()),
+            owner: user_names
+                .get(&item.owner_id)
+                .map(|n| n.to_string())
+                .unwrap_or_else(|| format!("User #{}", item.owner_id)),
+            id: item.id,
+            title: item.title,
+            priority: item.priority,
+            due_date: item.due_date,
+        })
+        .filter(|listing| {
+            status_filter
+                .as_ref()
+                .is_none_or(|wanted| *wanted == listing.status)
+        })
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&listings)?);
+        return Ok(());
+    }
+
+    if listings.is_empty() {
+        println!("No action items match those filters.");
+        return Ok(());
+    }
+
+    println!(
+        "{:<10} {:<12} {:<8} {:<20} {:<10} Title",
+        "ID", "Status", "Priority", "Owner", "Due"
+    );
+    println!("{}", "-".repeat(100));
+    for listing in &listings {
+        println!(
+            "{:<10} {:<12} {:<8} {:<20} {:<10} {}",
+            listing.id,
+            listing.status,
+            listing.priority,
+            listing.owner,
+            listing.due_date.map(csv_date).unwrap_or_default(),
+            listing.title
+        );
+    }
+    println!("\n{} action item(s)", listings.len());
+
+    Ok(())
+}
+
+// ============================================================================
+// JSON backup
+// ============================================================================
+
+/// Rows per INSERT, well under PostgreSQL's limit of 65535 bind parameters.
+const INSERT_CHUNK: usize = 1000;
+
+/// Insert rows in chunks, skipping any that conflict with existing ones. Evaluates to the
+/// number of rows inserted.
+macro_rules! insert_rows {
+    ($conn:expr, $table:expr, $rows:expr) => {{
+        let mut inserted = 0;
+        for chunk in $rows.chunks(INSERT_CHUNK) {
+            inserted += diesel::insert_into($table)
+                .values(chunk)
+                .on_conflict_do_nothing()
+                .execute($conn)?;
+        }
+        inserted
+    }};
+}
+
+/// Ids of dumped rows that already belong to a different row in the database. `key` holds
+/// the columns that tell rows apart, e.g. a user's email.
+fn clashing_ids<I, K>(dumped: impl IntoIterator<Item = (I, K)>, stored: Vec<(I, K)>) -> HashSet<I>
+where
+    I: Eq + std::hash::Hash,
+    K: PartialEq,
+{
+    let dumped: HashMap<I, K> = dumped.into_iter().collect();
+    stored
+        .into_iter()
+        .filter(|(id, key)| dumped.get(id).is_some_and(|dumped| dumped != key))
+        .map(|(id, _)| id)
+        .collect()
+}
+
+/// Remove the rows `orphaned` picks out of `rows`, returning them.
+fn take_orphans<T>(rows: &mut Vec<T>, orphaned: impl Fn(&T) -> bool) -> Vec<T> {
+    let (orphans, kept) = std::mem::take(rows)
+        .into_iter()
+        .partition(|row| orphaned(row));
+    *rows = kept;
+    orphans
+}
+
+/// Tables with a SERIAL id, whose sequences must move past imported ids.
+const SERIAL_TABLES: [&str; 8] = [
+    "users",
+    "vendors",
+    "categories",
+    "status_history",
+    "notes",
+    "item_audit",
+    "tags",
+    "saved_views",
+];
+
+/// Every table `export-json` backs up.
+fn load_dump(conn: &mut PgConnection) -> anyhow::Result<Dump> {
+    // One snapshot, so rows added mid-export can't reference rows we missed
+    conn.build_transaction()
+        .read_only()
+        .repeatable_read()
+        .run(|conn| {
+            Ok::<_, anyhow::Error>(Dump {
+                users: users::table.order(users::id).load(conn)?,
+                vendors: vendors::table.order(vendors::id).load(conn)?,
+                categories: categories::table.order(categories::id).load(conn)?,
+                action_items: action_items::table.order(action_items::id).load(conn)?,
+                status_history: status_history::table.order(status_history::id).load(conn)?,
+                notes: notes::table.order(notes::id).load(conn)?,
+                item_audit: item_audit::table.order(item_audit::id).load(conn)?,
+                item_assignees: item_assignees::table
+                    .order((item_assignees::action_item_id, item_assignees::user_id))
+                    .load(conn)?,
+                item_watchers: item_watchers::table
+                    .order((item_watchers::action_item_id, item_watchers::user_id))
+                    .load(conn)?,
+                tags: tags::table.order(tags::id).load(conn)?,
+                item_tags: item_tags::table
+                    .order((item_tags::action_item_id, item_tags::tag_id))
+                    .load(conn)?,
+                item_links: item_links::table
+                    .order((
+                        item_links::from_item_id,
+                        item_links::to_item_id,
+                        item_links::link_type,
+                    ))
+                    .load(conn)?,
+                note_mentions: note_mentions::table
+                    .order((note_mentions::note_id, note_mentions::user_id))
+                    .load(conn)?,
+                saved_views: saved_views::table.order(saved_views::id).load(conn)?,
+            })
+        })
+}
+
+fn export_json(conn: &mut PgConnection, out: PathBuf) -> anyhow::Result<()> {
+    let dump = load_dump(conn)?;
+
+    let file = std::fs::File::create(&out)
+        .with_context(|| format!("Failed to create file: {}", out.display()))?;
+    serde_json::to_writer_pretty(std::io::BufWriter::new(file), &dump)?;
+
+    println!(
+        "Exported {} users, {} vendors, {} action items to {}",
+        dump.users.len(),
+        dump.vendors.len(),
+        dump.action_items.len(),
+        out.display()
+    );
+
+    Ok(())
+}
+
+fn import_json(conn: &mut PgConnection, file: PathBuf, replace: bool) -> anyhow::Result<()> {
+    let contents = std::fs::read_to_string(&file)
+        .with_context(|| format!("Failed to read file: {}", file.display()))?;
+    let mut dump: Dump = serde_json::from_str(&contents)
+        .with_context(|| format!("Invalid dump: {}", file.display()))?;
+
+    // An item's next occurrence may land in a later insert chunk, so link them afterwards
+    let next_occurrences: Vec<(String, String)> = dump
+        .action_items
+        .iter_mut()
+        .filter_map(|item| {
+            let next = item.next_occurrence_id.take()?;
+            Some((item.id.clone(), next))
+        })
+        .collect();
+
+    conn.transaction::<_, anyhow::Error, _>(|conn| {
+        if replace {
+            diesel::sql_query(
+                "TRUNCATE webhooks, saved_views, note_mentions, item_links, item_tags, tags, \
+                 item_watchers, item_assignees, item_audit, notes, status_history, \
+                 idempotency_keys, action_items, categories, vendors, api_keys, users",
+            )
+            .execute(conn)?;
+        }
+
+        // Merging keeps whatever row already holds a dumped id. If that row is a different
+        // one, the dumped row's children are left out rather than attached to it, so each
+        // parent table gets a set of ids whose subtree is skipped.
+        let mut left_out = 0;
+
+        let users_inserted = insert_rows!(conn, users::table, dump.users);
+        let skipped_users = clashing_ids(
+            dump.users.iter().map(|u| (u.id, u.email.clone())),
+            users::table
+                .filter(users::id.eq_any(dump.users.iter().map(|u| u.id).collect::<Vec<_>>()))
+                .select((users::id, users::email))
+                .load(conn)?,
+        );
+
+        let vendors_inserted = insert_rows!(conn, vendors::table, dump.vendors);
+        let skipped_vendors = clashing_ids(
+            dump.vendors.iter().map(|v| (v.id, v.prefix.clone())),
+            vendors::table
+                .filter(vendors::id.eq_any(dump.vendors.iter().map(|v| v.id).collect::<Vec<_>>()))
+                .select((vendors::id, vendors::prefix))
+                .load(conn)?,
+        );
+
+        let orphans = take_orphans(&mut dump.categories, |c| {
+            skipped_vendors.contains(&c.vendor_id)
+        });
+        left_out += orphans.len();
+        let mut skipped_categories: HashSet<i32> = orphans.iter().map(|c| c.id).collect();
+        let categories_inserted = insert_rows!(conn, categories::table, dump.categories);
+        skipped_categories.extend(clashing_ids(
+            dump.categories
+                .iter()
+                .map(|c| (c.id, (c.vendor_id, c.name.clone()))),
+            categories::table
+                .filter(
+                    categories::id.eq_any(dump.categories.iter().map(|c| c.id).collect::<Vec<_>>()),
+                )
+                .select((categories::id, (categories::vendor_id, categories::name)))
+                .load(conn)?,
+        ));
+
+        let orphans = take_orphans(&mut dump.action_items, |i| {
+            skipped_vendors.contains(&i.vendor_id)
+                || skipped_categories.contains(&i.category_id)
+                || skipped_users.contains(&i.created_by_id)
+                || skipped_users.contains(&i.owner_id)
+        });
+        left_out += orphans.len();
+        let mut skipped_items: HashSet<String> = orphans.into_iter().map(|i| i.id).collect();
+        let items_inserted = insert_rows!(conn, action_items::table, dump.action_items);
+        // Item ids are meaningful on their own, so the same id created at the same moment is
+        // the same item
+        skipped_items.extend(clashing_ids(
+            dump.action_items
+                .iter()
+                .map(|i| (i.id.clone(), i.created_at)),
+            action_items::table
+                .filter(
+                    action_items::id.eq_any(
+                        dump.action_items
+                            .iter()
+                            .map(|i| i.id.as_str())
+                            .collect::<Vec<_>>(),
+                    ),
+                )
+                .select((action_items::id, action_items::created_at))
+                .load(conn)?,
+        ));
+
+        left_out += take_orphans(&mut dump.status_history, |h| {
+            skipped_items.contains(&h.action_item_id) || skipped_users.contains(&h.changed_by_id)
+        })
+        .len();
+        let status_history_inserted =
+            insert_rows!(conn, status_history::table, dump.status_history);
+
+        let orphans = take_orphans(&mut dump.notes, |n| {
+            skipped_items.contains(&n.action_item_id) || skipped_users.contains(&n.author_id)
+        });
+        left_out += orphans.len();
+        let mut skipped_notes: HashSet<i32> = orphans.iter().map(|n| n.id).collect();
+        let notes_inserted = insert_rows!(conn, notes::table, dump.notes);
+        skipped_notes.extend(clashing_ids(
+            dump.notes
+                .iter()
+                .map(|n| (n.id, (n.action_item_id.clone(), n.created_at))),
+            notes::table
+                .filter(notes::id.eq_any(dump.notes.iter().map(|n| n.id).collect::<Vec<_>>()))
+                .select((notes::id, (notes::action_item_id, notes::created_at)))
+                .load(conn)?,
+        ));
+
+        left_out += take_orphans(&mut dump.item_audit, |a| {
+            skipped_items.contains(&a.action_item_id) || skipped_users.contains(&a.changed_by_id)
+        })
+        .len();
+        let item_audit_inserted = insert_rows!(conn, item_audit::table, dump.item_audit);
+
+        left_out += take_orphans(&mut dump.item_assignees, |a| {
+            skipped_items.contains(&a.action_item_id) || skipped_users.contains(&a.user_id)
+        })
+        .len();
+        let item_assignees_inserted =
+            insert_rows!(conn, item_assignees::table, dump.item_assignees);
+
+        left_out += take_orphans(&mut dump.item_watchers, |w| {
+            skipped_items.contains(&w.action_item_id) || skipped_users.contains(&w.user_id)
+        })
+        .len();
+        let item_watchers_inserted = insert_rows!(conn, item_watchers::table, dump.item_watchers);
+
+        let tags_inserted = insert_rows!(conn, tags::table, dump.tags);
+        let skipped_tags = clashing_ids(
+            dump.tags.iter().map(|t| (t.id, t.name.clone())),
+            tags::table
+                .filter(tags::id.eq_any(dump.tags.iter().map(|t| t.id).collect::<Vec<_>>()))
+                .select((tags::id, tags::name))
+                .load(conn)?,
+        );
+
+        left_out += take_orphans(&mut dump.item_tags, |t| {
+            skipped_items.contains(&t.action_item_id) || skipped_tags.contains(&t.tag_id)
+        })
+        .len();
+        let item_tags_inserted = insert_rows!(conn, item_tags::table, dump.item_tags);
+
+        left_out += take_orphans(&mut dump.item_links, |l| {
+            skipped_items.contains(&l.from_item_id) || skipped_items.contains(&l.to_item_id)
+        })
+        .len();
+        let item_links_inserted = insert_rows!(conn, item_links::table, dump.item_links);
+
+        left_out += take_orphans(&mut dump.note_mentions, |m| {
+            skipped_notes.contains(&m.note_id) || skipped_users.contains(&m.user_id)
+        })
+        .len();
+        let note_mentions_inserted = insert_rows!(conn, note_mentions::table, dump.note_mentions);
+
+        left_out += take_orphans(&mut dump.saved_views, |v| {
+            skipped_users.contains(&v.user_id)
+        })
+        .len();
+        let saved_views_inserted = insert_rows!(conn, saved_views::table, dump.saved_views);
+
+        let counts = [
+            ("users", users_inserted),
+            ("vendors", vendors_inserted),
+            ("categories", categories_inserted),
+            ("action_items", items_inserted),
+            ("status_history", status_history_inserted),
+            ("notes", notes_inserted),
+            ("item_audit", item_audit_inserted),
+            ("item_assignees", item_assignees_inserted),
+            ("item_watchers", item_watchers_inserted),
+            ("tags", tags_inserted),
+            ("item_tags", item_tags_inserted),
+            ("item_links", item_links_inserted),
+            ("note_mentions", note_mentions_inserted),
+            ("saved_views", saved_views_inserted),
+        ];
+
+        for (item_id, next_id) in &next_occurrences {
+            if skipped_items.contains(item_id) || skipped_items.contains(next_id) {
+                continue;
+            }
+            diesel::update(action_items::table.filter(action_items::id.eq(item_id)))
+                .set(action_items::next_occurrence_id.eq(next_id))
+                .execute(conn)?;
+        }
+
+        // Ids were inserted explicitly, so the sequences never advanced
+        for table in SERIAL_TABLES {
+            diesel::sql_query(format!(
+                "SELECT setval(pg_get_serial_sequence('{table}', 'id'), \
+                 COALESCE(MAX(id), 1), MAX(id) IS NOT NULL) FROM {table}"
+            ))
+            .execute(conn)?;
+        }
+
+        for (table, count) in counts {
+            println!("  {:<16} {} inserted", table, count);
+        }
+        if left_out > 0 {
+            println!(
+                "  {} row(s) left out: their parent's id already belongs to a different row",
+                left_out
+            );
+        }
+        Ok(())
+    })?;
+
+    println!(
+        "\nImport complete ({})",
+        if replace { "replaced" } else { "merged" }
+    );
+
+    Ok(())
+}
+
+fn merge_vendors(
+    conn: &mut PgConnection,
+    from: String,
+    into: String,
+    by: String,
+) -> anyhow::Result<()> {
+    if from == into {
+        anyhow::bail!("--from and --into must be different vendors");
+    }
+
+    let changed_by_id: i32 = users::table
+        .filter(users::email.eq(&by))
+        .select(users::id)
+        .first(conn)
+        .optional()?
+        .with_context(|| format!("No user with email '{}'", by))?;
+
+    let mappings = conn.transaction::<_, anyhow::Error, _>(|conn| {
+        // Lock both vendors, in id order so two merges can't deadlock. Item creation
+        // claims numbers by updating the vendor row, so it waits for us.
+        let mut locked: Vec<Vendor> = vendors::table
+            .filter(vendors::prefix.eq_any([&from, &into]))
+            .order(vendors::id)
+            .for_update()
+            .load(conn)?;
+        let target = locked
+            .iter()
+            .position(|v| v.prefix == into)
+            .map(|i| locked.remove(i))
+            .with_context(|| format!("Vendor '{}' not found", into))?;
+        let source = locked
+            .pop()
+            .with_context(|| format!("Vendor '{}' not found", from))?;
+
+        // Categories are unique per vendor by name: reuse the target's where names match,
+        // move the rest over
+        let source_categories: Vec<Category> = categories::table
+            .filter(categories::vendor_id.eq(source.id))
+            .load(conn)?;
+        let target_categories: HashMap<String, i32> = categories::table
+            .filter(categories::vendor_id.eq(target.id))
+            .select((categories::name, categories::id))
+            .load::<(String, i32)>(conn)?
+            .into_iter()
+            .collect();
+        let now = chrono::Utc::now();
+        let mut category_moves: HashMap<i32, i32> = HashMap::new();
+        for category in &source_categories {
+            match target_categories.get(&category.name) {
+                Some(&target_category) => {
+                    category_moves.insert(category.id, target_category);
+                }
+                None => {
+                    diesel::update(categories::table.filter(categories::id.eq(category.id)))
+                        .set((
+                            categories::vendor_id.eq(target.id),
+                            categories::updated_at.eq(now),
+                        ))
+                        .execute(conn)?;
+                }
+            }
+        }
+
+        // Number past everything the target has used, soft-deleted items included
+        let max_number: Option<i32> = action_items::table
+            .filter(action_items::vendor_id.eq(target.id))
+            .select(diesel::dsl::max(action_items::number))
+            .first(conn)?;
+        let mut next_number = target.next_number.max(max_number.unwrap_or(0) + 1);
+
+        let source_items: Vec<(String, i32)> = action_items::table
+            .filter(action_items::vendor_id.eq(source.id))
+            .order(action_items::number.asc())
+            .select((action_items::id, action_items::category_id))
+            .load(conn)?;
+
+        let mut mappings = Vec::with_capacity(source_items.len());
+        let mut audit = Vec::new();
+        for (old_id, old_category) in source_items {
+            let new_id = format_item_id(&target.prefix, next_number, target.number_padding);
+            let new_category = category_moves
+                .get(&old_category)
+                .copied()
+                .unwrap_or(old_category);
+            // notes, status_history and the other item tables follow via ON UPDATE CASCADE
+            diesel::update(action_items::table.filter(action_items::id.eq(&old_id)))
+                .set((
+                    action_items::id.eq(&new_id),
+                    action_items::vendor_id.eq(target.id),
+                    action_items::number.eq(next_number),
+                    action_items::category_id.eq(new_category),
+                    action_items::updated_at.eq(now),
+                ))
+                .execute(conn)?;
+
+            // Recorded the way the web UI's move records it
+            audit.push(NewItemAudit {
+                action_item_id: new_id.clone(),
+                field: "id".to_string(),
+                old_value: Some(old_id.clone()),
+                new_value: Some(new_id.clone()),
+                changed_by_id,
+            });
+            if new_category != old_category {
+                audit.push(NewItemAudit {
+                    action_item_id: new_id.clone(),
+                    field: "category_id".to_string(),
+                    old_value: Some(old_category.to_string()),
+                    new_value: Some(new_category.to_string()),
+                    changed_by_id,
+                });
+            }
+            mappings.push((old_id, new_id));
+            next_number += 1;
+        }
+        insert_rows!(conn, item_audit::table, audit);
+
+        for source_category in category_moves.keys() {
+            diesel::delete(categories::table.filter(categories::id.eq(*source_category)))
+                .execute(conn)?;
+        }
+
+        diesel::update(vendors::table.filter(vendors::id.eq(target.id)))
+            .set((
+                vendors::next_number.eq(next_number),
+                vendors::updated_at.eq(now),
+            ))
+            .execute(conn)?;
+        diesel::update(vendors::table.filter(vendors::id.eq(source.id)))
+            .set((vendors::archived.eq(true), vendors::updated_at.eq(now)))
+            .execute(conn)?;
+
+        Ok(mappings)
+    })?;
+
+    for (old_id, new_id) in &mappings {
+        println!("{} -> {}", old_id, new_id);
+    }
+    println!(
+        "\nMerged {} item(s) from '{}' into '{}'; '{}' is now archived",
+        mappings.len(),
+        from,
+        into,
+        from
+    );
+
+    Ok(())
+}
+
 // ============================================================================
 // Main
 // ============================================================================
@@ -845,6 +2374,20 @@ fn establish_connection() -> PgConnection {
         .unwrap_or_else(|_| panic!("Error connecting to {}", database_url))
 }
 
+/// A connection for commands that can do without one, or `None` if `DATABASE_URL` is unset
+/// or unreachable.
+fn try_connection() -> Option<PgConnection> {
+    dotenvy::dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").ok()?;
+    match PgConnection::establish(&database_url) {
+        Ok(conn) => Some(conn),
+        Err(e) => {
+            eprintln!("Could not connect to {}: {}", database_url, e);
+            None
+        }
+    }
+}
+
 fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
 
@@ -875,25 +2418,54 @@ fn main() -> anyhow::Result<()> {
             let results: Vec<User> = users::table.order(users::name.asc()).load(&mut conn)?;
 
             println!(
-                "{:<5} {:<30} {:<30} {:<10}",
-                "ID", "Name", "Email", "Initials"
+                "{:<5} {:<30} {:<30} {:<10} {:<8} {:<8}",
+                "ID", "Name", "Email", "Initials", "Active", "Role"
             );
-            println!("{}", "-".repeat(80));
+            println!("{}", "-".repeat(97));
             for user in results {
                 println!(
-                    "{:<5} {:<30} {:<30} {:<10}",
+                    "{:<5} {:<30} {:<30} {:<10} {:<8} {:<8}",
                     user.id,
                     user.name,
                     user.email,
-                    user.initials.unwrap_or_default()
+                    user.initials.unwrap_or_default(),
+                    if user.active { "yes" } else { "no" },
+                    user.role
                 );
             }
         }
 
+        Commands::DeactivateUser { email } => {
+            let mut conn = establish_connection();
+
+            let updated = diesel::update(users::table.filter(users::email.eq(&email)))
+                .set(users::active.eq(false))
+                .execute(&mut conn)?;
+            if updated == 0 {
+                anyhow::bail!("No user with email '{}'", email);
+            }
+
+            println!("Deactivated user <{}>", email);
+        }
+
+        Commands::SetRole { email, role } => {
+            let mut conn = establish_connection();
+
+            let updated = diesel::update(users::table.filter(users::email.eq(&email)))
+                .set(users::role.eq(&role))
+                .execute(&mut conn)?;
+            if updated == 0 {
+                anyhow::bail!("No user with email '{}'", email);
+            }
+
+            println!("<{}> is now {}", email, role);
+        }
+
         Commands::CreateVendor {
             prefix,
             name,
             description,
+            number_padding,
         } => {
             // Validate prefix
             if prefix.len() < 2 || prefix.len() > 5 {
@@ -909,6 +2481,7 @@ fn main() -> anyhow::Result<()> {
                 prefix,
                 name,
                 description,
+                number_padding,
             };
 
             diesel::insert_into(vendors::table)
@@ -929,40 +2502,1072 @@ fn main() -> anyhow::Result<()> {
                 .load(&mut conn)?;
 
             println!(
-                "{:<5} {:<10} {:<30} {:<10}",
-                "ID", "Prefix", "Name", "Next #"
+                "{:<5} {:<10} {:<30} {:<10} {:<8}",
+                "ID", "Prefix", "Name", "Next #", "Padding"
             );
-            println!("{}", "-".repeat(60));
+            println!("{}", "-".repeat(69));
             for vendor in results {
                 println!(
-                    "{:<5} {:<10} {:<30} {:<10}",
-                    vendor.id, vendor.prefix, vendor.name, vendor.next_number
+                    "{:<5} {:<10} {:<30} {:<10} {:<8}",
+                    vendor.id,
+                    vendor.prefix,
+                    vendor.name,
+                    vendor.next_number,
+                    vendor.number_padding
                 );
             }
         }
 
-        Commands::ResetSequence { vendor } => {
+        Commands::ListItems {
+            vendor,
+            status,
+            owner,
+            json,
+        } => {
+            list_items(vendor, status, owner, json)?;
+        }
+
+        Commands::ResetSequence { vendor, to } => {
             let mut conn = establish_connection();
 
             let vendor_record: Vendor = vendors::table
                 .filter(vendors::prefix.eq(&vendor))
                 .first(&mut conn)?;
 
+            // Soft-deleted items still hold their ids, so they count too
+            let max_number: Option<i32> = action_items::table
+                .filter(action_items::vendor_id.eq(vendor_record.id))
+                .select(diesel::dsl::max(action_items::number))
+                .first(&mut conn)?;
+            let min_next = max_number.unwrap_or(0) + 1;
+
+            let new_next = match to {
+                Some(n) if n < min_next => anyhow::bail!(
+                    "--to {} would reuse existing item numbers; the highest is {}",
+                    n,
+                    min_next - 1
+                ),
+                Some(n) => n,
+                None => min_next,
+            };
+
+            diesel::update(vendors::table.filter(vendors::id.eq(vendor_record.id)))
+                .set(vendors::next_number.eq(new_next))
+                .execute(&mut conn)?;
+
             println!(
-                "Vendor {} ({}) - current next_number: {}",
-                vendor_record.prefix, vendor_record.name, vendor_record.next_number
+                "Updated vendor '{}' next_number: {} -> {}",
+                vendor_record.prefix, vendor_record.next_number, new_next
             );
-            println!("To reset, manually update the vendors table.");
+        }
+
+        Commands::MergeVendors { from, into, by } => {
+            merge_vendors(&mut establish_connection(), from, into, by)?;
+        }
+
+        Commands::CreateApiKey { user, label } => {
+            let mut conn = establish_connection();
+
+            let user_record: User = users::table
+                .filter(users::email.eq(&user))
+                .first(&mut conn)
+                .optional()?
+                .with_context(|| format!("No user with email '{}'", user))?;
+
+            let key: String = rand::rng()
+                .sample_iter(rand::distr::Alphanumeric)
+                .take(40)
+                .map(char::from)
+                .collect();
+
+            // Stored the same way the backend hashes incoming X-API-Key headers.
+            let new_key = NewApiKey {
+                key_hash: format!("{:x}", Sha256::digest(key.as_bytes())),
+                user_id: user_record.id,
+                label,
+            };
+
+            diesel::insert_into(api_keys::table)
+                .values(&new_key)
+                .execute(&mut conn)?;
+
+            println!(
+                "Created API key '{}' for {} <{}>",
+                new_key.label, user_record.name, user_record.email
+            );
+            println!("{}", key);
+            println!("Store it now; it cannot be shown again.");
+        }
+
+        Commands::AddWebhook {
+            url,
+            status,
+            vendor,
+            secret,
+        } => {
+            if !(url.starts_with("https://") || url.starts_with("http://")) {
+                anyhow::bail!("--url must be an http(s) URL");
+            }
+            // Must match the event types the backend looks up in webhooks.rs
+            let event_type = match status.as_deref().map(normalize_status).transpose()? {
+                Some(status) => format!("status_changed:{}", status),
+                None => "status_changed".to_string(),
+            };
+
+            let mut conn = establish_connection();
+
+            let vendor_id = match &vendor {
+                Some(prefix) => {
+                    let vendor: Vendor = vendors::table
+                        .filter(vendors::prefix.eq(prefix))
+                        .first(&mut conn)
+                        .optional()?
+                        .with_context(|| format!("Vendor with prefix '{}' not found", prefix))?;
+                    Some(vendor.id)
+                }
+                None => None,
+            };
+
+            let generated = secret.is_none();
+            let secret = secret.unwrap_or_else(|| {
+                rand::rng()
+                    .sample_iter(rand::distr::Alphanumeric)
+                    .take(40)
+                    .map(char::from)
+                    .collect()
+            });
+
+            let id: i32 = diesel::insert_into(webhooks::table)
+                .values(&NewWebhook {
+                    url: url.clone(),
+                    event_type: event_type.clone(),
+                    vendor_id,
+                    secret: secret.clone(),
+                })
+                .returning(webhooks::id)
+                .get_result(&mut conn)?;
+
+            println!(
+                "Registered webhook {} for {} ({}) -> {}",
+                id,
+                event_type,
+                vendor.as_deref().unwrap_or("all vendors"),
+                url
+            );
+            if generated {
+                println!("Signing secret: {}", secret);
+            }
         }
 
         Commands::ImportCsv {
             file,
             vendor,
             dry_run,
+            century_pivot,
+            mapping,
+        } => {
+            // A dry run still parses and validates the file without a database
+            let mut conn = if dry_run {
+                try_connection()
+            } else {
+                Some(establish_connection())
+            };
+            import_csv(conn.as_mut(), file, vendor, dry_run, century_pivot, mapping)?;
+        }
+
+        Commands::ExportCsv {
+            vendor,
+            out,
+            status,
         } => {
-            import_csv(file, vendor, dry_run)?;
+            export_csv(&mut establish_connection(), vendor, out, status)?;
+        }
+
+        Commands::ExportJson { out } => {
+            export_json(&mut establish_connection(), out)?;
+        }
+
+        Commands::ImportJson {
+            file,
+            replace,
+            merge: _,
+        } => {
+            import_json(&mut establish_connection(), file, replace)?;
         }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestDb;
+    use diesel::connection::SimpleConnection;
+
+    fn date(y: i32, m: u32, d: u32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(y, m, d)
+    }
+
+    #[test]
+    fn parse_notes_returns_initials_with_date_and_content() {
+        let notes = parse_notes(
+            "3/4/2024 MF: Sent the drawings\n3/11/2024 jd: Vendor confirmed",
+            DEFAULT_CENTURY_PIVOT,
+        );
+
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes[0].date, date(2024, 3, 4));
+        assert_eq!(notes[0].initials.as_deref(), Some("MF"));
+        assert_eq!(notes[0].content, "MF: Sent the drawings");
+        assert_eq!(notes[1].date, date(2024, 3, 11));
+        assert_eq!(notes[1].initials.as_deref(), Some("jd"));
+        assert_eq!(notes[1].content, "jd: Vendor confirmed");
+    }
+
+    #[test]
+    fn parse_notes_appends_continuation_lines() {
+        let notes = parse_notes(
+            "Imported from the old sheet\n3/4/24 MF: First line\n  second line\n\nthird line",
+            DEFAULT_CENTURY_PIVOT,
+        );
+
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes[0].date, None);
+        assert_eq!(notes[0].content, "Imported from the old sheet");
+        assert_eq!(notes[1].date, date(2024, 3, 4));
+        assert_eq!(notes[1].initials.as_deref(), Some("MF"));
+        assert_eq!(notes[1].content, "MF: First line\nsecond line\nthird line");
+    }
+
+    #[test]
+    fn parse_notes_without_initials_has_none() {
+        let notes = parse_notes(
+            "3/4/2024 Waiting on the quote: due Friday",
+            DEFAULT_CENTURY_PIVOT,
+        );
+
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].date, date(2024, 3, 4));
+        assert_eq!(notes[0].initials, None);
+        assert_eq!(notes[0].content, "Waiting on the quote: due Friday");
+    }
+
+    /// Columns read from a mapping file with `contents`.
+    fn mapping(contents: &str) -> anyhow::Result<CsvColumns> {
+        let path =
+            std::env::temp_dir().join(format!("mapping-{:016x}.toml", rand::random::<u64>()));
+        std::fs::write(&path, contents).unwrap();
+        let columns = CsvColumns::from_mapping_file(&path);
+        std::fs::remove_file(&path).unwrap();
+        columns
+    }
+
+    #[test]
+    fn standard_columns_read_the_export_headers() {
+        let header_row = csv::StringRecord::from(CSV_HEADERS.to_vec());
+        let positions = CsvColumns::standard().positions(&header_row).unwrap();
+        assert_eq!(positions, (0..11).map(Some).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn mapped_columns_read_renamed_headers_and_leave_unmapped_fields_blank() {
+        let columns = mapping(
+            r#"
+            action_item_id = "ID"
+            title = "Summary"
+            create_date = "Opened"
+            category = "Area"
+            priority = "Prio"
+            status = "State"
+            owner = "Assigned To"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(columns.id_header(), "ID");
+
+        let header_row = csv::StringRecord::from(vec![
+            "Assigned To",
+            "ID",
+            "Summary",
+            " Opened ",
+            "Area",
+            "Prio",
+            "State",
+            "Comments",
+        ]);
+        let positions = columns.positions(&header_row).unwrap();
+        let record = csv::StringRecord::from(vec![
+            "Ada Lovelace",
+            "AD-5",
+            "Order parts",
+            "3/4/2024",
+            "Thermal",
+            "High",
+            "Open",
+            "ignored",
+        ]);
+        let row = CsvRow::from_record(&record, &positions);
+        assert_eq!(row.action_item_id, "AD-5");
+        assert_eq!(row.title, "Order parts");
+        assert_eq!(row.create_date, "3/4/2024");
+        assert_eq!(row.owner, "Ada Lovelace");
+        assert_eq!(row.status, "Open");
+        assert_eq!(row.created_by, "");
+        assert_eq!(row.notes, "");
+
+        // Short rows read as blank rather than failing
+        let row = CsvRow::from_record(&csv::StringRecord::from(vec!["Ada Lovelace"]), &positions);
+        assert_eq!(row.owner, "Ada Lovelace");
+        assert_eq!(row.title, "");
+    }
+
+    #[test]
+    fn mappings_must_name_required_fields_and_only_known_ones() {
+        let err = mapping(r#"action_item_id = "ID""#).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Mapping file must map required field(s): title, create_date, category, priority, status"
+        );
+
+        let err =
+            mapping("action_item_id = \"ID\"\nowners = \"Owner\"\nassignee = \"Who\"").unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("Unknown field(s) in mapping file: assignee, owners. Fields are: action_item_id, title,"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn positions_report_every_mapped_header_the_file_lacks() {
+        let header_row = csv::StringRecord::from(vec!["Action Item #", "Title", "Status"]);
+        let err = CsvColumns::standard().positions(&header_row).unwrap_err();
+        assert!(
+            err.to_string().starts_with(
+                "Column(s) 'Create Date', 'Created by', 'Due Date', 'Category', 'Owner', "
+            ),
+            "{}",
+            err
+        );
+        assert!(
+            err.to_string().ends_with(
+                "not found in the header row. Columns are: Action Item #, Title, Status"
+            ),
+            "{}",
+            err
+        );
+    }
+
+    fn user(id: i32, name: &str, initials: Option<&str>) -> User {
+        User {
+            id,
+            email: format!("user{}@example.org", id),
+            name: name.to_string(),
+            initials: initials.map(str::to_string),
+            created_at: chrono::Utc::now(),
+            active: true,
+            role: default_role(),
+        }
+    }
+
+    fn row(id: &str, created_by: &str, owner: &str, category: &str) -> CsvRow {
+        CsvRow {
+            action_item_id: id.to_string(),
+            title: "Order parts".to_string(),
+            create_date: "3/4/2024".to_string(),
+            created_by: created_by.to_string(),
+            due_date: String::new(),
+            category: category.to_string(),
+            owner: owner.to_string(),
+            priority: "High".to_string(),
+            status: "Open".to_string(),
+            status_date: String::new(),
+            notes: String::new(),
+        }
+    }
+
+    #[test]
+    fn unresolved_references_are_all_reported_with_unknown_users_grouped() {
+        let users = [
+            user(1, "Mike Fitzgerald", Some("MF")),
+            user(2, "Ada Lovelace", None),
+        ];
+        let rows = [
+            row("AD-001", "M. Fitzgerald", "Ada", "Thermal"),
+            row("AD-002", "Bob", "MF", ""),
+            row("AD-003", "MF", "Bob", &"x".repeat(101)),
+            row("AD-004", "Carol", "", "Thermal"),
+        ];
+        let mut cache = HashMap::new();
+        let (unknown_users, bad_categories) = unresolved_references(&rows, &users, &mut cache);
+
+        assert_eq!(
+            unknown_users,
+            [
+                (
+                    "Bob".to_string(),
+                    vec!["row 2 created_by".to_string(), "row 3 owner".to_string()]
+                ),
+                ("Carol".to_string(), vec!["row 4 created_by".to_string()]),
+            ]
+        );
+        assert_eq!(bad_categories.len(), 2);
+        assert_eq!(bad_categories[0], "Row 2: Item 'AD-002' has no category");
+        assert!(
+            bad_categories[1].starts_with("Row 3: Category 'xxx"),
+            "{}",
+            bad_categories[1]
+        );
+        assert!(bad_categories[1].ends_with("is longer than 100 characters"));
+
+        // Resolved names are cached for the import itself
+        assert_eq!(cache.get("M. Fitzgerald"), Some(&1));
+        assert_eq!(cache.get("Ada"), Some(&2));
+        assert_eq!(cache.get("MF"), Some(&1));
+    }
+
+    #[test]
+    fn imported_ids_are_re_padded_to_the_vendor_padding() {
+        let repad = |id: &str, padding: i32| {
+            let (prefix, number) = parse_item_id(id).unwrap();
+            format_item_id(&prefix, number, padding)
+        };
+        assert_eq!(repad("AD-5", 3), "AD-005");
+        assert_eq!(repad(" AD-06 ", 4), "AD-0006");
+        assert_eq!(repad("AD-1000", 3), "AD-1000");
+        assert_eq!(repad("AD-0042", 1), "AD-42");
+        assert!(parse_item_id("AD5").is_err());
+        assert!(parse_item_id("AD-five").is_err());
+    }
+
+    #[test]
+    fn parse_date_accepts_us_and_iso_formats() {
+        for input in [
+            "3/4/2024",
+            "03/04/2024",
+            "3/4/24",
+            "2024-03-04",
+            " 3/4/2024 ",
+        ] {
+            assert_eq!(
+                parse_date(input, DEFAULT_CENTURY_PIVOT).ok(),
+                date(2024, 3, 4),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_date_splits_two_digit_years_at_the_pivot() {
+        assert_eq!(parse_date("1/2/69", 70).ok(), date(2069, 1, 2));
+        assert_eq!(parse_date("1/2/70", 70).ok(), date(1970, 1, 2));
+        assert_eq!(parse_date("1/2/99", 0).ok(), date(1999, 1, 2));
+        assert_eq!(parse_date("1/2/99", 100).ok(), date(2099, 1, 2));
+    }
+
+    #[test]
+    fn parse_date_rejects_garbage_with_the_original_message() {
+        let err = parse_date(" next tuesday ", DEFAULT_CENTURY_PIVOT).unwrap_err();
+        assert_eq!(err.to_string(), "Cannot parse date: 'next tuesday'");
+        assert!(parse_date("13/1/2024", DEFAULT_CENTURY_PIVOT).is_err());
+        assert!(parse_date("3/4/124", DEFAULT_CENTURY_PIVOT).is_err());
+    }
+
+    /// A path in the temp dir that no other test uses.
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("{:016x}-{}", rand::random::<u64>(), name))
+    }
+
+    /// Everything `export-json` would write for `conn`, for comparing databases.
+    fn dump_json(conn: &mut PgConnection) -> serde_json::Value {
+        serde_json::to_value(load_dump(conn).unwrap()).unwrap()
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    fn import_json_replace_swaps_out_every_table() {
+        let mut source = TestDb::new();
+        let ada = source.user("ada@example.com", "Ada Lovelace", Some("AL"));
+        let vendor = source.vendor("AD");
+        let category = source.category(&vendor, "Optics");
+        source.item(&category, &ada, &ada);
+        let backup = temp_path("backup.json");
+        export_json(&mut source.conn, backup.clone()).unwrap();
+
+        // The target has rows in the tables a dump leaves out, which still reference users
+        // and items: idempotency keys and API keys
+        let mut target = TestDb::new();
+        let bob = target.user("bob@example.com", "Bob Ross", Some("BR"));
+        let vendor = target.vendor("XY");
+        let category = target.category(&vendor, "Paint");
+        let item = target.item(&category, &bob, &bob);
+        diesel::sql_query(
+            "INSERT INTO idempotency_keys (user_id, key, action_item_id) VALUES ($1, 'k', $2)",
+        )
+        .bind::<diesel::sql_types::Integer, _>(bob.id)
+        .bind::<diesel::sql_types::Text, _>(&item.id)
+        .execute(&mut target.conn)
+        .unwrap();
+        diesel::insert_into(api_keys::table)
+            .values(&NewApiKey {
+                key_hash: "hash".to_string(),
+                user_id: bob.id,
+                label: "ci".to_string(),
+            })
+            .execute(&mut target.conn)
+            .unwrap();
+
+        import_json(&mut target.conn, backup.clone(), true).unwrap();
+        std::fs::remove_file(&backup).unwrap();
+
+        assert_eq!(dump_json(&mut target.conn), dump_json(&mut source.conn));
+        let api_key_count: i64 = api_keys::table
+            .count()
+            .get_result(&mut target.conn)
+            .unwrap();
+        assert_eq!(api_key_count, 0);
+    }
+
+    /// A database with at least one row in most of the tables a backup covers.
+    fn populated() -> TestDb {
+        let mut db = TestDb::new();
+        let ada = db.user("ada@example.com", "Ada Lovelace", Some("AL"));
+        let bob = db.user("bob@example.com", "Bob Ross", Some("BR"));
+        let vendor = db.vendor("AD");
+        let category = db.category(&vendor, "Optics");
+        let first = db.item(&category, &ada, &bob);
+        let second = db.item(&category, &bob, &ada);
+        diesel::insert_into(status_history::table)
+            .values(&NewStatusHistory {
+                action_item_id: first.id.clone(),
+                status: "In Progress".to_string(),
+                changed_by_id: bob.id,
+                changed_at: chrono::Utc::now(),
+                comment: None,
+                from_status: Some("New".to_string()),
+            })
+            .execute(&mut db.conn)
+            .unwrap();
+        diesel::insert_into(notes::table)
+            .values(&NewNote {
+                action_item_id: first.id.clone(),
+                note_date: date(2024, 3, 4).unwrap(),
+                author_id: ada.id,
+                content: "Sent the drawings to @BR".to_string(),
+            })
+            .execute(&mut db.conn)
+            .unwrap();
+        for sql in [
+            "INSERT INTO note_mentions (note_id, user_id) SELECT id, $1 FROM notes",
+            "INSERT INTO tags (name) VALUES ('urgent')",
+            "INSERT INTO item_tags (action_item_id, tag_id) SELECT $2, id FROM tags",
+            "INSERT INTO item_links (from_item_id, to_item_id, link_type) VALUES ($2, $3, 'blocks')",
+            "INSERT INTO item_watchers (action_item_id, user_id) VALUES ($3, $1)",
+        ] {
+            diesel::sql_query(sql)
+                .bind::<diesel::sql_types::Integer, _>(bob.id)
+                .bind::<diesel::sql_types::Text, _>(&first.id)
+                .bind::<diesel::sql_types::Text, _>(&second.id)
+                .execute(&mut db.conn)
+                .unwrap_or_else(|e| panic!("{sql}: {e}"));
+        }
+        db
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    fn export_then_import_json_into_an_empty_database_round_trips() {
+        let mut source = populated();
+        let backup = temp_path("backup.json");
+        export_json(&mut source.conn, backup.clone()).unwrap();
+
+        let mut target = TestDb::new();
+        import_json(&mut target.conn, backup.clone(), false).unwrap();
+        std::fs::remove_file(&backup).unwrap();
+
+        let restored = dump_json(&mut target.conn);
+        assert_eq!(restored, dump_json(&mut source.conn));
+        for table in [
+            "status_history",
+            "notes",
+            "note_mentions",
+            "item_tags",
+            "item_links",
+        ] {
+            assert!(
+                !restored[table].as_array().unwrap().is_empty(),
+                "{table} should have rows to compare"
+            );
+        }
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    fn merging_leaves_out_children_of_rows_whose_ids_are_taken() {
+        let mut source = populated();
+        let backup = temp_path("backup.json");
+        export_json(&mut source.conn, backup.clone()).unwrap();
+
+        // The target's user 1 is someone else, and its vendor 1 has another prefix
+        let mut target = TestDb::new();
+        let carol = target.user("carol@example.com", "Carol Danvers", Some("CD"));
+        let vendor = target.vendor("XY");
+        target.category(&vendor, "Paint");
+        import_json(&mut target.conn, backup.clone(), false).unwrap();
+        std::fs::remove_file(&backup).unwrap();
+
+        let restored = dump_json(&mut target.conn);
+        // Carol keeps id 1; Bob (id 2 in the dump) came across untouched
+        let emails: Vec<&str> = restored["users"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|u| u["email"].as_str().unwrap())
+            .collect();
+        assert_eq!(emails, ["carol@example.com", "bob@example.com"]);
+        assert_eq!(restored["users"][0]["id"], carol.id);
+        // Everything under the clashing vendor, category and user stayed behind
+        for table in [
+            "action_items",
+            "status_history",
+            "notes",
+            "note_mentions",
+            "item_tags",
+            "item_links",
+            "item_watchers",
+        ] {
+            assert_eq!(restored[table], serde_json::json!([]), "{table}");
+        }
+        // The tag has no parent, so it merges
+        assert_eq!(restored["tags"][0]["name"], "urgent");
+    }
+
+    /// A spreadsheet export in the importer's format: two info rows, the header, `rows`.
+    fn csv_file(rows: &[[&str; 11]]) -> PathBuf {
+        let path = temp_path("items.csv");
+        let mut writer = csv::WriterBuilder::new()
+            .flexible(true)
+            .from_path(&path)
+            .unwrap();
+        writer.write_record(["Vendor AD Action Items"]).unwrap();
+        writer.write_record(["Exported 03/01/2024"]).unwrap();
+        writer.write_record(CSV_HEADERS).unwrap();
+        for row in rows {
+            writer.write_record(row).unwrap();
+        }
+        writer.flush().unwrap();
+        path
+    }
+
+    /// `item_id`'s status history, oldest first, as (status, changed on, from status).
+    fn timeline(
+        conn: &mut PgConnection,
+        item_id: &str,
+    ) -> Vec<(String, NaiveDate, Option<String>)> {
+        status_history::table
+            .filter(status_history::action_item_id.eq(item_id))
+            .order(status_history::id)
+            .select((
+                status_history::status,
+                status_history::changed_at,
+                status_history::from_status,
+            ))
+            .load::<(String, chrono::DateTime<chrono::Utc>, Option<String>)>(conn)
+            .unwrap()
+            .into_iter()
+            .map(|(status, at, from)| (status, at.date_naive(), from))
+            .collect()
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    fn import_csv_dates_the_current_status_by_its_status_date() {
+        let mut db = TestDb::new();
+        db.user("mf@example.com", "M. Fitzgerald", Some("MF"));
+        db.vendor("AD");
+        let file = csv_file(&[
+            [
+                "AD-001",
+                "Later status",
+                "1/2/2024",
+                "M. Fitzgerald",
+                "",
+                "Optics",
+                "",
+                "High",
+                "Complete",
+                "2/3/2024",
+                "",
+            ],
+            [
+                "AD-002",
+                "Blank status date",
+                "1/2/2024",
+                "M. Fitzgerald",
+                "",
+                "Optics",
+                "",
+                "High",
+                "Blocked",
+                "",
+                "",
+            ],
+            [
+                "AD-003",
+                "Status date too early",
+                "1/2/2024",
+                "M. Fitzgerald",
+                "",
+                "Optics",
+                "",
+                "High",
+                "Complete",
+                "12/1/2023",
+                "",
+            ],
+            [
+                "AD-004",
+                "Still new",
+                "1/2/2024",
+                "M. Fitzgerald",
+                "",
+                "Optics",
+                "",
+                "High",
+                "New",
+                "2/3/2024",
+                "",
+            ],
+        ]);
+        import_csv(
+            Some(&mut db.conn),
+            file.clone(),
+            None,
+            false,
+            DEFAULT_CENTURY_PIVOT,
+            None,
+        )
+        .unwrap();
+        std::fs::remove_file(&file).unwrap();
+
+        let created = date(2024, 1, 2).unwrap();
+        let new = |on| ("New".to_string(), on, None);
+        assert_eq!(
+            timeline(&mut db.conn, "AD-001"),
+            [
+                new(created),
+                (
+                    "Complete".to_string(),
+                    date(2024, 2, 3).unwrap(),
+                    Some("New".to_string())
+                ),
+            ]
+        );
+        // Without a later Status Date there is no separate "New" entry to tie with
+        assert_eq!(
+            timeline(&mut db.conn, "AD-002"),
+            [("Blocked".to_string(), created, None)]
+        );
+        assert_eq!(
+            timeline(&mut db.conn, "AD-003"),
+            [("Complete".to_string(), created, None)]
+        );
+        assert_eq!(timeline(&mut db.conn, "AD-004"), [new(created)]);
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    fn merge_vendors_renumbers_into_the_target_and_audits_the_new_ids() {
+        let mut db = TestDb::new();
+        let admin = db.user("admin@example.com", "Ada Admin", Some("AA"));
+        let source = db.vendor("XX");
+        let target = db.vendor("YY");
+        let shared_name = db.category(&source, "Design");
+        let source_only = db.category(&source, "Testing");
+        let target_design = db.category(&target, "Design");
+        db.item(&target_design, &admin, &admin);
+        let first = db.item(&shared_name, &admin, &admin);
+        let second = db.item(&source_only, &admin, &admin);
+
+        merge_vendors(&mut db.conn, "XX".into(), "YY".into(), admin.email.clone()).unwrap();
+
+        let items: Vec<(String, i32, i32)> = action_items::table
+            .order(action_items::number)
+            .select((
+                action_items::id,
+                action_items::vendor_id,
+                action_items::category_id,
+            ))
+            .load(&mut db.conn)
+            .unwrap();
+        assert_eq!(
+            items,
+            [
+                ("YY-001".to_string(), target.id, target_design.id),
+                ("YY-002".to_string(), target.id, target_design.id),
+                ("YY-003".to_string(), target.id, source_only.id),
+            ]
+        );
+
+        let audit: Vec<_> = item_audit::table
+            .order(item_audit::id)
+            .load::<ItemAudit>(&mut db.conn)
+            .unwrap()
+            .into_iter()
+            .map(|a| {
+                (
+                    a.action_item_id,
+                    a.field,
+                    a.old_value,
+                    a.new_value,
+                    a.changed_by_id,
+                )
+            })
+            .collect();
+        let some = |s: &str| Some(s.to_string());
+        assert_eq!(
+            audit,
+            [
+                (
+                    "YY-002".into(),
+                    "id".into(),
+                    some(&first.id),
+                    some("YY-002"),
+                    admin.id
+                ),
+                (
+                    "YY-002".into(),
+                    "category_id".into(),
+                    some(&shared_name.id.to_string()),
+                    some(&target_design.id.to_string()),
+                    admin.id
+                ),
+                (
+                    "YY-003".into(),
+                    "id".into(),
+                    some(&second.id),
+                    some("YY-003"),
+                    admin.id
+                ),
+            ]
+        );
+
+        let vendors: Vec<(String, i32, bool)> = vendors::table
+            .order(vendors::id)
+            .select((vendors::prefix, vendors::next_number, vendors::archived))
+            .load(&mut db.conn)
+            .unwrap();
+        assert_eq!(
+            vendors,
+            [("XX".to_string(), 3, true), ("YY".to_string(), 4, false)]
+        );
+        let categories: Vec<i32> = categories::table
+            .order(categories::id)
+            .select(categories::id)
+            .load(&mut db.conn)
+            .unwrap();
+        assert_eq!(categories, [source_only.id, target_design.id]);
+
+        let err = merge_vendors(
+            &mut db.conn,
+            "XX".into(),
+            "YY".into(),
+            "nobody@example.com".into(),
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "No user with email 'nobody@example.com'");
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    fn merge_vendors_waits_for_a_number_claimed_on_the_target() {
+        let mut db = TestDb::new();
+        let admin = db.user("admin@example.com", "Ada Admin", Some("AA"));
+        let source = db.vendor("XX");
+        let target = db.vendor("YY");
+        let source_design = db.category(&source, "Design");
+        let target_design = db.category(&target, "Design");
+        db.item(&source_design, &admin, &admin);
+
+        // Another session is part-way through creating YY-001, the way the backend does:
+        // it has claimed the number but not committed yet
+        let mut creating = db.connect();
+        creating.batch_execute("BEGIN").unwrap();
+        let claimed: i32 = diesel::update(vendors::table.filter(vendors::id.eq(target.id)))
+            .set(vendors::next_number.eq(vendors::next_number + 1))
+            .returning(vendors::next_number - 1)
+            .get_result(&mut creating)
+            .unwrap();
+
+        std::thread::scope(|scope| {
+            let merge = scope.spawn(|| {
+                merge_vendors(&mut db.conn, "XX".into(), "YY".into(), admin.email.clone())
+            });
+            std::thread::sleep(std::time::Duration::from_millis(300));
+            diesel::insert_into(action_items::table)
+                .values(&NewActionItem {
+                    id: format_item_id("YY", claimed, 3),
+                    vendor_id: target.id,
+                    number: claimed,
+                    title: "Created during the merge".to_string(),
+                    create_date: date(2024, 3, 4).unwrap(),
+                    created_by_id: admin.id,
+                    due_date: None,
+                    owner_id: admin.id,
+                    priority: "Medium".to_string(),
+                    description: None,
+                    category_id: target_design.id,
+                })
+                .execute(&mut creating)
+                .unwrap();
+            creating.batch_execute("COMMIT").unwrap();
+            merge.join().unwrap().unwrap();
+        });
+
+        let ids: Vec<String> = action_items::table
+            .order(action_items::number)
+            .select(action_items::id)
+            .load(&mut db.conn)
+            .unwrap();
+        assert_eq!(ids, ["YY-001", "YY-002"]);
+        let next_number: i32 = vendors::table
+            .find(target.id)
+            .select(vendors::next_number)
+            .first(&mut db.conn)
+            .unwrap();
+        assert_eq!(next_number, 3);
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    fn export_csv_then_import_into_an_empty_database_round_trips() {
+        let mut source = populated();
+        let exported = temp_path("exported.csv");
+        export_csv(&mut source.conn, "AD".into(), exported.clone(), None).unwrap();
+
+        let mut target = TestDb::new();
+        target.user("ada@example.com", "Ada Lovelace", Some("AL"));
+        target.user("bob@example.com", "Bob Ross", Some("BR"));
+        target.vendor("AD");
+        import_csv(
+            Some(&mut target.conn),
+            exported.clone(),
+            None,
+            false,
+            DEFAULT_CENTURY_PIVOT,
+            None,
+        )
+        .unwrap();
+        let reexported = temp_path("reexported.csv");
+        export_csv(&mut target.conn, "AD".into(), reexported.clone(), None).unwrap();
+
+        let first = std::fs::read_to_string(&exported).unwrap();
+        let second = std::fs::read_to_string(&reexported).unwrap();
+        std::fs::remove_file(&exported).unwrap();
+        std::fs::remove_file(&reexported).unwrap();
+        assert_eq!(second, first);
+        assert!(first.contains("Ada Lovelace,,Optics,Bob Ross,Medium,In Progress,"));
+        assert!(first.contains("03/04/2024 AL: Sent the drawings to @BR"));
+
+        let note_authors: Vec<String> = notes::table
+            .inner_join(users::table.on(users::id.eq(notes::author_id)))
+            .select(users::name)
+            .load(&mut target.conn)
+            .unwrap();
+        assert_eq!(note_authors, ["Ada Lovelace"]);
+    }
+
+    #[test]
+    fn dry_run_without_a_database_still_validates_the_file() {
+        let row = |create_date| {
+            [
+                "AD-001",
+                "Check the optics",
+                create_date,
+                "Nobody Known",
+                "",
+                "Optics",
+                "Nobody Known",
+                "High",
+                "In Progress",
+                "",
+                "",
+            ]
+        };
+        let good = csv_file(&[row("1/2/2024")]);
+        let bad = csv_file(&[row("13/45/2024")]);
+        let import = |file: &PathBuf, dry_run| {
+            import_csv(
+                None,
+                file.clone(),
+                None,
+                dry_run,
+                DEFAULT_CENTURY_PIVOT,
+                None,
+            )
+        };
+
+        import(&good, true).unwrap();
+        assert_eq!(
+            import(&bad, true).unwrap_err().to_string(),
+            "1 validation error(s) found"
+        );
+        assert_eq!(
+            import(&good, false).unwrap_err().to_string(),
+            "A database connection is required to import"
+        );
+        std::fs::remove_file(&good).unwrap();
+        std::fs::remove_file(&bad).unwrap();
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    fn imported_notes_are_attributed_to_the_initials_that_signed_them() {
+        let mut db = TestDb::new();
+        db.user("mf@example.com", "Mike Fitzgerald", Some("MF"));
+        db.user("jd@example.com", "Jane Doe", Some("JD"));
+        db.user("al@example.com", "Ada Lovelace", Some("AL"));
+        db.vendor("AD");
+        let file = csv_file(&[[
+            "AD-001",
+            "Check the optics",
+            "1/2/2024",
+            "Ada Lovelace",
+            "",
+            "Optics",
+            "Ada Lovelace",
+            "High",
+            "In Progress",
+            "",
+            "3/4/2024 MF: Sent the drawings\n3/5/2024 jd: Vendor confirmed\n3/6/2024 Unsigned",
+        ]]);
+        import_csv(
+            Some(&mut db.conn),
+            file.clone(),
+            None,
+            false,
+            DEFAULT_CENTURY_PIVOT,
+            None,
+        )
+        .unwrap();
+        std::fs::remove_file(&file).unwrap();
+
+        let authors: Vec<(NaiveDate, String)> = notes::table
+            .inner_join(users::table.on(users::id.eq(notes::author_id)))
+            .order(notes::note_date)
+            .select((notes::note_date, users::name))
+            .load(&mut db.conn)
+            .unwrap();
+        assert_eq!(
+            authors,
+            [
+                (date(2024, 3, 4).unwrap(), "Mike Fitzgerald".to_string()),
+                (date(2024, 3, 5).unwrap(), "Jane Doe".to_string()),
+                // No initials: the item's creator
+                (date(2024, 3, 6).unwrap(), "Ada Lovelace".to_string()),
+            ]
+        );
+    }
+}
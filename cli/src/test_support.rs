@@ -0,0 +1,146 @@
+//! Helpers for tests that need a database.
+//!
+//! Each [`TestDb`] is a fresh database created next to `TEST_DATABASE_URL` and migrated
+//! from `migrations/`, the same way the backend's tests set theirs up. It is dropped again
+//! when the `TestDb` goes out of scope. Tests using it are `#[ignore]`d, so run them with
+//! `TEST_DATABASE_URL=postgres://... cargo test -p cli -- --ignored`.
+
+use diesel::connection::SimpleConnection;
+use diesel::prelude::*;
+use diesel::PgConnection;
+
+use crate::schema::{action_items, categories, users, vendors};
+use crate::{
+    format_item_id, ActionItem, Category, NewActionItem, NewCategory, NewUser, NewVendor, User,
+    Vendor,
+};
+
+pub struct TestDb {
+    pub conn: PgConnection,
+    admin_url: String,
+    name: String,
+}
+
+/// `url` pointing at database `name` instead, keeping any query parameters.
+fn with_database(url: &str, name: &str) -> String {
+    let (base, params) = match url.split_once('?') {
+        Some((base, params)) => (base, Some(params)),
+        None => (url, None),
+    };
+    let server = base.rsplit_once('/').map_or(base, |(server, _)| server);
+    match params {
+        Some(params) => format!("{}/{}?{}", server, name, params),
+        None => format!("{}/{}", server, name),
+    }
+}
+
+impl TestDb {
+    pub fn new() -> Self {
+        let admin_url = std::env::var("TEST_DATABASE_URL")
+            .expect("TEST_DATABASE_URL must be set to run database tests");
+        let name = format!("tracker_cli_test_{:016x}", rand::random::<u64>());
+
+        let mut admin =
+            PgConnection::establish(&admin_url).expect("Failed to connect to TEST_DATABASE_URL");
+        admin
+            .batch_execute(&format!("CREATE DATABASE {}", name))
+            .expect("Failed to create test database");
+
+        let mut conn = PgConnection::establish(&with_database(&admin_url, &name))
+            .expect("Failed to connect to test database");
+        let migrations_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../migrations");
+        let mut migrations: Vec<_> = std::fs::read_dir(migrations_dir)
+            .expect("Failed to read migrations")
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        migrations.sort();
+        for migration in migrations {
+            let sql = std::fs::read_to_string(migration.join("up.sql")).unwrap();
+            conn.batch_execute(&sql)
+                .unwrap_or_else(|e| panic!("Migration {} failed: {}", migration.display(), e));
+        }
+
+        Self {
+            conn,
+            admin_url,
+            name,
+        }
+    }
+
+    /// A second connection to the same database, for tests that need two sessions.
+    pub fn connect(&self) -> PgConnection {
+        PgConnection::establish(&with_database(&self.admin_url, &self.name))
+            .expect("Failed to connect to test database")
+    }
+
+    pub fn user(&mut self, email: &str, name: &str, initials: Option<&str>) -> User {
+        diesel::insert_into(users::table)
+            .values(&NewUser {
+                email: email.to_string(),
+                name: name.to_string(),
+                initials: initials.map(str::to_string),
+            })
+            .get_result(&mut self.conn)
+            .expect("Failed to create user")
+    }
+
+    pub fn vendor(&mut self, prefix: &str) -> Vendor {
+        diesel::insert_into(vendors::table)
+            .values(&NewVendor {
+                prefix: prefix.to_string(),
+                name: format!("Vendor {}", prefix),
+                description: None,
+                number_padding: 3,
+            })
+            .get_result(&mut self.conn)
+            .expect("Failed to create vendor")
+    }
+
+    pub fn category(&mut self, vendor: &Vendor, name: &str) -> Category {
+        diesel::insert_into(categories::table)
+            .values(&NewCategory {
+                vendor_id: vendor.id,
+                name: name.to_string(),
+            })
+            .get_result(&mut self.conn)
+            .expect("Failed to create category")
+    }
+
+    /// An item under `category`'s vendor, numbered from the vendor's sequence.
+    pub fn item(&mut self, category: &Category, created_by: &User, owner: &User) -> ActionItem {
+        let vendor: Vendor =
+            diesel::update(vendors::table.filter(vendors::id.eq(category.vendor_id)))
+                .set(vendors::next_number.eq(vendors::next_number + 1))
+                .get_result(&mut self.conn)
+                .expect("Failed to claim item number");
+        let number = vendor.next_number - 1;
+
+        diesel::insert_into(action_items::table)
+            .values(&NewActionItem {
+                id: format_item_id(&vendor.prefix, number, vendor.number_padding),
+                vendor_id: vendor.id,
+                number,
+                title: format!("Item {}", number),
+                create_date: chrono::Utc::now().date_naive(),
+                created_by_id: created_by.id,
+                due_date: None,
+                owner_id: owner.id,
+                priority: "Medium".to_string(),
+                description: None,
+                category_id: category.id,
+            })
+            .get_result(&mut self.conn)
+            .expect("Failed to create item")
+    }
+}
+
+impl Drop for TestDb {
+    fn drop(&mut self) {
+        if let Ok(mut admin) = PgConnection::establish(&self.admin_url) {
+            let _ = admin.batch_execute(&format!(
+                "DROP DATABASE IF EXISTS {} WITH (FORCE)",
+                self.name
+            ));
+        }
+    }
+}
@@ -0,0 +1,9 @@
+use yew_router::utils::base_url;
+
+/// Resolve an absolute app path (e.g. `/api/items`) against the deployment's base path.
+///
+/// The backend rewrites `<base href>` in `index.html` from `PUBLIC_URL`, so when the app is
+/// served under a sub-path such as `/tracker`, `/api/items` becomes `/tracker/api/items`.
+pub fn url(path: &str) -> String {
+    format!("{}{}", base_url().unwrap_or_default(), path)
+}
@@ -1,13 +1,23 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, SecondsFormat, Utc};
 use gloo_net::http::Request;
 use gloo_storage::{LocalStorage, Storage};
 use js_sys::{Date, Object, Reflect};
-use shared::{ActivityEntry, ActivityEventType};
+use shared::{ActivityEntry, ActivityEventType, ActivityResponse};
 use wasm_bindgen::JsValue;
 use yew::prelude::*;
 
+use crate::api;
+
 const STORAGE_KEY: &str = "activity_last_viewed";
 
+/// `since` value that asks the server for activity from all time.
+const ALL_TIME: &str = "1970-01-01T00:00:00Z";
+
+/// RFC 3339 timestamp with a `Z` suffix, so it survives unencoded in a query string.
+fn now_rfc3339() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
 fn format_relative_time(dt: &DateTime<Utc>) -> String {
     let js_date = Date::new(&JsValue::from_f64(dt.timestamp_millis() as f64));
     let options = Object::new();
@@ -31,35 +41,42 @@ pub struct ActivitySidebarProps {
 #[function_component(ActivitySidebar)]
 pub fn activity_sidebar(props: &ActivitySidebarProps) -> Html {
     let entries = use_state(Vec::<ActivityEntry>::new);
+    let has_more = use_state(|| false);
+    let show_older = use_state(|| false);
     let loading = use_state(|| true);
 
     {
         let entries = entries.clone();
+        let has_more = has_more.clone();
         let loading = loading.clone();
         let refresh = props.refresh_trigger;
+        let older = *show_older;
 
-        use_effect_with(refresh, move |_| {
+        use_effect_with((refresh, older), move |_| {
             wasm_bindgen_futures::spawn_local(async move {
-                let since: String =
-                    LocalStorage::get(STORAGE_KEY).unwrap_or_else(|_| String::new());
+                let since: String = if older {
+                    ALL_TIME.to_string()
+                } else {
+                    LocalStorage::get(STORAGE_KEY).unwrap_or_else(|_| String::new())
+                };
 
                 let url = if since.is_empty() {
-                    "/api/activity?limit=50".to_string()
+                    api::url("/api/activity?limit=50")
                 } else {
-                    format!("/api/activity?since={}&limit=50", since)
+                    api::url(&format!("/api/activity?since={}&limit=50", since))
                 };
 
                 match Request::get(&url).send().await {
                     Ok(resp) if resp.ok() => {
-                        if let Ok(data) = resp.json::<Vec<ActivityEntry>>().await {
-                            entries.set(data);
+                        if let Ok(data) = resp.json::<ActivityResponse>().await {
+                            entries.set(data.entries);
+                            has_more.set(data.has_more);
                         }
                     }
                     _ => {}
                 }
 
-                let now = Utc::now().to_rfc3339();
-                let _ = LocalStorage::set(STORAGE_KEY, now);
+                let _ = LocalStorage::set(STORAGE_KEY, now_rfc3339());
 
                 loading.set(false);
             });
@@ -71,14 +88,34 @@ pub fn activity_sidebar(props: &ActivitySidebarProps) -> Html {
 
     let on_clear = {
         let entries = entries.clone();
+        let has_more = has_more.clone();
+        let show_older = show_older.clone();
         Callback::from(move |e: MouseEvent| {
             e.stop_propagation();
-            let now = Utc::now().to_rfc3339();
-            let _ = LocalStorage::set(STORAGE_KEY, now);
+            let _ = LocalStorage::set(STORAGE_KEY, now_rfc3339());
             entries.set(Vec::new());
+            has_more.set(false);
+            show_older.set(false);
+        })
+    };
+
+    let on_show_older = {
+        let show_older = show_older.clone();
+        let loading = loading.clone();
+        Callback::from(move |_: MouseEvent| {
+            loading.set(true);
+            show_older.set(true);
         })
     };
 
+    let older_btn = if *has_more && !*show_older {
+        html! {
+            <button class="activity-older-btn" onclick={on_show_older}>{ "See older" }</button>
+        }
+    } else {
+        html! {}
+    };
+
     let clear_btn = if entry_count > 0 {
         html! {
             <button class="activity-clear-btn" onclick={on_clear}>{ "Clear" }</button>
@@ -90,46 +127,54 @@ pub fn activity_sidebar(props: &ActivitySidebarProps) -> Html {
     let activity_content = if *loading {
         html! { <p class="activity-empty">{ "Loading..." }</p> }
     } else if entries.is_empty() {
-        html! { <p class="activity-empty">{ "No new activity." }</p> }
+        html! {
+            <>
+                <p class="activity-empty">{ "No new activity." }</p>
+                { older_btn }
+            </>
+        }
     } else {
         html! {
-            <ul class="activity-list">
-                { for entries.iter().map(|entry| {
-                    let item_id = entry.item_id.clone();
-                    let on_click = {
-                        let on_select = props.on_select_item.clone();
-                        let id = item_id.clone();
-                        Callback::from(move |_: MouseEvent| {
-                            on_select.emit(id.clone());
-                        })
-                    };
-
-                    let type_class = match entry.event_type {
-                        ActivityEventType::NoteAdded => "activity-type-note",
-                        ActivityEventType::StatusChanged => "activity-type-status",
-                    };
-
-                    let type_label = match entry.event_type {
-                        ActivityEventType::NoteAdded => "added a note",
-                        ActivityEventType::StatusChanged => "changed status",
-                    };
-
-                    html! {
-                        <li class={classes!("activity-entry", type_class)} onclick={on_click}>
-                            <div class="activity-entry-header">
-                                <span class="activity-item-id">{ &entry.item_id }</span>
-                                <span class="activity-time">{ format_relative_time(&entry.timestamp) }</span>
-                            </div>
-                            <div class="activity-actor">
-                                { &entry.actor_name }
-                                { " " }
-                                { type_label }
-                            </div>
-                            <div class="activity-detail">{ &entry.detail }</div>
-                        </li>
-                    }
-                })}
-            </ul>
+            <>
+                <ul class="activity-list">
+                    { for entries.iter().map(|entry| {
+                        let item_id = entry.item_id.clone();
+                        let on_click = {
+                            let on_select = props.on_select_item.clone();
+                            let id = item_id.clone();
+                            Callback::from(move |_: MouseEvent| {
+                                on_select.emit(id.clone());
+                            })
+                        };
+
+                        let type_class = match entry.event_type {
+                            ActivityEventType::NoteAdded => "activity-type-note",
+                            ActivityEventType::StatusChanged => "activity-type-status",
+                        };
+
+                        let type_label = match entry.event_type {
+                            ActivityEventType::NoteAdded => "added a note",
+                            ActivityEventType::StatusChanged => "changed status",
+                        };
+
+                        html! {
+                            <li class={classes!("activity-entry", type_class)} onclick={on_click}>
+                                <div class="activity-entry-header">
+                                    <span class="activity-item-id">{ &entry.item_id }</span>
+                                    <span class="activity-time">{ format_relative_time(&entry.timestamp) }</span>
+                                </div>
+                                <div class="activity-actor">
+                                    { &entry.actor_name }
+                                    { " " }
+                                    { type_label }
+                                </div>
+                                <div class="activity-detail">{ &entry.detail }</div>
+                            </li>
+                        }
+                    })}
+                </ul>
+                { older_btn }
+            </>
         }
     };
 
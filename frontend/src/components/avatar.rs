@@ -0,0 +1,40 @@
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct AvatarProps {
+    pub name: String,
+    #[prop_or_default]
+    pub initials: Option<String>,
+}
+
+fn name_to_color(name: &str) -> String {
+    let hash: u32 = name
+        .bytes()
+        .fold(0u32, |acc, b| acc.wrapping_mul(31).wrapping_add(b as u32));
+    let hue = hash % 360;
+    format!("hsl({}, 65%, 45%)", hue)
+}
+
+fn get_initials(name: &str, fallback_initials: Option<&str>) -> String {
+    if let Some(initials) = fallback_initials {
+        return initials.to_string();
+    }
+    name.split_whitespace()
+        .filter_map(|word| word.chars().next())
+        .take(2)
+        .collect::<String>()
+        .to_uppercase()
+}
+
+/// Colored circle showing a user's initials, with their full name as a tooltip.
+#[function_component(Avatar)]
+pub fn avatar(props: &AvatarProps) -> Html {
+    let initials = get_initials(&props.name, props.initials.as_deref());
+    let color = name_to_color(&props.name);
+
+    html! {
+        <span class="user-avatar" style={format!("background-color: {}", color)} title={props.name.clone()}>
+            { initials }
+        </span>
+    }
+}
@@ -3,10 +3,18 @@ use web_sys::window;
 use yew::prelude::*;
 use yew_router::prelude::*;
 
+use crate::api;
 use crate::Route;
 
+#[derive(Properties, PartialEq)]
+pub struct HeaderProps {
+    /// Shows a Users button that emits this when clicked.
+    #[prop_or_default]
+    pub on_manage_users: Option<Callback<()>>,
+}
+
 #[function_component(Header)]
-pub fn header() -> Html {
+pub fn header(props: &HeaderProps) -> Html {
     let logging_out = use_state(|| false);
 
     let on_logout = {
@@ -15,7 +23,7 @@ pub fn header() -> Html {
             let logging_out = logging_out.clone();
             logging_out.set(true);
             wasm_bindgen_futures::spawn_local(async move {
-                let _ = Request::post("/auth/logout").send().await;
+                let _ = Request::post(&api::url("/auth/logout")).send().await;
                 if let Some(w) = window() {
                     let _ = w.location().reload();
                 }
@@ -29,9 +37,16 @@ pub fn header() -> Html {
                 <Link<Route> to={Route::Home}>
                     <h1>{ "Cosmic Frontier" }<span class="header-subtitle">{ " Action Tracker" }</span></h1>
                 </Link<Route>>
-                <button class="btn-logout" onclick={on_logout} disabled={*logging_out}>
-                    { if *logging_out { "Logging out..." } else { "Logout" } }
-                </button>
+                <div class="header-buttons">
+                    if let Some(on_manage_users) = props.on_manage_users.clone() {
+                        <button class="btn-logout" onclick={Callback::from(move |_: MouseEvent| on_manage_users.emit(()))}>
+                            { "Users" }
+                        </button>
+                    }
+                    <button class="btn-logout" onclick={on_logout} disabled={*logging_out}>
+                        { if *logging_out { "Logging out..." } else { "Logout" } }
+                    </button>
+                </div>
             </nav>
         </header>
     }
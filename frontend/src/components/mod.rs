@@ -1,5 +1,9 @@
 pub mod activity_sidebar;
+pub mod avatar;
 pub mod header;
+pub mod toast;
 
 pub use activity_sidebar::ActivitySidebar;
+pub use avatar::Avatar;
 pub use header::Header;
+pub use toast::{use_toast, ToastProvider};
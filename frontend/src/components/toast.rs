@@ -0,0 +1,117 @@
+use gloo_timers::callback::Timeout;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use yew::prelude::*;
+
+/// How long a toast stays up before dismissing itself.
+const TOAST_DURATION_MS: u32 = 4000;
+
+static NEXT_TOAST_ID: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Clone, Copy, PartialEq)]
+enum ToastKind {
+    Success,
+    Error,
+}
+
+#[derive(Clone, PartialEq)]
+struct Toast {
+    id: u32,
+    kind: ToastKind,
+    message: String,
+}
+
+enum ToastAction {
+    Show(Toast),
+    Dismiss(u32),
+}
+
+#[derive(Default, PartialEq)]
+struct ToastList {
+    toasts: Vec<Toast>,
+}
+
+impl Reducible for ToastList {
+    type Action = ToastAction;
+
+    fn reduce(self: Rc<Self>, action: ToastAction) -> Rc<Self> {
+        let mut toasts = self.toasts.clone();
+        match action {
+            ToastAction::Show(toast) => toasts.push(toast),
+            ToastAction::Dismiss(id) => toasts.retain(|t| t.id != id),
+        }
+        Rc::new(Self { toasts })
+    }
+}
+
+/// Shows toasts from anywhere under a `ToastProvider`; get one with `use_toast`.
+#[derive(Clone, PartialEq)]
+pub struct ToastHandle {
+    dispatcher: UseReducerDispatcher<ToastList>,
+}
+
+impl ToastHandle {
+    pub fn success(&self, message: impl Into<String>) {
+        self.show(ToastKind::Success, message.into());
+    }
+
+    pub fn error(&self, message: impl Into<String>) {
+        self.show(ToastKind::Error, message.into());
+    }
+
+    fn show(&self, kind: ToastKind, message: String) {
+        let id = NEXT_TOAST_ID.fetch_add(1, Ordering::Relaxed);
+        self.dispatcher
+            .dispatch(ToastAction::Show(Toast { id, kind, message }));
+
+        let dispatcher = self.dispatcher.clone();
+        Timeout::new(TOAST_DURATION_MS, move || {
+            dispatcher.dispatch(ToastAction::Dismiss(id));
+        })
+        .forget();
+    }
+}
+
+#[hook]
+pub fn use_toast() -> ToastHandle {
+    use_context::<ToastHandle>().expect("use_toast needs a ToastProvider above it")
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ToastProviderProps {
+    pub children: Html,
+}
+
+/// Provides a `ToastHandle` to its children and renders their toasts stacked in the
+/// bottom-right corner. A toast dismisses itself after a few seconds, or when clicked.
+#[function_component(ToastProvider)]
+pub fn toast_provider(props: &ToastProviderProps) -> Html {
+    let list = use_reducer(ToastList::default);
+    let handle = ToastHandle {
+        dispatcher: list.dispatcher(),
+    };
+
+    html! {
+        <ContextProvider<ToastHandle> context={handle}>
+            { props.children.clone() }
+            <div class="toast-stack">
+                { for list.toasts.iter().map(|toast| {
+                    let dispatcher = list.dispatcher();
+                    let id = toast.id;
+                    let onclick = Callback::from(move |_: MouseEvent| {
+                        dispatcher.dispatch(ToastAction::Dismiss(id));
+                    });
+                    let class = match toast.kind {
+                        ToastKind::Success => "toast toast-success",
+                        ToastKind::Error => "toast toast-error",
+                    };
+                    html! {
+                        <div key={id} class={class} role="status" onclick={onclick}>
+                            { &toast.message }
+                        </div>
+                    }
+                }) }
+            </div>
+        </ContextProvider<ToastHandle>>
+    }
+}
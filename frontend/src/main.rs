@@ -2,7 +2,9 @@ use gloo_net::http::Request;
 use yew::prelude::*;
 use yew_router::prelude::*;
 
+mod api;
 mod components;
+mod markdown;
 mod pages;
 
 #[derive(Clone, Routable, PartialEq)]
@@ -32,8 +34,12 @@ fn app() -> Html {
         let auth_state = auth_state.clone();
         use_effect_with((), move |_| {
             wasm_bindgen_futures::spawn_local(async move {
-                match Request::get("/auth/me").send().await {
-                    Ok(resp) if resp.ok() => auth_state.set(Some(true)),
+                match Request::get(&api::url("/auth/me")).send().await {
+                    Ok(resp) if resp.ok() => {
+                        auth_state.set(Some(true));
+                        // Push the session's expiry out on every visit; best effort
+                        let _ = Request::post(&api::url("/auth/refresh")).send().await;
+                    }
                     _ => auth_state.set(Some(false)),
                 }
             });
@@ -50,11 +56,14 @@ fn app() -> Html {
             </div>
         },
         Some(false) => html! { <pages::login::Login /> },
+        // BrowserRouter takes its basename from the `<base href>` the backend injects.
         Some(true) => html! {
             <BrowserRouter>
-                <div class="container">
-                    <Switch<Route> render={switch} />
-                </div>
+                <components::ToastProvider>
+                    <div class="container">
+                        <Switch<Route> render={switch} />
+                    </div>
+                </components::ToastProvider>
             </BrowserRouter>
         },
     }
@@ -0,0 +1,279 @@
+//! Markdown for note content and item descriptions, rendered straight to Yew nodes.
+//!
+//! Nothing from the source is ever injected as HTML: raw HTML shows up as the text it
+//! is, and links only keep http(s) and mailto targets. Bare URLs are auto-linked as
+//! before, and resolved `@mentions` become chips, except inside code and existing links.
+
+use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag};
+use regex::Regex;
+use shared::{mention_matches, mention_tokens, User};
+use yew::prelude::*;
+
+enum Block {
+    Root,
+    /// Anything without a rendering of its own (e.g. an HTML block); children pass through
+    Fragment,
+    Element(&'static str),
+    OrderedList(u64),
+    Code,
+    /// Links and images; `None` when the target isn't a safe URL
+    Link(Option<String>),
+}
+
+struct Frame {
+    block: Block,
+    children: Vec<Html>,
+}
+
+impl Frame {
+    fn new(block: Block) -> Self {
+        Self {
+            block,
+            children: Vec::new(),
+        }
+    }
+
+    fn into_html(self) -> Html {
+        let children = self.children;
+        match self.block {
+            Block::Root => html! { <div class="markdown">{ for children }</div> },
+            Block::Fragment | Block::Link(None) => html! { <>{ for children }</> },
+            Block::Element(tag) => html! { <@{tag}>{ for children }</@> },
+            Block::OrderedList(start) => {
+                html! { <ol start={start.to_string()}>{ for children }</ol> }
+            }
+            Block::Code => html! { <pre><code>{ for children }</code></pre> },
+            Block::Link(Some(href)) => html! {
+                <a href={href} target="_blank" rel="noopener noreferrer" class="auto-link">{ for children }</a>
+            },
+        }
+    }
+}
+
+fn heading_tag(level: HeadingLevel) -> &'static str {
+    match level {
+        HeadingLevel::H1 => "h1",
+        HeadingLevel::H2 => "h2",
+        HeadingLevel::H3 => "h3",
+        HeadingLevel::H4 => "h4",
+        HeadingLevel::H5 => "h5",
+        HeadingLevel::H6 => "h6",
+    }
+}
+
+/// The link target if it is safe to follow; `javascript:` and friends are dropped.
+fn safe_url(url: &str) -> Option<String> {
+    let url = url.trim();
+    let lower = url.to_ascii_lowercase();
+    ["http://", "https://", "mailto:"]
+        .iter()
+        .any(|scheme| lower.starts_with(scheme))
+        .then(|| url.to_string())
+}
+
+/// Text with `@mentions` of the `mentioned` users as chips; other mentions stay text.
+fn mention_chips(text: &str, mentioned: &[&User], result: &mut Vec<Html>) {
+    let mut last_end = 0;
+
+    for mention in mention_tokens(text) {
+        let Some(user) = mentioned
+            .iter()
+            .find(|u| mention_matches(mention.token, &u.name, u.initials.as_deref()))
+        else {
+            continue;
+        };
+        if mention.start > last_end {
+            result.push(html! { <>{&text[last_end..mention.start]}</> });
+        }
+        result.push(html! {
+            <span class="mention" title={user.name.clone()}>{ &text[mention.start..mention.end()] }</span>
+        });
+        last_end = mention.end();
+    }
+    if last_end < text.len() {
+        result.push(html! { <>{&text[last_end..]}</> });
+    }
+}
+
+/// Plain text with bare http(s) URLs turned into links and mentions into chips.
+fn linkify_text(text: &str, mentioned: &[&User]) -> Html {
+    let url_regex = Regex::new(r"(https?://[^\s<>\[\]()]+)").unwrap();
+    let mut result = Vec::new();
+    let mut last_end = 0;
+
+    for cap in url_regex.captures_iter(text) {
+        let m = cap.get(0).unwrap();
+        if m.start() > last_end {
+            mention_chips(&text[last_end..m.start()], mentioned, &mut result);
+        }
+        let url = m.as_str();
+        result.push(html! {
+            <a href={url.to_string()} target="_blank" rel="noopener noreferrer" class="auto-link">{ url }</a>
+        });
+        last_end = m.end();
+    }
+    if last_end < text.len() {
+        mention_chips(&text[last_end..], mentioned, &mut result);
+    }
+    html! { <>{ for result }</> }
+}
+
+/// Render markdown `text`. Single newlines are kept (the containers use `pre-wrap`), so
+/// plain-text notes look the same as before.
+pub fn render_markdown(text: &str) -> Html {
+    render_note(text, &[])
+}
+
+/// Render a note: markdown as in [`render_markdown`], with `@mentions` of the given
+/// users (the note's resolved mentions) shown as chips.
+pub fn render_note(text: &str, mentioned: &[&User]) -> Html {
+    let mut stack = vec![Frame::new(Block::Root)];
+    // Text is split into several events around markup characters; collect a whole run
+    // before linkifying so a URL isn't cut in two
+    let mut pending_text = String::new();
+    // Inside code blocks and links, URLs stay plain text
+    let mut autolink_blocked = 0usize;
+
+    for event in Parser::new_ext(text, Options::ENABLE_STRIKETHROUGH) {
+        if let Event::Text(t) = &event {
+            pending_text.push_str(t);
+            continue;
+        }
+        if !pending_text.is_empty() {
+            let text = std::mem::take(&mut pending_text);
+            let node = if autolink_blocked > 0 {
+                html! { <>{ text }</> }
+            } else {
+                linkify_text(&text, mentioned)
+            };
+            stack.last_mut().unwrap().children.push(node);
+        }
+
+        let node = match event {
+            Event::Start(tag) => {
+                let block = match tag {
+                    Tag::Paragraph => Block::Element("p"),
+                    Tag::Heading { level, .. } => Block::Element(heading_tag(level)),
+                    Tag::BlockQuote(_) => Block::Element("blockquote"),
+                    Tag::CodeBlock(_) => Block::Code,
+                    Tag::List(Some(start)) => Block::OrderedList(start),
+                    Tag::List(None) => Block::Element("ul"),
+                    Tag::Item => Block::Element("li"),
+                    Tag::Emphasis => Block::Element("em"),
+                    Tag::Strong => Block::Element("strong"),
+                    Tag::Strikethrough => Block::Element("del"),
+                    // Images become links to the image: no remote loads from note content
+                    Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. } => {
+                        Block::Link(safe_url(&dest_url))
+                    }
+                    _ => Block::Fragment,
+                };
+                if matches!(block, Block::Code | Block::Link(_)) {
+                    autolink_blocked += 1;
+                }
+                stack.push(Frame::new(block));
+                continue;
+            }
+            Event::End(_) => {
+                // Start/End are balanced, so the root frame is never popped here
+                if stack.len() < 2 {
+                    continue;
+                }
+                let frame = stack.pop().unwrap();
+                if matches!(frame.block, Block::Code | Block::Link(_)) {
+                    autolink_blocked -= 1;
+                }
+                frame.into_html()
+            }
+            Event::Code(code) => html! { <code>{ code.to_string() }</code> },
+            Event::Html(raw) | Event::InlineHtml(raw) => html! { <>{ raw.to_string() }</> },
+            Event::SoftBreak => html! { <>{ "\n" }</> },
+            Event::HardBreak => html! { <br /> },
+            Event::Rule => html! { <hr /> },
+            _ => continue,
+        };
+        stack.last_mut().unwrap().children.push(node);
+    }
+
+    let mut root = stack.swap_remove(0);
+    if !pending_text.is_empty() {
+        root.children.push(linkify_text(&pending_text, mentioned));
+    }
+    root.into_html()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The rendered tree as markup, keeping only `href` attributes and escaping text, so
+    /// tests can tell elements from text that merely looks like HTML.
+    fn markup(node: &Html) -> String {
+        match node {
+            Html::VTag(tag) => {
+                let href = tag
+                    .attributes
+                    .iter()
+                    .find(|(name, _)| *name == "href")
+                    .map(|(_, value)| format!(" href=\"{}\"", value))
+                    .unwrap_or_default();
+                let children = tag.children().map(markup).unwrap_or_default();
+                format!("<{0}{1}>{2}</{0}>", tag.tag(), href, children)
+            }
+            Html::VText(text) => text.text.replace('<', "&lt;").replace('>', "&gt;"),
+            Html::VList(list) => list.iter().map(markup).collect(),
+            _ => String::new(),
+        }
+    }
+
+    #[test]
+    fn raw_html_is_shown_as_text() {
+        assert_eq!(
+            markup(&render_markdown("<script>alert(1)</script>")),
+            "<div>&lt;script&gt;alert(1)&lt;/script&gt;</div>"
+        );
+        assert_eq!(
+            markup(&render_markdown("Hi <b>there</b>")),
+            "<div><p>Hi &lt;b&gt;there&lt;/b&gt;</p></div>"
+        );
+    }
+
+    #[test]
+    fn only_safe_link_targets_are_kept() {
+        assert_eq!(
+            markup(&render_markdown(
+                "[x](javascript:alert(1)) [y](HTTPS://ok.example) ![img](https://img.example/a.png)"
+            )),
+            "<div><p>x <a href=\"HTTPS://ok.example\">y</a> \
+             <a href=\"https://img.example/a.png\">img</a></p></div>"
+        );
+        assert_eq!(
+            safe_url(" mailto:mf@example.org"),
+            Some("mailto:mf@example.org".to_string())
+        );
+        assert_eq!(safe_url("/items/AAA-001"), None);
+        assert_eq!(safe_url("data:text/html,hi"), None);
+    }
+
+    #[test]
+    fn bare_urls_are_linked_outside_code_and_links() {
+        assert_eq!(
+            markup(&render_markdown(
+                "see https://a.example and `https://b.example`\n\n\
+                 ```\nhttps://c.example\n```\n[d](https://d.example)"
+            )),
+            "<div><p>see <a href=\"https://a.example\">https://a.example</a> and \
+             <code>https://b.example</code></p>\
+             <pre><code>https://c.example\n</code></pre>\
+             <p><a href=\"https://d.example\">d</a></p></div>"
+        );
+    }
+
+    #[test]
+    fn single_newlines_are_kept() {
+        assert_eq!(
+            markup(&render_markdown("line one\nline two")),
+            "<div><p>line one\nline two</p></div>"
+        );
+    }
+}
@@ -1,12 +1,15 @@
 use std::cmp::Ordering;
 
+use chrono::NaiveDate;
 use gloo_net::http::Request;
-use shared::{ActionItemResponse, CategoryResponse, Vendor};
+use shared::{ActionItemResponse, CategoryResponse, ClientConfig, ItemListResponse, Vendor};
+use wasm_bindgen::closure::Closure;
 use wasm_bindgen::JsCast;
-use web_sys::HtmlSelectElement;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
 use yew::prelude::*;
 use yew_router::prelude::*;
 
+use crate::api;
 use crate::pages::status_style::{priority_class, status_class};
 use crate::Route;
 
@@ -27,6 +30,11 @@ enum SortDirection {
     Desc,
 }
 
+/// Vendor prefix, then number, so AD-1000 sorts after AD-999 whatever the padding.
+fn id_key(item: &ActionItemResponse) -> (&str, i32) {
+    (item.id.split('-').next().unwrap_or(""), item.number)
+}
+
 fn priority_ord(p: &str) -> u8 {
     match p {
         "High" => 0,
@@ -48,30 +56,71 @@ fn status_ord(s: &str) -> u8 {
     }
 }
 
-use crate::components::{ActivitySidebar, Header};
-use crate::pages::item_detail::ItemDetailModal;
-use crate::pages::item_form::NewItemModal;
-use crate::pages::manage_vendors::ManageVendorsModal;
+/// Open items due before `today`, the browser's local date. Undated items never are.
+fn is_overdue(item: &ActionItemResponse, today: NaiveDate) -> bool {
+    item.status != "Complete" && item.due_date.is_some_and(|d| d < today)
+}
 
-fn name_to_color(name: &str) -> String {
-    let hash: u32 = name
-        .bytes()
-        .fold(0u32, |acc, b| acc.wrapping_mul(31).wrapping_add(b as u32));
-    let hue = hash % 360;
-    format!("hsl({}, 65%, 45%)", hue)
+/// Case-insensitive substring match on id, title, category, owner and creator. `query`
+/// is already trimmed and lowercased; an empty one matches everything.
+fn matches_search(item: &ActionItemResponse, query: &str) -> bool {
+    query.is_empty()
+        || [
+            &item.id,
+            &item.title,
+            &item.category,
+            &item.owner_name,
+            &item.created_by_name,
+        ]
+        .iter()
+        .any(|field| field.to_lowercase().contains(query))
 }
 
-fn get_initials(name: &str, fallback_initials: Option<&str>) -> String {
-    if let Some(initials) = fallback_initials {
-        return initials.to_string();
+/// Table order for `col` in direction `dir`.
+fn compare_items(
+    a: &ActionItemResponse,
+    b: &ActionItemResponse,
+    col: SortColumn,
+    dir: SortDirection,
+) -> Ordering {
+    // Items without a due date stay last whichever way due dates are sorted
+    if col == SortColumn::DueDate {
+        match (&a.due_date, &b.due_date) {
+            (Some(_), None) => return Ordering::Less,
+            (None, Some(_)) => return Ordering::Greater,
+            _ => {}
+        }
+    }
+    let ord = match col {
+        SortColumn::Id => id_key(a).cmp(&id_key(b)),
+        SortColumn::Title => a.title.to_lowercase().cmp(&b.title.to_lowercase()),
+        SortColumn::Category => a.category.cmp(&b.category),
+        SortColumn::Priority => priority_ord(&a.priority).cmp(&priority_ord(&b.priority)),
+        SortColumn::Status => status_ord(&a.status).cmp(&status_ord(&b.status)),
+        SortColumn::Created => a.create_date.cmp(&b.create_date),
+        SortColumn::DueDate => a.due_date.cmp(&b.due_date),
+    };
+    match dir {
+        SortDirection::Asc => ord,
+        SortDirection::Desc => ord.reverse(),
     }
-    name.split_whitespace()
-        .filter_map(|word| word.chars().next())
-        .take(2)
-        .collect::<String>()
-        .to_uppercase()
 }
 
+/// Whether a key event comes from a form field the user is typing into.
+fn is_typing(e: &KeyboardEvent) -> bool {
+    e.target()
+        .and_then(|t| t.dyn_into::<web_sys::Element>().ok())
+        .is_some_and(|el| matches!(el.tag_name().as_str(), "INPUT" | "TEXTAREA" | "SELECT"))
+}
+
+const SEARCH_INPUT_ID: &str = "item-search";
+
+use crate::components::{ActivitySidebar, Avatar, Header};
+use crate::pages::item_detail::ItemDetailModal;
+use crate::pages::item_form::NewItemModal;
+use crate::pages::manage_users::ManageUsersModal;
+use crate::pages::manage_vendors::ManageVendorsModal;
+
 fn reload_page() {
     if let Some(window) = web_sys::window() {
         let _ = window.location().reload();
@@ -88,27 +137,54 @@ pub struct HomeProps {
 pub fn home(props: &HomeProps) -> Html {
     let navigator = use_navigator().unwrap();
     let items = use_state(Vec::<ActionItemResponse>::new);
+    let total_count = use_state(|| 0i64);
     let vendors = use_state(Vec::<Vendor>::new);
     let users = use_state(Vec::<shared::User>::new);
     let categories = use_state(Vec::<CategoryResponse>::new);
+    let read_only = use_state(|| false);
+    let title_max_length = use_state(|| shared::DEFAULT_TITLE_MAX_LENGTH);
+    let is_admin = use_state(|| false);
     let loading = use_state(|| true);
     let error = use_state(|| None::<String>);
     let show_new_item_modal = use_state(|| false);
     let selected_item_id = use_state(|| props.initial_item_id.clone());
+    // True when the open item's `/items/:id` entry was pushed by us, so closing can pop it
+    // rather than stacking a new history entry. False for deep links.
+    let pushed_item_route = use_mut_ref(|| false);
+    let refresh_trigger = use_state(|| 0u32);
 
+    // The URL is the source of truth for the open item, so back/forward open and close it
     {
         let selected_item_id = selected_item_id.clone();
+        let pushed_item_route = pushed_item_route.clone();
+        let refresh_trigger = refresh_trigger.clone();
         let initial = props.initial_item_id.clone();
         use_effect_with(initial.clone(), move |_| {
+            if initial.is_none() {
+                *pushed_item_route.borrow_mut() = false;
+                if selected_item_id.is_some() {
+                    refresh_trigger.set(*refresh_trigger + 1);
+                }
+            }
             selected_item_id.set(initial);
             || ()
         });
     }
 
-    let refresh_trigger = use_state(|| 0u32);
+    let open_item = {
+        let navigator = navigator.clone();
+        let pushed_item_route = pushed_item_route.clone();
+        Callback::from(move |item_id: String| {
+            *pushed_item_route.borrow_mut() = true;
+            navigator.push(&Route::Item { id: item_id });
+        })
+    };
+
     let filter_vendor_id = use_state(|| None::<i32>);
     let filter_owner_id = use_state(|| None::<i32>);
+    let search = use_state(String::new);
     let show_manage_vendors_modal = use_state(|| false);
+    let show_manage_users_modal = use_state(|| false);
     let show_completed = use_state(|| false);
     let sort_column = use_state(|| SortColumn::Id);
     let sort_direction = use_state(|| SortDirection::Asc);
@@ -118,23 +194,28 @@ pub fn home(props: &HomeProps) -> Html {
         let vendors = vendors.clone();
         let users = users.clone();
         let categories = categories.clone();
+        let total_count = total_count.clone();
+        let read_only = read_only.clone();
+        let title_max_length = title_max_length.clone();
+        let is_admin = is_admin.clone();
         let loading = loading.clone();
         let error = error.clone();
         let refresh = *refresh_trigger;
 
         use_effect_with(refresh, move |_| {
             wasm_bindgen_futures::spawn_local(async move {
-                // Fetch items
-                match Request::get("/api/items").send().await {
+                // Fetch items (first page at the largest page size; filtering happens client-side)
+                match Request::get(&api::url("/api/items?limit=500")).send().await {
                     Ok(resp) => {
                         if resp.status() == 401 {
                             reload_page();
                             return;
                         }
                         if resp.ok() {
-                            match resp.json::<Vec<ActionItemResponse>>().await {
+                            match resp.json::<ItemListResponse>().await {
                                 Ok(data) => {
-                                    items.set(data);
+                                    total_count.set(data.total_count);
+                                    items.set(data.items);
                                 }
                                 Err(e) => {
                                     error.set(Some(format!("Failed to parse response: {}", e)));
@@ -150,26 +231,41 @@ pub fn home(props: &HomeProps) -> Html {
                 }
 
                 // Fetch vendors for the dropdown
-                if let Ok(resp) = Request::get("/api/vendors").send().await {
+                if let Ok(resp) = Request::get(&api::url("/api/vendors")).send().await {
                     if let Ok(data) = resp.json::<Vec<Vendor>>().await {
                         vendors.set(data);
                     }
                 }
 
                 // Fetch users for the dropdown
-                if let Ok(resp) = Request::get("/api/users").send().await {
+                if let Ok(resp) = Request::get(&api::url("/api/users")).send().await {
                     if let Ok(data) = resp.json::<Vec<shared::User>>().await {
                         users.set(data);
                     }
                 }
 
                 // Fetch categories for the dropdown
-                if let Ok(resp) = Request::get("/api/categories").send().await {
+                if let Ok(resp) = Request::get(&api::url("/api/categories")).send().await {
                     if let Ok(data) = resp.json::<Vec<CategoryResponse>>().await {
                         categories.set(data);
                     }
                 }
 
+                // Fetch client config so maintenance mode is picked up (and cleared) on refresh
+                if let Ok(resp) = Request::get(&api::url("/api/config")).send().await {
+                    if let Ok(data) = resp.json::<ClientConfig>().await {
+                        read_only.set(data.read_only);
+                        title_max_length.set(data.title_max_length);
+                    }
+                }
+
+                // Fetch the current user's role so admin-only actions are shown
+                if let Ok(resp) = Request::get(&api::url("/auth/me")).send().await {
+                    if let Ok(data) = resp.json::<shared::CurrentUserResponse>().await {
+                        is_admin.set(data.role == shared::Role::Admin);
+                    }
+                }
+
                 loading.set(false);
             });
             || ()
@@ -207,13 +303,14 @@ pub fn home(props: &HomeProps) -> Html {
     };
 
     let on_item_detail_close = {
-        let selected_item_id = selected_item_id.clone();
-        let refresh_trigger = refresh_trigger.clone();
         let navigator = navigator.clone();
+        let pushed_item_route = pushed_item_route.clone();
         Callback::from(move |_| {
-            selected_item_id.set(None);
-            refresh_trigger.set(*refresh_trigger + 1);
-            navigator.push(&Route::Home);
+            if *pushed_item_route.borrow() {
+                navigator.back();
+            } else {
+                navigator.replace(&Route::Home);
+            }
         })
     };
 
@@ -233,6 +330,98 @@ pub fn home(props: &HomeProps) -> Html {
         })
     };
 
+    let on_manage_users_click = {
+        let show_manage_users_modal = show_manage_users_modal.clone();
+        Callback::from(move |_: ()| {
+            show_manage_users_modal.set(true);
+        })
+    };
+
+    let on_manage_users_close = {
+        let show_manage_users_modal = show_manage_users_modal.clone();
+        Callback::from(move |_| {
+            show_manage_users_modal.set(false);
+        })
+    };
+
+    // Global shortcuts: `n` opens New Item, `/` focuses search and Esc closes the topmost
+    // modal. Letters are ignored while typing in a field; Esc isn't, unless a field's own
+    // handler already dealt with it (e.g. cancelling an inline edit).
+    {
+        let show_new_item_modal = show_new_item_modal.clone();
+        let on_new_item_modal_close = on_new_item_modal_close.clone();
+        let on_manage_vendors_close = on_manage_vendors_close.clone();
+        let on_manage_users_close = on_manage_users_close.clone();
+        let on_item_detail_close = on_item_detail_close.clone();
+        let can_create = !vendors.is_empty() && !*read_only;
+        use_effect_with(
+            (
+                *show_new_item_modal,
+                *show_manage_vendors_modal,
+                *show_manage_users_modal,
+                selected_item_id.is_some(),
+                can_create,
+            ),
+            move |&(new_item_open, vendors_open, users_open, item_open, can_create)| {
+                let listener = Closure::<dyn Fn(KeyboardEvent)>::new(move |e: KeyboardEvent| {
+                    if e.key() == "Escape" {
+                        if e.default_prevented() {
+                            return;
+                        }
+                        if item_open {
+                            on_item_detail_close.emit(());
+                        } else if vendors_open {
+                            on_manage_vendors_close.emit(());
+                        } else if users_open {
+                            on_manage_users_close.emit(());
+                        } else if new_item_open {
+                            on_new_item_modal_close.emit(());
+                        }
+                        return;
+                    }
+
+                    let modal_open = new_item_open || vendors_open || users_open || item_open;
+                    if modal_open || is_typing(&e) || e.ctrl_key() || e.meta_key() || e.alt_key() {
+                        return;
+                    }
+                    match e.key().as_str() {
+                        "n" if can_create => {
+                            e.prevent_default();
+                            show_new_item_modal.set(true);
+                        }
+                        "/" => {
+                            e.prevent_default();
+                            if let Some(search) = web_sys::window()
+                                .and_then(|w| w.document())
+                                .and_then(|d| d.get_element_by_id(SEARCH_INPUT_ID))
+                                .and_then(|el| el.dyn_into::<web_sys::HtmlElement>().ok())
+                            {
+                                let _ = search.focus();
+                            }
+                        }
+                        _ => {}
+                    }
+                });
+
+                let document = web_sys::window().and_then(|w| w.document());
+                if let Some(document) = &document {
+                    let _ = document.add_event_listener_with_callback(
+                        "keydown",
+                        listener.as_ref().unchecked_ref(),
+                    );
+                }
+                move || {
+                    if let Some(document) = document {
+                        let _ = document.remove_event_listener_with_callback(
+                            "keydown",
+                            listener.as_ref().unchecked_ref(),
+                        );
+                    }
+                }
+            },
+        );
+    }
+
     let on_vendor_filter_change = {
         let filter_vendor_id = filter_vendor_id.clone();
         Callback::from(move |e: Event| {
@@ -259,6 +448,19 @@ pub fn home(props: &HomeProps) -> Html {
         })
     };
 
+    let on_search_input = {
+        let search = search.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target().unwrap().dyn_into().unwrap();
+            search.set(input.value());
+        })
+    };
+
+    let on_search_clear = {
+        let search = search.clone();
+        Callback::from(move |_: MouseEvent| search.set(String::new()))
+    };
+
     let on_sort = {
         let sort_column = sort_column.clone();
         let sort_direction = sort_direction.clone();
@@ -275,12 +477,7 @@ pub fn home(props: &HomeProps) -> Html {
         })
     };
 
-    let on_activity_select = {
-        let selected_item_id = selected_item_id.clone();
-        Callback::from(move |item_id: String| {
-            selected_item_id.set(Some(item_id));
-        })
-    };
+    let on_activity_select = open_item.clone();
 
     let sort_indicator = |col: SortColumn| -> &'static str {
         if *sort_column == col {
@@ -294,6 +491,7 @@ pub fn home(props: &HomeProps) -> Html {
     };
 
     // Apply filters and sorting to items
+    let search_query = search.trim().to_lowercase();
     let mut filtered_items: Vec<_> = items
         .iter()
         .filter(|item| {
@@ -306,50 +504,66 @@ pub fn home(props: &HomeProps) -> Html {
                 .map(|o| item.owner_id == *o)
                 .unwrap_or(true);
             let completed_match = *show_completed || item.status != "Complete";
-            vendor_match && owner_match && completed_match
+            vendor_match && owner_match && completed_match && matches_search(item, &search_query)
         })
         .collect();
 
     let col = *sort_column;
     let dir = *sort_direction;
-    filtered_items.sort_by(|a, b| {
-        let ord = match col {
-            SortColumn::Id => a.id.cmp(&b.id),
-            SortColumn::Title => a.title.to_lowercase().cmp(&b.title.to_lowercase()),
-            SortColumn::Category => a.category.cmp(&b.category),
-            SortColumn::Priority => priority_ord(&a.priority).cmp(&priority_ord(&b.priority)),
-            SortColumn::Status => status_ord(&a.status).cmp(&status_ord(&b.status)),
-            SortColumn::Created => a.create_date.cmp(&b.create_date),
-            SortColumn::DueDate => match (&a.due_date, &b.due_date) {
-                (Some(a_d), Some(b_d)) => a_d.cmp(b_d),
-                (Some(_), None) => Ordering::Less,
-                (None, Some(_)) => Ordering::Greater,
-                (None, None) => Ordering::Equal,
-            },
-        };
-        match dir {
-            SortDirection::Asc => ord,
-            SortDirection::Desc => ord.reverse(),
+    filtered_items.sort_by(|a, b| compare_items(a, b, col, dir));
+
+    // Summary of the filtered set for the table footer
+    let today = chrono::Local::now().date_naive();
+    let overdue_count = filtered_items
+        .iter()
+        .filter(|item| is_overdue(item, today))
+        .count();
+    let mut status_counts: Vec<(&str, usize)> = Vec::new();
+    for item in &filtered_items {
+        match status_counts.iter_mut().find(|(s, _)| *s == item.status) {
+            Some((_, n)) => *n += 1,
+            None => status_counts.push((&item.status, 1)),
         }
-    });
+    }
+    status_counts.sort_by_key(|(s, _)| status_ord(s));
 
     html! {
         <>
-            <Header />
+            <Header on_manage_users={on_manage_users_click} />
             <main>
+                if *read_only {
+                    <div class="read-only-banner">
+                        { "The tracker is in read-only maintenance mode. Changes are temporarily disabled." }
+                    </div>
+                }
                 <div class="page-header">
                     <h2>{ "Action Items" }</h2>
                     <div class="header-actions">
                         <button type="button" class="btn btn-secondary" onclick={on_manage_vendors_click}>
                             { "Manage Vendors" }
                         </button>
-                        <button type="button" class="btn btn-primary" onclick={on_new_item_click} disabled={vendors.is_empty()}>
+                        <button type="button" class="btn btn-primary" onclick={on_new_item_click} disabled={vendors.is_empty() || *read_only}>
                             { "+ New Item" }
                         </button>
                     </div>
                 </div>
 
                 <div class="filters">
+                    <div class="filter-group">
+                        <input
+                            type="search"
+                            id={SEARCH_INPUT_ID}
+                            class="search-input"
+                            placeholder="Search items..."
+                            value={(*search).clone()}
+                            oninput={on_search_input}
+                        />
+                        if !search.is_empty() {
+                            <button type="button" class="btn btn-small" onclick={on_search_clear} title="Clear search">
+                                { "×" }
+                            </button>
+                        }
+                    </div>
                     <div class="filter-group">
                         <label>{ "Vendor:" }</label>
                         <select onchange={on_vendor_filter_change}>
@@ -390,6 +604,12 @@ pub fn home(props: &HomeProps) -> Html {
                         </label>
                     </div>
                 </div>
+                <p class="shortcut-hint">
+                    { "Shortcuts: " }
+                    <kbd>{ "n" }</kbd>{ " new item, " }
+                    <kbd>{ "/" }</kbd>{ " search, " }
+                    <kbd>{ "Esc" }</kbd>{ " close" }
+                </p>
 
                 if *show_new_item_modal {
                     <NewItemModal
@@ -399,11 +619,17 @@ pub fn home(props: &HomeProps) -> Html {
                         on_close={on_new_item_modal_close}
                         on_created={on_item_created}
                         on_refresh={on_data_refresh.clone()}
+                        read_only={*read_only}
+                        title_max_length={*title_max_length}
                     />
                 }
 
                 if *show_manage_vendors_modal {
-                    <ManageVendorsModal on_close={on_manage_vendors_close} />
+                    <ManageVendorsModal on_close={on_manage_vendors_close} read_only={*read_only} />
+                }
+
+                if *show_manage_users_modal {
+                    <ManageUsersModal on_close={on_manage_users_close} />
                 }
 
                 if let Some(item_id) = (*selected_item_id).clone() {
@@ -412,6 +638,9 @@ pub fn home(props: &HomeProps) -> Html {
                         users={(*users).clone()}
                         categories={(*categories).clone()}
                         on_close={on_item_detail_close}
+                        read_only={*read_only}
+                        title_max_length={*title_max_length}
+                        is_admin={*is_admin}
                     />
                 }
 
@@ -464,18 +693,11 @@ pub fn home(props: &HomeProps) -> Html {
                                 </thead>
                                 <tbody>
                                     { for filtered_items.iter().map(|item| {
-                                        let item_id = item.id.clone();
-                                        let navigator = navigator.clone();
                                         let on_row_click = {
-                                            let item_id = item_id.clone();
-                                            Callback::from(move |_| {
-                                                navigator.push(&Route::Item { id: item_id.clone() });
-                                            })
+                                            let item_id = item.id.clone();
+                                            let open_item = open_item.clone();
+                                            Callback::from(move |_| open_item.emit(item_id.clone()))
                                         };
-                                        let creator_initials = get_initials(&item.created_by_name, item.created_by_initials.as_deref());
-                                        let creator_color = name_to_color(&item.created_by_name);
-                                        let owner_initials = get_initials(&item.owner_name, item.owner_initials.as_deref());
-                                        let owner_color = name_to_color(&item.owner_name);
                                         html! {
                                             <tr class="clickable-row" onclick={on_row_click}>
                                                 <td>
@@ -484,14 +706,10 @@ pub fn home(props: &HomeProps) -> Html {
                                                 <td class="item-title">{ &item.title }</td>
                                                 <td>{ &item.category }</td>
                                                 <td>
-                                                    <span class="user-avatar" style={format!("background-color: {}", creator_color)} title={item.created_by_name.clone()}>
-                                                        { creator_initials }
-                                                    </span>
+                                                    <Avatar name={item.created_by_name.clone()} initials={item.created_by_initials.clone()} />
                                                 </td>
                                                 <td>
-                                                    <span class="user-avatar" style={format!("background-color: {}", owner_color)} title={item.owner_name.clone()}>
-                                                        { owner_initials }
-                                                    </span>
+                                                    <Avatar name={item.owner_name.clone()} initials={item.owner_initials.clone()} />
                                                 </td>
                                                 <td class={priority_class(&item.priority)}>
                                                     { &item.priority }
@@ -500,14 +718,38 @@ pub fn home(props: &HomeProps) -> Html {
                                                     { &item.status }
                                                 </td>
                                                 <td>{ item.create_date.to_string() }</td>
-                                                <td>
+                                                <td class={classes!(is_overdue(item, today).then_some("overdue"))}>
                                                     { item.due_date.map(|d| d.to_string()).unwrap_or_else(|| "-".to_string()) }
                                                 </td>
                                             </tr>
                                         }
                                     })}
                                 </tbody>
+                                <tfoot>
+                                    <tr class="items-summary">
+                                        <td colspan="9">
+                                            <span class="summary-total">
+                                                { format!("{} item{}", filtered_items.len(), if filtered_items.len() == 1 { "" } else { "s" }) }
+                                            </span>
+                                            { for status_counts.iter().map(|(status, count)| html! {
+                                                <span class={classes!("summary-count", status_class(status))}>
+                                                    { format!("{}: {}", status, count) }
+                                                </span>
+                                            })}
+                                            if overdue_count > 0 {
+                                                <span class="summary-count summary-overdue">
+                                                    { format!("Overdue: {}", overdue_count) }
+                                                </span>
+                                            }
+                                        </td>
+                                    </tr>
+                                </tfoot>
                             </table>
+                            if *total_count > items.len() as i64 {
+                                <p class="items-truncated">
+                                    { format!("Showing the first {} of {} items.", items.len(), *total_count) }
+                                </p>
+                            }
                         }
                     </div>
                     <aside class="home-sidebar">
@@ -521,3 +763,97 @@ pub fn home(props: &HomeProps) -> Html {
         </>
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: &str, owner_name: &str) -> ActionItemResponse {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "vendor_id": 1,
+            "number": id.rsplit('-').next().unwrap().parse::<i32>().unwrap(),
+            "title": format!("Review {}", id),
+            "description": null,
+            "create_date": "2026-02-01",
+            "created_by_id": 1,
+            "created_by_name": "Mike Fox",
+            "created_by_initials": "MF",
+            "due_date": null,
+            "category_id": 1,
+            "category": "Thermal",
+            "owner_id": 2,
+            "owner_name": owner_name,
+            "owner_initials": null,
+            "priority": "Medium",
+            "created_at": "2026-02-01T09:00:00Z",
+            "updated_at": "2026-02-01T09:00:00Z",
+            "status": "New",
+            "status_changed_at": "2026-02-01T09:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn search_matches_any_listed_field_ignoring_case() {
+        let item = item("AD-012", "Ada Lovelace");
+        for query in ["", "ad-01", "review", "therm", "lovelace", "mike f"] {
+            assert!(matches_search(&item, query), "{}", query);
+        }
+        assert!(!matches_search(&item, "bob"));
+        // Callers lowercase the query first
+        assert!(!matches_search(&item, "Ada"));
+    }
+
+    fn due(mut item: ActionItemResponse, due_date: &str) -> ActionItemResponse {
+        item.due_date = Some(due_date.parse().unwrap());
+        item
+    }
+
+    #[test]
+    fn undated_items_sort_last_in_both_directions() {
+        let mut items = vec![
+            item("AD-001", "Ada Lovelace"),
+            due(item("AD-002", "Ada Lovelace"), "2026-03-01"),
+            due(item("AD-003", "Ada Lovelace"), "2026-01-15"),
+        ];
+        let order = |items: &[ActionItemResponse]| -> Vec<String> {
+            items.iter().map(|i| i.id.clone()).collect()
+        };
+
+        items.sort_by(|a, b| compare_items(a, b, SortColumn::DueDate, SortDirection::Asc));
+        assert_eq!(order(&items), ["AD-003", "AD-002", "AD-001"]);
+        items.sort_by(|a, b| compare_items(a, b, SortColumn::DueDate, SortDirection::Desc));
+        assert_eq!(order(&items), ["AD-002", "AD-003", "AD-001"]);
+    }
+
+    #[test]
+    fn ids_sort_by_prefix_then_number_whatever_the_padding() {
+        let mut items = [
+            item("XY-0001", "Ada Lovelace"),
+            item("AD-1000", "Ada Lovelace"),
+            item("AD-999", "Ada Lovelace"),
+        ];
+        let order = |items: &[ActionItemResponse]| -> Vec<String> {
+            items.iter().map(|i| i.id.clone()).collect()
+        };
+
+        items.sort_by(|a, b| compare_items(a, b, SortColumn::Id, SortDirection::Asc));
+        assert_eq!(order(&items), ["AD-999", "AD-1000", "XY-0001"]);
+        items.sort_by(|a, b| compare_items(a, b, SortColumn::Id, SortDirection::Desc));
+        assert_eq!(order(&items), ["XY-0001", "AD-1000", "AD-999"]);
+    }
+
+    #[test]
+    fn only_open_items_past_their_due_date_are_overdue() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        let open = item("AD-001", "Ada Lovelace");
+        assert!(is_overdue(&due(open.clone(), "2026-03-09"), today));
+        assert!(!is_overdue(&due(open.clone(), "2026-03-10"), today));
+        assert!(!is_overdue(&open, today));
+
+        let mut done = due(open, "2026-03-09");
+        done.status = "Complete".to_string();
+        assert!(!is_overdue(&done, today));
+    }
+}
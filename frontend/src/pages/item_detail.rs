@@ -1,12 +1,14 @@
 use chrono::{DateTime, NaiveDate, Utc};
 use gloo_net::http::Request;
 use js_sys::{Date, Object, Reflect};
-use regex::Regex;
 use shared::{ActionItemResponse, NoteResponse, StatusHistoryResponse};
 use wasm_bindgen::{JsCast, JsValue};
 use web_sys::{HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
 use yew::prelude::*;
 
+use crate::api;
+use crate::components::{use_toast, Avatar};
+use crate::markdown::{render_markdown, render_note};
 use crate::pages::status_style::{priority_class, status_class};
 
 // (display_name, api_value)
@@ -19,6 +21,9 @@ const STATUSES: &[(&str, &str)] = &[
     ("Blocked", "blocked"),
 ];
 
+/// The one status that can only be set with a comment (the server enforces this too).
+const BLOCKED: &str = "Blocked";
+
 fn display_to_api(display: &str) -> &'static str {
     STATUSES
         .iter()
@@ -32,7 +37,9 @@ enum HistoryEntry {
     Note {
         timestamp: DateTime<Utc>,
         author: String,
+        author_initials: Option<String>,
         content: String,
+        mentioned_user_ids: Vec<i32>,
     },
     StatusChange {
         timestamp: DateTime<Utc>,
@@ -49,28 +56,13 @@ pub struct ItemDetailModalProps {
     pub users: Vec<shared::User>,
     pub categories: Vec<shared::CategoryResponse>,
     pub on_close: Callback<()>,
-}
-
-fn linkify_text(text: &str) -> Html {
-    let url_regex = Regex::new(r"(https?://[^\s<>\[\]()]+)").unwrap();
-    let mut result = Vec::new();
-    let mut last_end = 0;
-
-    for cap in url_regex.captures_iter(text) {
-        let m = cap.get(0).unwrap();
-        if m.start() > last_end {
-            result.push(html! { <>{&text[last_end..m.start()]}</> });
-        }
-        let url = m.as_str();
-        result.push(html! {
-            <a href={url.to_string()} target="_blank" rel="noopener noreferrer" class="auto-link">{ url }</a>
-        });
-        last_end = m.end();
-    }
-    if last_end < text.len() {
-        result.push(html! { <>{&text[last_end..]}</> });
-    }
-    html! { <>{ for result }</> }
+    #[prop_or_default]
+    pub read_only: bool,
+    #[prop_or(shared::DEFAULT_TITLE_MAX_LENGTH)]
+    pub title_max_length: usize,
+    /// Show the Delete button; only admins may delete items.
+    #[prop_or_default]
+    pub is_admin: bool,
 }
 
 fn format_datetime(dt: &DateTime<Utc>) -> String {
@@ -108,17 +100,59 @@ fn format_naive_date(date: &NaiveDate) -> String {
         .unwrap_or_else(|| date.format("%b %d, %Y").to_string())
 }
 
-/// PATCH an item with the given JSON body; returns true on a successful response.
-async fn patch_item(item_id: &str, body: serde_json::Value) -> bool {
-    matches!(
-        Request::patch(&format!("/api/items/{}", item_id))
-            .header("Content-Type", "application/json")
-            .body(body.to_string())
-            .unwrap()
-            .send()
-            .await,
-        Ok(resp) if resp.ok()
-    )
+/// PATCH an item with the given JSON body. `expected_updated_at` is the version the edit
+/// was made against; the server rejects the edit if someone else changed the item since.
+/// Returns the server's error message on failure; callers refresh either way so a
+/// rejected edit shows the current values.
+async fn patch_item(
+    item_id: &str,
+    expected_updated_at: Option<DateTime<Utc>>,
+    mut body: serde_json::Value,
+) -> Result<(), String> {
+    if let Some(version) = expected_updated_at {
+        body["expected_updated_at"] = serde_json::json!(version);
+    }
+    match Request::patch(&api::url(&format!("/api/items/{}", item_id)))
+        .header("Content-Type", "application/json")
+        .body(body.to_string())
+        .unwrap()
+        .send()
+        .await
+    {
+        Ok(resp) if resp.ok() => Ok(()),
+        Ok(resp) => Err(resp
+            .json::<shared::ApiError>()
+            .await
+            .map(|e| e.error.message)
+            .unwrap_or_else(|_| "Failed to save changes".to_string())),
+        Err(_) => Err("Failed to save changes".to_string()),
+    }
+}
+
+/// POST a status change, given by display name. Returns the server's error message on
+/// failure, e.g. for a transition the workflow doesn't allow.
+async fn post_status(item_id: &str, status: &str, comment: Option<String>) -> Result<(), String> {
+    let mut body = serde_json::json!({
+        "status": display_to_api(status),
+    });
+    if let Some(comment) = comment {
+        body["comment"] = serde_json::json!(comment);
+    }
+    match Request::post(&api::url(&format!("/api/items/{}/status", item_id)))
+        .header("Content-Type", "application/json")
+        .body(body.to_string())
+        .unwrap()
+        .send()
+        .await
+    {
+        Ok(resp) if resp.ok() => Ok(()),
+        Ok(resp) => Err(resp
+            .json::<shared::ApiError>()
+            .await
+            .map(|e| e.error.message)
+            .unwrap_or_else(|_| "Failed to change status".to_string())),
+        Err(_) => Err("Failed to change status".to_string()),
+    }
 }
 
 #[function_component(ItemDetailModal)]
@@ -131,6 +165,9 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
     let submitting = use_state(|| false);
     let refresh_trigger = use_state(|| 0u32);
     let changing_status = use_state(|| false);
+    // Set while asking what the item is blocked on
+    let blocking = use_state(|| false);
+    let blocked_comment = use_state(String::new);
     let changing_owner = use_state(|| false);
     let changing_priority = use_state(|| false);
     let changing_due_date = use_state(|| false);
@@ -142,6 +179,15 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
     let edit_title_value = use_state(String::new);
     let edit_description_value = use_state(String::new);
     let saving = use_state(|| false);
+    let toast = use_toast();
+
+    // Set while a watch/unwatch request is in flight
+    let toggling_watch = use_state(|| false);
+
+    // Delete confirmation states
+    let confirming_delete = use_state(|| false);
+    let deleting = use_state(|| false);
+    let delete_error = use_state(|| None::<String>);
 
     let item_id = props.item_id.clone();
 
@@ -157,7 +203,10 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
             let iid = iid.clone();
             wasm_bindgen_futures::spawn_local(async move {
                 // Fetch item
-                match Request::get(&format!("/api/items/{}", iid)).send().await {
+                match Request::get(&api::url(&format!("/api/items/{}", iid)))
+                    .send()
+                    .await
+                {
                     Ok(resp) if resp.ok() => {
                         if let Ok(data) = resp.json::<ActionItemResponse>().await {
                             item.set(Some(data));
@@ -175,7 +224,7 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
                 let mut entries: Vec<(DateTime<Utc>, HistoryEntry)> = Vec::new();
 
                 // Fetch notes
-                if let Ok(resp) = Request::get(&format!("/api/items/{}/notes", iid))
+                if let Ok(resp) = Request::get(&api::url(&format!("/api/items/{}/notes", iid)))
                     .send()
                     .await
                 {
@@ -186,7 +235,9 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
                                 HistoryEntry::Note {
                                     timestamp: note.created_at,
                                     author: note.author_name,
+                                    author_initials: note.author_initials,
                                     content: note.content,
+                                    mentioned_user_ids: note.mentioned_user_ids,
                                 },
                             ));
                         }
@@ -194,27 +245,22 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
                 }
 
                 // Fetch status history
-                if let Ok(resp) = Request::get(&format!("/api/items/{}/history", iid))
+                if let Ok(resp) = Request::get(&api::url(&format!("/api/items/{}/history", iid)))
                     .send()
                     .await
                 {
                     if let Ok(status_changes) = resp.json::<Vec<StatusHistoryResponse>>().await {
-                        let mut prev_status: Option<String> = None;
-                        // Status history comes in desc order, reverse to get chronological
-                        let mut changes = status_changes;
-                        changes.reverse();
-                        for change in changes {
+                        for change in status_changes {
                             entries.push((
                                 change.changed_at,
                                 HistoryEntry::StatusChange {
                                     timestamp: change.changed_at,
                                     changed_by: change.changed_by_name,
-                                    from_status: prev_status.clone(),
-                                    to_status: change.status.clone(),
+                                    from_status: change.from_status,
+                                    to_status: change.status,
                                     comment: change.comment,
                                 },
                             ));
-                            prev_status = Some(change.status);
                         }
                     }
                 }
@@ -244,6 +290,7 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
         let submitting = submitting.clone();
         let refresh_trigger = refresh_trigger.clone();
         let item_id = item_id.clone();
+        let toast = toast.clone();
 
         Callback::from(move |e: SubmitEvent| {
             e.prevent_default();
@@ -257,6 +304,7 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
             let submitting = submitting.clone();
             let refresh_trigger = refresh_trigger.clone();
             let item_id = item_id.clone();
+            let toast = toast.clone();
 
             submitting.set(true);
 
@@ -265,7 +313,7 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
                     "content": content,
                 });
 
-                match Request::post(&format!("/api/items/{}/notes", item_id))
+                match Request::post(&api::url(&format!("/api/items/{}/notes", item_id)))
                     .header("Content-Type", "application/json")
                     .body(body.to_string())
                     .unwrap()
@@ -273,10 +321,17 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
                     .await
                 {
                     Ok(resp) if resp.ok() => {
+                        toast.success("Note added");
                         new_update_content.set(String::new());
                         refresh_trigger.set(*refresh_trigger + 1);
                     }
-                    _ => {}
+                    Ok(resp) => toast.error(
+                        resp.json::<shared::ApiError>()
+                            .await
+                            .map(|e| e.error.message)
+                            .unwrap_or_else(|_| "Failed to add note".to_string()),
+                    ),
+                    Err(_) => toast.error("Failed to add note"),
                 }
                 submitting.set(false);
             });
@@ -306,7 +361,11 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
         let editing_title = editing_title.clone();
         let edit_title_value = edit_title_value.clone();
         let item = item.clone();
+        let read_only = props.read_only;
         Callback::from(move |_| {
+            if read_only {
+                return;
+            }
             if let Some(ref i) = *item {
                 edit_title_value.set(i.title.clone());
                 editing_title.set(true);
@@ -323,6 +382,7 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
     };
 
     let on_title_blur = {
+        let toast = toast.clone();
         let editing_title = editing_title.clone();
         let edit_title_value = edit_title_value.clone();
         let item = item.clone();
@@ -345,6 +405,8 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
             let saving = saving.clone();
             let refresh_trigger = refresh_trigger.clone();
             let item_id = item_id.clone();
+            let toast = toast.clone();
+            let expected_updated_at = (*item).as_ref().map(|i| i.updated_at);
 
             saving.set(true);
 
@@ -353,9 +415,11 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
                     "title": new_title,
                 });
 
-                if patch_item(&item_id, body).await {
-                    refresh_trigger.set(*refresh_trigger + 1);
+                match patch_item(&item_id, expected_updated_at, body).await {
+                    Ok(()) => toast.success("Saved"),
+                    Err(msg) => toast.error(msg),
                 }
+                refresh_trigger.set(*refresh_trigger + 1);
                 saving.set(false);
                 editing_title.set(false);
             });
@@ -370,6 +434,8 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
                 e.prevent_default();
                 on_title_blur.emit(FocusEvent::new("blur").unwrap());
             } else if e.key() == "Escape" {
+                // Marks it handled so the global shortcut doesn't also close the modal
+                e.prevent_default();
                 editing_title.set(false);
             }
         })
@@ -380,7 +446,11 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
         let editing_description = editing_description.clone();
         let edit_description_value = edit_description_value.clone();
         let item = item.clone();
+        let read_only = props.read_only;
         Callback::from(move |_| {
+            if read_only {
+                return;
+            }
             if let Some(ref i) = *item {
                 edit_description_value.set(i.description.clone().unwrap_or_default());
                 editing_description.set(true);
@@ -397,6 +467,7 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
     };
 
     let on_description_blur = {
+        let toast = toast.clone();
         let editing_description = editing_description.clone();
         let edit_description_value = edit_description_value.clone();
         let item = item.clone();
@@ -419,6 +490,8 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
             let saving = saving.clone();
             let refresh_trigger = refresh_trigger.clone();
             let item_id = item_id.clone();
+            let toast = toast.clone();
+            let expected_updated_at = (*item).as_ref().map(|i| i.updated_at);
 
             saving.set(true);
 
@@ -429,9 +502,11 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
                     serde_json::json!({ "description": new_desc })
                 };
 
-                if patch_item(&item_id, body).await {
-                    refresh_trigger.set(*refresh_trigger + 1);
+                match patch_item(&item_id, expected_updated_at, body).await {
+                    Ok(()) => toast.success("Saved"),
+                    Err(msg) => toast.error(msg),
                 }
+                refresh_trigger.set(*refresh_trigger + 1);
                 saving.set(false);
                 editing_description.set(false);
             });
@@ -442,15 +517,19 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
         let editing_description = editing_description.clone();
         Callback::from(move |e: KeyboardEvent| {
             if e.key() == "Escape" {
+                e.prevent_default();
                 editing_description.set(false);
             }
         })
     };
 
-    // Status change handler
+    // Status change handler. Blocked needs a reason, so choosing it opens the inline
+    // prompt below the metadata instead of saving straight away.
     let on_status_change = {
         let item = item.clone();
         let changing_status = changing_status.clone();
+        let blocking = blocking.clone();
+        let toast = toast.clone();
         let refresh_trigger = refresh_trigger.clone();
         let item_id = item_id.clone();
         Callback::from(move |e: Event| {
@@ -465,30 +544,96 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
                 return;
             }
 
+            if new_status == BLOCKED {
+                // Keep showing the current status until the prompt is submitted
+                select.set_value(&current_status);
+                blocking.set(true);
+                return;
+            }
+
             let changing_status = changing_status.clone();
+            let toast = toast.clone();
             let refresh_trigger = refresh_trigger.clone();
             let item_id = item_id.clone();
 
             changing_status.set(true);
 
             wasm_bindgen_futures::spawn_local(async move {
-                let api_status = display_to_api(&new_status);
-                let body = serde_json::json!({
-                    "status": api_status,
-                });
+                match post_status(&item_id, &new_status, None).await {
+                    Ok(()) => toast.success(format!("Status changed to {}", new_status)),
+                    // e.g. a transition the workflow doesn't allow; the refresh resets the select
+                    Err(msg) => toast.error(msg),
+                }
+                refresh_trigger.set(*refresh_trigger + 1);
+                changing_status.set(false);
+            });
+        })
+    };
 
-                match Request::post(&format!("/api/items/{}/status", item_id))
-                    .header("Content-Type", "application/json")
-                    .body(body.to_string())
-                    .unwrap()
-                    .send()
-                    .await
-                {
-                    Ok(resp) if resp.ok() => {
-                        refresh_trigger.set(*refresh_trigger + 1);
+    let on_blocked_comment_input = {
+        let blocked_comment = blocked_comment.clone();
+        Callback::from(move |e: InputEvent| {
+            let textarea: HtmlTextAreaElement = e.target().unwrap().dyn_into().unwrap();
+            blocked_comment.set(textarea.value());
+        })
+    };
+
+    let on_blocked_cancel = {
+        let blocking = blocking.clone();
+        let blocked_comment = blocked_comment.clone();
+        Callback::from(move |_: MouseEvent| {
+            blocking.set(false);
+            blocked_comment.set(String::new());
+        })
+    };
+
+    let on_blocked_keydown = {
+        let blocking = blocking.clone();
+        let blocked_comment = blocked_comment.clone();
+        Callback::from(move |e: KeyboardEvent| {
+            if e.key() == "Escape" {
+                // Marks it handled so the global shortcut doesn't also close the modal
+                e.prevent_default();
+                blocking.set(false);
+                blocked_comment.set(String::new());
+            }
+        })
+    };
+
+    let on_blocked_submit = {
+        let blocking = blocking.clone();
+        let blocked_comment = blocked_comment.clone();
+        let changing_status = changing_status.clone();
+        let toast = toast.clone();
+        let refresh_trigger = refresh_trigger.clone();
+        let item_id = item_id.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+
+            let comment = blocked_comment.trim().to_string();
+            if comment.is_empty() {
+                return;
+            }
+
+            let blocking = blocking.clone();
+            let blocked_comment = blocked_comment.clone();
+            let changing_status = changing_status.clone();
+            let toast = toast.clone();
+            let refresh_trigger = refresh_trigger.clone();
+            let item_id = item_id.clone();
+
+            changing_status.set(true);
+
+            wasm_bindgen_futures::spawn_local(async move {
+                match post_status(&item_id, BLOCKED, Some(comment)).await {
+                    Ok(()) => {
+                        toast.success(format!("Status changed to {}", BLOCKED));
+                        blocking.set(false);
+                        blocked_comment.set(String::new());
                     }
-                    _ => {}
+                    Err(msg) => toast.error(msg),
                 }
+                refresh_trigger.set(*refresh_trigger + 1);
                 changing_status.set(false);
             });
         })
@@ -496,6 +641,7 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
 
     // Owner change handler
     let on_owner_change = {
+        let toast = toast.clone();
         let item = item.clone();
         let changing_owner = changing_owner.clone();
         let refresh_trigger = refresh_trigger.clone();
@@ -515,6 +661,8 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
             let changing_owner = changing_owner.clone();
             let refresh_trigger = refresh_trigger.clone();
             let item_id = item_id.clone();
+            let toast = toast.clone();
+            let expected_updated_at = (*item).as_ref().map(|i| i.updated_at);
 
             changing_owner.set(true);
 
@@ -523,9 +671,10 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
                     "owner_id": new_owner_id,
                 });
 
-                if patch_item(&item_id, body).await {
-                    refresh_trigger.set(*refresh_trigger + 1);
+                if let Err(msg) = patch_item(&item_id, expected_updated_at, body).await {
+                    toast.error(msg);
                 }
+                refresh_trigger.set(*refresh_trigger + 1);
                 changing_owner.set(false);
             });
         })
@@ -533,6 +682,7 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
 
     // Priority change handler
     let on_priority_change = {
+        let toast = toast.clone();
         let item = item.clone();
         let changing_priority = changing_priority.clone();
         let refresh_trigger = refresh_trigger.clone();
@@ -552,6 +702,8 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
             let changing_priority = changing_priority.clone();
             let refresh_trigger = refresh_trigger.clone();
             let item_id = item_id.clone();
+            let toast = toast.clone();
+            let expected_updated_at = (*item).as_ref().map(|i| i.updated_at);
 
             changing_priority.set(true);
 
@@ -560,9 +712,10 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
                     "priority": new_priority,
                 });
 
-                if patch_item(&item_id, body).await {
-                    refresh_trigger.set(*refresh_trigger + 1);
+                if let Err(msg) = patch_item(&item_id, expected_updated_at, body).await {
+                    toast.error(msg);
                 }
+                refresh_trigger.set(*refresh_trigger + 1);
                 changing_priority.set(false);
             });
         })
@@ -570,6 +723,7 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
 
     // Due date change handler
     let on_due_date_change = {
+        let toast = toast.clone();
         let item = item.clone();
         let changing_due_date = changing_due_date.clone();
         let refresh_trigger = refresh_trigger.clone();
@@ -590,6 +744,8 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
             let changing_due_date = changing_due_date.clone();
             let refresh_trigger = refresh_trigger.clone();
             let item_id = item_id.clone();
+            let toast = toast.clone();
+            let expected_updated_at = (*item).as_ref().map(|i| i.updated_at);
 
             changing_due_date.set(true);
 
@@ -600,9 +756,10 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
                     serde_json::json!({ "due_date": new_date })
                 };
 
-                if patch_item(&item_id, body).await {
-                    refresh_trigger.set(*refresh_trigger + 1);
+                if let Err(msg) = patch_item(&item_id, expected_updated_at, body).await {
+                    toast.error(msg);
                 }
+                refresh_trigger.set(*refresh_trigger + 1);
                 changing_due_date.set(false);
             });
         })
@@ -610,6 +767,7 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
 
     // Category change handler
     let on_category_change = {
+        let toast = toast.clone();
         let item = item.clone();
         let changing_category = changing_category.clone();
         let refresh_trigger = refresh_trigger.clone();
@@ -629,6 +787,8 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
             let changing_category = changing_category.clone();
             let refresh_trigger = refresh_trigger.clone();
             let item_id = item_id.clone();
+            let toast = toast.clone();
+            let expected_updated_at = (*item).as_ref().map(|i| i.updated_at);
 
             changing_category.set(true);
 
@@ -637,14 +797,135 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
                     "category_id": new_category_id,
                 });
 
-                if patch_item(&item_id, body).await {
-                    refresh_trigger.set(*refresh_trigger + 1);
+                if let Err(msg) = patch_item(&item_id, expected_updated_at, body).await {
+                    toast.error(msg);
                 }
+                refresh_trigger.set(*refresh_trigger + 1);
                 changing_category.set(false);
             });
         })
     };
 
+    let on_delete_click = {
+        let confirming_delete = confirming_delete.clone();
+        let delete_error = delete_error.clone();
+        Callback::from(move |_: MouseEvent| {
+            delete_error.set(None);
+            confirming_delete.set(true);
+        })
+    };
+
+    let on_delete_cancel = {
+        let confirming_delete = confirming_delete.clone();
+        Callback::from(move |_: MouseEvent| {
+            confirming_delete.set(false);
+        })
+    };
+
+    let on_delete_confirm = {
+        let item_id = item_id.clone();
+        let deleting = deleting.clone();
+        let confirming_delete = confirming_delete.clone();
+        let delete_error = delete_error.clone();
+        let toast = toast.clone();
+        let on_close = props.on_close.clone();
+        Callback::from(move |_: MouseEvent| {
+            let item_id = item_id.clone();
+            let deleting = deleting.clone();
+            let confirming_delete = confirming_delete.clone();
+            let delete_error = delete_error.clone();
+            let toast = toast.clone();
+            let on_close = on_close.clone();
+
+            deleting.set(true);
+
+            wasm_bindgen_futures::spawn_local(async move {
+                let result = match Request::delete(&api::url(&format!("/api/items/{}", item_id)))
+                    .send()
+                    .await
+                {
+                    Ok(resp) if resp.ok() => Ok(()),
+                    Ok(resp) => Err(resp
+                        .json::<shared::ApiError>()
+                        .await
+                        .map(|e| e.error.message)
+                        .unwrap_or_else(|_| "Failed to delete item".to_string())),
+                    Err(_) => Err("Failed to delete item".to_string()),
+                };
+                deleting.set(false);
+                match result {
+                    // Closing the modal refreshes the items list
+                    Ok(()) => {
+                        toast.success(format!("Deleted {}", item_id));
+                        on_close.emit(());
+                    }
+                    Err(msg) => {
+                        confirming_delete.set(false);
+                        delete_error.set(Some(msg));
+                    }
+                }
+            });
+        })
+    };
+
+    let on_watch_toggle = {
+        let item = item.clone();
+        let toggling_watch = toggling_watch.clone();
+        let refresh_trigger = refresh_trigger.clone();
+        let toast = toast.clone();
+        Callback::from(move |_: MouseEvent| {
+            let Some(current) = (*item).clone() else {
+                return;
+            };
+            let watching = current.watching.unwrap_or(false);
+
+            // Show the new state right away; it's put back if the server says no
+            item.set(Some(ActionItemResponse {
+                watching: Some(!watching),
+                watcher_count: (current.watcher_count + if watching { -1 } else { 1 }).max(0),
+                ..current.clone()
+            }));
+            toggling_watch.set(true);
+
+            let item = item.clone();
+            let toggling_watch = toggling_watch.clone();
+            let refresh_trigger = refresh_trigger.clone();
+            let toast = toast.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let url = api::url(&format!("/api/items/{}/watch", current.id));
+                let request = if watching {
+                    Request::delete(&url)
+                } else {
+                    Request::post(&url)
+                };
+                let result = match request.send().await {
+                    Ok(resp) if resp.ok() => Ok(()),
+                    // Already watching (409) or not watching (404), e.g. after toggling in
+                    // another tab: the state we wanted holds, but the count may be off
+                    Ok(resp) if resp.status() == 409 || resp.status() == 404 => {
+                        refresh_trigger.set(*refresh_trigger + 1);
+                        Ok(())
+                    }
+                    Ok(resp) if resp.status() == 401 => {
+                        Err("Your session has expired; reload the page to sign in again"
+                            .to_string())
+                    }
+                    Ok(resp) => Err(resp
+                        .json::<shared::ApiError>()
+                        .await
+                        .map(|e| e.error.message)
+                        .unwrap_or_else(|_| "Failed to update watching".to_string())),
+                    Err(_) => Err("Failed to update watching".to_string()),
+                };
+                toggling_watch.set(false);
+                if let Err(msg) = result {
+                    item.set(Some(current));
+                    toast.error(msg);
+                }
+            });
+        })
+    };
+
     html! {
         <div class="modal-backdrop" onclick={on_backdrop_click}>
             <div class="modal modal-large" onclick={on_modal_click}>
@@ -672,8 +953,12 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
                                     oninput={on_title_input}
                                     onblur={on_title_blur}
                                     onkeydown={on_title_keydown}
+                                    maxlength={props.title_max_length.to_string()}
                                     autofocus=true
                                 />
+                                <span class="char-count">
+                                    { format!("{}/{}", edit_title_value.chars().count(), props.title_max_length) }
+                                </span>
                             } else {
                                 <h2 class="editable-title" onclick={on_title_click} title="Click to edit">
                                     { &i.title }
@@ -681,6 +966,21 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
                                 </h2>
                             }
                         </div>
+                        <button
+                            type="button"
+                            class={classes!("watch-toggle", i.watching.unwrap_or(false).then_some("watching"))}
+                            onclick={on_watch_toggle}
+                            disabled={*toggling_watch || props.read_only}
+                            aria-pressed={i.watching.unwrap_or(false).to_string()}
+                            title={if i.watching.unwrap_or(false) {
+                                "Watching: you get emails about notes and status changes. Click to stop."
+                            } else {
+                                "Watch to get emails about notes and status changes"
+                            }}
+                        >
+                            { if i.watching.unwrap_or(false) { "🔔" } else { "🔕" } }
+                            <span class="watcher-count">{ i.watcher_count }</span>
+                        </button>
                         <button type="button" class="modal-close" onclick={on_close_btn}>{ "×" }</button>
                     </div>
                     <div class="modal-body">
@@ -695,7 +995,7 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
                                     class="inline-date-input"
                                     value={i.due_date.map(|d| d.to_string()).unwrap_or_default()}
                                     onchange={on_due_date_change}
-                                    disabled={*changing_due_date}
+                                    disabled={*changing_due_date || props.read_only}
                                 />
                                 if *changing_due_date {
                                     <span class="saving-indicator">{ " (saving...)" }</span>
@@ -706,7 +1006,7 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
                                 <select
                                     class="category-select"
                                     onchange={on_category_change}
-                                    disabled={*changing_category}
+                                    disabled={*changing_category || props.read_only}
                                 >
                                     { for props.categories.iter()
                                         .filter(|c| c.vendor_id == i.vendor_id)
@@ -726,7 +1026,7 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
                                 <select
                                     class={classes!("priority-select", priority_class(&i.priority))}
                                     onchange={on_priority_change}
-                                    disabled={*changing_priority}
+                                    disabled={*changing_priority || props.read_only}
                                 >
                                     <option value="High" selected={i.priority == "High"}>{ "High" }</option>
                                     <option value="Medium" selected={i.priority == "Medium"}>{ "Medium" }</option>
@@ -741,7 +1041,7 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
                                 <select
                                     class={classes!("status-select", status_class(&i.status))}
                                     onchange={on_status_change}
-                                    disabled={*changing_status}
+                                    disabled={*changing_status || props.read_only}
                                     value={i.status.clone()}
                                 >
                                     { for STATUSES.iter().map(|(display, _)| {
@@ -759,8 +1059,12 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
                                 <select
                                     class="owner-select"
                                     onchange={on_owner_change}
-                                    disabled={*changing_owner}
+                                    disabled={*changing_owner || props.read_only}
                                 >
+                                    // Deactivated owners aren't in the user list but still own the item
+                                    if !props.users.iter().any(|u| u.id == i.owner_id) {
+                                        <option value={i.owner_id.to_string()} selected={true}>{ format!("{} (inactive)", i.owner_name) }</option>
+                                    }
                                     { for props.users.iter().map(|u| {
                                         html! {
                                             <option value={u.id.to_string()} selected={u.id == i.owner_id}>{ &u.name }</option>
@@ -773,6 +1077,29 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
                             </span>
                         </div>
 
+                        if *blocking {
+                            <form class="add-update-form blocked-prompt" onsubmit={on_blocked_submit}>
+                                <textarea
+                                    placeholder="What is this blocked on?"
+                                    value={(*blocked_comment).clone()}
+                                    oninput={on_blocked_comment_input}
+                                    onkeydown={on_blocked_keydown}
+                                    rows="2"
+                                    autofocus=true
+                                />
+                                <button
+                                    type="submit"
+                                    class="btn btn-danger"
+                                    disabled={*changing_status || blocked_comment.trim().is_empty()}
+                                >
+                                    { "Mark Blocked" }
+                                </button>
+                                <button type="button" class="btn btn-secondary" onclick={on_blocked_cancel}>
+                                    { "Cancel" }
+                                </button>
+                            </form>
+                        }
+
                         <div class="description-section">
                             <h3>{ "Description" }</h3>
                             if *editing_description {
@@ -791,7 +1118,7 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
                                 <div class="description-content editable" onclick={on_description_click} title="Click to edit">
                                     if let Some(desc) = &i.description {
                                         if !desc.is_empty() {
-                                            { linkify_text(desc) }
+                                            { render_markdown(desc) }
                                         } else {
                                             <span class="placeholder">{ "Click to add description..." }</span>
                                         }
@@ -810,8 +1137,9 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
                                 value={(*new_update_content).clone()}
                                 oninput={on_update_change}
                                 rows="3"
+                                disabled={props.read_only}
                             />
-                            <button type="submit" class="btn btn-primary" disabled={*submitting || new_update_content.trim().is_empty()}>
+                            <button type="submit" class="btn btn-primary" disabled={*submitting || props.read_only || new_update_content.trim().is_empty()}>
                                 { if *submitting { "Adding..." } else { "Add Note" } }
                             </button>
                         </form>
@@ -823,14 +1151,22 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
                                 <ul class="updates-list">
                                     { for history.iter().map(|entry| {
                                         match entry {
-                                            HistoryEntry::Note { timestamp, author, content } => {
+                                            HistoryEntry::Note { timestamp, author, author_initials, content, mentioned_user_ids } => {
+                                                let mentioned: Vec<&shared::User> = props
+                                                    .users
+                                                    .iter()
+                                                    .filter(|u| mentioned_user_ids.contains(&u.id))
+                                                    .collect();
                                                 html! {
                                                     <li class="update-item">
                                                         <div class="update-header">
-                                                            <span class="update-author">{ author }</span>
+                                                            <span class="update-author">
+                                                                <Avatar name={author.clone()} initials={author_initials.clone()} />
+                                                                { author }
+                                                            </span>
                                                             <span class="update-date">{ format_datetime(timestamp) }</span>
                                                         </div>
-                                                        <div class="update-content">{ linkify_text(content) }</div>
+                                                        <div class="update-content">{ render_note(content, &mentioned) }</div>
                                                     </li>
                                                 }
                                             }
@@ -862,6 +1198,38 @@ pub fn item_detail_modal(props: &ItemDetailModalProps) -> Html {
                             }
                         </div>
                     </div>
+                    if props.is_admin && !props.read_only {
+                        <div class="modal-footer">
+                            if let Some(err) = (*delete_error).clone() {
+                                <span class="error">{ err }</span>
+                            }
+                            if *confirming_delete {
+                                <span class="delete-confirm-text">
+                                    { format!("Delete {}?", i.id) }
+                                </span>
+                                <button
+                                    type="button"
+                                    class="btn btn-danger"
+                                    onclick={on_delete_confirm}
+                                    disabled={*deleting}
+                                >
+                                    { if *deleting { "Deleting..." } else { "Delete" } }
+                                </button>
+                                <button
+                                    type="button"
+                                    class="btn btn-secondary"
+                                    onclick={on_delete_cancel}
+                                    disabled={*deleting}
+                                >
+                                    { "Cancel" }
+                                </button>
+                            } else {
+                                <button type="button" class="btn btn-danger" onclick={on_delete_click}>
+                                    { "Delete" }
+                                </button>
+                            }
+                        </div>
+                    }
                 } else {
                     <div class="modal-header">
                         <h2>{ "Item not found" }</h2>
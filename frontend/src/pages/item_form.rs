@@ -1,9 +1,12 @@
 use gloo_net::http::Request;
-use shared::{CategoryResponse, Vendor};
+use shared::{ActionItemResponse, CategoryResponse, Vendor};
 use wasm_bindgen::JsCast;
 use web_sys::{HtmlInputElement, HtmlSelectElement};
 use yew::prelude::*;
 
+use crate::api;
+use crate::components::use_toast;
+
 #[derive(Properties, PartialEq)]
 pub struct NewItemModalProps {
     pub vendors: Vec<Vendor>,
@@ -12,6 +15,10 @@ pub struct NewItemModalProps {
     pub on_close: Callback<()>,
     pub on_created: Callback<()>,
     pub on_refresh: Callback<()>,
+    #[prop_or_default]
+    pub read_only: bool,
+    #[prop_or(shared::DEFAULT_TITLE_MAX_LENGTH)]
+    pub title_max_length: usize,
 }
 
 #[function_component(NewItemModal)]
@@ -20,12 +27,22 @@ pub fn new_item_modal(props: &NewItemModalProps) -> Html {
     let due_date = use_state(String::new);
     let category_id = use_state(|| 0i32);
     let priority = use_state(|| "Medium".to_string());
+    let recurrence = use_state(|| "none".to_string());
     let vendor_id = use_state(|| props.vendors.first().map(|v| v.id).unwrap_or(0));
     let owner_id = use_state(|| props.users.first().map(|u| u.id).unwrap_or(0));
     let error = use_state(|| None::<String>);
     let submitting = use_state(|| false);
     let new_category_name = use_state(String::new);
     let adding_category = use_state(|| false);
+    let toast = use_toast();
+    // One key per open form, so a double-clicked or retried submit creates one item
+    let idempotency_key = use_state(|| {
+        format!(
+            "{:x}-{:x}",
+            js_sys::Date::now() as u64,
+            (js_sys::Math::random() * u32::MAX as f64) as u32
+        )
+    });
 
     // Filter categories for current vendor
     let vendor_categories: Vec<&CategoryResponse> = props
@@ -94,6 +111,14 @@ pub fn new_item_modal(props: &NewItemModalProps) -> Html {
         })
     };
 
+    let on_recurrence_change = {
+        let recurrence = recurrence.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target().unwrap().dyn_into().unwrap();
+            recurrence.set(select.value());
+        })
+    };
+
     let on_owner_change = {
         let owner_id = owner_id.clone();
         Callback::from(move |e: Event| {
@@ -145,6 +170,7 @@ pub fn new_item_modal(props: &NewItemModalProps) -> Html {
         let category_id = category_id.clone();
         let vendor_id = vendor_id.clone();
         let on_refresh = props.on_refresh.clone();
+        let toast = toast.clone();
 
         Callback::from(move |_| {
             let name = (*new_category_name).clone();
@@ -157,18 +183,22 @@ pub fn new_item_modal(props: &NewItemModalProps) -> Html {
             let category_id = category_id.clone();
             let vendor_id_val = *vendor_id;
             let on_refresh = on_refresh.clone();
+            let toast = toast.clone();
 
             wasm_bindgen_futures::spawn_local(async move {
                 let body = serde_json::json!({
                     "name": name,
                 });
 
-                match Request::post(&format!("/api/vendors/{}/categories", vendor_id_val))
-                    .header("Content-Type", "application/json")
-                    .body(body.to_string())
-                    .unwrap()
-                    .send()
-                    .await
+                match Request::post(&api::url(&format!(
+                    "/api/vendors/{}/categories",
+                    vendor_id_val
+                )))
+                .header("Content-Type", "application/json")
+                .body(body.to_string())
+                .unwrap()
+                .send()
+                .await
                 {
                     Ok(resp) if resp.ok() => {
                         if let Ok(cat) = resp.json::<CategoryResponse>().await {
@@ -179,7 +209,13 @@ pub fn new_item_modal(props: &NewItemModalProps) -> Html {
                         // Trigger a refresh to reload categories without closing modal
                         on_refresh.emit(());
                     }
-                    _ => {}
+                    Ok(resp) => toast.error(
+                        resp.json::<shared::ApiError>()
+                            .await
+                            .map(|e| e.error.message)
+                            .unwrap_or_else(|_| "Failed to add category".to_string()),
+                    ),
+                    Err(_) => toast.error("Failed to add category"),
                 }
             });
         })
@@ -190,11 +226,14 @@ pub fn new_item_modal(props: &NewItemModalProps) -> Html {
         let due_date = due_date.clone();
         let category_id = category_id.clone();
         let priority = priority.clone();
+        let recurrence = recurrence.clone();
         let vendor_id = vendor_id.clone();
         let owner_id = owner_id.clone();
         let error = error.clone();
         let submitting = submitting.clone();
         let on_created = props.on_created.clone();
+        let toast = toast.clone();
+        let idempotency_key = idempotency_key.clone();
 
         Callback::from(move |e: SubmitEvent| {
             e.prevent_default();
@@ -203,11 +242,14 @@ pub fn new_item_modal(props: &NewItemModalProps) -> Html {
             let due_date_val = (*due_date).clone();
             let category_id_val = *category_id;
             let priority_val = (*priority).clone();
+            let recurrence_val = (*recurrence).clone();
             let vendor_id_val = *vendor_id;
             let owner_id_val = *owner_id;
             let error = error.clone();
             let submitting = submitting.clone();
             let on_created = on_created.clone();
+            let toast = toast.clone();
+            let idempotency_key = (*idempotency_key).clone();
 
             if vendor_id_val == 0 {
                 error.set(Some("Please select a vendor".to_string()));
@@ -228,16 +270,22 @@ pub fn new_item_modal(props: &NewItemModalProps) -> Html {
                     "category_id": category_id_val,
                     "priority": priority_val,
                     "owner_id": owner_id_val,
+                    "recurrence": recurrence_val,
                 });
 
-                match Request::post(&format!("/api/vendors/{}/items", vendor_id_val))
+                match Request::post(&api::url(&format!("/api/vendors/{}/items", vendor_id_val)))
                     .header("Content-Type", "application/json")
+                    .header("Idempotency-Key", &idempotency_key)
                     .body(body.to_string())
                     .unwrap()
                     .send()
                     .await
                 {
                     Ok(resp) if resp.ok() => {
+                        match resp.json::<ActionItemResponse>().await {
+                            Ok(item) => toast.success(format!("Created {}", item.id)),
+                            Err(_) => toast.success("Item created"),
+                        }
                         on_created.emit(());
                     }
                     Ok(resp) => {
@@ -293,8 +341,12 @@ pub fn new_item_modal(props: &NewItemModalProps) -> Html {
                             id="title"
                             value={(*title).clone()}
                             oninput={on_title_change}
+                            maxlength={props.title_max_length.to_string()}
                             required=true
                         />
+                        <span class="char-count">
+                            { format!("{}/{}", title.chars().count(), props.title_max_length) }
+                        </span>
                     </div>
 
                     <div class="form-group">
@@ -317,7 +369,7 @@ pub fn new_item_modal(props: &NewItemModalProps) -> Html {
                                     value={(*new_category_name).clone()}
                                     oninput={on_new_category_input}
                                 />
-                                <button type="button" class="btn btn-small btn-primary" onclick={on_add_category}>
+                                <button type="button" class="btn btn-small btn-primary" onclick={on_add_category} disabled={props.read_only}>
                                     { "Add" }
                                 </button>
                                 <button type="button" class="btn btn-small" onclick={on_cancel_add_category}>
@@ -335,7 +387,7 @@ pub fn new_item_modal(props: &NewItemModalProps) -> Html {
                                         }
                                     })}
                                 </select>
-                                <button type="button" class="btn btn-small" onclick={on_show_add_category} title="Add new category">
+                                <button type="button" class="btn btn-small" onclick={on_show_add_category} title="Add new category" disabled={props.read_only}>
                                     { "+" }
                                 </button>
                             </div>
@@ -351,6 +403,15 @@ pub fn new_item_modal(props: &NewItemModalProps) -> Html {
                         </select>
                     </div>
 
+                    <div class="form-group">
+                        <label for="recurrence">{ "Repeats" }</label>
+                        <select id="recurrence" onchange={on_recurrence_change}>
+                            <option value="none" selected={*recurrence == "none"}>{ "Never" }</option>
+                            <option value="weekly" selected={*recurrence == "weekly"}>{ "Weekly" }</option>
+                            <option value="monthly" selected={*recurrence == "monthly"}>{ "Monthly" }</option>
+                        </select>
+                    </div>
+
                     <div class="form-group">
                         <label for="owner">{ "Owner" }</label>
                         <select id="owner" onchange={on_owner_change}>
@@ -365,7 +426,7 @@ pub fn new_item_modal(props: &NewItemModalProps) -> Html {
                     </div>
 
                     <div class="form-actions">
-                        <button type="submit" class="btn btn-primary" disabled={*submitting}>
+                        <button type="submit" class="btn btn-primary" disabled={*submitting || props.read_only}>
                             { if *submitting { "Creating..." } else { "Create Item" } }
                         </button>
                         <button type="button" class="btn" onclick={on_cancel}>
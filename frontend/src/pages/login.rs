@@ -1,5 +1,7 @@
 use yew::prelude::*;
 
+use crate::api;
+
 #[function_component(Login)]
 pub fn login() -> Html {
     html! {
@@ -8,7 +10,7 @@ pub fn login() -> Html {
                 <h1 class="login-title">{ "Cosmic Frontier" }</h1>
                 <p class="login-subtitle">{ "Action Tracker" }</p>
                 <p>{ "Sign in to continue" }</p>
-                <a href="/auth/login" class="login-button">
+                <a href={api::url("/auth/login")} class="login-button">
                     { "Sign in with Google" }
                 </a>
             </div>
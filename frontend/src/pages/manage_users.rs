@@ -0,0 +1,119 @@
+use gloo_net::http::Request;
+use yew::prelude::*;
+
+use crate::api;
+use crate::components::Avatar;
+
+#[derive(Properties, PartialEq)]
+pub struct ManageUsersModalProps {
+    pub on_close: Callback<()>,
+}
+
+/// Everyone who can sign in, deactivated users last. Users appear on their first OAuth
+/// login, or are created ahead of it by an admin (`POST /api/users` or the CLI's
+/// `create-user`); there is no add form here yet.
+#[function_component(ManageUsersModal)]
+pub fn manage_users_modal(props: &ManageUsersModalProps) -> Html {
+    let users = use_state(Vec::<shared::User>::new);
+    let loading = use_state(|| true);
+    let error = use_state(|| None::<String>);
+
+    // Fetch users (include inactive)
+    {
+        let users = users.clone();
+        let loading = loading.clone();
+        let error = error.clone();
+
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                match Request::get(&api::url("/api/users?include_inactive=true"))
+                    .send()
+                    .await
+                {
+                    Ok(resp) if resp.ok() => {
+                        if let Ok(data) = resp.json::<Vec<shared::User>>().await {
+                            users.set(data);
+                        }
+                    }
+                    Ok(resp) => {
+                        error.set(Some(format!("Failed to fetch users: {}", resp.status())));
+                    }
+                    Err(e) => {
+                        error.set(Some(format!("Request error: {}", e)));
+                    }
+                }
+                loading.set(false);
+            });
+            || ()
+        });
+    }
+
+    let on_backdrop_click = {
+        let on_close = props.on_close.clone();
+        Callback::from(move |_| {
+            on_close.emit(());
+        })
+    };
+
+    let on_modal_click = Callback::from(|e: MouseEvent| {
+        e.stop_propagation();
+    });
+
+    let active_users: Vec<&shared::User> = users.iter().filter(|u| u.active).collect();
+    let inactive_users: Vec<&shared::User> = users.iter().filter(|u| !u.active).collect();
+
+    let user_row = |u: &&shared::User| {
+        html! {
+            <tr class={classes!((!u.active).then_some("user-inactive"))}>
+                <td>
+                    <span class="user-name-cell">
+                        <Avatar name={u.name.clone()} initials={u.initials.clone()} />
+                        { &u.name }
+                    </span>
+                </td>
+                <td>{ u.initials.as_deref().unwrap_or("-") }</td>
+                <td>{ &u.email }</td>
+                <td>{ if u.active { "Active" } else { "Deactivated" } }</td>
+            </tr>
+        }
+    };
+
+    html! {
+        <div class="modal-backdrop" onclick={on_backdrop_click}>
+            <div class="modal" onclick={on_modal_click}>
+                <div class="modal-header">
+                    <h2>{ "Manage Users" }</h2>
+                    <button class="btn-close" onclick={
+                        let on_close = props.on_close.clone();
+                        Callback::from(move |_: MouseEvent| on_close.emit(()))
+                    }>{ "\u{00d7}" }</button>
+                </div>
+
+                <div class="modal-body">
+                    if let Some(err) = (*error).clone() {
+                        <p class="error">{ err }</p>
+                    }
+
+                    if *loading {
+                        <p>{ "Loading..." }</p>
+                    } else {
+                        <table class="table manage-users-list">
+                            <thead>
+                                <tr>
+                                    <th>{ "Name" }</th>
+                                    <th>{ "Initials" }</th>
+                                    <th>{ "Email" }</th>
+                                    <th>{ "Status" }</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                { for active_users.iter().map(user_row) }
+                                { for inactive_users.iter().map(user_row) }
+                            </tbody>
+                        </table>
+                    }
+                </div>
+            </div>
+        </div>
+    }
+}
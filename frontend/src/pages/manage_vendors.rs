@@ -3,6 +3,8 @@ use wasm_bindgen::JsCast;
 use web_sys::HtmlInputElement;
 use yew::prelude::*;
 
+use crate::api;
+
 #[derive(Clone, PartialEq, serde::Deserialize)]
 pub struct VendorEntry {
     pub id: i32,
@@ -15,6 +17,8 @@ pub struct VendorEntry {
 #[derive(Properties, PartialEq)]
 pub struct ManageVendorsModalProps {
     pub on_close: Callback<()>,
+    #[prop_or_default]
+    pub read_only: bool,
 }
 
 #[function_component(ManageVendorsModal)]
@@ -38,7 +42,7 @@ pub fn manage_vendors_modal(props: &ManageVendorsModalProps) -> Html {
 
         use_effect_with(refresh, move |_| {
             wasm_bindgen_futures::spawn_local(async move {
-                match Request::get("/api/vendors?include_archived=true")
+                match Request::get(&api::url("/api/vendors?include_archived=true"))
                     .send()
                     .await
                 {
@@ -120,7 +124,7 @@ pub fn manage_vendors_modal(props: &ManageVendorsModalProps) -> Html {
                     "description": if desc_val.is_empty() { None::<String> } else { Some(desc_val) },
                 });
 
-                match Request::post("/api/vendors")
+                match Request::post(&api::url("/api/vendors"))
                     .header("Content-Type", "application/json")
                     .body(body.to_string())
                     .unwrap()
@@ -209,7 +213,7 @@ pub fn manage_vendors_modal(props: &ManageVendorsModalProps) -> Html {
                                 oninput={on_description_input}
                             />
                         </div>
-                        <button type="submit" class="btn btn-primary" disabled={*submitting}>
+                        <button type="submit" class="btn btn-primary" disabled={*submitting || props.read_only}>
                             { if *submitting { "Adding..." } else { "Add Vendor" } }
                         </button>
                     </form>
@@ -234,7 +238,7 @@ pub fn manage_vendors_modal(props: &ManageVendorsModalProps) -> Html {
                                         let refresh_trigger = refresh_trigger.clone();
                                         wasm_bindgen_futures::spawn_local(async move {
                                             let body = serde_json::json!({ "archived": true });
-                                            let _ = Request::patch(&format!("/api/vendors/{}", vendor_id))
+                                            let _ = Request::patch(&api::url(&format!("/api/vendors/{}", vendor_id)))
                                                 .header("Content-Type", "application/json")
                                                 .body(body.to_string())
                                                 .unwrap()
@@ -249,7 +253,7 @@ pub fn manage_vendors_modal(props: &ManageVendorsModalProps) -> Html {
                                             <td>{ &v.name }</td>
                                             <td>{ v.description.as_deref().unwrap_or("-") }</td>
                                             <td>
-                                                <button type="button" class="btn btn-small btn-danger" onclick={on_archive}>
+                                                <button type="button" class="btn btn-small btn-danger" onclick={on_archive} disabled={props.read_only}>
                                                     { "Archive" }
                                                 </button>
                                             </td>
@@ -263,7 +267,7 @@ pub fn manage_vendors_modal(props: &ManageVendorsModalProps) -> Html {
                                         let refresh_trigger = refresh_trigger.clone();
                                         wasm_bindgen_futures::spawn_local(async move {
                                             let body = serde_json::json!({ "archived": false });
-                                            let _ = Request::patch(&format!("/api/vendors/{}", vendor_id))
+                                            let _ = Request::patch(&api::url(&format!("/api/vendors/{}", vendor_id)))
                                                 .header("Content-Type", "application/json")
                                                 .body(body.to_string())
                                                 .unwrap()
@@ -278,7 +282,7 @@ pub fn manage_vendors_modal(props: &ManageVendorsModalProps) -> Html {
                                             <td>{ &v.name }</td>
                                             <td>{ v.description.as_deref().unwrap_or("-") }</td>
                                             <td>
-                                                <button type="button" class="btn btn-small btn-success" onclick={on_unarchive}>
+                                                <button type="button" class="btn btn-small btn-success" onclick={on_unarchive} disabled={props.read_only}>
                                                     { "Unarchive" }
                                                 </button>
                                             </td>
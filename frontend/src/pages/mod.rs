@@ -2,5 +2,6 @@ pub mod home;
 pub mod item_detail;
 pub mod item_form;
 pub mod login;
+pub mod manage_users;
 pub mod manage_vendors;
 pub mod status_style;
@@ -0,0 +1 @@
+ALTER TABLE vendors ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
@@ -0,0 +1,7 @@
+ALTER TABLE notes DROP CONSTRAINT notes_action_item_id_fkey;
+ALTER TABLE notes ADD CONSTRAINT notes_action_item_id_fkey
+    FOREIGN KEY (action_item_id) REFERENCES action_items(id);
+
+ALTER TABLE status_history DROP CONSTRAINT status_history_action_item_id_fkey;
+ALTER TABLE status_history ADD CONSTRAINT status_history_action_item_id_fkey
+    FOREIGN KEY (action_item_id) REFERENCES action_items(id);
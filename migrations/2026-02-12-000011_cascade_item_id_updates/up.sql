@@ -0,0 +1,8 @@
+-- Let an item's id change (moving it to another vendor) carry its notes and history along
+ALTER TABLE notes DROP CONSTRAINT notes_action_item_id_fkey;
+ALTER TABLE notes ADD CONSTRAINT notes_action_item_id_fkey
+    FOREIGN KEY (action_item_id) REFERENCES action_items(id) ON UPDATE CASCADE;
+
+ALTER TABLE status_history DROP CONSTRAINT status_history_action_item_id_fkey;
+ALTER TABLE status_history ADD CONSTRAINT status_history_action_item_id_fkey
+    FOREIGN KEY (action_item_id) REFERENCES action_items(id) ON UPDATE CASCADE;
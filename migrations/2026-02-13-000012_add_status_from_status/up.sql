@@ -0,0 +1,10 @@
+ALTER TABLE status_history ADD COLUMN from_status VARCHAR(50);
+
+-- Backfill from each item's previous entry; the first entry per item stays NULL
+UPDATE status_history sh
+SET from_status = prev.from_status
+FROM (
+    SELECT id, LAG(status) OVER (PARTITION BY action_item_id ORDER BY changed_at, id) AS from_status
+    FROM status_history
+) prev
+WHERE prev.id = sh.id AND prev.from_status IS NOT NULL;
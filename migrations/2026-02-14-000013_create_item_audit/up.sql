@@ -0,0 +1,11 @@
+CREATE TABLE item_audit (
+    id SERIAL PRIMARY KEY,
+    action_item_id VARCHAR(20) NOT NULL REFERENCES action_items(id) ON UPDATE CASCADE,
+    field VARCHAR(50) NOT NULL,
+    old_value TEXT,
+    new_value TEXT,
+    changed_by_id INTEGER NOT NULL REFERENCES users(id),
+    changed_at TIMESTAMPTZ DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX idx_item_audit_item ON item_audit(action_item_id, changed_at DESC);
@@ -0,0 +1,10 @@
+-- Additional owners beyond action_items.owner_id, which stays the primary owner
+CREATE TABLE item_assignees (
+    action_item_id VARCHAR(20) NOT NULL REFERENCES action_items(id) ON UPDATE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    role VARCHAR(50) NOT NULL DEFAULT 'secondary',
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL,
+    PRIMARY KEY (action_item_id, user_id)
+);
+
+CREATE INDEX idx_item_assignees_user ON item_assignees(user_id);
@@ -0,0 +1,8 @@
+CREATE TABLE item_watchers (
+    action_item_id VARCHAR(20) NOT NULL REFERENCES action_items(id) ON UPDATE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL,
+    PRIMARY KEY (action_item_id, user_id)
+);
+
+CREATE INDEX idx_item_watchers_user ON item_watchers(user_id);
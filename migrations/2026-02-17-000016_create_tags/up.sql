@@ -0,0 +1,14 @@
+-- Cross-vendor labels; names are stored lowercased
+CREATE TABLE tags (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(50) NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL
+);
+
+CREATE TABLE item_tags (
+    action_item_id VARCHAR(20) NOT NULL REFERENCES action_items(id) ON UPDATE CASCADE,
+    tag_id INTEGER NOT NULL REFERENCES tags(id),
+    PRIMARY KEY (action_item_id, tag_id)
+);
+
+CREATE INDEX idx_item_tags_tag ON item_tags(tag_id);
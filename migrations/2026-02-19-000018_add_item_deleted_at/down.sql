@@ -0,0 +1 @@
+ALTER TABLE action_items DROP COLUMN deleted_at;
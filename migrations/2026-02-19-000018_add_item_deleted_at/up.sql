@@ -0,0 +1,2 @@
+-- Soft delete: set instead of removing the row, cleared again on restore
+ALTER TABLE action_items ADD COLUMN deleted_at TIMESTAMPTZ;
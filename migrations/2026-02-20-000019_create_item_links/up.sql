@@ -0,0 +1,11 @@
+-- Directed links between items: `blocks` reads "from blocks to"; `relates_to` is symmetric
+CREATE TABLE item_links (
+    from_item_id VARCHAR(20) NOT NULL REFERENCES action_items(id) ON UPDATE CASCADE,
+    to_item_id VARCHAR(20) NOT NULL REFERENCES action_items(id) ON UPDATE CASCADE,
+    link_type VARCHAR(20) NOT NULL CHECK (link_type IN ('blocks', 'relates_to')),
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL,
+    PRIMARY KEY (from_item_id, to_item_id, link_type),
+    CHECK (from_item_id <> to_item_id)
+);
+
+CREATE INDEX idx_item_links_to ON item_links(to_item_id);
@@ -0,0 +1,2 @@
+-- Inactive users keep their history but are no longer offered as owners
+ALTER TABLE users ADD COLUMN active BOOLEAN NOT NULL DEFAULT TRUE;
@@ -0,0 +1,8 @@
+-- Users resolved from `@mentions` in a note's content
+CREATE TABLE note_mentions (
+    note_id INTEGER NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    PRIMARY KEY (note_id, user_id)
+);
+
+CREATE INDEX idx_note_mentions_user ON note_mentions(user_id);
@@ -0,0 +1,9 @@
+-- Per-user named filters; `filter_json` holds an object of `GET /api/items` query parameters
+CREATE TABLE saved_views (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    filter_json TEXT NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL,
+    UNIQUE (user_id, name)
+);
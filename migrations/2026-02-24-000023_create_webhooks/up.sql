@@ -0,0 +1,13 @@
+-- Outgoing HTTP notifications. `event_type` is `status_changed` (every status change) or
+-- `status_changed:<Status>` (changes to that status); a NULL vendor_id matches all vendors.
+CREATE TABLE webhooks (
+    id SERIAL PRIMARY KEY,
+    url TEXT NOT NULL,
+    event_type VARCHAR(50) NOT NULL,
+    vendor_id INTEGER REFERENCES vendors(id) ON DELETE CASCADE,
+    -- HMAC-SHA256 key for the X-Tracker-Signature header
+    secret TEXT NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX idx_webhooks_event_type ON webhooks(event_type);
@@ -0,0 +1,3 @@
+ALTER TABLE action_items
+    DROP COLUMN next_occurrence_id,
+    DROP COLUMN recurrence;
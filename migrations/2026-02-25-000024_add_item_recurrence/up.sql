@@ -0,0 +1,7 @@
+-- Complete recurring items are cloned into a fresh item once their interval has passed;
+-- next_occurrence_id points at that clone so each completion is only cloned once
+ALTER TABLE action_items
+    ADD COLUMN recurrence VARCHAR(20) NOT NULL DEFAULT 'none'
+        CHECK (recurrence IN ('none', 'weekly', 'monthly')),
+    ADD COLUMN next_occurrence_id VARCHAR(20)
+        REFERENCES action_items(id) ON UPDATE CASCADE ON DELETE SET NULL;
@@ -0,0 +1,4 @@
+-- Admins manage vendors and categories; promote them with `action-tracker-cli set-role`
+ALTER TABLE users
+    ADD COLUMN role VARCHAR(20) NOT NULL DEFAULT 'member'
+        CHECK (role IN ('admin', 'member'));
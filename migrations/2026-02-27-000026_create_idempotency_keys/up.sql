@@ -0,0 +1,12 @@
+-- Idempotency-Key values seen on item creation, so a retried or double-submitted create
+-- returns the first item instead of making another. action_item_id is NULL while the
+-- first request is still in flight.
+CREATE TABLE idempotency_keys (
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    key VARCHAR(255) NOT NULL,
+    action_item_id VARCHAR(20) REFERENCES action_items(id) ON UPDATE CASCADE ON DELETE CASCADE,
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL,
+    PRIMARY KEY (user_id, key)
+);
+
+CREATE INDEX idx_idempotency_keys_created_at ON idempotency_keys(created_at);
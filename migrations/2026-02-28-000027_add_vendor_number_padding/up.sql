@@ -0,0 +1,3 @@
+-- Digits an item number is zero-padded to in its id (3: AD-001)
+ALTER TABLE vendors ADD COLUMN number_padding INTEGER NOT NULL DEFAULT 3
+    CHECK (number_padding BETWEEN 1 AND 6);
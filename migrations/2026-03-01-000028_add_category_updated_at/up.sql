@@ -0,0 +1,2 @@
+-- Row version for If-Match on category renames, as vendors have
+ALTER TABLE categories ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
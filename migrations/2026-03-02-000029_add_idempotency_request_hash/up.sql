@@ -0,0 +1,3 @@
+-- Hash of the create that claimed each key, so reusing a key for a different request can
+-- be refused. NULL on keys claimed before the column existed.
+ALTER TABLE idempotency_keys ADD COLUMN request_hash VARCHAR(64);
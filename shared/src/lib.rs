@@ -1,3 +1,7 @@
+pub mod mentions;
+pub mod spreadsheet;
 pub mod types;
 
+pub use mentions::*;
+pub use spreadsheet::*;
 pub use types::*;
@@ -0,0 +1,147 @@
+//! `@mentions` in note text. The backend resolves them when a note is saved and the
+//! frontend finds them again to draw chips, so both sides share these rules.
+
+/// A mention in note text, without its `@`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MentionToken<'a> {
+    /// Byte offset of the `@`
+    pub start: usize,
+    pub token: &'a str,
+}
+
+impl MentionToken<'_> {
+    /// Byte offset just past the token.
+    pub fn end(&self) -> usize {
+        self.start + 1 + self.token.len()
+    }
+}
+
+fn is_token_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '.' | '_' | '-')
+}
+
+/// Every `@token` in `text`, in order. An `@` only starts a mention at the beginning of
+/// the text or after a character that can't be part of a word, so email addresses don't
+/// count; trailing punctuation ("thanks @mike.") is not part of the token.
+pub fn mention_tokens(text: &str) -> Vec<MentionToken<'_>> {
+    let mut tokens = Vec::new();
+    let mut prev: Option<char> = None;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let starts_mention = c == '@' && !prev.is_some_and(|p| p.is_alphanumeric() || p == '_');
+        prev = Some(c);
+        if !starts_mention {
+            continue;
+        }
+
+        let mut end = i + 1;
+        while let Some(&(j, next)) = chars.peek() {
+            if !is_token_char(next) {
+                break;
+            }
+            end = j + next.len_utf8();
+            prev = Some(next);
+            chars.next();
+        }
+        let token = text[i + 1..end].trim_end_matches(['.', '-']);
+        if !token.is_empty() {
+            tokens.push(MentionToken { start: i, token });
+        }
+    }
+    tokens
+}
+
+/// Whether `token` names the user: their initials, their first name, or their full name
+/// written without spaces, all ignoring case.
+pub fn mention_matches(token: &str, name: &str, initials: Option<&str>) -> bool {
+    let token = token.to_lowercase();
+    if initials.is_some_and(|i| !i.is_empty() && i.to_lowercase() == token) {
+        return true;
+    }
+    let name = name.to_lowercase();
+    name.split_whitespace().next() == Some(token.as_str())
+        || name.split_whitespace().collect::<String>() == token
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(text: &str) -> Vec<&str> {
+        mention_tokens(text).into_iter().map(|m| m.token).collect()
+    }
+
+    #[test]
+    fn finds_mentions_with_their_offsets() {
+        let text = "@MF can you ask @jane_doe";
+        let found = mention_tokens(text);
+        assert_eq!(
+            found,
+            [
+                MentionToken {
+                    start: 0,
+                    token: "MF"
+                },
+                MentionToken {
+                    start: 16,
+                    token: "jane_doe"
+                },
+            ]
+        );
+        assert_eq!(&text[found[1].start..found[1].end()], "@jane_doe");
+    }
+
+    #[test]
+    fn email_addresses_are_not_mentions() {
+        assert!(tokens("mail mike@example.org or a_b@example.org").is_empty());
+        assert_eq!(tokens("cc (@mike) and \"@ada\""), ["mike", "ada"]);
+    }
+
+    #[test]
+    fn trailing_punctuation_is_not_part_of_the_token() {
+        assert_eq!(tokens("thanks @mike."), ["mike"]);
+        assert_eq!(tokens("@mike, @ada! @j.doe-"), ["mike", "ada", "j.doe"]);
+        assert!(tokens("a lone @ sign, @. and @-").is_empty());
+    }
+
+    #[test]
+    fn unicode_names_are_tokens_and_offsets_stay_on_char_boundaries() {
+        let text = "über @Zoë and @José.";
+        let found = mention_tokens(text);
+        assert_eq!(
+            found.iter().map(|m| m.token).collect::<Vec<_>>(),
+            ["Zoë", "José"]
+        );
+        for m in &found {
+            assert_eq!(&text[m.start..m.end()], format!("@{}", m.token));
+        }
+        // A letter in any script glues the @ to it, like an email address
+        assert!(tokens("é@zoë").is_empty());
+    }
+
+    #[test]
+    fn matches_initials_first_name_or_full_name_ignoring_case() {
+        assert!(mention_matches("mf", "Mike Fox", Some("MF")));
+        assert!(mention_matches("Mike", "Mike Fox", Some("MF")));
+        assert!(mention_matches("mikefox", "Mike Fox", None));
+        assert!(mention_matches("zoë", "Zoë Brown", None));
+    }
+
+    #[test]
+    fn unknown_mentions_match_nobody() {
+        let users = [("Mike Fox", Some("MF")), ("Ada Lovelace", None)];
+        let resolved: Vec<_> = tokens("@mike please check with @nobody")
+            .into_iter()
+            .map(|token| {
+                users
+                    .iter()
+                    .find(|(name, initials)| mention_matches(token, name, *initials))
+                    .map(|(name, _)| *name)
+            })
+            .collect();
+        assert_eq!(resolved, [Some("Mike Fox"), None]);
+        assert!(!mention_matches("fox", "Mike Fox", Some("MF")));
+        assert!(!mention_matches("", "Mike Fox", Some("")));
+    }
+}
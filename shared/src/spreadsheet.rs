@@ -0,0 +1,77 @@
+//! The spreadsheet CSV format. The backend exports it and `cli import` reads it back, so
+//! both sides share the column layout and how dates and notes are written.
+
+use chrono::NaiveDate;
+
+/// Column headers of the spreadsheet format, in the order the importer expects.
+pub const CSV_HEADERS: [&str; 11] = [
+    "Action Item #",
+    "Title",
+    "Create Date",
+    "Created by",
+    "Due Date",
+    "Category",
+    "Owner",
+    "Priority",
+    "Status",
+    "Status Date",
+    "Notes",
+];
+
+/// A date as the spreadsheets write it, `MM/DD/YYYY`.
+pub fn csv_date(date: NaiveDate) -> String {
+    date.format("%m/%d/%Y").to_string()
+}
+
+/// The author initials a note body of the form "INITIALS: content" starts with.
+pub fn note_author_initials(content: &str) -> Option<&str> {
+    let (prefix, _) = content.split_once(':')?;
+    let prefix = prefix.trim();
+    if (1..=4).contains(&prefix.len()) && prefix.chars().all(|c| c.is_ascii_alphabetic()) {
+        Some(prefix)
+    } else {
+        None
+    }
+}
+
+/// Render one note as a `MM/DD/YYYY INITIALS: content` line. Imported notes already carry
+/// their author's initials, so they are not prefixed twice; without `initials` the line is
+/// just the date and content.
+pub fn note_line(note_date: NaiveDate, content: &str, initials: Option<&str>) -> String {
+    let date = csv_date(note_date);
+    match initials {
+        Some(initials)
+            if !note_author_initials(content)
+                .is_some_and(|signed| signed.eq_ignore_ascii_case(initials)) =>
+        {
+            format!("{} {}: {}", date, initials, content)
+        }
+        _ => format!("{} {}", date, content),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn note_lines_are_signed_once() {
+        let date = NaiveDate::from_ymd_opt(2024, 3, 5).unwrap();
+        assert_eq!(
+            note_line(date, "Sent drawings", Some("MF")),
+            "03/05/2024 MF: Sent drawings"
+        );
+        assert_eq!(
+            note_line(date, "mf: Sent drawings", Some("MF")),
+            "03/05/2024 mf: Sent drawings"
+        );
+        assert_eq!(
+            note_line(date, "JD: Sent drawings", Some("MF")),
+            "03/05/2024 MF: JD: Sent drawings"
+        );
+        assert_eq!(
+            note_line(date, "Sent drawings", None),
+            "03/05/2024 Sent drawings"
+        );
+    }
+}
@@ -5,41 +5,6 @@ use serde::{Deserialize, Serialize};
 // Enums
 // ============================================================================
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub enum Category {
-    Programmatic,
-    SwOps,
-    Mechanical,
-    Adcs,
-    Systems,
-    ConOps,
-}
-
-impl Category {
-    pub fn as_str(&self) -> &'static str {
-        match self {
-            Category::Programmatic => "Programmatic",
-            Category::SwOps => "SW / Ops",
-            Category::Mechanical => "Mechanical",
-            Category::Adcs => "ADCS",
-            Category::Systems => "Systems",
-            Category::ConOps => "ConOps",
-        }
-    }
-
-    pub fn all() -> &'static [Category] {
-        &[
-            Category::Programmatic,
-            Category::SwOps,
-            Category::Mechanical,
-            Category::Adcs,
-            Category::Systems,
-            Category::ConOps,
-        ]
-    }
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Priority {
@@ -97,6 +62,57 @@ impl Status {
     }
 }
 
+/// What a user may do. Members work on items; admins also manage vendors and categories.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Admin,
+    #[default]
+    Member,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Admin => "admin",
+            Role::Member => "member",
+        }
+    }
+
+    /// Parse a stored role; anything unrecognised is a member.
+    pub fn from_db(value: &str) -> Self {
+        if value == Role::Admin.as_str() {
+            Role::Admin
+        } else {
+            Role::Member
+        }
+    }
+}
+
+/// How often a completed item comes back as a fresh one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Recurrence {
+    #[default]
+    None,
+    Weekly,
+    Monthly,
+}
+
+impl Recurrence {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Recurrence::None => "none",
+            Recurrence::Weekly => "weekly",
+            Recurrence::Monthly => "monthly",
+        }
+    }
+
+    pub fn all() -> &'static [Recurrence] {
+        &[Recurrence::None, Recurrence::Weekly, Recurrence::Monthly]
+    }
+}
+
 // ============================================================================
 // Domain Types
 // ============================================================================
@@ -110,6 +126,8 @@ pub struct Vendor {
     pub next_number: i32,
     pub created_at: DateTime<Utc>,
     pub archived: bool,
+    /// Digits item numbers are zero-padded to in ids
+    pub number_padding: i32,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -128,6 +146,8 @@ pub struct User {
     pub name: String,
     pub initials: Option<String>,
     pub created_at: DateTime<Utc>,
+    /// Inactive users still own their old items but aren't offered as owners
+    pub active: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -139,7 +159,8 @@ pub struct ActionItem {
     pub create_date: NaiveDate,
     pub created_by_id: i32,
     pub due_date: Option<NaiveDate>,
-    pub category: Category,
+    /// Categories are per-vendor rows in the `categories` table
+    pub category_id: i32,
     pub owner_id: i32,
     pub priority: Priority,
     pub created_at: DateTime<Utc>,
@@ -178,11 +199,32 @@ pub struct StatusHistory {
 // API Request Types
 // ============================================================================
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CreateUser {
+    pub email: String,
+    pub name: String,
+    /// Derived from the name when omitted
+    #[serde(default)]
+    pub initials: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UpdateUser {
+    pub name: Option<String>,
+    pub initials: Option<String>,
+    /// Always rejected: the email is what ties a user to their OAuth login
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CreateVendor {
     pub prefix: String,
     pub name: String,
     pub description: Option<String>,
+    /// Defaults to `DEFAULT_NUMBER_PADDING`
+    #[serde(default)]
+    pub number_padding: Option<i32>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -196,20 +238,27 @@ pub struct UpdateVendor {
 pub struct CreateActionItem {
     pub title: String,
     pub due_date: Option<NaiveDate>,
-    pub category: Category,
+    /// Must belong to the vendor the item is created under
+    pub category_id: i32,
     pub owner_id: i32,
     pub priority: Priority,
     pub description: Option<String>,
+    #[serde(default)]
+    pub recurrence: Recurrence,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct UpdateActionItem {
     pub title: Option<String>,
     pub due_date: Option<Option<NaiveDate>>,
-    pub category: Option<Category>,
+    pub category_id: Option<i32>,
     pub owner_id: Option<i32>,
     pub priority: Option<Priority>,
     pub description: Option<Option<String>>,
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+    /// Reject the update with 409 if the item's `updated_at` no longer matches
+    pub expected_updated_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -218,12 +267,29 @@ pub struct CreateNote {
     pub content: String,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UpdateNote {
+    pub content: String,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ChangeStatus {
     pub status: Status,
     pub comment: Option<String>,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReopenItem {
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BulkStatusChange {
+    pub item_ids: Vec<String>,
+    pub status: Status,
+    pub comment: Option<String>,
+}
+
 // ============================================================================
 // API Response Types
 // ============================================================================
@@ -272,18 +338,58 @@ impl ApiError {
     pub fn internal_error(message: impl Into<String>) -> Self {
         Self::new("INTERNAL_ERROR", message)
     }
+
+    pub fn service_unavailable(message: impl Into<String>) -> Self {
+        Self::new("SERVICE_UNAVAILABLE", message)
+    }
+
+    pub fn payload_too_large(message: impl Into<String>) -> Self {
+        Self::new("PAYLOAD_TOO_LARGE", message)
+    }
+
+    pub fn unsupported_media_type(message: impl Into<String>) -> Self {
+        Self::new("UNSUPPORTED_MEDIA_TYPE", message)
+    }
+
+    pub fn rate_limited(message: impl Into<String>) -> Self {
+        Self::new("RATE_LIMITED", message)
+    }
+
+    pub fn unprocessable(message: impl Into<String>) -> Self {
+        Self::new("UNPROCESSABLE", message)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct HealthResponse {
+    /// "ok", or "degraded" when a dependency is down
     pub status: String,
+    /// Database check result: "ok" or "unreachable"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub db: Option<String>,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClientConfig {
+    pub read_only: bool,
+    pub title_max_length: usize,
+}
+
+/// Item numbers are zero-padded to this many digits in ids unless the vendor says
+/// otherwise (`AD-001`); numbers that outgrow the padding just get longer.
+pub const DEFAULT_NUMBER_PADDING: i32 = 3;
+pub const MAX_NUMBER_PADDING: i32 = 6;
+
+/// Default (and largest allowed) item title length; matches the `VARCHAR(500)` column.
+pub const DEFAULT_TITLE_MAX_LENGTH: usize = 500;
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CurrentUserResponse {
     pub user_id: i32,
     pub email: String,
     pub name: String,
+    #[serde(default)]
+    pub role: Role,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -291,6 +397,11 @@ pub struct LogoutResponse {
     pub status: String,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenRefreshResponse {
+    pub expires_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CategoryResponse {
     pub id: i32,
@@ -298,6 +409,7 @@ pub struct CategoryResponse {
     pub name: String,
     pub description: Option<String>,
     pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -322,6 +434,34 @@ pub struct ActionItemResponse {
     pub updated_at: DateTime<Utc>,
     pub status: String,
     pub status_changed_at: DateTime<Utc>,
+    /// Secondary assignees; the primary owner is `owner_id`
+    #[serde(default)]
+    pub assignees: Vec<AssigneeResponse>,
+    #[serde(default)]
+    pub watcher_count: i64,
+    /// Whether the requesting user watches the item. Only looked up when fetching a single
+    /// item (`GET /api/items/:item_id`); `None` elsewhere.
+    #[serde(default)]
+    pub watching: Option<bool>,
+    /// Tag names, alphabetical
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Ids of linked items, grouped by link type
+    #[serde(default)]
+    pub links: ItemLinks,
+    /// Set while the item is soft-deleted; only listed with `include_deleted=true`
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// `none`, `weekly` or `monthly`
+    #[serde(default = "default_recurrence")]
+    pub recurrence: String,
+    /// The item generated from this one after it was completed, if any
+    #[serde(default)]
+    pub next_occurrence_id: Option<String>,
+}
+
+fn default_recurrence() -> String {
+    Recurrence::None.as_str().to_string()
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -331,8 +471,12 @@ pub struct NoteResponse {
     pub date: NaiveDate,
     pub author_id: i32,
     pub author_name: String,
+    pub author_initials: Option<String>,
     pub content: String,
     pub created_at: DateTime<Utc>,
+    /// Users `@mentioned` in the content; unresolved mentions are just text
+    #[serde(default)]
+    pub mentioned_user_ids: Vec<i32>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -343,12 +487,16 @@ pub struct NoteCreateResponse {
     pub author_id: i32,
     pub content: String,
     pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub mentioned_user_ids: Vec<i32>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct StatusHistoryResponse {
     pub id: i32,
     pub action_item_id: String,
+    /// Status before this change; `None` for an item's first entry.
+    pub from_status: Option<String>,
     pub status: String,
     pub changed_by_id: i32,
     pub changed_by_name: String,
@@ -373,12 +521,192 @@ pub struct ActivityEntry {
     pub detail: String,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchField {
+    Title,
+    Description,
+    Notes,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemSearchResult {
+    pub item: ActionItemResponse,
+    /// Fields that contained the search text, most relevant first.
+    pub matched_fields: Vec<SearchField>,
+}
+
+/// One page of `GET /api/items`, plus how many items match the filters in total.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemListResponse {
+    pub items: Vec<ActionItemResponse>,
+    pub total_count: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActivityResponse {
+    pub entries: Vec<ActivityEntry>,
+    pub has_more: bool,
+}
+
+/// How many items are currently in one status.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusCount {
+    pub status: String,
+    pub count: i64,
+}
+
+/// The authenticated user's summary from `GET /api/me/dashboard`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DashboardResponse {
+    /// Items the user owns, per current status; every status is listed, in workflow order
+    pub status_counts: Vec<StatusCount>,
+    /// Open items the user owns whose due date has passed, most overdue first
+    pub overdue: Vec<ActionItemResponse>,
+    /// The user's own latest notes and status changes, newest first
+    pub recent_activity: Vec<ActivityEntry>,
+}
+
+/// How many items have one priority.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriorityCount {
+    pub priority: String,
+    pub count: i64,
+}
+
+/// A vendor's item summary from `GET /api/vendors/:id/stats`. Soft-deleted items don't count.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VendorStats {
+    pub vendor_id: i32,
+    pub total_items: i64,
+    /// Per current status; every status is listed, in workflow order
+    pub status_counts: Vec<StatusCount>,
+    /// Per priority; every priority is listed, High first
+    pub priority_counts: Vec<PriorityCount>,
+    /// Mean days from an item's first status entry to its first Complete one, over items
+    /// that have ever been completed; `None` if none have
+    pub avg_days_to_complete: Option<f64>,
+}
+
+/// A user assigned to an item alongside its primary owner (`owner_id`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssigneeResponse {
+    pub user_id: i32,
+    pub user_name: String,
+    pub user_initials: Option<String>,
+    pub role: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddAssignee {
+    pub user_id: i32,
+    /// Defaults to "secondary"
+    pub role: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagResponse {
+    pub id: i32,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Body for creating a tag and for attaching one to an item. Names are matched
+/// case-insensitively.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagName {
+    pub name: String,
+}
+
+/// Links between an item and others, as item ids.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ItemLinks {
+    /// Items this one blocks
+    #[serde(default)]
+    pub blocks: Vec<String>,
+    /// Items blocking this one
+    #[serde(default)]
+    pub blocked_by: Vec<String>,
+    /// Related items, whichever side created the link
+    #[serde(default)]
+    pub relates_to: Vec<String>,
+}
+
+/// Body for adding and removing an item link; `link_type` is "blocks" or "relates_to".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemLinkReq {
+    pub to_item_id: String,
+    pub link_type: String,
+}
+
+/// A user's named item filter. `filter` is an object of `GET /api/items` query
+/// parameters, e.g. `{"vendor_id": 1, "status": "In Progress"}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedView {
+    pub id: i32,
+    pub name: String,
+    pub filter: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CreateSavedView {
+    pub name: String,
+    pub filter: serde_json::Value,
+}
+
+/// One field edit recorded by `PATCH /api/items/:item_id`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemAuditEntry {
+    pub id: i32,
+    pub action_item_id: String,
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub changed_by_id: i32,
+    pub changed_by_name: String,
+    pub changed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkStatusOutcome {
+    Updated,
+    NotFound,
+    /// The workflow doesn't allow moving from the item's current status
+    NotAllowed,
+}
+
+/// Per-item result of `POST /api/items/bulk/status`, in request order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BulkStatusResult {
+    pub item_id: String,
+    pub outcome: BulkStatusOutcome,
+    /// Status before the change (or the blocking status for `not_allowed`)
+    pub from_status: Option<String>,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct StatusChangeResponse {
     pub id: i32,
     pub action_item_id: String,
+    pub from_status: Option<String>,
     pub status: String,
     pub changed_by_id: i32,
     pub changed_at: DateTime<Utc>,
     pub comment: Option<String>,
 }
+
+/// Body of a webhook delivery. Receivers can verify it against the `X-Tracker-Signature`
+/// header: `sha256=` followed by the hex HMAC-SHA256 of the body, keyed with the
+/// webhook's secret.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookEvent {
+    /// Always the specific event, e.g. `status_changed:Blocked`
+    pub event_type: String,
+    pub item_id: String,
+    pub item_title: String,
+    pub vendor_id: i32,
+    pub change: StatusChangeResponse,
+}